@@ -0,0 +1,473 @@
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use crate::client::resolve_error::ResolveError;
+use crate::client::udp_transport::MAX_DATAGRAM_SIZE;
+use crate::messages::message_base::MessageBase;
+use crate::records::opt_record::OptRecord;
+use crate::utils::random;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_RETRIES: u32 = 2;
+
+//DNS FLAG DAY 2020 - BIG ENOUGH FOR MOST ANSWERS, SMALL ENOUGH TO NOT FRAGMENT
+const DEFAULT_PAYLOAD_SIZE: u16 = 1232;
+
+//RFC 5452 9.2 - QUERIES TO ONE SERVER ARE SPREAD OVER THIS MANY UDP SOCKETS, EACH ON ITS OWN RANDOM PORT
+const UDP_POOL_SIZE: usize = 8;
+
+//A SOCKET IS SWAPPED FOR ONE ON A NEW PORT AFTER THIS MANY QUERIES, SO THE PORTS IN USE KEEP MOVING
+const MAX_SOCKET_QUERIES: usize = 32;
+
+//ANSWERS WAITING ON ONE QUERY, ANYTHING PAST THIS IS SOMEONE FLOODING OUR ID AND GETS DROPPED
+const MAX_QUEUED_ANSWERS: usize = 8;
+
+//EVERY QUERY IN FLIGHT ON A SOCKET OR CONNECTION, BY ID - THE READER HANDS EACH ANSWER TO WHOEVER ASKED
+type Pending = Arc<Mutex<HashMap<u16, Sender<Vec<u8>>>>>;
+
+//AN ID HANDED OUT TO ONE QUERY, IT GOES BACK TO THE POOL WHEN THE QUERY IS DONE OR ITS FUTURE IS DROPPED HALF WAY
+struct Registration {
+    pending: Pending,
+    id: u16
+}
+
+impl Drop for Registration {
+
+    fn drop(&mut self) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&self.id);
+        }
+    }
+}
+
+struct UdpChannel {
+    socket: Arc<UdpSocket>,
+    pending: Pending,
+    reader: JoinHandle<()>
+}
+
+impl Drop for UdpChannel {
+
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+struct TcpChannel {
+    writer: tokio::sync::Mutex<OwnedWriteHalf>,
+    local: SocketAddr,
+    pending: Pending,
+    reader: JoinHandle<()>
+}
+
+impl Drop for TcpChannel {
+
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+//A SOCKET OF THE POOL AND HOW MANY QUERIES IT'S CARRIED
+type UdpSlot = Option<(Arc<UdpChannel>, usize)>;
+
+//A POOL OF UDP SOCKETS AND ONE CONNECTION PER SERVER, SHARED BY EVERY QUERY TO IT AND OPENED ON FIRST USE
+struct Upstream {
+    server: SocketAddr,
+    udp: tokio::sync::Mutex<Vec<UdpSlot>>,
+    tcp: tokio::sync::Mutex<Option<Arc<TcpChannel>>>
+}
+
+impl Upstream {
+
+    fn new(server: SocketAddr) -> Self {
+        Self {
+            server,
+            udp: tokio::sync::Mutex::new(vec![None; UDP_POOL_SIZE]),
+            tcp: tokio::sync::Mutex::new(None)
+        }
+    }
+}
+
+//THE ASYNC SIDE OF Client - SAME RETRIES AND FALLBACK, BUT ONE &self IS MEANT TO BE SHARED BY EVERY TASK
+//EVERY UDP ATTEMPT GOES OUT ON A RANDOM SOCKET OF THE POOL WITH A RANDOM ID, A SPOOFED ANSWER HAS TO GUESS BOTH
+pub struct AsyncClient {
+    upstreams: Vec<Upstream>,
+    timeout: Duration,
+    retries: u32,
+    payload_size: Option<u16>,
+    tcp_only: bool
+}
+
+impl AsyncClient {
+
+    pub fn new(server: SocketAddr) -> Self {
+        Self {
+            upstreams: vec![Upstream::new(server)],
+            timeout: DEFAULT_TIMEOUT,
+            retries: DEFAULT_RETRIES,
+            payload_size: Some(DEFAULT_PAYLOAD_SIZE),
+            tcp_only: false
+        }
+    }
+
+    //RETRIES GO ROUND THE SERVERS IN THE ORDER THEY WERE ADDED
+    pub fn add_server(&mut self, server: SocketAddr) {
+        self.upstreams.push(Upstream::new(server));
+    }
+
+    pub fn get_servers(&self) -> Vec<SocketAddr> {
+        self.upstreams.iter().map(|upstream| upstream.server).collect()
+    }
+
+    //PER ATTEMPT, A QUERY CAN TAKE UP TO timeout * (retries + 1)
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn set_retries(&mut self, retries: u32) {
+        self.retries = retries;
+    }
+
+    pub fn get_retries(&self) -> u32 {
+        self.retries
+    }
+
+    //None SENDS QUERIES WITHOUT AN OPT RECORD UNLESS THE MESSAGE ALREADY HAS ONE
+    pub fn set_payload_size(&mut self, payload_size: Option<u16>) {
+        self.payload_size = payload_size;
+    }
+
+    pub fn get_payload_size(&self) -> Option<u16> {
+        self.payload_size
+    }
+
+    //SKIP UDP ENTIRELY, OTHERWISE TCP IS ONLY USED WHEN A UDP ANSWER COMES BACK TRUNCATED
+    pub fn set_tcp_only(&mut self, tcp_only: bool) {
+        self.tcp_only = tcp_only;
+    }
+
+    pub fn is_tcp_only(&self) -> bool {
+        self.tcp_only
+    }
+
+    //THE ID IS REPLACED ON EVERY ATTEMPT WITH ONE NO OTHER QUERY ON THE SAME SOCKET OR CONNECTION IS USING
+    pub async fn query(&self, mut message: MessageBase) -> Result<MessageBase, ResolveError> {
+        if let Some(payload_size) = self.payload_size {
+            if message.get_opt_record().is_none() {
+                message.add_additional_records("", Box::new(OptRecord::new(payload_size, 0, 0, 0)));
+            }
+        }
+
+        let mut error = ResolveError::Timeout;
+
+        for attempt in 0..=self.retries as usize {
+            let upstream = &self.upstreams[attempt % self.upstreams.len()];
+
+            let response = match self.tcp_only {
+                false => match self.exchange_udp(upstream, &mut message).await {
+                    //RFC 7766 5 - THE WHOLE ANSWER DIDN'T FIT, ASK AGAIN OVER TCP
+                    Ok(response) if response.is_truncated() => self.exchange_tcp(upstream, &mut message).await,
+                    response => response
+                },
+                true => self.exchange_tcp(upstream, &mut message).await
+            };
+
+            match response {
+                Ok(response) => {
+                    return match ResolveError::from_response_code(response.get_response_code()) {
+                        //ANOTHER SERVER, OR THE SAME ONE A MOMENT LATER, MAY DO BETTER
+                        Some(ResolveError::ServFail) => {
+                            error = ResolveError::ServFail;
+                            continue;
+                        }
+                        Some(e) => Err(e),
+                        None => Ok(response)
+                    };
+                }
+                Err(e) => error = e
+            }
+        }
+
+        Err(error)
+    }
+
+    async fn exchange_udp(&self, upstream: &Upstream, message: &mut MessageBase) -> Result<MessageBase, ResolveError> {
+        let deadline = Instant::now()+self.timeout;
+        let channel = get_udp(upstream).await?;
+
+        let (registration, receiver) = register(&channel.pending)?;
+        message.set_id(registration.id);
+
+        channel.socket.send_to(&message.encode(), upstream.server).await.map_err(|e| ResolveError::Io(e.to_string()))?;
+
+        //KEEP LISTENING PAST A BROKEN ANSWER, ANYONE CAN SEND ONE WITH OUR ID
+        let mut response = wait(receiver, message, deadline, false).await?;
+        response.set_origin(upstream.server);
+        response.set_destination(channel.socket.local_addr().map_err(|e| ResolveError::Io(e.to_string()))?);
+        Ok(response)
+    }
+
+    async fn exchange_tcp(&self, upstream: &Upstream, message: &mut MessageBase) -> Result<MessageBase, ResolveError> {
+        let deadline = Instant::now()+self.timeout;
+        let channel = tokio::time::timeout_at(deadline, get_tcp(upstream)).await.map_err(|_| ResolveError::Timeout)??;
+
+        let (registration, receiver) = register(&channel.pending)?;
+        message.set_id(registration.id);
+
+        if channel.reader.is_finished() {
+            return Err(ResolveError::Io("Connection closed by the server".to_string()));
+        }
+
+        let encoded = message.encode();
+        if encoded.len() > u16::MAX as usize {
+            return Err(ResolveError::Io(format!("Message is too long for TCP: {} bytes", encoded.len())));
+        }
+
+        let mut buf = Vec::with_capacity(2+encoded.len());
+        buf.extend_from_slice(&(encoded.len() as u16).to_be_bytes());
+        buf.extend_from_slice(&encoded);
+
+        //ONE WRITE PER MESSAGE UNDER THE LOCK, SO QUERIES FROM DIFFERENT TASKS NEVER INTERLEAVE ON THE STREAM
+        let written = tokio::time::timeout_at(deadline, async {
+            let mut writer = channel.writer.lock().await;
+            writer.write_all(&buf).await?;
+            writer.flush().await
+        }).await.map_err(|_| ResolveError::Timeout)?;
+
+        if let Err(e) = written {
+            close_tcp(upstream, &channel).await;
+            return Err(ResolveError::Io(e.to_string()));
+        }
+
+        let mut response = wait(receiver, message, deadline, true).await?;
+        response.set_origin(upstream.server);
+        response.set_destination(channel.local);
+        Ok(response)
+    }
+}
+
+fn register(pending: &Pending) -> Result<(Registration, Receiver<Vec<u8>>), ResolveError> {
+    let mut map = pending.lock().map_err(|e| ResolveError::Io(e.to_string()))?;
+
+    if map.len() > u16::MAX as usize {
+        return Err(ResolveError::Io("Every ID is already in flight".to_string()));
+    }
+
+    let mut id = u16::from_be_bytes(random::gen_array());
+    while map.contains_key(&id) {
+        id = u16::from_be_bytes(random::gen_array());
+    }
+
+    let (sender, receiver) = mpsc::channel(MAX_QUEUED_ANSWERS);
+    map.insert(id, sender);
+
+    Ok((Registration {
+        pending: pending.clone(),
+        id
+    }, receiver))
+}
+
+//stream IS WHETHER ONLY THE SERVER COULD HAVE SENT WHAT COMES IN, A BROKEN ANSWER ON A CONNECTION IS THE ANSWER
+async fn wait(mut receiver: Receiver<Vec<u8>>, message: &MessageBase, deadline: Instant, stream: bool) -> Result<MessageBase, ResolveError> {
+    let mut malformed = None;
+
+    loop {
+        let buf = match tokio::time::timeout_at(deadline, receiver.recv()).await {
+            Ok(Some(buf)) => buf,
+            Ok(None) => return Err(malformed.unwrap_or(ResolveError::Io("Connection closed by the server".to_string()))),
+            Err(_) => return Err(malformed.unwrap_or(ResolveError::Timeout))
+        };
+
+        match MessageBase::from_bytes(&buf, 0) {
+            Ok(response) if response.is_response_to(message) => return Ok(response),
+            Ok(_) => {}
+            Err(e) if stream => return Err(ResolveError::Malformed(e)),
+            Err(e) => malformed = Some(ResolveError::Malformed(e))
+        }
+    }
+}
+
+fn route(pending: &Pending, buf: &[u8]) {
+    if buf.len() < 2 {
+        return;
+    }
+
+    if let Ok(pending) = pending.lock() {
+        if let Some(sender) = pending.get(&u16::from_be_bytes([buf[0], buf[1]])) {
+            let _ = sender.try_send(buf.to_vec());
+        }
+    }
+}
+
+//A RANDOM SLOT OF THE POOL, A SOCKET THAT'S SERVED ITS QUERIES OR LOST ITS READER IS REPLACED BY ONE ON A NEW PORT
+//QUERIES STILL WAITING ON THE OLD ONE HOLD ON TO IT, ITS READER STOPS WHEN THE LAST OF THEM IS DONE
+async fn get_udp(upstream: &Upstream) -> Result<Arc<UdpChannel>, ResolveError> {
+    let mut udp = upstream.udp.lock().await;
+    let slot = &mut udp[u32::from_be_bytes(random::gen_array()) as usize % UDP_POOL_SIZE];
+
+    if let Some((channel, queries)) = slot.as_mut().filter(|(channel, queries)| !channel.reader.is_finished() && *queries < MAX_SOCKET_QUERIES) {
+        *queries += 1;
+        return Ok(channel.clone());
+    }
+
+    let socket = Arc::new(match upstream.server {
+        SocketAddr::V4(_) => UdpSocket::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))).await,
+        SocketAddr::V6(_) => UdpSocket::bind(SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0))).await
+    }.map_err(|e| ResolveError::Io(e.to_string()))?);
+
+    let pending = Pending::default();
+
+    let channel = Arc::new(UdpChannel {
+        socket: socket.clone(),
+        pending: pending.clone(),
+        reader: tokio::spawn(read_udp(socket, upstream.server, pending))
+    });
+
+    *slot = Some((channel.clone(), 1));
+    Ok(channel)
+}
+
+async fn read_udp(socket: Arc<UdpSocket>, server: SocketAddr, pending: Pending) {
+    let mut buf = vec![0u8; MAX_DATAGRAM_SIZE];
+
+    loop {
+        let (size, source) = match socket.recv_from(&mut buf).await {
+            Ok(received) => received,
+            Err(e) if matches!(e.kind(), ErrorKind::Interrupted | ErrorKind::ConnectionRefused | ErrorKind::ConnectionReset) => continue,
+            Err(_) => break
+        };
+
+        //ANYONE CAN SEND TO OUR PORT, ONLY THE SERVER'S ANSWERS COUNT
+        if source == server {
+            route(&pending, &buf[..size]);
+        }
+    }
+
+    //WAITERS FIND OUT THE SOCKET IS GONE INSTEAD OF SITTING OUT THEIR TIMEOUT
+    if let Ok(mut pending) = pending.lock() {
+        pending.clear();
+    }
+}
+
+async fn get_tcp(upstream: &Upstream) -> Result<Arc<TcpChannel>, ResolveError> {
+    let mut tcp = upstream.tcp.lock().await;
+
+    //THE SERVER CLOSES IDLE CONNECTIONS, THAT ENDS THE READER AND THE NEXT QUERY OPENS A NEW ONE
+    if let Some(channel) = tcp.as_ref().filter(|channel| !channel.reader.is_finished()) {
+        return Ok(channel.clone());
+    }
+
+    let stream = TcpStream::connect(upstream.server).await.map_err(|e| ResolveError::Io(e.to_string()))?;
+    stream.set_nodelay(true).map_err(|e| ResolveError::Io(e.to_string()))?;
+    let local = stream.local_addr().map_err(|e| ResolveError::Io(e.to_string()))?;
+
+    let (reader, writer) = stream.into_split();
+    let pending = Pending::default();
+
+    let channel = Arc::new(TcpChannel {
+        writer: tokio::sync::Mutex::new(writer),
+        local,
+        pending: pending.clone(),
+        reader: tokio::spawn(read_tcp(reader, pending))
+    });
+
+    *tcp = Some(channel.clone());
+    Ok(channel)
+}
+
+//ONLY CLOSES channel IF IT'S STILL THE CURRENT ONE, ANOTHER TASK MAY HAVE REPLACED IT ALREADY
+async fn close_tcp(upstream: &Upstream, channel: &Arc<TcpChannel>) {
+    let mut tcp = upstream.tcp.lock().await;
+
+    if tcp.as_ref().is_some_and(|current| Arc::ptr_eq(current, channel)) {
+        *tcp = None;
+    }
+}
+
+async fn read_tcp(mut stream: OwnedReadHalf, pending: Pending) {
+    while let Ok(length) = stream.read_u16().await {
+        let mut buf = vec![0u8; length as usize];
+        if stream.read_exact(&mut buf).await.is_err() {
+            break;
+        }
+
+        route(&pending, &buf);
+    }
+
+    //WAITERS FIND OUT THE CONNECTION IS GONE INSTEAD OF SITTING OUT THEIR TIMEOUT
+    if let Ok(mut pending) = pending.lock() {
+        pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashSet;
+    use std::net::{IpAddr, SocketAddr, UdpSocket};
+    use std::sync::mpsc::{channel, Receiver};
+    use std::thread;
+    use crate::client::async_client::AsyncClient;
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::types::Types;
+    use crate::messages::message_base::MessageBase;
+    use crate::records::a_record::ARecord;
+    use crate::utils::dns_query::DnsQuery;
+
+    //ANSWERS EVERY QUERY WITH ONE A RECORD, REPORTS THE SOURCE PORT OF EACH
+    fn serve() -> (SocketAddr, Receiver<u16>) {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = socket.local_addr().unwrap();
+        let (tx, rx) = channel();
+
+        thread::spawn(move || {
+            let mut buf = [0u8; 512];
+            while let Ok((length, source)) = socket.recv_from(&mut buf) {
+                let request = MessageBase::from_bytes(&buf[..length], 0).unwrap();
+                let question = request.get_queries().remove(0);
+
+                let mut response = MessageBase::new(request.get_id());
+                response.set_qr(true);
+                response.add_query(question.clone());
+                response.add_answers(&question.get_query().unwrap(), Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 1]))));
+
+                socket.send_to(&response.encode(), source).unwrap();
+                tx.send(source.port()).unwrap();
+            }
+        });
+
+        (address, rx)
+    }
+
+    #[test]
+    fn queries_leave_from_more_than_one_port() {
+        let (server, ports) = serve();
+        let client = AsyncClient::new(server);
+
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        runtime.block_on(async {
+            for _ in 0..64 {
+                let mut message = MessageBase::new(0);
+                message.add_query(DnsQuery::new("example.com", Types::A, DnsClasses::In));
+
+                let response = client.query(message).await.unwrap();
+                assert_eq!(response.get_answers().len(), 1);
+            }
+        });
+
+        let ports: HashSet<u16> = ports.try_iter().collect();
+        assert!(ports.len() > 1);
+    }
+}
@@ -0,0 +1,382 @@
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::client::inter::transport_base::TransportBase;
+use crate::client::resolve_error::ResolveError;
+use crate::client::tcp_transport::TcpTransport;
+use crate::client::udp_transport;
+use crate::messages::message_base::MessageBase;
+use crate::records::opt_record::OptRecord;
+use crate::utils::random;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_RETRIES: u32 = 2;
+
+//DNS FLAG DAY 2020 - BIG ENOUGH FOR MOST ANSWERS, SMALL ENOUGH TO NOT FRAGMENT
+const DEFAULT_PAYLOAD_SIZE: u16 = 1232;
+
+struct Upstream {
+    server: SocketAddr,
+    //ONLY SERVERS ADDED BY ADDRESS ARE ASKED OVER UDP, ONE ADDED AS A TRANSPORT NEVER SEES A PLAINTEXT DATAGRAM
+    udp: bool,
+    transport: Mutex<Box<dyn TransportBase>>
+}
+
+pub struct Client {
+    upstreams: Vec<Upstream>,
+    timeout: Duration,
+    retries: u32,
+    payload_size: Option<u16>,
+    tcp_only: bool
+}
+
+impl Client {
+
+    pub fn new(server: SocketAddr) -> Self {
+        Self::with_transport(Box::new(TcpTransport::new(server)), true)
+    }
+
+    //FOR DOT AND THE LIKE, THE SERVER IS ONLY EVER ASKED THROUGH transport
+    pub fn from_transport(transport: Box<dyn TransportBase>) -> Self {
+        Self::with_transport(transport, false)
+    }
+
+    fn with_transport(transport: Box<dyn TransportBase>, udp: bool) -> Self {
+        Self {
+            upstreams: vec![Upstream {
+                server: transport.get_server(),
+                udp,
+                transport: Mutex::new(transport)
+            }],
+            timeout: DEFAULT_TIMEOUT,
+            retries: DEFAULT_RETRIES,
+            payload_size: Some(DEFAULT_PAYLOAD_SIZE),
+            tcp_only: false
+        }
+    }
+
+    //RETRIES GO ROUND THE SERVERS IN THE ORDER THEY WERE ADDED
+    pub fn add_server(&mut self, server: SocketAddr) {
+        self.upstreams.push(Upstream {
+            server,
+            udp: true,
+            transport: Mutex::new(Box::new(TcpTransport::new(server)))
+        });
+    }
+
+    pub fn add_transport(&mut self, transport: Box<dyn TransportBase>) {
+        self.upstreams.push(Upstream {
+            server: transport.get_server(),
+            udp: false,
+            transport: Mutex::new(transport)
+        });
+    }
+
+    pub fn get_servers(&self) -> Vec<SocketAddr> {
+        self.upstreams.iter().map(|upstream| upstream.server).collect()
+    }
+
+    //PER ATTEMPT, A QUERY CAN TAKE UP TO timeout * (retries + 1)
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn set_retries(&mut self, retries: u32) {
+        self.retries = retries;
+    }
+
+    pub fn get_retries(&self) -> u32 {
+        self.retries
+    }
+
+    //None SENDS QUERIES WITHOUT AN OPT RECORD UNLESS THE MESSAGE ALREADY HAS ONE
+    pub fn set_payload_size(&mut self, payload_size: Option<u16>) {
+        self.payload_size = payload_size;
+    }
+
+    pub fn get_payload_size(&self) -> Option<u16> {
+        self.payload_size
+    }
+
+    //SKIP UDP ENTIRELY, OTHERWISE TCP IS ONLY USED WHEN A UDP ANSWER COMES BACK TRUNCATED
+    pub fn set_tcp_only(&mut self, tcp_only: bool) {
+        self.tcp_only = tcp_only;
+    }
+
+    pub fn is_tcp_only(&self) -> bool {
+        self.tcp_only
+    }
+
+    //THE ID IS ALWAYS REPLACED WITH A RANDOM ONE, IT'S HALF OF WHAT KEEPS SPOOFED ANSWERS OUT
+    pub fn query(&self, mut message: MessageBase) -> Result<MessageBase, ResolveError> {
+        message.set_id(u16::from_be_bytes(random::gen_array()));
+
+        if let Some(payload_size) = self.payload_size {
+            if message.get_opt_record().is_none() {
+                message.add_additional_records("", Box::new(OptRecord::new(payload_size, 0, 0, 0)));
+            }
+        }
+
+        let mut error = ResolveError::Timeout;
+
+        for attempt in 0..=self.retries as usize {
+            let upstream = &self.upstreams[attempt % self.upstreams.len()];
+
+            let response = match upstream.udp && !self.tcp_only {
+                true => match self.exchange_udp(upstream, &message) {
+                    //RFC 7766 5 - THE WHOLE ANSWER DIDN'T FIT, ASK AGAIN OVER TCP
+                    Ok(response) if response.is_truncated() => self.exchange_stream(upstream, &mut message),
+                    response => response
+                },
+                false => self.exchange_stream(upstream, &mut message)
+            };
+
+            match response {
+                Ok(response) => {
+                    return match ResolveError::from_response_code(response.get_response_code()) {
+                        //ANOTHER SERVER, OR THE SAME ONE A MOMENT LATER, MAY DO BETTER
+                        Some(ResolveError::ServFail) => {
+                            error = ResolveError::ServFail;
+                            continue;
+                        }
+                        Some(e) => Err(e),
+                        None => Ok(response)
+                    };
+                }
+                Err(e) => error = e
+            }
+        }
+
+        Err(error)
+    }
+
+    fn exchange_stream(&self, upstream: &Upstream, message: &mut MessageBase) -> Result<MessageBase, ResolveError> {
+        let mut transport = upstream.transport.lock().map_err(|e| ResolveError::Io(e.to_string()))?;
+        transport.set_timeout(self.timeout);
+        transport.query(message)
+    }
+
+    fn exchange_udp(&self, upstream: &Upstream, message: &MessageBase) -> Result<MessageBase, ResolveError> {
+        let server = upstream.server;
+        let socket = udp_transport::bind(server)?;
+        socket.send_to(&message.encode(), server).map_err(|e| ResolveError::Io(e.to_string()))?;
+
+        let deadline = Instant::now()+self.timeout;
+        let mut malformed = None;
+
+        let response = udp_transport::receive(&socket, server, deadline, |buf| {
+            //ANYONE CAN SEND TO OUR PORT, ONLY THE SERVER'S ANSWER TO THIS QUESTION COUNTS
+            if buf.len() < 2 || u16::from_be_bytes([buf[0], buf[1]]) != message.get_id() {
+                return None;
+            }
+
+            match MessageBase::from_bytes(buf, 0) {
+                Ok(response) => response.is_response_to(message).then_some(response),
+                //KEEP LISTENING, A BROKEN PACKET WITH OUR ID MIGHT NOT BE THE SERVER'S
+                Err(e) => {
+                    malformed = Some(ResolveError::Malformed(e));
+                    None
+                }
+            }
+        });
+
+        match response {
+            Ok(mut response) => {
+                response.set_origin(server);
+                response.set_destination(socket.local_addr().map_err(|e| ResolveError::Io(e.to_string()))?);
+                Ok(response)
+            }
+            Err(ResolveError::Timeout) => Err(malformed.unwrap_or(ResolveError::Timeout)),
+            Err(e) => Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::{Read, Write};
+    use std::net::{IpAddr, SocketAddr, TcpListener, UdpSocket};
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+    use crate::client::dns_client::Client;
+    use crate::client::resolve_error::ResolveError;
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::response_codes::ResponseCodes;
+    use crate::messages::inter::types::Types;
+    use crate::messages::message_base::MessageBase;
+    use crate::records::a_record::ARecord;
+    use crate::utils::dns_query::DnsQuery;
+
+    fn query() -> MessageBase {
+        let mut message = MessageBase::new(0);
+        message.add_query(DnsQuery::new("example.com", Types::A, DnsClasses::In));
+        message
+    }
+
+    fn client(servers: &[SocketAddr], retries: u32) -> Client {
+        let mut client = Client::new(servers[0]);
+        for server in &servers[1..] {
+            client.add_server(*server);
+        }
+        client.set_timeout(Duration::from_millis(300));
+        client.set_retries(retries);
+        client
+    }
+
+    //THE QUERY TURNED INTO A RESPONSE, ANSWERED WITH address
+    fn answer(buf: &[u8], address: [u8; 4]) -> MessageBase {
+        let mut response = MessageBase::from_bytes(buf, 0).unwrap();
+        response.set_qr(true);
+        response.add_answers("example.com", Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from(address))));
+        response
+    }
+
+    fn addresses(response: &MessageBase) -> Vec<IpAddr> {
+        response.get_answers().iter()
+            .flat_map(|(_, records)| records.iter())
+            .filter_map(|record| record.as_any().downcast_ref::<ARecord>()?.get_address())
+            .collect()
+    }
+
+    //EVERY QUERY IS PASSED TO handle ALONG WITH WHERE TO ANSWER IT, AND COUNTED ON THE RETURNED CHANNEL
+    fn serve<F>(mut handle: F) -> (SocketAddr, mpsc::Receiver<()>)
+    where
+        F: FnMut(&UdpSocket, SocketAddr, &[u8]) + Send + 'static
+    {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let server = socket.local_addr().unwrap();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+
+            loop {
+                let (size, source) = socket.recv_from(&mut buf).unwrap();
+                if sender.send(()).is_err() {
+                    return;
+                }
+                handle(&socket, source, &buf[..size]);
+            }
+        });
+
+        (server, receiver)
+    }
+
+    #[test]
+    fn truncated_answer_is_asked_again_over_tcp() {
+        //UDP AND TCP ON THE SAME PORT, LIKE A REAL SERVER
+        let (socket, listener) = loop {
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            if let Ok(listener) = TcpListener::bind(socket.local_addr().unwrap()) {
+                break (socket, listener);
+            }
+        };
+        let server = socket.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            let (size, source) = socket.recv_from(&mut buf).unwrap();
+            let mut response = MessageBase::from_bytes(&buf[..size], 0).unwrap();
+            response.set_qr(true);
+            response.set_truncated(true);
+            socket.send_to(&response.encode(), source).unwrap();
+        });
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut length = [0u8; 2];
+            stream.read_exact(&mut length).unwrap();
+            let mut buf = vec![0u8; u16::from_be_bytes(length) as usize];
+            stream.read_exact(&mut buf).unwrap();
+
+            let encoded = answer(&buf, [192, 0, 2, 1]).encode();
+            stream.write_all(&[&(encoded.len() as u16).to_be_bytes()[..], &encoded].concat()).unwrap();
+        });
+
+        let response = client(&[server], 0).query(query()).unwrap();
+        assert!(!response.is_truncated());
+        assert_eq!(addresses(&response), vec![IpAddr::from([192, 0, 2, 1])]);
+    }
+
+    #[test]
+    fn spoofed_answers_are_ignored() {
+        let spoofer = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        let (server, _queries) = serve(move |socket, source, buf| {
+            let mut response = answer(buf, [192, 0, 2, 66]);
+
+            //THE RIGHT ID FROM THE WRONG ADDRESS, THEN THE WRONG ID FROM THE RIGHT ONE
+            spoofer.send_to(&response.encode(), source).unwrap();
+            response.set_id(response.get_id().wrapping_add(1));
+            socket.send_to(&response.encode(), source).unwrap();
+
+            socket.send_to(&answer(buf, [192, 0, 2, 1]).encode(), source).unwrap();
+        });
+
+        let response = client(&[server], 0).query(query()).unwrap();
+        assert_eq!(addresses(&response), vec![IpAddr::from([192, 0, 2, 1])]);
+        assert_eq!(response.get_origin(), Some(server));
+    }
+
+    #[test]
+    fn servfail_moves_on_to_the_next_server() {
+        let (failing, failing_queries) = serve(|socket, source, buf| {
+            let mut response = MessageBase::from_bytes(buf, 0).unwrap();
+            response.set_qr(true);
+            response.set_response_code(ResponseCodes::ServerFailure);
+            socket.send_to(&response.encode(), source).unwrap();
+        });
+        let (working, working_queries) = serve(|socket, source, buf| {
+            socket.send_to(&answer(buf, [192, 0, 2, 1]).encode(), source).unwrap();
+        });
+
+        let response = client(&[failing, working], 1).query(query()).unwrap();
+        assert_eq!(response.get_origin(), Some(working));
+        assert_eq!(failing_queries.try_iter().count(), 1);
+        assert_eq!(working_queries.try_iter().count(), 1);
+
+        //WITH NOWHERE ELSE TO GO IT'S ServFail
+        assert_eq!(client(&[failing], 0).query(query()).err(), Some(ResolveError::ServFail));
+    }
+
+    #[test]
+    fn other_errors_are_final() {
+        let (missing, _queries) = serve(|socket, source, buf| {
+            let mut response = MessageBase::from_bytes(buf, 0).unwrap();
+            response.set_qr(true);
+            response.set_response_code(ResponseCodes::NameError);
+            socket.send_to(&response.encode(), source).unwrap();
+        });
+        let (working, working_queries) = serve(|socket, source, buf| {
+            socket.send_to(&answer(buf, [192, 0, 2, 1]).encode(), source).unwrap();
+        });
+
+        assert_eq!(client(&[missing, working], 1).query(query()).err(), Some(ResolveError::NxDomain));
+        assert_eq!(working_queries.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn retries_go_round_the_servers() {
+        let (silent, silent_queries) = serve(|_, _, _| {});
+        let (working, working_queries) = serve(|socket, source, buf| {
+            socket.send_to(&answer(buf, [192, 0, 2, 1]).encode(), source).unwrap();
+        });
+
+        let response = client(&[silent, working], 1).query(query()).unwrap();
+        assert_eq!(response.get_origin(), Some(working));
+        assert_eq!(silent_queries.try_iter().count(), 1);
+        assert_eq!(working_queries.try_iter().count(), 1);
+
+        //FOUR ATTEMPTS OVER TWO SILENT SERVERS, EACH ASKED TWICE IN TURN
+        let (other, other_queries) = serve(|_, _, _| {});
+        assert_eq!(client(&[silent, other], 3).query(query()).err(), Some(ResolveError::Timeout));
+        assert_eq!(silent_queries.try_iter().count(), 2);
+        assert_eq!(other_queries.try_iter().count(), 2);
+    }
+}
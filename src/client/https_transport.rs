@@ -0,0 +1,546 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use bytes::Bytes;
+use h2::client::SendRequest;
+use http::{header, Method, Request};
+use rustls::pki_types::ServerName;
+use rustls::ClientConfig;
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+use tokio_rustls::TlsConnector;
+use crate::client::inter::http_methods::HttpMethods;
+use crate::client::inter::transport_base::TransportBase;
+use crate::client::resolve_error::ResolveError;
+use crate::client::tls_config::TlsConfig;
+use crate::messages::inter::padding_policies::PaddingPolicies;
+use crate::messages::inter::types::Types;
+use crate::messages::message_base::MessageBase;
+use crate::utils::base64;
+use crate::utils::domain_utils::unpack_domain;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_PATH: &str = "/dns-query";
+const DNS_MESSAGE: &str = "application/dns-message";
+
+//NOTHING BIGGER FITS IN A DNS MESSAGE, A LONGER BODY ISN'T ONE
+const MAX_BODY_SIZE: usize = 65535;
+
+struct Connection {
+    sender: SendRequest<Bytes>,
+    driver: JoinHandle<()>
+}
+
+//WHAT CAME BACK FOR ONE QUERY, max_age IS WHAT'S LEFT OF THE HTTP FRESHNESS LIFETIME
+struct HttpResponse {
+    body: Vec<u8>,
+    max_age: Option<u32>
+}
+
+//BLOCKING ON THE OUTSIDE, h2 UNDERNEATH RUNS ON A RUNTIME OF ITS OWN - DON'T CALL IT FROM INSIDE ANOTHER TOKIO RUNTIME
+pub struct HttpsTransport {
+    server: SocketAddr,
+    server_name: ServerName<'static>,
+    authority: String,
+    path: String,
+    method: HttpMethods,
+    tls_config: TlsConfig,
+    timeout: Duration,
+    padding: bool,
+    runtime: Runtime,
+    connection: Option<Connection>
+}
+
+impl HttpsTransport {
+
+    //server_name IS CHECKED AGAINST THE CERTIFICATE AND SENT AS SNI AND :authority
+    pub fn new(server: SocketAddr, server_name: &str) -> Result<Self, String> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        let authority = match (server_name.parse::<IpAddr>(), server.port()) {
+            (Ok(IpAddr::V6(address)), 443) => format!("[{}]", address),
+            (Ok(IpAddr::V6(address)), port) => format!("[{}]:{}", address, port),
+            (_, 443) => server_name.to_string(),
+            (_, port) => format!("{}:{}", server_name, port)
+        };
+
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_alpn_protocols(vec![b"h2".to_vec()]);
+
+        Ok(Self {
+            server,
+            server_name: ServerName::try_from(server_name.to_string()).map_err(|_| format!("Invalid TLS server name: {}", server_name))?,
+            authority,
+            path: DEFAULT_PATH.to_string(),
+            method: HttpMethods::Post,
+            tls_config,
+            timeout: DEFAULT_TIMEOUT,
+            padding: true,
+            runtime,
+            connection: None
+        })
+    }
+
+    //THE PATH OF THE URI TEMPLATE, A GET ADDS ITS dns PARAMETER TO WHATEVER QUERY STRING IS ALREADY THERE
+    pub fn set_path(&mut self, path: &str) {
+        self.path = path.to_string();
+    }
+
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    //GET IS THE CACHE FRIENDLY ONE, POST KEEPS THE QUERY OUT OF THE URI
+    pub fn set_method(&mut self, method: HttpMethods) {
+        self.method = method;
+    }
+
+    pub fn get_method(&self) -> HttpMethods {
+        self.method
+    }
+
+    //ROOTS AND SPKI PINS - SEE TlsConfig, ALPN IS ALREADY SET TO h2
+    pub fn get_tls_config_mut(&mut self) -> &mut TlsConfig {
+        &mut self.tls_config
+    }
+
+    //RFC 8484 8.1 - QUERIES WITHOUT THEIR OWN PADDING POLICY ARE PADDED TO 128 BYTE BLOCKS
+    pub fn set_padding(&mut self, padding: bool) {
+        self.padding = padding;
+    }
+
+    pub fn is_padding(&self) -> bool {
+        self.padding
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connection.as_ref().is_some_and(|connection| !connection.driver.is_finished())
+    }
+
+    pub fn close(&mut self) {
+        if let Some(connection) = self.connection.take() {
+            connection.driver.abort();
+        }
+    }
+
+    fn build_request(&self, query: &[u8]) -> Result<Request<()>, ResolveError> {
+        let builder = match self.method {
+            HttpMethods::Get => {
+                let separator = if self.path.contains('?') { '&' } else { '?' };
+                Request::builder()
+                    .method(Method::GET)
+                    .uri(format!("https://{}{}{}dns={}", self.authority, self.path, separator, base64::encode_url(query)))
+            }
+            HttpMethods::Post => {
+                Request::builder()
+                    .method(Method::POST)
+                    .uri(format!("https://{}{}", self.authority, self.path))
+                    .header(header::CONTENT_TYPE, DNS_MESSAGE)
+                    .header(header::CONTENT_LENGTH, query.len())
+            }
+        };
+
+        builder.header(header::ACCEPT, DNS_MESSAGE).body(()).map_err(|e| ResolveError::Io(e.to_string()))
+    }
+}
+
+impl TransportBase for HttpsTransport {
+
+    fn get_server(&self) -> SocketAddr {
+        self.server
+    }
+
+    //COVERS CONNECTING AND EVERY ANSWER OF ONE CALL
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    //EVERY QUERY IS ITS OWN HTTP/2 STREAM ON ONE CONNECTION, ALL IN FLIGHT AT ONCE
+    fn query_all(&mut self, messages: &mut [MessageBase]) -> Vec<Result<MessageBase, ResolveError>> {
+        let mut requests = Vec::new();
+
+        for message in messages.iter_mut() {
+            //RFC 8484 4.1 - ID 0 SO THE SAME QUESTION IS THE SAME URI, AND SO THE SAME CACHE ENTRY
+            message.set_id(0);
+
+            if self.padding && message.get_padding_policy() == PaddingPolicies::None {
+                message.set_padding_policy(PaddingPolicies::Query);
+            }
+
+            let query = message.encode();
+            requests.push(self.build_request(&query).map(|request| (request, query)));
+        }
+
+        let config = match self.tls_config.get_client_config() {
+            Ok(config) => config,
+            Err(e) => return messages.iter().map(|_| Err(ResolveError::Io(e.clone()))).collect()
+        };
+
+        let server = self.server;
+        let server_name = self.server_name.clone();
+        let timeout = self.timeout;
+        let connection = &mut self.connection;
+
+        let responses = self.runtime.block_on(async move {
+            let deadline = tokio::time::Instant::now()+timeout;
+
+            //A CONNECTION THE SERVER HAS SINCE CLOSED GETS REPLACED BEFORE ANYTHING GOES OUT ON IT
+            if connection.as_ref().is_some_and(|connection| connection.driver.is_finished()) {
+                *connection = None;
+            }
+
+            if connection.is_none() {
+                match tokio::time::timeout_at(deadline, connect(server, server_name, config)).await {
+                    Ok(Ok(connected)) => *connection = Some(connected),
+                    Ok(Err(e)) => return requests.iter().map(|_| Err(e.clone())).collect(),
+                    Err(_) => return requests.iter().map(|_| Err(ResolveError::Timeout)).collect()
+                }
+            }
+
+            let sender = connection.as_ref().unwrap().sender.clone();
+            let mut handles = Vec::new();
+
+            for request in requests {
+                handles.push(request.map(|(request, query)| tokio::spawn(send(sender.clone(), request, query))));
+            }
+
+            let mut responses = Vec::new();
+
+            for handle in handles {
+                responses.push(match handle {
+                    Ok(mut handle) => match tokio::time::timeout_at(deadline, &mut handle).await {
+                        Ok(Ok(response)) => response,
+                        Ok(Err(e)) => Err(ResolveError::Io(e.to_string())),
+                        Err(_) => {
+                            handle.abort();
+                            Err(ResolveError::Timeout)
+                        }
+                    },
+                    Err(e) => Err(e)
+                });
+            }
+
+            responses
+        });
+
+        responses.into_iter().zip(messages.iter()).map(|(response, message)| {
+            let mut response = response?;
+
+            if let Some(max_age) = response.max_age {
+                cap_ttls(&mut response.body, max_age).map_err(ResolveError::Malformed)?;
+            }
+
+            let mut response = MessageBase::from_bytes(&response.body, 0).map_err(ResolveError::Malformed)?;
+
+            if !response.is_response_to(message) {
+                return Err(ResolveError::Malformed("Response doesn't answer the query".to_string()));
+            }
+
+            response.set_origin(server);
+            Ok(response)
+        }).collect()
+    }
+}
+
+async fn connect(server: SocketAddr, server_name: ServerName<'static>, config: Arc<ClientConfig>) -> Result<Connection, ResolveError> {
+    let socket = tokio::net::TcpStream::connect(server).await.map_err(|e| ResolveError::Io(e.to_string()))?;
+    socket.set_nodelay(true).map_err(|e| ResolveError::Io(e.to_string()))?;
+
+    let stream = TlsConnector::from(config).connect(server_name, socket).await.map_err(|e| ResolveError::Io(e.to_string()))?;
+
+    if stream.get_ref().1.alpn_protocol() != Some(b"h2") {
+        return Err(ResolveError::Io("Server didn't negotiate HTTP/2".to_string()));
+    }
+
+    let (sender, connection) = h2::client::handshake(stream).await.map_err(|e| ResolveError::Io(e.to_string()))?;
+
+    let driver = tokio::spawn(async move {
+        let _ = connection.await;
+    });
+
+    Ok(Connection {
+        sender,
+        driver
+    })
+}
+
+async fn send(sender: SendRequest<Bytes>, request: Request<()>, query: Vec<u8>) -> Result<HttpResponse, ResolveError> {
+    let mut sender = sender.ready().await.map_err(|e| ResolveError::Io(e.to_string()))?;
+
+    let post = request.method() == Method::POST;
+    let (response, mut stream) = sender.send_request(request, !post).map_err(|e| ResolveError::Io(e.to_string()))?;
+
+    if post {
+        stream.send_data(Bytes::from(query), true).map_err(|e| ResolveError::Io(e.to_string()))?;
+    }
+
+    let response = response.await.map_err(|e| ResolveError::Io(e.to_string()))?;
+
+    if !response.status().is_success() {
+        return Err(ResolveError::HttpStatus(response.status().as_u16()));
+    }
+
+    let content_type = response.headers().get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()).unwrap_or_default();
+    if !content_type.split(';').next().unwrap_or_default().trim().eq_ignore_ascii_case(DNS_MESSAGE) {
+        return Err(ResolveError::Malformed(format!("Unexpected content type: {}", content_type)));
+    }
+
+    //RFC 8484 5.1 - A CACHE MAY HAVE HELD THE ANSWER FOR age OF ITS max-age ALREADY
+    let max_age = get_max_age(response.headers()).map(|max_age| {
+        let age = response.headers().get(header::AGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u32>().ok())
+            .unwrap_or(0);
+        max_age.saturating_sub(age)
+    });
+
+    let mut body = response.into_body();
+    let mut buf = Vec::new();
+
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| ResolveError::Io(e.to_string()))?;
+        let _ = body.flow_control().release_capacity(chunk.len());
+
+        if buf.len()+chunk.len() > MAX_BODY_SIZE {
+            return Err(ResolveError::Malformed(format!("Response body is longer than {} bytes", MAX_BODY_SIZE)));
+        }
+
+        buf.extend_from_slice(&chunk);
+    }
+
+    Ok(HttpResponse {
+        body: buf,
+        max_age
+    })
+}
+
+fn get_max_age(headers: &http::HeaderMap) -> Option<u32> {
+    headers.get_all(header::CACHE_CONTROL).iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .find_map(|directive| {
+            let (name, value) = directive.trim().split_once('=')?;
+            match name.trim().eq_ignore_ascii_case("max-age") {
+                true => value.trim().trim_matches('"').parse::<u32>().ok(),
+                false => None
+            }
+        })
+}
+
+//NO RECORD MAY OUTLIVE THE HTTP RESPONSE IT CAME IN, DONE ON THE WIRE SO EVERY RECORD TYPE IS COVERED
+fn cap_ttls(buf: &mut [u8], max_ttl: u32) -> Result<(), String> {
+    if buf.len() < 12 {
+        return Err(format!("Message is shorter than the header: {} bytes", buf.len()));
+    }
+
+    let qd_count = u16::from_be_bytes([buf[4], buf[5]]);
+    let rr_count = [6, 8, 10].iter().map(|&i| u16::from_be_bytes([buf[i], buf[i+1]]) as usize).sum::<usize>();
+    let mut off = 12;
+
+    for _ in 0..qd_count {
+        off += unpack_domain(buf, off)?.1+4;
+    }
+
+    for _ in 0..rr_count {
+        off += unpack_domain(buf, off)?.1;
+
+        let header = buf.get(off..off+10).ok_or("Record header runs past the end of the message")?;
+        let _type = u16::from_be_bytes([header[0], header[1]]);
+        let ttl = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        let length = u16::from_be_bytes([header[8], header[9]]) as usize;
+
+        //THE OPT TTL IS EXTENDED RCODE AND FLAGS, NOT A TTL
+        if _type != Types::Opt.get_code() && ttl > max_ttl {
+            buf[off+4..off+8].copy_from_slice(&max_ttl.to_be_bytes());
+        }
+
+        off += 10+length;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::net::{IpAddr, SocketAddr, TcpListener};
+    use std::sync::mpsc::{channel, Receiver};
+    use std::sync::Arc;
+    use std::thread;
+    use bytes::Bytes;
+    use http::{header, Response};
+    use rustls::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer};
+    use tokio_rustls::TlsAcceptor;
+    use crate::client::https_transport::HttpsTransport;
+    use crate::client::inter::http_methods::HttpMethods;
+    use crate::client::inter::transport_base::TransportBase;
+    use crate::client::tls_config::tests::{authority, leaf};
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::types::Types;
+    use crate::messages::message_base::MessageBase;
+    use crate::records::a_record::ARecord;
+    use crate::utils::base64;
+    use crate::utils::dns_query::DnsQuery;
+
+    //WHAT THE SERVER SAW OF ONE REQUEST
+    struct Seen {
+        method: String,
+        path: String,
+        query: Option<String>,
+        content_type: Option<String>,
+        message: Vec<u8>
+    }
+
+    //ONE TLS CONNECTION SPEAKING h2, EVERY QUERY IS ANSWERED WITH AN A RECORD AT TTL 3600 AND ONE AT TTL 60
+    //UNDER max-age=300 WITH age: 100
+    fn serve() -> (SocketAddr, Vec<u8>, Receiver<Seen>) {
+        let root = authority("Test Root", None);
+        let server = leaf(&root);
+
+        let mut config = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions().unwrap()
+            .with_no_client_auth()
+            .with_single_cert(vec![server.0.der().clone()], PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(server.1.serialize_der()))).unwrap();
+        config.alpn_protocols = vec![b"h2".to_vec()];
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let address = listener.local_addr().unwrap();
+        let (tx, rx) = channel();
+
+        thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+
+            runtime.block_on(async move {
+                let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+                let (socket, _) = listener.accept().await.unwrap();
+                let stream = TlsAcceptor::from(Arc::new(config)).accept(socket).await.unwrap();
+                let mut connection = h2::server::handshake(stream).await.unwrap();
+
+                while let Some(Ok((request, mut respond))) = connection.accept().await {
+                    let tx = tx.clone();
+
+                    tokio::spawn(async move {
+                        let (parts, mut body) = request.into_parts();
+
+                        let mut message = Vec::new();
+                        while let Some(Ok(chunk)) = body.data().await {
+                            let _ = body.flow_control().release_capacity(chunk.len());
+                            message.extend_from_slice(&chunk);
+                        }
+
+                        if let Some(dns) = parts.uri.query().and_then(|query| query.strip_prefix("dns=")) {
+                            let padding = "=".repeat((4-dns.len()%4)%4);
+                            message = base64::decode(&format!("{}{}", dns.replace('-', "+").replace('_', "/"), padding)).unwrap();
+                        }
+
+                        let query = MessageBase::from_bytes(&message, 0).unwrap();
+                        let mut answer = MessageBase::new(query.get_id());
+                        answer.set_qr(true);
+                        for question in query.get_queries() {
+                            answer.add_query(question);
+                        }
+                        answer.add_answers("example.com", Box::new(ARecord::new(DnsClasses::In, false, 3600, IpAddr::from([192, 0, 2, 1]))));
+                        answer.add_answers("example.com", Box::new(ARecord::new(DnsClasses::In, false, 60, IpAddr::from([192, 0, 2, 2]))));
+
+                        let response = Response::builder()
+                            .header(header::CONTENT_TYPE, "application/dns-message")
+                            .header(header::CACHE_CONTROL, "public, max-age=300")
+                            .header(header::AGE, "100")
+                            .body(()).unwrap();
+
+                        let mut stream = respond.send_response(response, false).unwrap();
+                        stream.send_data(Bytes::from(answer.encode()), true).unwrap();
+
+                        tx.send(Seen {
+                            method: parts.method.to_string(),
+                            path: parts.uri.path().to_string(),
+                            query: parts.uri.query().map(|query| query.to_string()),
+                            content_type: parts.headers.get(header::CONTENT_TYPE).map(|value| value.to_str().unwrap().to_string()),
+                            message
+                        }).unwrap();
+                    });
+                }
+            });
+        });
+
+        (address, root.0.der().to_vec(), rx)
+    }
+
+    fn transport(address: SocketAddr, root: &[u8], method: HttpMethods) -> HttpsTransport {
+        let mut transport = HttpsTransport::new(address, "dns.example").unwrap();
+        transport.set_method(method);
+        transport.get_tls_config_mut().clear_root_certificates();
+        transport.get_tls_config_mut().add_root_certificate(root).unwrap();
+        transport
+    }
+
+    fn query() -> MessageBase {
+        let mut message = MessageBase::new(0x1234);
+        message.set_recursion_desired(true);
+        message.add_query(DnsQuery::new("example.com", Types::A, DnsClasses::In));
+        message
+    }
+
+    fn get_ttls(response: &MessageBase) -> Vec<u32> {
+        response.get_answers().get(&"example.com".to_string()).unwrap().iter()
+            .map(|record| record.get_ttl())
+            .collect()
+    }
+
+    #[test]
+    fn get_sends_base64url_without_padding_and_id_zero() {
+        let (address, root, rx) = serve();
+        let mut transport = transport(address, &root, HttpMethods::Get);
+
+        let response = transport.query(&mut query()).unwrap();
+        let seen = rx.recv().unwrap();
+
+        assert_eq!(seen.method, "GET");
+        assert_eq!(seen.path, "/dns-query");
+        assert_eq!(seen.content_type, None);
+
+        let dns = seen.query.unwrap().strip_prefix("dns=").unwrap().to_string();
+        assert!(dns.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+
+        //RFC 8484 8.1 - PADDED TO A 128 BYTE BLOCK UNLESS TURNED OFF
+        assert_eq!(seen.message.len()%128, 0);
+        assert_eq!(MessageBase::from_bytes(&seen.message, 0).unwrap().get_id(), 0);
+        assert_eq!(response.get_id(), 0);
+    }
+
+    #[test]
+    fn post_sends_the_message_as_the_body() {
+        let (address, root, rx) = serve();
+        let mut transport = transport(address, &root, HttpMethods::Post);
+        transport.set_padding(false);
+
+        let mut message = query();
+        transport.query(&mut message).unwrap();
+        let seen = rx.recv().unwrap();
+
+        assert_eq!(seen.method, "POST");
+        assert_eq!(seen.path, "/dns-query");
+        assert_eq!(seen.query, None);
+        assert_eq!(seen.content_type.as_deref(), Some("application/dns-message"));
+        assert_eq!(seen.message, message.encode());
+        assert_eq!(MessageBase::from_bytes(&seen.message, 0).unwrap().get_id(), 0);
+    }
+
+    #[test]
+    fn ttls_are_capped_to_max_age_minus_age() {
+        let (address, root, _rx) = serve();
+        let mut transport = transport(address, &root, HttpMethods::Post);
+
+        let response = transport.query(&mut query()).unwrap();
+
+        assert_eq!(get_ttls(&response), vec![200, 60]);
+    }
+}
@@ -0,0 +1,25 @@
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum HttpMethods {
+    Get,
+    Post
+}
+
+impl HttpMethods {
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        for c in [Self::Get, Self::Post] {
+            if c.get_name().eq_ignore_ascii_case(name) {
+                return Ok(c);
+            }
+        }
+
+        Err(format!("Couldn't find for name: {}", name))
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            Self::Get => "GET",
+            Self::Post => "POST"
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod stream_base;
+pub mod transport_base;
+pub mod http_methods;
+#[cfg(feature = "quic")]
+pub mod quic_error_codes;
@@ -0,0 +1,45 @@
+//RFC 9250 4.3 - APPLICATION ERROR CODES FOR CLOSING A DOQ CONNECTION OR RESETTING ONE OF ITS STREAMS
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum QuicErrorCodes {
+    NoError,
+    InternalError,
+    ProtocolError,
+    RequestCancelled,
+    ExcessiveLoad,
+    UnspecifiedError
+}
+
+impl QuicErrorCodes {
+
+    pub fn from_code(code: u32) -> Result<Self, String> {
+        for c in [Self::NoError, Self::InternalError, Self::ProtocolError, Self::RequestCancelled, Self::ExcessiveLoad, Self::UnspecifiedError] {
+            if c.get_code() == code {
+                return Ok(c);
+            }
+        }
+
+        Err(format!("Couldn't find for code: {}", code))
+    }
+
+    pub fn get_code(&self) -> u32 {
+        match self {
+            Self::NoError => 0x0,
+            Self::InternalError => 0x1,
+            Self::ProtocolError => 0x2,
+            Self::RequestCancelled => 0x3,
+            Self::ExcessiveLoad => 0x4,
+            Self::UnspecifiedError => 0x5
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            Self::NoError => "DOQ_NO_ERROR",
+            Self::InternalError => "DOQ_INTERNAL_ERROR",
+            Self::ProtocolError => "DOQ_PROTOCOL_ERROR",
+            Self::RequestCancelled => "DOQ_REQUEST_CANCELLED",
+            Self::ExcessiveLoad => "DOQ_EXCESSIVE_LOAD",
+            Self::UnspecifiedError => "DOQ_UNSPECIFIED_ERROR"
+        }
+    }
+}
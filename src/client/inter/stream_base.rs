@@ -0,0 +1,15 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+
+//ANY STREAM CARRYING LENGTH PREFIXED MESSAGES, THE SOCKET UNDERNEATH IS WHERE TIMEOUTS ARE SET
+pub trait StreamBase: Read + Write {
+
+    fn get_socket(&self) -> &TcpStream;
+}
+
+impl StreamBase for TcpStream {
+
+    fn get_socket(&self) -> &TcpStream {
+        self
+    }
+}
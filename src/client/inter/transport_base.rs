@@ -0,0 +1,21 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+use crate::client::resolve_error::ResolveError;
+use crate::messages::message_base::MessageBase;
+
+//A CONNECTION ORIENTED WAY TO ONE SERVER, Client HOLDS ONE PER SERVER AND KEEPS IT BETWEEN QUERIES
+pub trait TransportBase: Send {
+
+    fn get_server(&self) -> SocketAddr;
+
+    fn set_timeout(&mut self, timeout: Duration);
+
+    fn get_timeout(&self) -> Duration;
+
+    //MESSAGE IDS ARE THE TRANSPORT'S TO PICK, RESULTS COME BACK IN THE ORDER OF messages
+    fn query_all(&mut self, messages: &mut [MessageBase]) -> Vec<Result<MessageBase, ResolveError>>;
+
+    fn query(&mut self, message: &mut MessageBase) -> Result<MessageBase, ResolveError> {
+        self.query_all(std::slice::from_mut(message)).remove(0)
+    }
+}
@@ -0,0 +1,15 @@
+pub mod inter;
+pub mod resolve_error;
+pub mod dns_client;
+pub mod tcp_transport;
+pub mod udp_transport;
+#[cfg(feature = "tls")]
+pub mod tls_config;
+#[cfg(feature = "tls")]
+pub mod tls_transport;
+#[cfg(feature = "https")]
+pub mod https_transport;
+#[cfg(feature = "quic")]
+pub mod quic_transport;
+#[cfg(feature = "async")]
+pub mod async_client;
@@ -0,0 +1,245 @@
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use quinn::crypto::rustls::QuicClientConfig;
+use quinn::{ClientConfig, Connection, Endpoint, ReadError, ReadToEndError, VarInt};
+use tokio::runtime::Runtime;
+use tokio::time::Instant;
+use crate::client::inter::quic_error_codes::QuicErrorCodes;
+use crate::client::inter::transport_base::TransportBase;
+use crate::client::resolve_error::ResolveError;
+use crate::client::tls_config::TlsConfig;
+use crate::messages::inter::padding_policies::PaddingPolicies;
+use crate::messages::message_base::MessageBase;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+//RFC 9250 4.1.1 - THE ALPN TOKEN THAT MAKES A QUIC CONNECTION A DOQ ONE
+pub const DOQ_ALPN: &[u8] = b"doq";
+
+//THE 2 BYTE LENGTH PREFIX AND THE LONGEST MESSAGE IT CAN DESCRIBE
+pub const MAX_STREAM_SIZE: usize = 2+65535;
+
+/*
+RFC 9250 4.2 - ONE QUERY PER BIDIRECTIONAL STREAM, EACH WAY IS PREFIXED WITH ITS LENGTH THE SAME AS TCP
++--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+|                    LENGTH                     |
++--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+|                    MESSAGE                    |
++--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+*/
+
+//BLOCKING ON THE OUTSIDE, quinn UNDERNEATH RUNS ON A RUNTIME OF ITS OWN - DON'T CALL IT FROM INSIDE ANOTHER TOKIO RUNTIME
+pub struct QuicTransport {
+    server: SocketAddr,
+    server_name: String,
+    tls_config: TlsConfig,
+    timeout: Duration,
+    padding: bool,
+    runtime: Runtime,
+    endpoint: Option<Endpoint>,
+    connection: Option<Connection>
+}
+
+impl QuicTransport {
+
+    //server_name IS WHAT THE CERTIFICATE IS CHECKED AGAINST AND WHAT GOES OUT AS SNI, AN IP ADDRESS WORKS TOO
+    pub fn new(server: SocketAddr, server_name: &str) -> Result<Self, String> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        rustls::pki_types::ServerName::try_from(server_name).map_err(|_| format!("Invalid TLS server name: {}", server_name))?;
+
+        let mut tls_config = TlsConfig::new();
+        tls_config.set_alpn_protocols(vec![DOQ_ALPN.to_vec()]);
+
+        Ok(Self {
+            server,
+            server_name: server_name.to_string(),
+            tls_config,
+            timeout: DEFAULT_TIMEOUT,
+            padding: true,
+            runtime,
+            endpoint: None,
+            connection: None
+        })
+    }
+
+    //ROOTS AND SPKI PINS - SEE TlsConfig, ALPN IS ALREADY SET TO doq
+    pub fn get_tls_config_mut(&mut self) -> &mut TlsConfig {
+        &mut self.tls_config
+    }
+
+    //RFC 9250 5.4 - QUERIES WITHOUT THEIR OWN PADDING POLICY ARE PADDED TO 128 BYTE BLOCKS
+    pub fn set_padding(&mut self, padding: bool) {
+        self.padding = padding;
+    }
+
+    pub fn is_padding(&self) -> bool {
+        self.padding
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connection.as_ref().is_some_and(|connection| connection.close_reason().is_none())
+    }
+
+    pub fn close(&mut self) {
+        if let Some(connection) = self.connection.take() {
+            connection.close(VarInt::from_u32(QuicErrorCodes::NoError.get_code()), b"");
+        }
+    }
+}
+
+impl TransportBase for QuicTransport {
+
+    fn get_server(&self) -> SocketAddr {
+        self.server
+    }
+
+    //COVERS CONNECTING, THE HANDSHAKE AND EVERY ANSWER OF ONE CALL
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    //EVERY QUERY IS ITS OWN STREAM ON ONE CONNECTION, ALL IN FLIGHT AT ONCE
+    fn query_all(&mut self, messages: &mut [MessageBase]) -> Vec<Result<MessageBase, ResolveError>> {
+        let mut queries = Vec::new();
+
+        for message in messages.iter_mut() {
+            //RFC 9250 4.2.1 - THE STREAM ALREADY TELLS ANSWERS APART, THE ID MUST BE 0
+            message.set_id(0);
+
+            if self.padding && message.get_padding_policy() == PaddingPolicies::None {
+                message.set_padding_policy(PaddingPolicies::Query);
+            }
+
+            let encoded = message.encode();
+            queries.push(match encoded.len() > u16::MAX as usize {
+                true => Err(ResolveError::Io(format!("Message is too long for QUIC: {} bytes", encoded.len()))),
+                false => {
+                    let mut query = Vec::with_capacity(2+encoded.len());
+                    query.extend_from_slice(&(encoded.len() as u16).to_be_bytes());
+                    query.extend_from_slice(&encoded);
+                    Ok(query)
+                }
+            });
+        }
+
+        let config = match self.tls_config.get_client_config()
+                .and_then(|config| QuicClientConfig::try_from(config).map_err(|e| e.to_string())) {
+            Ok(config) => ClientConfig::new(Arc::new(config)),
+            Err(e) => return messages.iter().map(|_| Err(ResolveError::Io(e.clone()))).collect()
+        };
+
+        let server = self.server;
+        let server_name = self.server_name.clone();
+        let timeout = self.timeout;
+        let endpoint = &mut self.endpoint;
+        let connection = &mut self.connection;
+
+        let responses = self.runtime.block_on(async move {
+            let deadline = Instant::now()+timeout;
+
+            //A CONNECTION THE SERVER HAS SINCE CLOSED, OR LET IDLE OUT, GETS REPLACED BEFORE ANYTHING GOES OUT ON IT
+            if connection.as_ref().is_some_and(|connection| connection.close_reason().is_some()) {
+                *connection = None;
+            }
+
+            if connection.is_none() {
+                match tokio::time::timeout_at(deadline, connect(endpoint, server, &server_name, config)).await {
+                    Ok(Ok(connected)) => *connection = Some(connected),
+                    Ok(Err(e)) => return queries.iter().map(|_| Err(e.clone())).collect(),
+                    Err(_) => return queries.iter().map(|_| Err(ResolveError::Timeout)).collect()
+                }
+            }
+
+            let connection = connection.as_ref().unwrap();
+            let mut handles = Vec::new();
+
+            for query in queries {
+                handles.push(query.map(|query| tokio::spawn(exchange(connection.clone(), query, deadline))));
+            }
+
+            let mut responses = Vec::new();
+
+            for handle in handles {
+                responses.push(match handle {
+                    Ok(handle) => handle.await.unwrap_or_else(|e| Err(ResolveError::Io(e.to_string()))),
+                    Err(e) => Err(e)
+                });
+            }
+
+            responses
+        });
+
+        let local = self.endpoint.as_ref().and_then(|endpoint| endpoint.local_addr().ok());
+
+        responses.into_iter().zip(messages.iter()).map(|(response, message)| {
+            let response = response?;
+
+            if response.len() < 2 || u16::from_be_bytes([response[0], response[1]]) as usize != response.len()-2 {
+                return Err(ResolveError::Malformed("Stream length doesn't match the length prefix".to_string()));
+            }
+
+            let mut response = MessageBase::from_bytes(&response[2..], 0).map_err(ResolveError::Malformed)?;
+
+            if !response.is_response_to(message) {
+                return Err(ResolveError::Malformed("Response doesn't answer the query".to_string()));
+            }
+
+            response.set_origin(server);
+            if let Some(local) = local {
+                response.set_destination(local);
+            }
+            Ok(response)
+        }).collect()
+    }
+}
+
+async fn connect(endpoint: &mut Option<Endpoint>, server: SocketAddr, server_name: &str, config: ClientConfig) -> Result<Connection, ResolveError> {
+    if endpoint.is_none() {
+        let local = match server {
+            SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+            SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0))
+        };
+
+        *endpoint = Some(Endpoint::client(local).map_err(|e| ResolveError::Io(e.to_string()))?);
+    }
+
+    endpoint.as_ref().unwrap()
+        .connect_with(config, server, server_name).map_err(|e| ResolveError::Io(e.to_string()))?
+        .await.map_err(|e| ResolveError::Io(e.to_string()))
+}
+
+async fn exchange(connection: Connection, query: Vec<u8>, deadline: Instant) -> Result<Vec<u8>, ResolveError> {
+    let (mut send, mut recv) = tokio::time::timeout_at(deadline, connection.open_bi()).await
+        .map_err(|_| ResolveError::Timeout)?
+        .map_err(|e| ResolveError::Io(e.to_string()))?;
+
+    //THE FIN IS PART OF THE PROTOCOL, IT'S HOW THE SERVER KNOWS THE WHOLE QUERY IS IN
+    send.write_all(&query).await.map_err(|e| ResolveError::Io(e.to_string()))?;
+    send.finish().map_err(|e| ResolveError::Io(e.to_string()))?;
+
+    match tokio::time::timeout_at(deadline, recv.read_to_end(MAX_STREAM_SIZE)).await {
+        Ok(Ok(response)) => Ok(response),
+        Ok(Err(ReadToEndError::Read(ReadError::Reset(code)))) => {
+            let code = u32::try_from(code.into_inner()).map_err(|e| e.to_string()).and_then(QuicErrorCodes::from_code);
+            Err(ResolveError::Io(match code {
+                Ok(code) => format!("Server reset the stream: {}", code.get_name()),
+                Err(e) => format!("Server reset the stream: {}", e)
+            }))
+        }
+        Ok(Err(e)) => Err(ResolveError::Io(e.to_string())),
+        Err(_) => {
+            //RFC 9250 4.5 - LET THE SERVER KNOW NOBODY IS WAITING ON THIS ONE ANYMORE
+            let _ = recv.stop(VarInt::from_u32(QuicErrorCodes::RequestCancelled.get_code()));
+            Err(ResolveError::Timeout)
+        }
+    }
+}
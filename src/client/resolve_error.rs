@@ -0,0 +1,52 @@
+use std::error::Error;
+use std::fmt;
+use crate::messages::inter::response_codes::ResponseCodes;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResolveError {
+    Timeout,
+    ServFail,
+    NxDomain,
+    ErrorResponse(ResponseCodes),
+    Malformed(String),
+    HttpStatus(u16),
+    Loop(String),
+    LameDelegation(String),
+    NoService,
+    NoAddress(String),
+    Io(String)
+}
+
+impl ResolveError {
+
+    //NOERROR ISN'T AN ERROR, EVERYTHING ELSE THE SERVER CAN ANSWER WITH IS
+    pub fn from_response_code(response_code: ResponseCodes) -> Option<Self> {
+        match response_code {
+            ResponseCodes::NoError => None,
+            ResponseCodes::ServerFailure => Some(Self::ServFail),
+            ResponseCodes::NameError => Some(Self::NxDomain),
+            response_code => Some(Self::ErrorResponse(response_code))
+        }
+    }
+}
+
+impl fmt::Display for ResolveError {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout => write!(f, "Timed out waiting for a response"),
+            Self::ServFail => write!(f, "Server failed to complete the query: SERVFAIL"),
+            Self::NxDomain => write!(f, "Domain doesn't exist: NXDOMAIN"),
+            Self::ErrorResponse(response_code) => write!(f, "Server answered with an error: {}", response_code.get_name()),
+            Self::Malformed(e) => write!(f, "Malformed response: {}", e),
+            Self::HttpStatus(status) => write!(f, "Server answered with HTTP status: {}", status),
+            Self::Loop(e) => write!(f, "Gave up following the answer: {}", e),
+            Self::LameDelegation(zone) => write!(f, "No server delegated {} would answer for it", zone),
+            Self::NoService => write!(f, "Domain says the service isn't available there"),
+            Self::NoAddress(name) => write!(f, "Found no addresses for: {}", name),
+            Self::Io(e) => write!(f, "{}", e)
+        }
+    }
+}
+
+impl Error for ResolveError {}
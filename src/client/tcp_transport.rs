@@ -0,0 +1,464 @@
+use std::collections::HashSet;
+use std::io::{ErrorKind, Read};
+use std::net::{SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+use crate::client::inter::stream_base::StreamBase;
+use crate::client::inter::transport_base::TransportBase;
+use crate::client::resolve_error::ResolveError;
+use crate::messages::message_base::MessageBase;
+use crate::utils::random;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/*
+RFC 1035 4.2.2 - EVERY MESSAGE ON THE STREAM IS PREFIXED WITH ITS LENGTH
++--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+|                    LENGTH                     |
++--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+|                    MESSAGE                    |
++--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+--+
+*/
+
+pub struct TcpTransport {
+    server: SocketAddr,
+    timeout: Duration,
+    stream: Option<TcpStream>
+}
+
+impl TcpTransport {
+
+    pub fn new(server: SocketAddr) -> Self {
+        Self {
+            server,
+            timeout: DEFAULT_TIMEOUT,
+            stream: None
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    pub fn close(&mut self) {
+        self.stream = None;
+    }
+}
+
+impl TransportBase for TcpTransport {
+
+    fn get_server(&self) -> SocketAddr {
+        self.server
+    }
+
+    //COVERS CONNECTING, SENDING AND EVERY ANSWER OF ONE CALL
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    //EVERY QUERY GOES OUT BEFORE ANY ANSWER IS READ, RFC 7766 6.2.1.1 LETS THE SERVER ANSWER THEM IN ANY ORDER
+    fn query_all(&mut self, messages: &mut [MessageBase]) -> Vec<Result<MessageBase, ResolveError>> {
+        let server = self.server;
+        pipeline(&mut self.stream, messages, self.timeout, |deadline| connect(server, deadline))
+    }
+}
+
+pub fn connect(server: SocketAddr, deadline: Instant) -> Result<TcpStream, ResolveError> {
+    let stream = TcpStream::connect_timeout(&server, remaining(deadline)?).map_err(map_error)?;
+    stream.set_nodelay(true).map_err(|e| ResolveError::Io(e.to_string()))?;
+    Ok(stream)
+}
+
+//EACH MESSAGE GETS A RANDOM ID THAT'S UNIQUE WITHIN THE CALL, RESULTS COME BACK IN THE ORDER OF messages
+//stream IS LEFT OPEN FOR THE NEXT CALL UNLESS SOMETHING WENT WRONG ON IT
+pub fn pipeline<S, F>(stream: &mut Option<S>, messages: &mut [MessageBase], timeout: Duration, mut connect: F) -> Vec<Result<MessageBase, ResolveError>>
+where
+    S: StreamBase,
+    F: FnMut(Instant) -> Result<S, ResolveError>
+{
+    let mut ids = HashSet::new();
+    for message in messages.iter_mut() {
+        let mut id = u16::from_be_bytes(random::gen_array());
+        while !ids.insert(id) {
+            id = u16::from_be_bytes(random::gen_array());
+        }
+        message.set_id(id);
+    }
+
+    let mut results: Vec<Option<Result<MessageBase, ResolveError>>> = messages.iter().map(|_| None).collect();
+    let deadline = Instant::now()+timeout;
+
+    //THE SERVER MAY HAVE CLOSED A CONNECTION WE LEFT IDLE, THAT'S WORTH ONE RECONNECT
+    let mut reconnected = stream.is_none();
+
+    loop {
+        match exchange(stream, &mut connect, messages, &mut results, deadline) {
+            Ok(()) => break,
+            Err(e) => {
+                *stream = None;
+
+                if reconnected || !matches!(e, ResolveError::Io(_)) {
+                    for result in results.iter_mut().filter(|result| result.is_none()) {
+                        *result = Some(Err(e.clone()));
+                    }
+                    break;
+                }
+
+                reconnected = true;
+            }
+        }
+    }
+
+    results.into_iter().map(|result| result.unwrap_or(Err(ResolveError::Timeout))).collect()
+}
+
+fn exchange<S, F>(stream: &mut Option<S>, connect: &mut F, messages: &[MessageBase], results: &mut [Option<Result<MessageBase, ResolveError>>], deadline: Instant) -> Result<(), ResolveError>
+where
+    S: StreamBase,
+    F: FnMut(Instant) -> Result<S, ResolveError>
+{
+    let mut buf = Vec::new();
+
+    for (message, result) in messages.iter().zip(results.iter_mut()) {
+        if result.is_some() {
+            continue;
+        }
+
+        let encoded = message.encode();
+        if encoded.len() > u16::MAX as usize {
+            *result = Some(Err(ResolveError::Io(format!("Message is too long for TCP: {} bytes", encoded.len()))));
+            continue;
+        }
+
+        buf.extend_from_slice(&(encoded.len() as u16).to_be_bytes());
+        buf.extend_from_slice(&encoded);
+    }
+
+    if buf.is_empty() {
+        return Ok(());
+    }
+
+    if stream.is_none() {
+        *stream = Some(connect(deadline)?);
+    }
+
+    let stream = stream.as_mut().unwrap();
+    let server = stream.get_socket().peer_addr().map_err(|e| ResolveError::Io(e.to_string()))?;
+    let local = stream.get_socket().local_addr().map_err(|e| ResolveError::Io(e.to_string()))?;
+
+    stream.get_socket().set_write_timeout(Some(remaining(deadline)?)).map_err(|e| ResolveError::Io(e.to_string()))?;
+    stream.write_all(&buf).map_err(map_error)?;
+    stream.flush().map_err(map_error)?;
+
+    while results.iter().any(|result| result.is_none()) {
+        stream.get_socket().set_read_timeout(Some(remaining(deadline)?)).map_err(|e| ResolveError::Io(e.to_string()))?;
+        let response = read_message(stream, deadline)?;
+
+        match MessageBase::from_bytes(&response, 0) {
+            Ok(mut response) => {
+                let index = messages.iter().zip(results.iter())
+                    .position(|(message, result)| result.is_none() && response.is_response_to(message));
+
+                //ANSWERS TO QUERIES WE GAVE UP ON IN AN EARLIER CALL CAN STILL BE ON THE STREAM
+                if let Some(index) = index {
+                    response.set_origin(server);
+                    response.set_destination(local);
+                    results[index] = Some(Ok(response));
+                }
+            }
+            Err(e) => {
+                if response.len() < 2 {
+                    continue;
+                }
+
+                let id = u16::from_be_bytes([response[0], response[1]]);
+                let index = messages.iter().zip(results.iter())
+                    .position(|(message, result)| result.is_none() && message.get_id() == id);
+
+                if let Some(index) = index {
+                    results[index] = Some(Err(ResolveError::Malformed(e)));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+//THE CALLER KEEPS THE SOCKET'S READ TIMEOUT AT OR UNDER WHAT'S LEFT BEFORE deadline
+pub fn read_message<R: Read>(stream: &mut R, deadline: Instant) -> Result<Vec<u8>, ResolveError> {
+    let mut length = [0u8; 2];
+    read_exact(stream, &mut length, deadline)?;
+
+    let mut buf = vec![0u8; u16::from_be_bytes(length) as usize];
+    read_exact(stream, &mut buf, deadline)?;
+
+    Ok(buf)
+}
+
+fn read_exact<R: Read>(stream: &mut R, buf: &mut [u8], deadline: Instant) -> Result<(), ResolveError> {
+    let mut read = 0;
+
+    while read < buf.len() {
+        remaining(deadline)?;
+
+        match stream.read(&mut buf[read..]) {
+            Ok(0) => return Err(ResolveError::Io("Connection closed by the server".to_string())),
+            Ok(n) => read += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(map_error(e))
+        }
+    }
+
+    Ok(())
+}
+
+pub fn remaining(deadline: Instant) -> Result<Duration, ResolveError> {
+    match deadline.saturating_duration_since(Instant::now()) {
+        remaining if remaining.is_zero() => Err(ResolveError::Timeout),
+        remaining => Ok(remaining)
+    }
+}
+
+pub fn map_error(e: std::io::Error) -> ResolveError {
+    match e.kind() {
+        ErrorKind::WouldBlock | ErrorKind::TimedOut => ResolveError::Timeout,
+        _ => ResolveError::Io(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::time::Duration;
+    use crate::client::inter::stream_base::StreamBase;
+    use crate::client::resolve_error::ResolveError;
+    use crate::client::tcp_transport::pipeline;
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::types::Types;
+    use crate::messages::message_base::MessageBase;
+    use crate::records::unknown_record::UnknownRecord;
+    use crate::utils::dns_query::DnsQuery;
+
+    type Respond = Box<dyn FnMut(Vec<MessageBase>) -> Vec<MessageBase>>;
+
+    //THE MESSAGES LIVE IN MEMORY, THE REAL SOCKET IS ONLY THERE FOR TIMEOUTS AND ADDRESSES
+    //EVERY FLUSH HANDS WHAT WAS WRITTEN TO respond, WHATEVER IT RETURNS IS READ BACK IN THAT ORDER
+    struct MemoryStream {
+        socket: TcpStream,
+        _peer: TcpStream,
+        respond: Respond,
+        written: Vec<u8>,
+        readable: Vec<u8>,
+        writes: usize
+    }
+
+    impl MemoryStream {
+
+        fn new(respond: Respond) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let socket = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+            let (peer, _) = listener.accept().unwrap();
+
+            Self {
+                socket,
+                _peer: peer,
+                respond,
+                written: Vec::new(),
+                readable: Vec::new(),
+                writes: 0
+            }
+        }
+    }
+
+    impl Read for MemoryStream {
+
+        //NOTHING LEFT TO READ IS THE SERVER CLOSING THE CONNECTION
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let size = buf.len().min(self.readable.len());
+            buf[..size].copy_from_slice(&self.readable[..size]);
+            self.readable.drain(..size);
+            Ok(size)
+        }
+    }
+
+    impl Write for MemoryStream {
+
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.writes += 1;
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            let mut queries = Vec::new();
+            let mut off = 0;
+
+            while off < self.written.len() {
+                let length = u16::from_be_bytes([self.written[off], self.written[off+1]]) as usize;
+                queries.push(MessageBase::from_bytes(&self.written[off+2..off+2+length], 0).unwrap());
+                off += 2+length;
+            }
+
+            self.written.clear();
+
+            for response in (self.respond)(queries) {
+                let encoded = response.encode();
+                self.readable.extend_from_slice(&(encoded.len() as u16).to_be_bytes());
+                self.readable.extend_from_slice(&encoded);
+            }
+
+            Ok(())
+        }
+    }
+
+    impl StreamBase for MemoryStream {
+
+        fn get_socket(&self) -> &TcpStream {
+            &self.socket
+        }
+    }
+
+    fn query(name: &str) -> MessageBase {
+        let mut message = MessageBase::new(0);
+        message.add_query(DnsQuery::new(name, Types::A, DnsClasses::In));
+        message
+    }
+
+    fn answer(mut message: MessageBase) -> MessageBase {
+        message.set_qr(true);
+        message
+    }
+
+    fn names(results: &[Result<MessageBase, ResolveError>]) -> Vec<String> {
+        results.iter()
+            .map(|result| result.as_ref().ok().unwrap().get_queries()[0].get_query().unwrap())
+            .collect()
+    }
+
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn pipelined_answers_in_any_order() {
+        let mut stream = None;
+        let mut messages = vec![query("a.example"), query("b.example"), query("c.example")];
+
+        let results = pipeline(&mut stream, &mut messages, TIMEOUT, |_| {
+            Ok(MemoryStream::new(Box::new(|queries: Vec<MessageBase>| queries.into_iter().rev().map(answer).collect())))
+        });
+
+        assert_eq!(names(&results), vec!["a.example", "b.example", "c.example"]);
+        for (message, result) in messages.iter().zip(results.iter()) {
+            assert_eq!(result.as_ref().ok().unwrap().get_id(), message.get_id());
+        }
+
+        //EVERY QUERY WENT OUT IN ONE WRITE, AND THE STREAM IS KEPT FOR THE NEXT CALL
+        let stream = stream.unwrap();
+        assert_eq!(stream.writes, 1);
+        assert!(stream.readable.is_empty());
+    }
+
+    #[test]
+    fn stale_answers_are_skipped() {
+        let mut stream = None;
+        let mut previous: Vec<MessageBase> = Vec::new();
+
+        let respond = move |queries: Vec<MessageBase>| {
+            //EVERYTHING ANSWERED LAST TIME COMES BACK AGAIN FIRST
+            let ids: Vec<u16> = queries.iter().map(|query| query.get_id()).collect();
+            let mut responses: Vec<MessageBase> = previous.drain(..).filter(|stale| !ids.contains(&stale.get_id())).collect();
+
+            for query in queries {
+                previous.push(answer(MessageBase::from_bytes(&query.encode(), 0).unwrap()));
+                responses.push(answer(query));
+            }
+
+            responses
+        };
+
+        let mut respond: Option<Respond> = Some(Box::new(respond));
+        let mut connects = 0;
+
+        //THE SAME QUESTION BOTH TIMES, ONLY THE ID TELLS THE STALE ANSWER APART
+        for name in ["a.example", "a.example"] {
+            let mut messages = vec![query(name)];
+            let results = pipeline(&mut stream, &mut messages, TIMEOUT, |_| {
+                connects += 1;
+                Ok(MemoryStream::new(respond.take().unwrap()))
+            });
+
+            assert_eq!(results[0].as_ref().ok().unwrap().get_id(), messages[0].get_id());
+        }
+
+        assert_eq!(connects, 1);
+        assert!(stream.unwrap().readable.is_empty());
+    }
+
+    #[test]
+    fn idle_close_is_worth_one_reconnect() {
+        let mut stream = Some(MemoryStream::new(Box::new(|_| Vec::new())));
+        let mut connects = 0;
+
+        let results = pipeline(&mut stream, &mut [query("a.example")], TIMEOUT, |_| {
+            connects += 1;
+            Ok(MemoryStream::new(Box::new(|queries: Vec<MessageBase>| queries.into_iter().map(answer).collect())))
+        });
+
+        assert_eq!(names(&results), vec!["a.example"]);
+        assert_eq!(connects, 1);
+        assert!(stream.is_some());
+    }
+
+    #[test]
+    fn a_closed_reconnect_is_not_tried_again() {
+        let mut stream = Some(MemoryStream::new(Box::new(|_| Vec::new())));
+        let mut connects = 0;
+
+        let results = pipeline(&mut stream, &mut [query("a.example")], TIMEOUT, |_| {
+            connects += 1;
+            Ok(MemoryStream::new(Box::new(|_| Vec::new())))
+        });
+
+        assert!(matches!(results[0], Err(ResolveError::Io(_))));
+        assert_eq!(connects, 1);
+        assert!(stream.is_none());
+
+        //A FRESH CONNECTION CLOSING STRAIGHT AWAY ISN'T IDLE, THAT'S NO RECONNECT AT ALL
+        let results = pipeline(&mut stream, &mut [query("a.example")], TIMEOUT, |_| {
+            connects += 1;
+            Ok(MemoryStream::new(Box::new(|_| Vec::new())))
+        });
+
+        assert!(matches!(results[0], Err(ResolveError::Io(_))));
+        assert_eq!(connects, 2);
+    }
+
+    #[test]
+    fn messages_over_65535_bytes_are_refused() {
+        let mut large = query("large.example");
+        for _ in 0..2 {
+            large.add_additional_records("large.example", Box::new(UnknownRecord::new(DnsClasses::In, 0, 65280, &[0u8; 40000])));
+        }
+
+        let mut stream = None;
+        let mut messages = vec![query("a.example"), large];
+
+        let results = pipeline(&mut stream, &mut messages, TIMEOUT, |_| {
+            Ok(MemoryStream::new(Box::new(|queries: Vec<MessageBase>| queries.into_iter().map(answer).collect())))
+        });
+
+        assert_eq!(names(&results[..1]), vec!["a.example"]);
+        assert!(matches!(&results[1], Err(ResolveError::Io(e)) if e.starts_with("Message is too long for TCP")));
+
+        //NOTHING THAT FITS, NOTHING TO CONNECT FOR
+        let mut stream = None;
+        let results = pipeline::<MemoryStream, _>(&mut stream, &mut messages[1..], TIMEOUT, |_| unreachable!());
+        assert!(matches!(&results[0], Err(ResolveError::Io(_))));
+        assert!(stream.is_none());
+    }
+}
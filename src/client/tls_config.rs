@@ -0,0 +1,299 @@
+use std::sync::Arc;
+use ring::digest::{digest, SHA256};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use webpki::{EndEntityCert, KeyUsage, VerifiedPath};
+use crate::utils::base64;
+
+//HOW ENCRYPTED TRANSPORTS CHECK WHO THEY'RE TALKING TO, THE rustls CONFIG IS BUILT ONCE AND REBUILT ONLY AFTER A CHANGE
+pub struct TlsConfig {
+    roots: RootCertStore,
+    pins: Vec<Vec<u8>>,
+    alpn_protocols: Vec<Vec<u8>>,
+    config: Option<Arc<ClientConfig>>
+}
+
+impl Default for TlsConfig {
+
+    fn default() -> Self {
+        Self {
+            roots: RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec()
+            },
+            pins: Vec::new(),
+            alpn_protocols: Vec::new(),
+            config: None
+        }
+    }
+}
+
+impl TlsConfig {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //DER, ON TOP OF THE BUNDLED WEB PKI ROOTS - A PRIVATE CA OR A TEST SERVER'S SELF SIGNED ONE
+    pub fn add_root_certificate(&mut self, certificate: &[u8]) -> Result<(), String> {
+        self.roots.add(CertificateDer::from(certificate.to_vec())).map_err(|e| format!("Invalid root certificate: {}", e))?;
+        self.config = None;
+        Ok(())
+    }
+
+    pub fn clear_root_certificates(&mut self) {
+        self.roots = RootCertStore::empty();
+        self.config = None;
+    }
+
+    //RFC 7858 4.2 - BASE64 SHA-256 OF THE SUBJECT PUBLIC KEY INFO, THE SAME FORMAT AS HPKP pin-sha256
+    //A PINNED LEAF KEY IS ENOUGH ON ITS OWN, A PINNED INTERMEDIATE OR ROOT ALSO NEEDS THE CHAIN AND SERVER NAME TO VERIFY
+    pub fn add_spki_pin(&mut self, pin: &str) -> Result<(), String> {
+        let pin = base64::decode(pin)?;
+
+        if pin.len() != 32 {
+            return Err(format!("Invalid SPKI pin length: {}", pin.len()));
+        }
+
+        self.pins.push(pin);
+        self.config = None;
+        Ok(())
+    }
+
+    pub fn set_alpn_protocols(&mut self, alpn_protocols: Vec<Vec<u8>>) {
+        self.alpn_protocols = alpn_protocols;
+        self.config = None;
+    }
+
+    pub fn get_alpn_protocols(&self) -> &[Vec<u8>] {
+        &self.alpn_protocols
+    }
+
+    pub fn get_client_config(&mut self) -> Result<Arc<ClientConfig>, String> {
+        if let Some(ref config) = self.config {
+            return Ok(config.clone());
+        }
+
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| e.to_string())?;
+
+        let mut config = match self.pins.is_empty() {
+            true => builder.with_root_certificates(self.roots.clone()).with_no_client_auth(),
+            false => builder.dangerous().with_custom_certificate_verifier(Arc::new(SpkiPinVerifier {
+                roots: self.roots.clone(),
+                pins: self.pins.clone(),
+                provider
+            })).with_no_client_auth()
+        };
+        config.alpn_protocols = self.alpn_protocols.clone();
+
+        let config = Arc::new(config);
+        self.config = Some(config.clone());
+        Ok(config)
+    }
+}
+
+#[derive(Debug)]
+struct SpkiPinVerifier {
+    roots: RootCertStore,
+    pins: Vec<Vec<u8>>,
+    provider: Arc<CryptoProvider>
+}
+
+impl SpkiPinVerifier {
+
+    fn is_pinned(&self, spki: &[u8]) -> bool {
+        self.pins.iter().any(|pin| pin.as_slice() == digest(&SHA256, spki).as_ref())
+    }
+
+    //TRUST ANCHORS KEEP THEIR KEY WITHOUT THE OUTER SEQUENCE, INTERMEDIATES KEEP ALL OF IT
+    fn is_pinned_path(&self, path: &VerifiedPath<'_>) -> bool {
+        path.intermediate_certificates().any(|certificate| self.is_pinned(certificate.subject_public_key_info().as_ref())) ||
+            self.is_pinned(&der_sequence(path.anchor().subject_public_key_info.as_ref()))
+    }
+}
+
+impl ServerCertVerifier for SpkiPinVerifier {
+
+    //THE HANDSHAKE SIGNATURE PROVES THE SERVER HOLDS THE LEAF KEY, SO A PINNED LEAF IS TRUSTED AS IS - RFC 7858 OUT OF BAND KEY PINNING
+    //ANY OTHER PIN ONLY COUNTS ON A PATH THAT VERIFIES UP TO A ROOT, A LEAF MERELY SENT ALONG WITH THE PINNED INTERMEDIATE ISN'T ENOUGH
+    fn verify_server_cert(&self, end_entity: &CertificateDer<'_>, intermediates: &[CertificateDer<'_>], server_name: &ServerName<'_>,
+            _ocsp_response: &[u8], now: UnixTime) -> Result<ServerCertVerified, rustls::Error> {
+        let certificate = EndEntityCert::try_from(end_entity)
+            .map_err(|e| rustls::Error::General(format!("Invalid server certificate: {}", e)))?;
+
+        if self.is_pinned(certificate.subject_public_key_info().as_ref()) {
+            return Ok(ServerCertVerified::assertion());
+        }
+
+        //PATH BUILDING MOVES ON TO THE NEXT CANDIDATE WHEN A PATH CARRIES NO PIN
+        let is_pinned_path = |path: &VerifiedPath<'_>| match self.is_pinned_path(path) {
+            true => Ok(()),
+            false => Err(webpki::Error::UnknownIssuer)
+        };
+
+        certificate.verify_for_usage(self.provider.signature_verification_algorithms.all, &self.roots.roots, intermediates, now,
+                KeyUsage::server_auth(), None, Some(&is_pinned_path))
+            .map_err(|e| rustls::Error::General(format!("No verified chain carries a pinned key: {}", e)))?;
+
+        certificate.verify_is_valid_for_subject_name(server_name)
+            .map_err(|e| rustls::Error::General(format!("Server certificate doesn't match the server name: {}", e)))?;
+
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+//DER SEQUENCE HEADER AROUND contents, LONG FORM LENGTHS TAKE AS FEW BYTES AS THEY NEED
+fn der_sequence(contents: &[u8]) -> Vec<u8> {
+    let mut buf = vec![0x30];
+
+    match contents.len() {
+        length @ 0..=0x7f => buf.push(length as u8),
+        length => {
+            let bytes = (length as u32).to_be_bytes();
+            let skip = bytes.iter().take_while(|b| **b == 0).count();
+            buf.push(0x80 | (bytes.len()-skip) as u8);
+            buf.extend_from_slice(&bytes[skip..]);
+        }
+    }
+
+    buf.extend_from_slice(contents);
+    buf
+}
+
+#[cfg(test)]
+pub mod tests {
+
+    use std::sync::Arc;
+    use rcgen::{BasicConstraints, Certificate, CertificateParams, DnType, ExtendedKeyUsagePurpose, IsCa, KeyPair};
+    use ring::digest::{digest, SHA256};
+    use rustls::client::danger::ServerCertVerifier;
+    use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+    use crate::client::tls_config::{SpkiPinVerifier, TlsConfig};
+    use crate::utils::base64;
+
+    pub type Issued = (Certificate, KeyPair);
+
+    pub fn authority(name: &str, issuer: Option<&Issued>) -> Issued {
+        let key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        params.distinguished_name.push(DnType::CommonName, name);
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+
+        let certificate = match issuer {
+            Some((issuer, issuer_key)) => params.signed_by(&key, issuer, issuer_key),
+            None => params.self_signed(&key)
+        }.unwrap();
+
+        (certificate, key)
+    }
+
+    pub fn leaf(issuer: &Issued) -> Issued {
+        let key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(vec!["dns.example".to_string()]).unwrap();
+        params.distinguished_name.push(DnType::CommonName, "dns.example");
+        params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+
+        (params.signed_by(&key, &issuer.0, &issuer.1).unwrap(), key)
+    }
+
+    fn pin(key: &KeyPair) -> String {
+        base64::encode(digest(&SHA256, &key.public_key_der()).as_ref())
+    }
+
+    fn verify(config: &TlsConfig, server_name: &str, chain: &[&Certificate]) -> Result<(), rustls::Error> {
+        let verifier = SpkiPinVerifier {
+            roots: config.roots.clone(),
+            pins: config.pins.clone(),
+            provider: Arc::new(rustls::crypto::ring::default_provider())
+        };
+
+        let intermediates: Vec<CertificateDer> = chain[1..].iter().map(|certificate| certificate.der().clone()).collect();
+
+        verifier.verify_server_cert(chain[0].der(), &intermediates, &ServerName::try_from(server_name.to_string()).unwrap(), &[], UnixTime::now())
+            .map(|_| ())
+    }
+
+    //LOCAL CA -> INTERMEDIATE -> dns.example, WITH THE CA AS THE ONLY ROOT
+    fn chain() -> (Issued, Issued, Issued, TlsConfig) {
+        let root = authority("Test Root", None);
+        let intermediate = authority("Test Intermediate", Some(&root));
+        let leaf = leaf(&intermediate);
+
+        let mut config = TlsConfig::new();
+        config.clear_root_certificates();
+        config.add_root_certificate(root.0.der()).unwrap();
+
+        (root, intermediate, leaf, config)
+    }
+
+    #[test]
+    fn pinned_intermediate_on_verified_chain() {
+        let (_, intermediate, leaf, mut config) = chain();
+        config.add_spki_pin(&pin(&intermediate.1)).unwrap();
+
+        assert!(verify(&config, "dns.example", &[&leaf.0, &intermediate.0]).is_ok());
+    }
+
+    #[test]
+    fn pinned_root_on_verified_chain() {
+        let (root, intermediate, leaf, mut config) = chain();
+        config.add_spki_pin(&pin(&root.1)).unwrap();
+
+        assert!(verify(&config, "dns.example", &[&leaf.0, &intermediate.0]).is_ok());
+    }
+
+    #[test]
+    fn pinned_leaf_needs_no_roots() {
+        let (_, intermediate, leaf, mut config) = chain();
+        config.clear_root_certificates();
+        config.add_spki_pin(&pin(&leaf.1)).unwrap();
+
+        assert!(verify(&config, "dns.example", &[&leaf.0]).is_ok());
+        assert!(verify(&config, "dns.example", &[&leaf.0, &intermediate.0]).is_ok());
+    }
+
+    #[test]
+    fn wrong_pin_is_rejected() {
+        let (_, intermediate, leaf, mut config) = chain();
+        config.add_spki_pin(&pin(&KeyPair::generate().unwrap())).unwrap();
+
+        assert!(verify(&config, "dns.example", &[&leaf.0, &intermediate.0]).is_err());
+    }
+
+    #[test]
+    fn forged_leaf_with_pinned_intermediate_is_rejected() {
+        let (_, intermediate, _, mut config) = chain();
+        config.add_spki_pin(&pin(&intermediate.1)).unwrap();
+
+        //SAME NAMES, BUT SIGNED BY A KEY THE ATTACKER HOLDS INSTEAD OF THE PINNED INTERMEDIATE'S
+        let forger = authority("Test Intermediate", None);
+        let forged = leaf(&forger);
+
+        assert!(verify(&config, "dns.example", &[&forged.0, &intermediate.0]).is_err());
+        assert!(verify(&config, "dns.example", &[&forged.0, &intermediate.0, &forger.0]).is_err());
+    }
+
+    #[test]
+    fn pinned_intermediate_still_checks_the_name() {
+        let (_, intermediate, leaf, mut config) = chain();
+        config.add_spki_pin(&pin(&intermediate.1)).unwrap();
+
+        assert!(verify(&config, "other.example", &[&leaf.0, &intermediate.0]).is_err());
+    }
+}
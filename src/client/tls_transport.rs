@@ -0,0 +1,142 @@
+use std::net::{SocketAddr, TcpStream};
+use std::time::{Duration, Instant};
+use rustls::pki_types::ServerName;
+use rustls::{ClientConnection, StreamOwned};
+use crate::client::inter::stream_base::StreamBase;
+use crate::client::inter::transport_base::TransportBase;
+use crate::client::resolve_error::ResolveError;
+use crate::client::tcp_transport::{connect, map_error, pipeline, remaining};
+use crate::client::tls_config::TlsConfig;
+use crate::messages::inter::padding_policies::PaddingPolicies;
+use crate::messages::message_base::MessageBase;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+//RFC 7858 3.4 LEAVES IT TO US, CLOSING BEFORE THE SERVER DOES SAVES A FAILED WRITE ON A DEAD CONNECTION
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+type TlsStream = StreamOwned<ClientConnection, TcpStream>;
+
+impl StreamBase for TlsStream {
+
+    fn get_socket(&self) -> &TcpStream {
+        &self.sock
+    }
+}
+
+pub struct TlsTransport {
+    server: SocketAddr,
+    server_name: ServerName<'static>,
+    tls_config: TlsConfig,
+    timeout: Duration,
+    idle_timeout: Duration,
+    padding: bool,
+    stream: Option<TlsStream>,
+    last_used: Instant
+}
+
+impl TlsTransport {
+
+    //server_name IS WHAT THE CERTIFICATE IS CHECKED AGAINST AND WHAT GOES OUT AS SNI, AN IP ADDRESS WORKS TOO
+    pub fn new(server: SocketAddr, server_name: &str) -> Result<Self, String> {
+        let server_name = ServerName::try_from(server_name.to_string())
+            .map_err(|_| format!("Invalid TLS server name: {}", server_name))?;
+
+        Ok(Self {
+            server,
+            server_name,
+            tls_config: TlsConfig::new(),
+            timeout: DEFAULT_TIMEOUT,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            padding: true,
+            stream: None,
+            last_used: Instant::now()
+        })
+    }
+
+    //ROOTS AND SPKI PINS - SEE TlsConfig
+    pub fn get_tls_config_mut(&mut self) -> &mut TlsConfig {
+        &mut self.tls_config
+    }
+
+    pub fn set_idle_timeout(&mut self, idle_timeout: Duration) {
+        self.idle_timeout = idle_timeout;
+    }
+
+    pub fn get_idle_timeout(&self) -> Duration {
+        self.idle_timeout
+    }
+
+    //RFC 8467 - QUERIES WITHOUT THEIR OWN PADDING POLICY ARE PADDED TO 128 BYTE BLOCKS
+    pub fn set_padding(&mut self, padding: bool) {
+        self.padding = padding;
+    }
+
+    pub fn is_padding(&self) -> bool {
+        self.padding
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    pub fn close(&mut self) {
+        if let Some(mut stream) = self.stream.take() {
+            stream.conn.send_close_notify();
+            let _ = stream.conn.complete_io(&mut stream.sock);
+        }
+    }
+}
+
+impl TransportBase for TlsTransport {
+
+    fn get_server(&self) -> SocketAddr {
+        self.server
+    }
+
+    //COVERS CONNECTING, THE HANDSHAKE, SENDING AND EVERY ANSWER OF ONE CALL
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn query_all(&mut self, messages: &mut [MessageBase]) -> Vec<Result<MessageBase, ResolveError>> {
+        if self.stream.is_some() && self.last_used.elapsed() >= self.idle_timeout {
+            self.close();
+        }
+
+        if self.padding {
+            for message in messages.iter_mut().filter(|message| message.get_padding_policy() == PaddingPolicies::None) {
+                message.set_padding_policy(PaddingPolicies::Query);
+            }
+        }
+
+        let config = match self.tls_config.get_client_config() {
+            Ok(config) => config,
+            Err(e) => return messages.iter().map(|_| Err(ResolveError::Io(e.clone()))).collect()
+        };
+
+        let server = self.server;
+        let server_name = self.server_name.clone();
+
+        let results = pipeline(&mut self.stream, messages, self.timeout, |deadline| {
+            let mut socket = connect(server, deadline)?;
+            let mut connection = ClientConnection::new(config.clone(), server_name.clone()).map_err(|e| ResolveError::Io(e.to_string()))?;
+
+            //HANDSHAKE UP FRONT SO A BAD CERTIFICATE COMES BACK AS ITSELF, NOT AS A FAILED WRITE
+            while connection.is_handshaking() {
+                socket.set_read_timeout(Some(remaining(deadline)?)).map_err(|e| ResolveError::Io(e.to_string()))?;
+                socket.set_write_timeout(Some(remaining(deadline)?)).map_err(|e| ResolveError::Io(e.to_string()))?;
+                connection.complete_io(&mut socket).map_err(map_error)?;
+            }
+
+            Ok(StreamOwned::new(connection, socket))
+        });
+
+        self.last_used = Instant::now();
+        results
+    }
+}
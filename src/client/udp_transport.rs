@@ -0,0 +1,42 @@
+use std::io::ErrorKind;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use std::time::Instant;
+use crate::client::resolve_error::ResolveError;
+use crate::client::tcp_transport::remaining;
+
+//A SERVER IGNORING THE SIZE WE ADVERTISED STILL GETS READ WHOLE INSTEAD OF CUT SHORT
+pub const MAX_DATAGRAM_SIZE: usize = 65535;
+
+//A FRESH SOCKET PER QUERY GETS A FRESH RANDOM SOURCE PORT FROM THE OS
+pub fn bind(server: SocketAddr) -> Result<UdpSocket, ResolveError> {
+    match server {
+        SocketAddr::V4(_) => UdpSocket::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))),
+        SocketAddr::V6(_) => UdpSocket::bind(SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)))
+    }.map_err(|e| ResolveError::Io(e.to_string()))
+}
+
+//EVERY DATAGRAM FROM server GOES TO handle UNTIL IT RETURNS Some, ANYONE ELSE SENDING TO OUR PORT IS IGNORED
+pub fn receive<T, F>(socket: &UdpSocket, server: SocketAddr, deadline: Instant, mut handle: F) -> Result<T, ResolveError>
+where
+    F: FnMut(&[u8]) -> Option<T>
+{
+    let mut buf = vec![0u8; MAX_DATAGRAM_SIZE];
+
+    loop {
+        socket.set_read_timeout(Some(remaining(deadline)?)).map_err(|e| ResolveError::Io(e.to_string()))?;
+
+        let (size, source) = match socket.recv_from(&mut buf) {
+            Ok(received) => received,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted) => continue,
+            Err(e) => return Err(ResolveError::Io(e.to_string()))
+        };
+
+        if source != server {
+            continue;
+        }
+
+        if let Some(result) = handle(&buf[..size]) {
+            return Ok(result);
+        }
+    }
+}
@@ -1,15 +1,21 @@
-use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::net::{IpAddr, SocketAddr};
+use crate::client::dns_client::Client;
 use crate::messages::inter::dns_classes::DnsClasses;
 use crate::messages::inter::types::Types;
 use crate::messages::message_base::MessageBase;
 use crate::records::a_record::ARecord;
 use crate::records::inter::record_base::RecordBase;
 use crate::utils::dns_query::DnsQuery;
-use crate::utils::random;
+use crate::utils::name::Name;
 
 mod messages;
 mod records;
 mod utils;
+mod zone;
+mod pcap;
+mod client;
+mod server;
+mod resolver;
 
 //GET AWAY FROM USING ENUM FOR TYPE, GO WITH METHOD USED IN rlibdht TO HANDLE CUSTOM MESSAGES
 
@@ -90,32 +96,21 @@ fn main() {
     */
 
 
-    let socket = UdpSocket::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))).expect("Failed to bind socket");
+    let client = Client::new(SocketAddr::from((IpAddr::from([1, 1, 1, 1]), 53)));
 
-    let mut message = MessageBase::new(random::gen());
+    let mut message = MessageBase::new(0);
     //message.add_query(DnsQuery::new("outlook.office.com", Types::A, DnsClasses::In));
     //message.add_query(DnsQuery::new("google.com", Types::A, DnsClasses::In));
     //message.add_query(DnsQuery::new("gmail.com", Types::Mx, DnsClasses::In));
-    message.add_query(DnsQuery::new("1.1.1.1.in-addr.arpa", Types::A, DnsClasses::In));
+    message.add_query(DnsQuery::new(&Name::from_ip(IpAddr::from([1, 1, 1, 1])).to_string(), Types::Ptr, DnsClasses::In));
     //message.add_query(DnsQuery::new("microsoft.com", Types::Srv, DnsClasses::In));
     message.set_recursion_desired(true);
 
     //message.add_query(DnsQuery::new("github.com", Types::Aaaa, DnsClasses::In));
 
 
-    let encoded = message.encode();
-    println!("{:x?}", &encoded);
-
-    socket.send_to(message.encode().as_slice(), SocketAddr::from((IpAddr::from([1, 1, 1, 1]), 53))).expect("Failed to send message");
-
-    let mut buf = [0u8; 512];
-    match socket.recv_from(&mut buf) {
-        Ok((size, src_addr)) => {
-            println!("{:x?}", &buf[0..size]);
-
-            let message = MessageBase::from_bytes(&buf, 0);
-            println!("{:x?}", &message.encode());
-        }
-        _ => {}
+    match client.query(message) {
+        Ok(response) => println!("{}", response),
+        Err(e) => println!("{}", e)
     }
 }
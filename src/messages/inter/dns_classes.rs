@@ -26,4 +26,31 @@ impl DnsClasses {
             Self::Hs => 4
         }
     }
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        for c in [Self::In, Self::Cs, Self::Ch, Self::Hs] {
+            if c.get_name().eq_ignore_ascii_case(name) {
+                return Ok(c);
+            }
+        }
+
+        if let Some(prefix) = name.get(..5) {
+            if prefix.eq_ignore_ascii_case("CLASS") {
+                if let Ok(code) = name[5..].parse::<u16>() {
+                    return Self::from_code(code);
+                }
+            }
+        }
+
+        Err(format!("Couldn't find for name: {}", name))
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            Self::In => "IN",
+            Self::Cs => "CS",
+            Self::Ch => "CH",
+            Self::Hs => "HS"
+        }
+    }
 }
@@ -2,3 +2,5 @@ pub mod types;
 pub mod response_codes;
 pub mod op_codes;
 pub mod dns_classes;
+pub mod padding_policies;
+pub mod transports;
@@ -2,13 +2,15 @@
 pub enum OpCodes {
     Query,
     IQuery,
-    Status
+    Status,
+    Notify,
+    Update
 }
 
 impl OpCodes {
 
     pub fn from_code(code: u8) -> Result<Self, String> {
-        for c in [Self::Query, Self::IQuery, Self::Status] {
+        for c in [Self::Query, Self::IQuery, Self::Status, Self::Notify, Self::Update] {
             if c.get_code() == code {
                 return Ok(c);
             }
@@ -21,7 +23,19 @@ impl OpCodes {
         match self {
             Self::Query => 0,
             Self::IQuery => 1,
-            Self::Status => 2
+            Self::Status => 2,
+            Self::Notify => 4,
+            Self::Update => 5
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            Self::Query => "QUERY",
+            Self::IQuery => "IQUERY",
+            Self::Status => "STATUS",
+            Self::Notify => "NOTIFY",
+            Self::Update => "UPDATE"
         }
     }
 }
@@ -0,0 +1,21 @@
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum PaddingPolicies {
+    None,
+    Query,
+    Response,
+    Block(u16)
+}
+
+impl PaddingPolicies {
+
+    //RFC 8467 - BLOCK-LENGTH PADDING, 128 FOR QUERIES AND 468 FOR RESPONSES
+    pub fn get_block_size(&self) -> Option<usize> {
+        match self {
+            Self::None => None,
+            Self::Query => Some(128),
+            Self::Response => Some(468),
+            Self::Block(0) => None,
+            Self::Block(size) => Some(*size as usize)
+        }
+    }
+}
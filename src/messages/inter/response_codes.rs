@@ -5,13 +5,18 @@ pub enum ResponseCodes {
     ServerFailure,
     NameError,
     NotImplemented,
-    Refused
+    Refused,
+    YxDomain,
+    YxRrSet,
+    NxRrSet,
+    NotAuth,
+    NotZone
 }
 
 impl ResponseCodes {
 
     pub fn from_code(code: u8) -> Result<Self, String> {
-        for c in [Self::NoError, Self::FormatError, Self::ServerFailure, Self::NameError, Self::NotImplemented, Self::Refused] {
+        for c in [Self::NoError, Self::FormatError, Self::ServerFailure, Self::NameError, Self::NotImplemented, Self::Refused, Self::YxDomain, Self::YxRrSet, Self::NxRrSet, Self::NotAuth, Self::NotZone] {
             if c.get_code() == code {
                 return Ok(c);
             }
@@ -27,7 +32,28 @@ impl ResponseCodes {
             Self::ServerFailure => 2,
             Self::NameError => 3,
             Self::NotImplemented => 4,
-            Self::Refused => 5
+            Self::Refused => 5,
+            Self::YxDomain => 6,
+            Self::YxRrSet => 7,
+            Self::NxRrSet => 8,
+            Self::NotAuth => 9,
+            Self::NotZone => 10
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            Self::NoError => "NOERROR",
+            Self::FormatError => "FORMERR",
+            Self::ServerFailure => "SERVFAIL",
+            Self::NameError => "NXDOMAIN",
+            Self::NotImplemented => "NOTIMP",
+            Self::Refused => "REFUSED",
+            Self::YxDomain => "YXDOMAIN",
+            Self::YxRrSet => "YXRRSET",
+            Self::NxRrSet => "NXRRSET",
+            Self::NotAuth => "NOTAUTH",
+            Self::NotZone => "NOTZONE"
         }
     }
 }
@@ -0,0 +1,5 @@
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Transports {
+    Udp,
+    Tcp
+}
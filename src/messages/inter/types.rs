@@ -1,9 +1,12 @@
+use std::fmt;
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum Types {
     A,
     Aaaa,
     Ns,
     Cname,
+    Dname,
     Soa,
     Ptr,
     Mx,
@@ -17,13 +20,14 @@ pub enum Types {
     Spf,
     Tsig,
     Any,
-    Caa
+    Caa,
+    Unknown(u16)
 }
 
 impl Types {
 
     pub fn from_code(code: u16) -> Result<Self, String> {
-        for c in [Self::A, Self::Aaaa, Self::Ns, Self::Cname, Self::Soa, Self::Ptr, Self::Mx, Self::Txt, Self::Opt, Self::Rrsig, Self::Nsec, Self::DnsKey, Self::Https, Self::Srv, Self::Spf, Self::Tsig, Self::Any, Self::Caa] {
+        for c in [Self::A, Self::Aaaa, Self::Ns, Self::Cname, Self::Dname, Self::Soa, Self::Ptr, Self::Mx, Self::Txt, Self::Opt, Self::Rrsig, Self::Nsec, Self::DnsKey, Self::Https, Self::Srv, Self::Spf, Self::Tsig, Self::Any, Self::Caa] {
             if c.get_code() == code {
                 return Ok(c);
             }
@@ -38,6 +42,7 @@ impl Types {
             Self::Aaaa => 28,
             Self::Ns => 2,
             Self::Cname => 5,
+            Self::Dname => 39,
             Self::Soa => 6,
             Self::Ptr => 12,
             Self::Mx => 15,
@@ -51,7 +56,78 @@ impl Types {
             Self::Spf => 99,
             Self::Tsig => 250,
             Self::Any => 255,
-            Self::Caa => 257
+            Self::Caa => 257,
+            Self::Unknown(code) => *code
         }
     }
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        for c in [Self::A, Self::Aaaa, Self::Ns, Self::Cname, Self::Dname, Self::Soa, Self::Ptr, Self::Mx, Self::Txt, Self::Opt, Self::Rrsig, Self::Nsec, Self::DnsKey, Self::Https, Self::Srv, Self::Spf, Self::Tsig, Self::Any, Self::Caa] {
+            if c.get_name().eq_ignore_ascii_case(name) {
+                return Ok(c);
+            }
+        }
+
+        //RFC 3597 - TYPEnnn NAMES ANY TYPE, KNOWN OR NOT
+        if let Some(prefix) = name.get(..4) {
+            if prefix.eq_ignore_ascii_case("TYPE") {
+                if let Ok(code) = name[4..].parse::<u16>() {
+                    return Ok(Self::from_code(code).unwrap_or(Self::Unknown(code)));
+                }
+            }
+        }
+
+        Err(format!("Couldn't find for name: {}", name))
+    }
+
+    //THE MNEMONIC OF A TYPE WE KNOW, Display GIVES THE RFC 3597 TYPEnnn NAME FOR ONE WE DON'T
+    pub fn get_name(&self) -> &str {
+        match self {
+            Self::A => "A",
+            Self::Aaaa => "AAAA",
+            Self::Ns => "NS",
+            Self::Cname => "CNAME",
+            Self::Dname => "DNAME",
+            Self::Soa => "SOA",
+            Self::Ptr => "PTR",
+            Self::Mx => "MX",
+            Self::Txt => "TXT",
+            Self::Srv => "SRV",
+            Self::Opt => "OPT",
+            Self::Rrsig => "RRSIG",
+            Self::Nsec => "NSEC",
+            Self::DnsKey => "DNSKEY",
+            Self::Https => "HTTPS",
+            Self::Spf => "SPF",
+            Self::Tsig => "TSIG",
+            Self::Any => "ANY",
+            Self::Caa => "CAA",
+            Self::Unknown(_) => "UNKNOWN"
+        }
+    }
+}
+
+impl fmt::Display for Types {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(code) => write!(f, "TYPE{}", code),
+            _ => write!(f, "{}", self.get_name())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn unknown_types_display_as_type_nnn() {
+        assert_eq!(Types::Mx.get_name(), "MX");
+        assert_eq!(Types::Mx.to_string(), "MX");
+        assert_eq!(Types::Unknown(65280).to_string(), "TYPE65280");
+        assert_eq!(Types::from_name("TYPE65280"), Ok(Types::Unknown(65280)));
+        assert_eq!(Types::from_name("type15"), Ok(Types::Mx));
+    }
 }
@@ -1,13 +1,18 @@
 use std::collections::HashMap;
+use std::fmt;
 use std::net::SocketAddr;
 use crate::messages::inter::op_codes::OpCodes;
+use crate::messages::inter::padding_policies::PaddingPolicies;
 use crate::messages::inter::response_codes::ResponseCodes;
 use crate::messages::inter::types::Types;
+use crate::messages::message_dump;
 use crate::records::a_record::ARecord;
 use crate::records::aaaa_record::AAAARecord;
 use crate::records::cname_record::CNameRecord;
+use crate::records::dname_record::DNameRecord;
 use crate::records::dnskey_record::DNSKeyRecord;
 use crate::records::https_record::HttpsRecord;
+use crate::records::inter::opt_codes::OptCodes;
 use crate::records::inter::record_base::RecordBase;
 use crate::records::mx_record::MxRecord;
 use crate::records::ns_record::NsRecord;
@@ -18,9 +23,13 @@ use crate::records::rrsig_record::RRSigRecord;
 use crate::records::soa_record::SoaRecord;
 use crate::records::srv_record::SrvRecord;
 use crate::records::txt_record::TxtRecord;
+use crate::records::unknown_record::UnknownRecord;
 use crate::utils::dns_query::DnsQuery;
-use crate::utils::domain_utils::{pack_domain, unpack_domain};
+use crate::utils::domain_utils::{pack_domain, to_fqdn, unpack_domain};
 use crate::utils::ordered_map::OrderedMap;
+
+type RecordSection = OrderedMap<String, Vec<Box<dyn RecordBase>>>;
+
 /*
                                1  1  1  1  1  1
  0  1  2  3  4  5  6  7  8  9  0  1  2  3  4  5
@@ -50,6 +59,7 @@ pub struct MessageBase {
     recursion_available: bool,
     authenticated_data: bool,
     checking_disabled: bool,
+    padding_policy: PaddingPolicies,
     //length: usize,
     origin: Option<SocketAddr>,
     destination: Option<SocketAddr>,
@@ -73,6 +83,7 @@ impl Default for MessageBase {
             recursion_available: false,
             authenticated_data: false,
             checking_disabled: false,
+            padding_policy: PaddingPolicies::None,
             //length: 12,
             origin: None,
             destination: None,
@@ -94,6 +105,54 @@ impl MessageBase {
     }
 
     pub fn encode(&self) -> Vec<u8> {
+        match self.padding_policy.get_block_size() {
+            Some(block_size) => self.encode_padded(block_size),
+            None => self.encode_with(&self.additional_records)
+        }
+    }
+
+    fn encode_padded(&self, block_size: usize) -> Vec<u8> {
+        let mut additional_records: OrderedMap<String, Vec<Box<dyn RecordBase>>> = OrderedMap::new();
+        let mut opt_position = None;
+
+        for (query, records) in self.additional_records.iter() {
+            let mut cloned = Vec::new();
+
+            for record in records {
+                let mut record = record.dyn_clone();
+
+                if let Some(opt) = record.as_any_mut().downcast_mut::<OptRecord>() {
+                    opt.remove_option(&OptCodes::Padding);
+                    opt_position.get_or_insert((query.clone(), cloned.len()));
+                }
+
+                cloned.push(record);
+            }
+
+            additional_records.insert(query.clone(), cloned);
+        }
+
+        let (query, index) = match opt_position {
+            Some(position) => position,
+            None => {
+                let records = additional_records.entry(String::new()).or_default();
+                records.push(Box::new(OptRecord::new(512, 0, 0, 0)));
+                (String::new(), records.len()-1)
+            }
+        };
+
+        //PADDING OPTION HEADER IS 4 BYTES, THE REST IS ZEROS UP TO THE NEXT BLOCK
+        let length = self.encode_with(&additional_records).len()+4;
+        let padding = (block_size - length % block_size) % block_size;
+
+        additional_records.get_mut(&query).unwrap()[index].as_any_mut()
+            .downcast_mut::<OptRecord>().unwrap()
+            .set_option(OptCodes::Padding, vec![0u8; padding]);
+
+        self.encode_with(&additional_records)
+    }
+
+    fn encode_with(&self, additional_records: &OrderedMap<String, Vec<Box<dyn RecordBase>>>) -> Vec<u8> {
         let mut buf = vec![0u8; 12];//self.length];
 
         buf.splice(0..2, self.id.to_be_bytes());
@@ -111,19 +170,27 @@ impl MessageBase {
 
         buf.splice(2..4, flags.to_be_bytes());
 
-        buf.splice(4..6, (self.queries.len() as u16).to_be_bytes());
-
         let mut label_map = HashMap::new();
         let mut off = 12;
+        let mut i: u16 = 0;
 
+        //A QUESTION WHOSE NAME CAN'T BE PACKED IS LEFT OUT, LIKE A RECORD THAT CAN'T BE
         for query in &self.queries {
-            let q = query.to_bytes(&mut label_map, off);
-            buf.extend_from_slice(&q);
-            off += q.len();
+            let mut query_map = label_map.clone();
+
+            if let Ok(q) = query.to_bytes(&mut query_map, off) {
+                buf.extend_from_slice(&q);
+                off += q.len();
+                label_map = query_map;
+                i += 1;
+            }
         }
 
+        buf.splice(4..6, i.to_be_bytes());
+
         let (answers, i) = Self::records_to_bytes(off, &self.answers, &mut label_map);
         buf.extend_from_slice(&answers);
+        off += answers.len();
 
         buf.splice(6..8, i.to_be_bytes());
 
@@ -131,12 +198,13 @@ impl MessageBase {
 
         let (answers, i) = Self::records_to_bytes(off, &self.name_servers, &mut label_map);
         buf.extend_from_slice(&answers);
+        off += answers.len();
 
         buf.splice(8..10, i.to_be_bytes());
 
 
 
-        let (answers, i) = Self::records_to_bytes(off, &self.additional_records, &mut label_map);
+        let (answers, i) = Self::records_to_bytes(off, additional_records, &mut label_map);
         buf.extend_from_slice(&answers);
 
         buf.splice(10..12, i.to_be_bytes());
@@ -144,13 +212,70 @@ impl MessageBase {
         buf
     }
 
-    pub fn from_bytes(buf: &[u8], off: usize) -> Self {
+    //WHAT encode() ACTUALLY PUT ON THE WIRE, BYTE BY BYTE
+    pub fn dump(&self) -> String {
+        message_dump::dump(&self.encode(), 0)
+    }
+
+    //SAME ID, QR SET AND THE QUESTION ECHOED BACK - NAMES COMPARE CASE INSENSITIVE, RFC 4343
+    pub fn is_response_to(&self, request: &MessageBase) -> bool {
+        if !self.qr || self.id != request.id || self.queries.len() != request.queries.len() {
+            return false;
+        }
+
+        self.queries.iter().zip(request.queries.iter()).all(|(a, b)| {
+            let (Ok(a_name), Ok(b_name)) = (a.get_query(), b.get_query()) else {
+                return false;
+            };
+
+            a_name.trim_end_matches('.').eq_ignore_ascii_case(b_name.trim_end_matches('.')) &&
+                a.get_type() == b.get_type() &&
+                a.get_dns_class() == b.get_dns_class()
+        })
+    }
+
+    //RFC 2181 9 - THE HEADER AND QUESTION WITH TC SET, WHAT GOES BACK WHEN THE WHOLE ANSWER WON'T FIT
+    pub fn to_truncated(&self) -> Self {
+        let mut truncated = Self {
+            id: self.id,
+            op_code: self.op_code,
+            response_code: self.response_code,
+            qr: self.qr,
+            authoritative: self.authoritative,
+            truncated: true,
+            recursion_desired: self.recursion_desired,
+            recursion_available: self.recursion_available,
+            authenticated_data: self.authenticated_data,
+            checking_disabled: self.checking_disabled,
+            padding_policy: self.padding_policy,
+            origin: self.origin,
+            destination: self.destination,
+            queries: self.queries.clone(),
+            ..Default::default()
+        };
+
+        if let Some(opt) = self.get_opt_record() {
+            truncated.add_additional_records("", Box::new(opt.clone()));
+        }
+
+        truncated
+    }
+
+    pub fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
+        //COMPRESSION POINTERS COUNT FROM THE START OF THE MESSAGE, NOT THE START OF BUF
+        let buf = buf.get(off..).unwrap_or_default();
+        let off = 0;
+
+        if buf.len() < 12 {
+            return Err(format!("Message is shorter than the header: {} bytes", buf.len()));
+        }
+
         let id = u16::from_be_bytes([buf[off], buf[off+1]]);
 
         let flags = u16::from_be_bytes([buf[off+2], buf[off+3]]);
 
         let qr = (flags & 0x8000) != 0;
-        let op_code = OpCodes::from_code(((flags >> 11) & 0x0F) as u8).unwrap();
+        let op_code = OpCodes::from_code(((flags >> 11) & 0x0F) as u8)?;
         let authoritative = (flags & 0x0400) != 0;
         let truncated = (flags & 0x0200) != 0;
         let recursion_desired = (flags & 0x0100) != 0;
@@ -158,47 +283,32 @@ impl MessageBase {
         //let z = (flags & 0x0040) != 0;
         let authenticated_data = (flags & 0x0020) != 0;
         let checking_disabled = (flags & 0x0010) != 0;
-        let response_code = ResponseCodes::from_code((flags & 0x000F) as u8).unwrap();
-
-        println!("ID: {} QR: {} OP_CODE: {:?} AUTH: {} TRUN: {} REC_DES: {} REC_AVA: {} AUTH_DAT: {} CHK_DIS: {} RES_CODE: {:?}",
-                id,
-                qr,
-                op_code,
-                authoritative,
-                truncated,
-                recursion_desired,
-                recursion_available,
-                authenticated_data,
-                checking_disabled,
-                response_code);
+        let response_code = ResponseCodes::from_code((flags & 0x000F) as u8)?;
 
         let qd_count = u16::from_be_bytes([buf[off+4], buf[off+5]]);
         let an_count = u16::from_be_bytes([buf[off+6], buf[off+7]]);
         let ns_count = u16::from_be_bytes([buf[off+8], buf[off+9]]);
         let ar_count = u16::from_be_bytes([buf[off+10], buf[off+11]]);
 
-        println!("{} {} {} {}", qd_count, an_count, ns_count, ar_count);
-
         let mut queries = Vec::new();
         let mut off = 12;
 
-        for i in 0..qd_count {
-            let query = DnsQuery::from_bytes(buf, off);
+        for _ in 0..qd_count {
+            let query = DnsQuery::from_bytes(buf, off)?;
             off += query.get_length();
-            println!("{}", query.to_string());
             queries.push(query);
         }
 
-        let (answers, length) = Self::records_from_bytes(buf, off, an_count);
+        let (answers, length) = Self::records_from_bytes(buf, off, an_count)?;
         off += length;
 
-        let (name_servers, length) = Self::records_from_bytes(buf, off, ns_count);
+        let (name_servers, length) = Self::records_from_bytes(buf, off, ns_count)?;
         off += length;
 
-        let (additional_records, length) = Self::records_from_bytes(buf, off, ar_count);
+        let (additional_records, length) = Self::records_from_bytes(buf, off, ar_count)?;
         off += length;
 
-        Self {
+        Ok(Self {
             id,
             op_code,
             response_code,
@@ -209,6 +319,7 @@ impl MessageBase {
             recursion_available,
             authenticated_data,
             checking_disabled,
+            padding_policy: PaddingPolicies::None,
             //length: off,
             origin: None,
             destination: None,
@@ -216,7 +327,7 @@ impl MessageBase {
             answers,
             name_servers,
             additional_records
-        }
+        })
     }
 
     fn records_to_bytes(off: usize, records: &OrderedMap<String, Vec<Box<dyn RecordBase>>>, label_map: &mut HashMap<String, usize>) -> (Vec<u8>, u16) {
@@ -226,104 +337,113 @@ impl MessageBase {
 
         for (query, records) in records.iter() {
             for record in records {
-                match record.to_bytes(label_map, off) {
-                    Ok(e) => {
-                        //println!("{}: {}", query, record.to_string());
-                        match query.len() {
-                            0 => {
-                                buf.push(0);
-                            }
-                            _ => {
-                                let eq = pack_domain(query, label_map, off);
-                                buf.extend_from_slice(&eq);
-                                off += eq.len();
-                            }
-                        }
-
-                        buf.extend_from_slice(&e);
-                        off += e.len();
+                //PACK INTO A COPY SO A FAILED RECORD DOESN'T LEAVE POINTERS TO BYTES WE NEVER WROTE
+                let mut record_map = label_map.clone();
+
+                let eq = match query.len() {
+                    0 => vec![0],
+                    _ => match pack_domain(query, &mut record_map, off) {
+                        Ok(eq) => eq,
+                        Err(_) => continue
                     }
-                    Err(_) => {}
+                };
+
+                //RDATA NAMES ARE PACKED RELATIVE TO WHERE THE RECORD STARTS, AFTER ITS OWNER NAME
+                if let Ok(e) = record.to_bytes(&mut record_map, off+eq.len()) {
+                    buf.extend_from_slice(&eq);
+                    buf.extend_from_slice(&e);
+                    off += eq.len()+e.len();
+                    *label_map = record_map;
+                    i += 1;
                 }
-                i += 1;
             }
         }
 
         (buf, i)
     }
 
-    fn records_from_bytes(buf: &[u8], off: usize, count: u16) -> (OrderedMap<String, Vec<Box<dyn RecordBase>>>, usize) {
-        let mut records: OrderedMap<String, Vec<Box<dyn RecordBase>>> = OrderedMap::new();
+    fn records_from_bytes(buf: &[u8], off: usize, count: u16) -> Result<(RecordSection, usize), String> {
+        let mut records: RecordSection = OrderedMap::new();
         let mut pos = off;
 
         for _ in 0..count {
-            let (domain, length) = unpack_domain(buf, pos);
+            let (domain, length) = unpack_domain(buf, pos)?;
             pos += length;
 
-
-            let record = match Types::from_code(u16::from_be_bytes([buf[pos], buf[pos+1]])).unwrap() {
-                Types::A => {
-                    ARecord::from_bytes(buf, pos+2).dyn_clone()
-                }
-                Types::Aaaa => {
-                    AAAARecord::from_bytes(buf, pos+2).dyn_clone()
-                }
-                Types::Ns => {
-                    NsRecord::from_bytes(buf, pos+2).dyn_clone()
-                }
-                Types::Cname => {
-                    CNameRecord::from_bytes(buf, pos+2).dyn_clone()
-                }
-                Types::Soa => {
-                    SoaRecord::from_bytes(buf, pos+2).dyn_clone()
-                }
-                Types::Ptr => {
-                    PtrRecord::from_bytes(buf, pos+2).dyn_clone()
-                }
-                Types::Mx => {
-                    MxRecord::from_bytes(buf, pos+2).dyn_clone()
-                }
-                Types::Txt => {
-                    TxtRecord::from_bytes(buf, pos+2).dyn_clone()
-                }
-                Types::Srv => {
-                    SrvRecord::from_bytes(buf, pos+2).dyn_clone()
-                }
-                Types::Opt => {
-                    OptRecord::from_bytes(buf, pos+2).dyn_clone()
-                }
-                Types::Rrsig => {
-                    RRSigRecord::from_bytes(buf, pos+2).dyn_clone()
-                }
-                Types::Nsec => {
-                    NsecRecord::from_bytes(buf, pos+2).dyn_clone()
-                }
-                Types::DnsKey => {
-                    DNSKeyRecord::from_bytes(buf, pos+2).dyn_clone()
-                }
-                Types::Https => {
-                    HttpsRecord::from_bytes(buf, pos+2).dyn_clone()
-                }
-                Types::Spf => {
-                    todo!()
-                }
-                Types::Tsig => {
-                    todo!()
-                }
-                Types::Caa => {
-                    todo!()
-                }
-                _ => {
-                    todo!()
-                }
-            };
-            println!("{}: {}", domain, record.to_string());
+            let record = Self::record_from_bytes(buf, pos)?;
 
             records.entry(domain).or_insert_with(Vec::new).push(record);
-            pos += 10+u16::from_be_bytes([buf[off+8], buf[off+9]]) as usize;
+            pos += 10+u16::from_be_bytes([buf[pos+8], buf[pos+9]]) as usize;
+        }
+
+        Ok((records, pos-off))
+    }
+
+    //OFF IS WHERE THE TYPE STARTS, TYPES WE CAN'T DECODE COME BACK AS AN UnknownRecord
+    pub fn record_from_bytes(buf: &[u8], off: usize) -> Result<Box<dyn RecordBase>, String> {
+        //EVERY DECODER TRUSTS THE FIXED FIELDS AND RDLENGTH, SO THEY'RE CHECKED ONCE HERE
+        if off+10 > buf.len() {
+            return Err("Record header runs past the end of the message".to_string());
+        }
+
+        if off+10+u16::from_be_bytes([buf[off+8], buf[off+9]]) as usize > buf.len() {
+            return Err("RDATA runs past the end of the message".to_string());
         }
 
-        (records, pos-off)
+        let code = u16::from_be_bytes([buf[off], buf[off+1]]);
+
+        let record = match Types::from_code(code).unwrap_or(Types::Unknown(code)) {
+            Types::A => {
+                ARecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+            Types::Aaaa => {
+                AAAARecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+            Types::Ns => {
+                NsRecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+            Types::Cname => {
+                CNameRecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+            Types::Dname => {
+                DNameRecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+            Types::Soa => {
+                SoaRecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+            Types::Ptr => {
+                PtrRecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+            Types::Mx => {
+                MxRecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+            Types::Txt => {
+                TxtRecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+            Types::Srv => {
+                SrvRecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+            Types::Opt => {
+                OptRecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+            Types::Rrsig => {
+                RRSigRecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+            Types::Nsec => {
+                NsecRecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+            Types::DnsKey => {
+                DNSKeyRecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+            Types::Https => {
+                HttpsRecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+            _ => {
+                UnknownRecord::from_bytes(buf, off+2)?.dyn_clone()
+            }
+        };
+
+        Ok(record)
     }
 
     pub fn set_id(&mut self, id: u16) {
@@ -398,6 +518,22 @@ impl MessageBase {
         self.recursion_available
     }
 
+    pub fn set_authenticated_data(&mut self, authenticated_data: bool) {
+        self.authenticated_data = authenticated_data;
+    }
+
+    pub fn is_authenticated_data(&self) -> bool {
+        self.authenticated_data
+    }
+
+    pub fn set_checking_disabled(&mut self, checking_disabled: bool) {
+        self.checking_disabled = checking_disabled;
+    }
+
+    pub fn is_checking_disabled(&self) -> bool {
+        self.checking_disabled
+    }
+
     pub fn set_response_code(&mut self, response_code: ResponseCodes) {
         self.response_code = response_code;
     }
@@ -406,6 +542,14 @@ impl MessageBase {
         self.response_code
     }
 
+    pub fn set_padding_policy(&mut self, padding_policy: PaddingPolicies) {
+        self.padding_policy = padding_policy;
+    }
+
+    pub fn get_padding_policy(&self) -> PaddingPolicies {
+        self.padding_policy
+    }
+
     pub fn total_queries(&self) -> usize {
         self.queries.len()
     }
@@ -419,18 +563,20 @@ impl MessageBase {
     }
 
     pub fn add_answers(&mut self, query: &str, record: Box<dyn RecordBase>) {
-        if self.answers.contains_key(&query.to_string()) {
-            self.answers.get_mut(&query.to_string()).unwrap().push(record);
-            return;
-        }
+        self.answers.entry(query.to_string()).or_default().push(record);
+    }
 
-        //self.answers.push(record);
+    pub fn add_name_servers(&mut self, query: &str, record: Box<dyn RecordBase>) {
+        self.name_servers.entry(query.to_string()).or_default().push(record);
     }
 
-    /*
-    pub fn get_answers(&self) -> &Vec<Box<dyn DnsRecord>> {
+    pub fn add_additional_records(&mut self, query: &str, record: Box<dyn RecordBase>) {
+        self.additional_records.entry(query.to_string()).or_default().push(record);
+    }
+
+    pub fn get_answers(&self) -> &OrderedMap<String, Vec<Box<dyn RecordBase>>> {
         &self.answers
-    }*/
+    }
 
     pub fn get_name_servers(&self) -> &OrderedMap<String, Vec<Box<dyn RecordBase>>> {
         &self.name_servers
@@ -439,4 +585,412 @@ impl MessageBase {
     pub fn get_additional_records(&self) -> &OrderedMap<String, Vec<Box<dyn RecordBase>>> {
         &self.additional_records
     }
+
+    pub fn get_opt_record(&self) -> Option<&OptRecord> {
+        self.additional_records.iter()
+            .flat_map(|(_, records)| records.iter())
+            .find_map(|record| record.as_any().downcast_ref::<OptRecord>())
+    }
+
+    //DIG +short - ONLY THE ANSWER RDATA, ONE RECORD PER LINE
+    pub fn to_short_string(&self) -> String {
+        let mut short = String::new();
+
+        for (_, records) in self.answers.iter() {
+            for record in records {
+                short.push_str(&record.rdata_to_string());
+                short.push('\n');
+            }
+        }
+
+        short
+    }
+}
+
+//DIG STYLE, SEE to_short_string FOR +short
+impl fmt::Display for MessageBase {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let opt = self.get_opt_record();
+
+        //RFC 6891 6.1.3 - THE OPT TTL CARRIES THE UPPER 8 BITS OF THE RCODE
+        let response_code = match opt {
+            Some(opt) if opt.get_ext_rcode() != 0 => format!("RCODE{}", ((opt.get_ext_rcode() as u16) << 4) | self.response_code.get_code() as u16),
+            _ => self.response_code.get_name().to_string()
+        };
+
+        writeln!(f, ";; ->>HEADER<<- opcode: {}, status: {}, id: {}", self.op_code.get_name(), response_code, self.id)?;
+
+        let mut flags = String::new();
+        for (set, flag) in [(self.qr, "qr"), (self.authoritative, "aa"), (self.truncated, "tc"), (self.recursion_desired, "rd"),
+                (self.recursion_available, "ra"), (self.authenticated_data, "ad"), (self.checking_disabled, "cd")] {
+            if set {
+                flags.push(' ');
+                flags.push_str(flag);
+            }
+        }
+
+        let count = |records: &OrderedMap<String, Vec<Box<dyn RecordBase>>>| records.iter().map(|(_, records)| records.len()).sum::<usize>();
+        writeln!(f, ";; flags:{}; QUERY: {}, ANSWER: {}, AUTHORITY: {}, ADDITIONAL: {}",
+                flags, self.queries.len(), count(&self.answers), count(&self.name_servers), count(&self.additional_records))?;
+
+        if let Some(opt) = opt {
+            writeln!(f, "\n;; OPT PSEUDOSECTION:")?;
+            writeln!(f, "; {}", opt.to_string())?;
+
+            for (code, option) in opt.get_raw_options() {
+                writeln!(f, "; {}", OptRecord::option_to_string(*code, option))?;
+            }
+        }
+
+        if !self.queries.is_empty() {
+            writeln!(f, "\n;; QUESTION SECTION:")?;
+
+            for query in &self.queries {
+                writeln!(f, ";{}\t\t{}\t{}", to_fqdn(&query.get_query().unwrap_or_default()), query.get_dns_class().get_name(), query.get_type())?;
+            }
+        }
+
+        for (title, records) in [("ANSWER", &self.answers), ("AUTHORITY", &self.name_servers), ("ADDITIONAL", &self.additional_records)] {
+            let mut lines = Vec::new();
+
+            for (name, records) in records.iter() {
+                for record in records {
+                    if record.get_type() == Types::Opt {
+                        continue;
+                    }
+
+                    //TO_STRING IS "TTL CLASS TYPE RDATA", DIG SEPARATES THE FIRST FOUR WITH TABS
+                    let record = record.to_string();
+                    let fields: Vec<&str> = record.splitn(4, ' ').collect();
+                    lines.push(format!("{}\t{}", to_fqdn(name), fields.join("\t")));
+                }
+            }
+
+            if !lines.is_empty() {
+                writeln!(f, "\n;; {} SECTION:", title)?;
+
+                for line in lines {
+                    writeln!(f, "{}", line)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::any::Any;
+    use std::collections::HashMap;
+    use std::net::IpAddr;
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::padding_policies::PaddingPolicies;
+    use crate::messages::inter::types::Types;
+    use crate::messages::message_base::MessageBase;
+    use crate::records::a_record::ARecord;
+    use crate::records::aaaa_record::AAAARecord;
+    use crate::records::cname_record::CNameRecord;
+    use crate::records::inter::opt_codes::OptCodes;
+    use crate::records::inter::record_base::RecordBase;
+    use crate::records::mx_record::MxRecord;
+    use crate::records::ns_record::NsRecord;
+    use crate::records::nsec_record::NsecRecord;
+    use crate::records::opt_record::OptRecord;
+    use crate::records::ptr_record::PtrRecord;
+    use crate::records::soa_record::SoaRecord;
+    use crate::utils::dns_query::DnsQuery;
+    use crate::utils::domain_utils::unpack_domain;
+    use crate::utils::ordered_map::OrderedMap;
+
+    #[derive(Clone)]
+    struct UnencodableRecord;
+
+    impl RecordBase for UnencodableRecord {
+
+        fn from_bytes(_buf: &[u8], _off: usize) -> Result<Self, String> {
+            Ok(Self)
+        }
+
+        fn to_bytes(&self, _label_map: &mut HashMap<String, usize>, _off: usize) -> Result<Vec<u8>, String> {
+            Err("Can't encode this record".to_string())
+        }
+
+        fn get_type(&self) -> Types {
+            Types::A
+        }
+
+        fn get_dns_class(&self) -> Result<DnsClasses, String> {
+            Ok(DnsClasses::In)
+        }
+
+        fn get_ttl(&self) -> u32 {
+            0
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn upcast(&self) -> &dyn RecordBase {
+            self
+        }
+
+        fn upcast_mut(&mut self) -> &mut dyn RecordBase {
+            self
+        }
+
+        fn dyn_clone(&self) -> Box<dyn RecordBase> {
+            Box::new(self.clone())
+        }
+
+        fn rdata_to_string(&self) -> String {
+            "unencodable".to_string()
+        }
+
+        fn to_string(&self) -> String {
+            "[RECORD] unencodable".to_string()
+        }
+    }
+
+    fn section_strings(records: &OrderedMap<String, Vec<Box<dyn RecordBase>>>) -> Vec<String> {
+        records.iter()
+            .flat_map(|(owner, records)| records.iter().map(move |record| format!("{}: {}", owner, record.to_string())))
+            .collect()
+    }
+
+    //WALKS count RECORDS BY THEIR RDLENGTH WITHOUT DECODING THEM
+    fn skip_records(buf: &[u8], mut pos: usize, count: usize) -> usize {
+        for _ in 0..count {
+            let (_, length) = unpack_domain(buf, pos).unwrap();
+            pos += length+10+u16::from_be_bytes([buf[pos+length+8], buf[pos+length+9]]) as usize;
+        }
+
+        pos
+    }
+
+    #[test]
+    fn round_trip_compressed_rdata_names() {
+        let records: Vec<(Box<dyn RecordBase>, &str)> = vec![
+            (Box::new(CNameRecord::new(DnsClasses::In, 300, "www.example.net")), "www.example.net"),
+            (Box::new(MxRecord::new(DnsClasses::In, 300, 10, "mail.example.net")), "mail.example.net"),
+            (Box::new(NsRecord::new(DnsClasses::In, 300, "ns1.example.net")), "ns1.example.net"),
+            (Box::new(PtrRecord::new(DnsClasses::In, false, 300, "host.example.net")), "host.example.net"),
+            (Box::new(NsecRecord::new(DnsClasses::In, false, 300, "next.example.net", vec![1, 28])), "next.example.net"),
+            (Box::new(SoaRecord::new(DnsClasses::In, 300, "ns1.example.net", "hostmaster.example.net", 1, 7200, 900, 1209600, 300)), "hostmaster.example.net")
+        ];
+
+        //THE OWNER SHARES NO LABELS WITH THE QUESTION SO IT'S WRITTEN IN FULL, THE ADDITIONAL OWNER IS A POINTER INTO THE RDATA
+        for (record, target) in records {
+            let expected = record.to_string();
+
+            let mut message = MessageBase::new(0x1234);
+            message.add_query(DnsQuery::new("example.com", record.get_type(), DnsClasses::In));
+            message.answers.entry("example.org".to_string()).or_default().push(record);
+            message.add_additional_records(target, Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 1]))));
+
+            let buf = message.encode();
+
+            let pos = 12+DnsQuery::from_bytes(&buf, 12).unwrap().get_length();
+            let (answers, _) = MessageBase::records_from_bytes(&buf, pos, 1).unwrap();
+            assert_eq!(answers.get(&"example.org".to_string()).unwrap()[0].to_string(), expected);
+
+            let pos = skip_records(&buf, pos, 1);
+            assert_eq!(unpack_domain(&buf, pos).unwrap().0, target);
+        }
+    }
+
+    #[test]
+    fn round_trip_compresses_across_sections() {
+        let mut message = MessageBase::new(0x1234);
+        message.add_query(DnsQuery::new("example.com", Types::Ns, DnsClasses::In));
+        message.answers.entry("example.com".to_string()).or_default()
+            .push(Box::new(CNameRecord::new(DnsClasses::In, 300, "alias.example.net")));
+        message.name_servers.entry("example.net".to_string()).or_default()
+            .push(Box::new(NsRecord::new(DnsClasses::In, 300, "ns2.example.net")));
+        message.add_additional_records("ns2.example.net", Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 2]))));
+
+        let buf = message.encode();
+
+        let pos = 12+DnsQuery::from_bytes(&buf, 12).unwrap().get_length();
+        let pos = skip_records(&buf, pos, 1);
+        assert_eq!(unpack_domain(&buf, pos).unwrap().0, "example.net");
+
+        let pos = skip_records(&buf, pos, 1);
+        assert_eq!(unpack_domain(&buf, pos).unwrap().0, "ns2.example.net");
+        assert_eq!(skip_records(&buf, pos, 1), buf.len());
+    }
+
+    #[test]
+    fn round_trip_counts_only_encoded_records() {
+        let mut message = MessageBase::new(0x1234);
+        message.add_query(DnsQuery::new("example.com", Types::A, DnsClasses::In));
+        message.add_additional_records("dropped.example.net", Box::new(UnencodableRecord));
+        message.add_additional_records("host.example.net", Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 1]))));
+
+        let buf = message.encode();
+        assert_eq!(u16::from_be_bytes([buf[10], buf[11]]), 1);
+
+        //THE DROPPED OWNER NAME NEVER REACHED THE WIRE, NOTHING MAY POINT AT IT
+        let decoded = MessageBase::from_bytes(&buf, 0).unwrap();
+        let records = decoded.additional_records.get(&"host.example.net".to_string()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].to_string(), message.additional_records.get(&"host.example.net".to_string()).unwrap()[0].to_string());
+        assert!(!decoded.additional_records.contains_key(&"dropped.example.net".to_string()));
+        assert_eq!(skip_records(&buf, 12+decoded.queries[0].get_length(), 1), buf.len());
+    }
+
+    #[test]
+    fn round_trip_advances_by_each_rdlength() {
+        let mut message = MessageBase::new(0x1234);
+        message.add_query(DnsQuery::new("example.com", Types::A, DnsClasses::In));
+        message.answers.entry("example.com".to_string()).or_default()
+            .push(Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 1]))));
+        message.answers.entry("example.com".to_string()).or_default()
+            .push(Box::new(CNameRecord::new(DnsClasses::In, 300, "alias.example.net")));
+        message.name_servers.entry("example.net".to_string()).or_default()
+            .push(Box::new(NsRecord::new(DnsClasses::In, 300, "ns.example.net")));
+        message.add_additional_records("ns.example.net", Box::new(AAAARecord::new(DnsClasses::In, false, 300, "2001:db8::53".parse().unwrap())));
+
+        let buf = message.encode();
+        let decoded = MessageBase::from_bytes(&buf, 0).unwrap();
+
+        assert_eq!(section_strings(&decoded.answers), section_strings(&message.answers));
+        assert_eq!(section_strings(&decoded.name_servers), section_strings(&message.name_servers));
+        assert_eq!(section_strings(&decoded.additional_records), section_strings(&message.additional_records));
+    }
+
+    #[test]
+    fn short_string_holds_only_answer_rdata() {
+        let mut message = MessageBase::new(0x1234);
+        message.add_query(DnsQuery::new("example.com", Types::A, DnsClasses::In));
+        message.answers.entry("example.com".to_string()).or_default()
+            .push(Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 1]))));
+        message.answers.entry("example.com".to_string()).or_default()
+            .push(Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 2]))));
+
+        assert_eq!(message.to_short_string(), "192.0.2.1\n192.0.2.2\n");
+        assert!(message.to_string().contains(";; ANSWER SECTION:"));
+    }
+
+    #[test]
+    fn from_bytes_rejects_truncated_header() {
+        let buf = MessageBase::new(0x1234).encode();
+
+        assert!(MessageBase::from_bytes(&buf, 0).is_ok());
+        assert!(MessageBase::from_bytes(&buf[..11], 0).is_err());
+        assert!(MessageBase::from_bytes(&[], 0).is_err());
+    }
+
+    #[test]
+    fn from_bytes_rejects_rdata_past_the_end() {
+        let mut message = MessageBase::new(0x1234);
+        message.add_query(DnsQuery::new("example.com", Types::A, DnsClasses::In));
+        message.answers.entry("example.com".to_string()).or_default()
+            .push(Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 1]))));
+
+        let buf = message.encode();
+        assert!(MessageBase::from_bytes(&buf, 0).is_ok());
+        assert!(MessageBase::from_bytes(&buf[..buf.len()-1], 0).is_err());
+
+        //RDLENGTH SITS RIGHT BEFORE THE 4 ADDRESS BYTES
+        let mut long = buf.clone();
+        let length = long.len();
+        long.splice(length-6..length-4, 5u16.to_be_bytes());
+        assert!(MessageBase::from_bytes(&long, 0).is_err());
+    }
+
+    #[test]
+    fn from_bytes_rejects_bad_compression_pointers() {
+        //ONE QUESTION WHOSE NAME POINTS AHEAD, AT ITSELF, OR BACK INTO ITSELF
+        let header = [0x12, 0x34, 0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0];
+
+        let mut forward = header.to_vec();
+        forward.extend_from_slice(&[0xC0, 18, 0, 1, 0, 1, 3, b'c', b'o', b'm', 0]);
+        assert!(MessageBase::from_bytes(&forward, 0).is_err());
+
+        let mut own = header.to_vec();
+        own.extend_from_slice(&[0xC0, 12, 0, 1, 0, 1]);
+        assert!(MessageBase::from_bytes(&own, 0).is_err());
+
+        let mut cycle = header.to_vec();
+        cycle.extend_from_slice(&[3, b'w', b'w', b'w', 0xC0, 12, 0, 1, 0, 1]);
+        assert!(MessageBase::from_bytes(&cycle, 0).is_err());
+    }
+
+    //ONE QUESTION AND answers A RECORDS, ENOUGH OF THEM TAKES IT PAST A BLOCK
+    fn padded_message(answers: usize, padding_policy: PaddingPolicies) -> MessageBase {
+        let mut message = MessageBase::new(0x1234);
+        message.add_query(DnsQuery::new("example.com", Types::A, DnsClasses::In));
+
+        for i in 0..answers {
+            message.add_answers("example.com", Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, i as u8]))));
+        }
+
+        message.set_padding_policy(padding_policy);
+        message
+    }
+
+    fn padding_options(buf: &[u8]) -> Vec<Vec<u8>> {
+        let message = MessageBase::from_bytes(buf, 0).unwrap();
+        message.get_opt_record().unwrap().get_options(&OptCodes::Padding).into_iter().cloned().collect()
+    }
+
+    #[test]
+    fn encode_padded_lands_on_the_block() {
+        for (padding_policy, block_size) in [(PaddingPolicies::Query, 128), (PaddingPolicies::Response, 468), (PaddingPolicies::Block(64), 64), (PaddingPolicies::Block(200), 200)] {
+            for answers in [0, 1, 10, 40] {
+                let message = padded_message(answers, padding_policy);
+                let buf = message.encode();
+
+                assert_eq!(buf.len() % block_size, 0, "{} answers padded to {}", answers, block_size);
+                //THE SMALLEST MULTIPLE THAT FITS THE MESSAGE, A ROOT OPT RR OF 11 BYTES AND THE 4 BYTE OPTION HEADER
+                let minimum = message.encode_with(&message.additional_records).len()+11+4;
+                assert!(buf.len() >= minimum && buf.len() < minimum+block_size);
+                assert_eq!(padding_options(&buf).len(), 1);
+            }
+        }
+
+        //THE SMALLEST QUERY STILL FILLS A WHOLE BLOCK
+        assert_eq!(padded_message(0, PaddingPolicies::Query).encode().len(), 128);
+        assert_eq!(padded_message(0, PaddingPolicies::Response).encode().len(), 468);
+    }
+
+    #[test]
+    fn encode_padded_replaces_existing_padding() {
+        let mut message = padded_message(1, PaddingPolicies::Query);
+        let mut opt = OptRecord::new(1232, 0, 0, 0);
+        opt.add_option(OptCodes::Padding, vec![0; 300]);
+        opt.add_option(OptCodes::Nsid, Vec::new());
+        opt.add_option(OptCodes::Padding, vec![0; 5]);
+        message.add_additional_records("", Box::new(opt));
+
+        let buf = message.encode();
+        assert_eq!(buf.len(), 128);
+        assert_eq!(padding_options(&buf).len(), 1);
+
+        let decoded = MessageBase::from_bytes(&buf, 0).unwrap();
+        let opt = decoded.get_opt_record().unwrap();
+        assert_eq!(opt.get_payload_size(), 1232);
+        assert_eq!(opt.get_options(&OptCodes::Nsid).len(), 1);
+        assert_eq!(decoded.get_additional_records().iter().map(|(_, records)| records.len()).sum::<usize>(), 1);
+
+        //ENCODING WORKS ON A COPY, THE MESSAGE KEEPS ITS OWN OPTIONS
+        assert_eq!(message.get_opt_record().unwrap().get_options(&OptCodes::Padding).len(), 2);
+    }
+
+    #[test]
+    fn block_zero_adds_no_padding() {
+        let padded = padded_message(3, PaddingPolicies::Block(0));
+        let plain = padded_message(3, PaddingPolicies::None);
+
+        assert_eq!(padded.encode(), plain.encode());
+        assert!(MessageBase::from_bytes(&padded.encode(), 0).unwrap().get_opt_record().is_none());
+    }
 }
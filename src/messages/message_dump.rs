@@ -0,0 +1,566 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+use crate::messages::inter::dns_classes::DnsClasses;
+use crate::messages::inter::op_codes::OpCodes;
+use crate::messages::inter::response_codes::ResponseCodes;
+use crate::messages::inter::types::Types;
+use crate::records::inter::dnssec_algorithms::DnsSecAlgorithms;
+use crate::records::inter::svc_param_keys::SvcParamKeys;
+use crate::records::opt_record::OptRecord;
+use crate::utils::string_utils::{escape_string, quote_string};
+use crate::utils::time_utils::to_timestamp;
+
+const BYTES_PER_LINE: usize = 8;
+const MAX_POINTER_HOPS: usize = 128;
+
+struct Annotation {
+    off: Option<usize>,
+    length: usize,
+    depth: usize,
+    text: String
+}
+
+//WALKS THE RAW BYTES ON ITS OWN, NOTHING HERE GOES THROUGH THE DECODER SO THE TWO CAN BE COMPARED
+struct MessageDump<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    end: usize,
+    depth: usize,
+    annotations: Vec<Annotation>
+}
+
+pub fn dump(buf: &[u8], off: usize) -> String {
+    let buf = buf.get(off..).unwrap_or_default();
+
+    let mut dump = MessageDump {
+        buf,
+        pos: 0,
+        end: buf.len(),
+        depth: 0,
+        annotations: Vec::new()
+    };
+
+    if let Err(e) = dump.walk() {
+        dump.depth = 0;
+        let remaining = dump.buf.len().saturating_sub(dump.pos);
+        dump.annotate(dump.pos.min(dump.buf.len()), remaining, format!("ERROR: {}", e));
+    }
+
+    dump.render()
+}
+
+impl<'a> MessageDump<'a> {
+
+    fn walk(&mut self) -> Result<(), String> {
+        self.take(2, |b| format!("ID: {}", u16::from_be_bytes([b[0], b[1]])))?;
+        self.header_flags()?;
+
+        let qd_count = self.take_u16(|v| format!("QDCOUNT: {}", v))?;
+        let an_count = self.take_u16(|v| format!("ANCOUNT: {}", v))?;
+        let ns_count = self.take_u16(|v| format!("NSCOUNT: {}", v))?;
+        let ar_count = self.take_u16(|v| format!("ARCOUNT: {}", v))?;
+
+        for i in 0..qd_count {
+            self.heading(format!("QUESTION #{}", i+1));
+            self.depth += 1;
+            self.question()?;
+            self.depth -= 1;
+        }
+
+        for (section, count) in [("ANSWER", an_count), ("AUTHORITY", ns_count), ("ADDITIONAL", ar_count)] {
+            for i in 0..count {
+                self.heading(format!("{} #{}", section, i+1));
+                self.depth += 1;
+                self.record()?;
+                self.depth -= 1;
+            }
+        }
+
+        if self.pos < self.buf.len() {
+            let remaining = self.buf.len()-self.pos;
+            self.take(remaining, |_| format!("TRAILING DATA: {} bytes past the last record", remaining))?;
+        }
+
+        Ok(())
+    }
+
+    fn header_flags(&mut self) -> Result<(), String> {
+        let flags = self.take_u16(|v| format!("FLAGS: 0x{:04x}", v))?;
+
+        let op_code = ((flags >> 11) & 0x0F) as u8;
+        let response_code = (flags & 0x000F) as u8;
+
+        self.depth += 1;
+        self.bits(flags, 0x8000, format!("QR: {}", if flags & 0x8000 != 0 { "response" } else { "query" }));
+        self.bits(flags, 0x7800, format!("OPCODE: {} ({})", OpCodes::from_code(op_code).map(|o| o.get_name().to_string()).unwrap_or(format!("OPCODE{}", op_code)), op_code));
+        self.bits(flags, 0x0400, format!("AA: {}", if flags & 0x0400 != 0 { "authoritative" } else { "not authoritative" }));
+        self.bits(flags, 0x0200, format!("TC: {}", if flags & 0x0200 != 0 { "truncated" } else { "not truncated" }));
+        self.bits(flags, 0x0100, format!("RD: {}", if flags & 0x0100 != 0 { "recursion desired" } else { "no recursion" }));
+        self.bits(flags, 0x0080, format!("RA: {}", if flags & 0x0080 != 0 { "recursion available" } else { "no recursion available" }));
+        self.bits(flags, 0x0040, format!("Z: {}", (flags >> 6) & 1));
+        self.bits(flags, 0x0020, format!("AD: {}", if flags & 0x0020 != 0 { "authenticated data" } else { "not authenticated" }));
+        self.bits(flags, 0x0010, format!("CD: {}", if flags & 0x0010 != 0 { "checking disabled" } else { "checking enabled" }));
+        self.bits(flags, 0x000F, format!("RCODE: {} ({})", ResponseCodes::from_code(response_code).map(|r| r.get_name().to_string()).unwrap_or(format!("RCODE{}", response_code)), response_code));
+        self.depth -= 1;
+
+        Ok(())
+    }
+
+    fn question(&mut self) -> Result<(), String> {
+        self.name("QNAME")?;
+        self.take_u16(|v| format!("QTYPE: {}", type_name(v)))?;
+        self.take_u16(|v| {
+            //MDNS (RFC 6762 5.4) REUSES THE TOP BIT TO ASK FOR A UNICAST RESPONSE
+            match v & 0x8000 {
+                0 => format!("QCLASS: {}", class_name(v)),
+                _ => format!("QCLASS: {}, unicast response requested", class_name(v & 0x7FFF))
+            }
+        })?;
+
+        Ok(())
+    }
+
+    fn record(&mut self) -> Result<(), String> {
+        self.name("NAME")?;
+        let _type = self.take_u16(|v| format!("TYPE: {}", type_name(v)))?;
+
+        if _type == Types::Opt.get_code() {
+            return self.opt_record();
+        }
+
+        self.take_u16(|v| {
+            //MDNS (RFC 6762 10.2) REUSES THE TOP BIT FOR CACHE FLUSH
+            match v & 0x8000 {
+                0 => format!("CLASS: {}", class_name(v)),
+                _ => format!("CLASS: {}, cache flush", class_name(v & 0x7FFF))
+            }
+        })?;
+        self.take(4, |b| format!("TTL: {}", u32::from_be_bytes([b[0], b[1], b[2], b[3]])))?;
+
+        self.rdata(_type)
+    }
+
+    fn opt_record(&mut self) -> Result<(), String> {
+        self.take_u16(|v| format!("UDP PAYLOAD SIZE: {}", v))?;
+        self.take(1, |b| format!("EXTENDED RCODE: {}", b[0]))?;
+        self.take(1, |b| format!("EDNS VERSION: {}", b[0]))?;
+        let flags = self.take_u16(|v| format!("EDNS FLAGS: 0x{:04x}", v))?;
+
+        self.depth += 1;
+        self.bits(flags, 0x8000, format!("DO: {}", if flags & 0x8000 != 0 { "DNSSEC answers OK" } else { "no DNSSEC" }));
+        self.bits(flags, 0x7FFF, format!("Z: 0x{:04x}", flags & 0x7FFF));
+        self.depth -= 1;
+
+        self.rdata(Types::Opt.get_code())
+    }
+
+    fn rdata(&mut self, _type: u16) -> Result<(), String> {
+        let length = self.take_u16(|v| format!("RDLENGTH: {}", v))? as usize;
+        let end = self.check(length).map_err(|_| format!("RDLENGTH {} runs past the end of the message", length))?;
+
+        let outer = self.end;
+        let depth = self.depth;
+        self.end = end;
+
+        self.heading("RDATA".to_string());
+        self.depth += 1;
+        let result = self.rdata_fields(Types::from_code(_type).unwrap_or(Types::Unknown(_type)));
+
+        //A FIELD THAT RAN SHORT IS REPORTED BUT THE WALK CARRIES ON AT THE NEXT RECORD
+        self.end = end;
+        self.depth = depth+1;
+
+        if let Err(e) = result {
+            self.annotate(self.pos, end-self.pos, format!("ERROR: {}", e));
+            self.pos = end;
+        }
+
+        if self.pos < end {
+            let remaining = end-self.pos;
+            self.take(remaining, |_| format!("UNPARSED: {} bytes left inside RDLENGTH", remaining))?;
+        }
+
+        self.depth = depth;
+        self.end = outer;
+
+        Ok(())
+    }
+
+    fn rdata_fields(&mut self, _type: Types) -> Result<(), String> {
+        match _type {
+            Types::A => {
+                self.take(4, |b| format!("ADDRESS: {}", Ipv4Addr::new(b[0], b[1], b[2], b[3])))?;
+            }
+            Types::Aaaa => {
+                self.take(16, |b| format!("ADDRESS: {}", Ipv6Addr::from(<[u8; 16]>::try_from(b).unwrap())))?;
+            }
+            Types::Ns => {
+                self.name("NSDNAME")?;
+            }
+            Types::Cname => {
+                self.name("CNAME")?;
+            }
+            Types::Dname => {
+                self.name("DNAME")?;
+            }
+            Types::Ptr => {
+                self.name("PTRDNAME")?;
+            }
+            Types::Mx => {
+                self.take_u16(|v| format!("PREFERENCE: {}", v))?;
+                self.name("EXCHANGE")?;
+            }
+            Types::Soa => {
+                self.name("MNAME")?;
+                self.name("RNAME")?;
+                for field in ["SERIAL", "REFRESH", "RETRY", "EXPIRE", "MINIMUM"] {
+                    self.take(4, |b| format!("{}: {}", field, u32::from_be_bytes([b[0], b[1], b[2], b[3]])))?;
+                }
+            }
+            Types::Txt | Types::Spf => {
+                while self.pos < self.end {
+                    self.character_string("STRING")?;
+                }
+            }
+            Types::Srv => {
+                self.take_u16(|v| format!("PRIORITY: {}", v))?;
+                self.take_u16(|v| format!("WEIGHT: {}", v))?;
+                self.take_u16(|v| format!("PORT: {}", v))?;
+                self.name("TARGET")?;
+            }
+            Types::Opt => {
+                while self.pos < self.end {
+                    self.opt_option()?;
+                }
+            }
+            Types::Rrsig => {
+                self.take_u16(|v| format!("TYPE COVERED: {}", type_name(v)))?;
+                self.take(1, |b| format!("ALGORITHM: {}", algorithm_name(b[0])))?;
+                self.take(1, |b| format!("LABELS: {}", b[0]))?;
+                self.take(4, |b| format!("ORIGINAL TTL: {}", u32::from_be_bytes([b[0], b[1], b[2], b[3]])))?;
+                self.take(4, |b| format!("EXPIRATION: {}", to_timestamp(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))))?;
+                self.take(4, |b| format!("INCEPTION: {}", to_timestamp(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))))?;
+                self.take_u16(|v| format!("KEY TAG: {}", v))?;
+                self.name("SIGNER")?;
+                let remaining = self.end-self.pos;
+                self.take(remaining, |_| format!("SIGNATURE: {} bytes", remaining))?;
+            }
+            Types::Nsec => {
+                self.name("NEXT DOMAIN")?;
+                self.type_bitmaps()?;
+            }
+            Types::DnsKey => {
+                let flags = self.take_u16(|v| format!("FLAGS: {}", v))?;
+                self.depth += 1;
+                self.bits(flags, 0x0100, format!("ZONE KEY: {}", flags & 0x0100 != 0));
+                self.bits(flags, 0x0080, format!("REVOKE: {}", flags & 0x0080 != 0));
+                self.bits(flags, 0x0001, format!("SECURE ENTRY POINT: {}", flags & 0x0001 != 0));
+                self.depth -= 1;
+                self.take(1, |b| format!("PROTOCOL: {}", b[0]))?;
+                self.take(1, |b| format!("ALGORITHM: {}", algorithm_name(b[0])))?;
+                let remaining = self.end-self.pos;
+                self.take(remaining, |_| format!("PUBLIC KEY: {} bytes", remaining))?;
+            }
+            Types::Https => {
+                self.take_u16(|v| format!("PRIORITY: {}{}", v, if v == 0 { " (alias mode)" } else { "" }))?;
+                self.name("TARGET")?;
+                while self.pos < self.end {
+                    self.svc_param()?;
+                }
+            }
+            _ => {
+                let remaining = self.end-self.pos;
+                self.take(remaining, |_| format!("DATA: {} bytes", remaining))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn opt_option(&mut self) -> Result<(), String> {
+        let code = self.take_u16(|v| format!("OPTION CODE: {}", v))?;
+        let length = self.take_u16(|v| format!("OPTION LENGTH: {}", v))? as usize;
+        self.take(length, |b| format!("OPTION DATA: {}", OptRecord::option_to_string(code, b)))?;
+        Ok(())
+    }
+
+    fn svc_param(&mut self) -> Result<(), String> {
+        let key = self.take_u16(|v| format!("KEY: {}", SvcParamKeys::from_code(v).map(|k| k.get_name().to_string()).unwrap_or(format!("key{}", v))))?;
+        let length = self.take_u16(|v| format!("LENGTH: {}", v))? as usize;
+
+        self.depth += 1;
+        match SvcParamKeys::from_code(key) {
+            Ok(SvcParamKeys::Alpn) => {
+                let end = self.end;
+                self.end = self.check(length)?;
+                while self.pos < self.end {
+                    self.character_string("ALPN ID")?;
+                }
+                self.end = end;
+            }
+            Ok(SvcParamKeys::Port) if length == 2 => {
+                self.take_u16(|v| format!("PORT: {}", v))?;
+            }
+            Ok(SvcParamKeys::Ipv4Hint) if length.is_multiple_of(4) => {
+                for _ in 0..length/4 {
+                    self.take(4, |b| format!("ADDRESS: {}", Ipv4Addr::new(b[0], b[1], b[2], b[3])))?;
+                }
+            }
+            Ok(SvcParamKeys::Ipv6Hint) if length.is_multiple_of(16) => {
+                for _ in 0..length/16 {
+                    self.take(16, |b| format!("ADDRESS: {}", Ipv6Addr::from(<[u8; 16]>::try_from(b).unwrap())))?;
+                }
+            }
+            Ok(SvcParamKeys::Mandatory) if length.is_multiple_of(2) => {
+                for _ in 0..length/2 {
+                    self.take_u16(|v| format!("KEY: {}", SvcParamKeys::from_code(v).map(|k| k.get_name().to_string()).unwrap_or(format!("key{}", v))))?;
+                }
+            }
+            _ => {
+                self.take(length, |_| format!("VALUE: {} bytes", length))?;
+            }
+        }
+        self.depth -= 1;
+
+        Ok(())
+    }
+
+    fn type_bitmaps(&mut self) -> Result<(), String> {
+        while self.pos < self.end {
+            let window = self.take(1, |b| format!("WINDOW: {}", b[0]))?[0] as u16;
+            let length = self.take(1, |b| format!("BITMAP LENGTH: {}", b[0]))?[0] as usize;
+
+            self.take(length, |b| {
+                let mut types = Vec::new();
+
+                for (i, &byte) in b.iter().enumerate() {
+                    for bit in 0..8 {
+                        if byte & (1 << (7-bit)) != 0 {
+                            types.push(type_name(window*256+(i as u16)*8+bit));
+                        }
+                    }
+                }
+
+                format!("BITMAP: {}", types.join(" "))
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn character_string(&mut self, field: &str) -> Result<(), String> {
+        let length = self.take(1, |b| format!("LENGTH: {}", b[0]))?[0] as usize;
+        self.take(length, |b| format!("{}: {}", field, quote_string(b)))?;
+        Ok(())
+    }
+
+    fn name(&mut self, field: &str) -> Result<(), String> {
+        let index = self.annotations.len();
+        self.heading(field.to_string());
+        self.depth += 1;
+
+        let mut labels = Vec::new();
+
+        loop {
+            let length = self.take(1, |b| match b[0] & 0xC0 {
+                0xC0 => "COMPRESSION POINTER".to_string(),
+                0x00 if b[0] == 0 => "ROOT".to_string(),
+                0x00 => format!("LABEL LENGTH: {}", b[0]),
+                _ => format!("RESERVED LABEL TYPE: 0x{:02x}", b[0])
+            })?[0];
+
+            match length & 0xC0 {
+                0xC0 => {
+                    //THE POINTER BYTES ARE RE-ANNOTATED AS ONE RANGE SO THE TARGET SITS NEXT TO THEM
+                    self.annotations.pop();
+                    self.pos -= 1;
+
+                    let target = (((length & 0x3F) as usize) << 8) | *self.buf.get(self.pos+1).ok_or("compression pointer cut short")? as usize;
+                    let resolved = self.resolve(target, self.pos);
+
+                    match resolved {
+                        Ok(pointed) => {
+                            self.take(2, |_| format!("POINTER -> 0x{:04x} ({})", target, to_name(&pointed)))?;
+                            labels.extend(pointed);
+                        }
+                        Err(e) => {
+                            self.take(2, |_| format!("POINTER -> 0x{:04x} (ERROR: {})", target, e))?;
+                            labels.push(b"?".to_vec());
+                        }
+                    }
+                    break;
+                }
+                0x00 if length == 0 => break,
+                0x00 => {
+                    let label = self.take(length as usize, |b| format!("LABEL: {}", escape_string(b, b".")))?;
+                    labels.push(label.to_vec());
+                }
+                _ => return Err(format!("label type 0x{:02x} is reserved", length & 0xC0))
+            }
+        }
+
+        self.depth -= 1;
+        self.annotations[index].text = format!("{}: {}", field, to_name(&labels));
+
+        Ok(())
+    }
+
+    //FOLLOWS POINTERS WITHOUT ANNOTATING, EVERY HOP HAS TO POINT BACKWARDS SO LOOPS END
+    fn resolve(&self, target: usize, from: usize) -> Result<Vec<Vec<u8>>, String> {
+        let mut labels = Vec::new();
+        let mut pos = target;
+        let mut hops = 0;
+
+        if pos >= from {
+            return Err("pointer doesn't point backwards".to_string());
+        }
+
+        loop {
+            let length = *self.buf.get(pos).ok_or("pointer target is past the end")?;
+
+            match length & 0xC0 {
+                0xC0 => {
+                    hops += 1;
+                    if hops > MAX_POINTER_HOPS {
+                        return Err("too many pointer hops".to_string());
+                    }
+
+                    let next = (((length & 0x3F) as usize) << 8) | *self.buf.get(pos+1).ok_or("pointer target is past the end")? as usize;
+                    if next >= pos {
+                        return Err("pointer loop".to_string());
+                    }
+
+                    pos = next;
+                }
+                0x00 if length == 0 => return Ok(labels),
+                0x00 => {
+                    let label = self.buf.get(pos+1..pos+1+length as usize).ok_or("label at pointer target is past the end")?;
+                    labels.push(label.to_vec());
+                    pos += 1+length as usize;
+                }
+                _ => return Err(format!("label type 0x{:02x} is reserved", length & 0xC0))
+            }
+        }
+    }
+
+    fn check(&self, length: usize) -> Result<usize, String> {
+        if self.pos+length > self.end {
+            return Err(format!("needed {} bytes at 0x{:04x}, only {} left", length, self.pos, self.end-self.pos.min(self.end)));
+        }
+
+        Ok(self.pos+length)
+    }
+
+    fn take<F: FnOnce(&[u8]) -> String>(&mut self, length: usize, text: F) -> Result<&'a [u8], String> {
+        let end = self.check(length)?;
+        let bytes = &self.buf[self.pos..end];
+
+        self.annotate(self.pos, length, text(bytes));
+        self.pos = end;
+
+        Ok(bytes)
+    }
+
+    fn take_u16<F: FnOnce(u16) -> String>(&mut self, text: F) -> Result<u16, String> {
+        let bytes = self.take(2, |b| text(u16::from_be_bytes([b[0], b[1]])))?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn annotate(&mut self, off: usize, length: usize, text: String) {
+        self.annotations.push(Annotation {
+            off: Some(off),
+            length,
+            depth: self.depth,
+            text
+        });
+    }
+
+    fn heading(&mut self, text: String) {
+        self.annotations.push(Annotation {
+            off: Some(self.pos),
+            length: 0,
+            depth: self.depth,
+            text
+        });
+    }
+
+    //WIRESHARK STYLE, THE BITS A FIELD OWNS ARE SHOWN AND THE REST ARE DOTS
+    fn bits(&mut self, value: u16, mask: u16, text: String) {
+        let mut pattern = String::new();
+
+        for bit in (0..16).rev() {
+            if bit != 15 && bit % 4 == 3 {
+                pattern.push(' ');
+            }
+
+            pattern.push(match (mask >> bit) & 1 {
+                0 => '.',
+                _ if (value >> bit) & 1 == 1 => '1',
+                _ => '0'
+            });
+        }
+
+        self.annotations.push(Annotation {
+            off: None,
+            length: 0,
+            depth: self.depth,
+            text: format!("{} = {}", pattern, text)
+        });
+    }
+
+    fn render(&self) -> String {
+        let mut dump = String::new();
+
+        for annotation in &self.annotations {
+            let bytes = match annotation.off {
+                Some(off) => &self.buf[off..off+annotation.length],
+                None => &[][..]
+            };
+
+            let mut chunks = bytes.chunks(BYTES_PER_LINE);
+            let first = chunks.next().unwrap_or_default();
+
+            let off = match annotation.off {
+                Some(off) => format!("{:04x}", off),
+                None => "    ".to_string()
+            };
+
+            dump.push_str(&format!("{}  {:<width$}  {}{}\n", off, to_hex(first), "  ".repeat(annotation.depth), annotation.text, width = BYTES_PER_LINE*3-1));
+
+            for (i, chunk) in chunks.enumerate() {
+                dump.push_str(&format!("{:04x}  {}\n", annotation.off.unwrap()+(i+1)*BYTES_PER_LINE, to_hex(chunk)));
+            }
+        }
+
+        dump
+    }
+}
+
+fn to_hex(buf: &[u8]) -> String {
+    buf.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" ")
+}
+
+fn to_name(labels: &[Vec<u8>]) -> String {
+    if labels.is_empty() {
+        return ".".to_string();
+    }
+
+    labels.iter().map(|label| escape_string(label, b".") + ".").collect()
+}
+
+fn type_name(code: u16) -> String {
+    format!("{} ({})", Types::from_code(code).unwrap_or(Types::Unknown(code)), code)
+}
+
+fn class_name(code: u16) -> String {
+    match DnsClasses::from_code(code) {
+        Ok(dns_class) => format!("{} ({})", dns_class.get_name(), code),
+        Err(_) => format!("CLASS{} ({})", code, code)
+    }
+}
+
+fn algorithm_name(code: u8) -> String {
+    match DnsSecAlgorithms::from_code(code) {
+        Ok(algorithm) => format!("{} ({})", algorithm.get_mnemonic(), code),
+        Err(_) => code.to_string()
+    }
+}
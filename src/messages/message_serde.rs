@@ -0,0 +1,285 @@
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use crate::messages::inter::dns_classes::DnsClasses;
+use crate::messages::inter::op_codes::OpCodes;
+use crate::messages::inter::response_codes::ResponseCodes;
+use crate::messages::inter::types::Types;
+use crate::messages::message_base::MessageBase;
+use crate::records::inter::record_base::RecordBase;
+use crate::records::record_serde::{record_from_json, record_to_json};
+use crate::utils::dns_query::DnsQuery;
+use crate::utils::domain_utils::{from_fqdn, to_fqdn};
+use crate::utils::hex;
+use crate::utils::ordered_map::OrderedMap;
+
+//RFC 8427 - DNS MESSAGES AS JSON
+
+fn query_to_json(query: &DnsQuery) -> Map<String, Value> {
+    let mut map = Map::new();
+    map.insert("NAME".to_string(), Value::from(to_fqdn(&query.get_query().unwrap_or_default())));
+    map.insert("TYPE".to_string(), Value::from(query.get_type().get_code()));
+    map.insert("TYPEname".to_string(), Value::from(query.get_type().to_string()));
+    map.insert("CLASS".to_string(), Value::from(query.get_dns_class().get_code() | if query.is_unicast_response() { 0x8000 } else { 0 }));
+    map.insert("CLASSname".to_string(), Value::from(query.get_dns_class().get_name()));
+    map
+}
+
+//QUESTIONS USE NAME/TYPE/CLASS INSIDE questionRRs AND QNAME/QTYPE/QCLASS AT THE TOP LEVEL
+fn query_from_json(map: &Map<String, Value>, prefix: &str) -> Result<DnsQuery, String> {
+    let name = match map.get(&format!("{}NAME", prefix)) {
+        Some(Value::String(name)) => from_fqdn(name).map_err(|e| format!("Invalid {}NAME: {}", prefix, e))?,
+        _ => return Err(format!("Question needs a {}NAME string", prefix))
+    };
+
+    let _type = match (map.get(&format!("{}TYPE", prefix)), map.get(&format!("{}TYPEname", prefix))) {
+        (Some(code), _) => {
+            let code = code.as_u64().and_then(|code| u16::try_from(code).ok()).ok_or(format!("{}TYPE must be 0-65535", prefix))?;
+            Types::from_code(code).unwrap_or(Types::Unknown(code))
+        }
+        (None, Some(Value::String(name))) => Types::from_name(name)?,
+        _ => Types::A
+    };
+
+    let dns_class = match (map.get(&format!("{}CLASS", prefix)), map.get(&format!("{}CLASSname", prefix))) {
+        (Some(code), _) => code.as_u64().and_then(|code| u16::try_from(code).ok()).ok_or(format!("{}CLASS must be 0-65535", prefix))?,
+        (None, Some(Value::String(name))) => DnsClasses::from_name(name)?.get_code(),
+        _ => DnsClasses::In.get_code()
+    };
+
+    //MDNS UNICAST RESPONSE BIT RIDES ON THE CLASS
+    let mut query = DnsQuery::new(&name, _type, DnsClasses::from_code(dns_class & 0x7FFF)?);
+    query.set_unicast_response((dns_class & 0x8000) != 0);
+
+    Ok(query)
+}
+
+fn records_to_json(records: &OrderedMap<String, Vec<Box<dyn RecordBase>>>) -> Result<Vec<Value>, String> {
+    let mut array = Vec::new();
+
+    for (name, records) in records.iter() {
+        for record in records {
+            array.push(Value::Object(record_to_json(Some(name), record.as_ref())?));
+        }
+    }
+
+    Ok(array)
+}
+
+fn records_from_json<F: FnMut(&str, Box<dyn RecordBase>)>(map: &Map<String, Value>, key: &str, mut add: F) -> Result<(), String> {
+    match map.get(key) {
+        Some(Value::Array(array)) => {
+            for record in array {
+                match record {
+                    Value::Object(record) => {
+                        let (name, record) = record_from_json(record).map_err(|e| format!("{}: {}", key, e))?;
+                        add(&name, record);
+                    }
+                    _ => return Err(format!("{} must hold RR objects", key))
+                }
+            }
+            Ok(())
+        }
+        Some(_) => Err(format!("{} must be an array", key)),
+        None => Ok(())
+    }
+}
+
+//RFC 8427 SAYS BOOLEAN, SOME TOOLS WRITE 0 AND 1
+fn get_bool(map: &Map<String, Value>, key: &str) -> Result<bool, String> {
+    match map.get(key) {
+        Some(Value::Bool(value)) => Ok(*value),
+        Some(Value::Number(value)) if value.as_u64() == Some(0) || value.as_u64() == Some(1) => Ok(value.as_u64() == Some(1)),
+        Some(_) => Err(format!("{} must be a boolean", key)),
+        None => Ok(false)
+    }
+}
+
+fn get_u64(map: &Map<String, Value>, key: &str, max: u64) -> Result<u64, String> {
+    match map.get(key) {
+        Some(value) => value.as_u64().filter(|value| *value <= max).ok_or(format!("{} must be 0-{}", key, max)),
+        None => Ok(0)
+    }
+}
+
+pub fn message_to_json(message: &MessageBase) -> Result<Map<String, Value>, String> {
+    let count = |records: &OrderedMap<String, Vec<Box<dyn RecordBase>>>| records.iter().map(|(_, records)| records.len()).sum::<usize>();
+    let queries = message.get_queries();
+
+    let mut map = Map::new();
+    map.insert("ID".to_string(), Value::from(message.get_id()));
+    map.insert("QR".to_string(), Value::from(message.is_qr()));
+    map.insert("Opcode".to_string(), Value::from(message.get_op_code().get_code()));
+    map.insert("AA".to_string(), Value::from(message.is_authoritative()));
+    map.insert("TC".to_string(), Value::from(message.is_truncated()));
+    map.insert("RD".to_string(), Value::from(message.is_recursion_desired()));
+    map.insert("RA".to_string(), Value::from(message.is_recursion_available()));
+    map.insert("AD".to_string(), Value::from(message.is_authenticated_data()));
+    map.insert("CD".to_string(), Value::from(message.is_checking_disabled()));
+    map.insert("RCODE".to_string(), Value::from(message.get_response_code().get_code()));
+    map.insert("QDCOUNT".to_string(), Value::from(queries.len()));
+    map.insert("ANCOUNT".to_string(), Value::from(count(message.get_answers())));
+    map.insert("NSCOUNT".to_string(), Value::from(count(message.get_name_servers())));
+    map.insert("ARCOUNT".to_string(), Value::from(count(message.get_additional_records())));
+
+    //RFC 8427 2.1 - THE FLAT Q* MEMBERS ONLY MAKE SENSE WITH EXACTLY ONE QUESTION
+    if queries.len() == 1 {
+        for (key, value) in query_to_json(&queries[0]) {
+            map.insert(format!("Q{}", key), value);
+        }
+    }
+
+    map.insert("questionRRs".to_string(), Value::Array(queries.iter().map(|query| Value::Object(query_to_json(query))).collect()));
+    map.insert("answerRRs".to_string(), Value::Array(records_to_json(message.get_answers())?));
+    map.insert("authorityRRs".to_string(), Value::Array(records_to_json(message.get_name_servers())?));
+    map.insert("additionalRRs".to_string(), Value::Array(records_to_json(message.get_additional_records())?));
+    map.insert("messageOctetsHEX".to_string(), Value::from(hex::encode(&message.encode())));
+
+    Ok(map)
+}
+
+//messageOctetsHEX IS THE WHOLE MESSAGE, WHEN IT'S THERE IT WINS OVER THE PARSED MEMBERS
+pub fn message_from_json(map: &Map<String, Value>) -> Result<MessageBase, String> {
+    if let Some(octets) = map.get("messageOctetsHEX") {
+        let buf = hex::decode(octets.as_str().ok_or("messageOctetsHEX must be a string")?)?;
+
+        return MessageBase::from_bytes(&buf, 0);
+    }
+
+    let mut message = MessageBase::new(get_u64(map, "ID", 65535)? as u16);
+    message.set_qr(get_bool(map, "QR")?);
+    message.set_op_code(OpCodes::from_code(get_u64(map, "Opcode", 15)? as u8)?);
+    message.set_authoritative(get_bool(map, "AA")?);
+    message.set_truncated(get_bool(map, "TC")?);
+    message.set_recursion_desired(get_bool(map, "RD")?);
+    message.set_recursion_available(get_bool(map, "RA")?);
+    message.set_authenticated_data(get_bool(map, "AD")?);
+    message.set_checking_disabled(get_bool(map, "CD")?);
+    message.set_response_code(ResponseCodes::from_code(get_u64(map, "RCODE", 15)? as u8)?);
+
+    match map.get("questionRRs") {
+        Some(Value::Array(queries)) => {
+            for query in queries {
+                match query {
+                    Value::Object(query) => message.add_query(query_from_json(query, "")?),
+                    _ => return Err("questionRRs must hold question objects".to_string())
+                }
+            }
+        }
+        Some(_) => return Err("questionRRs must be an array".to_string()),
+        None if map.contains_key("QNAME") => message.add_query(query_from_json(map, "Q")?),
+        None => {}
+    }
+
+    records_from_json(map, "answerRRs", |name, record| message.add_answers(name, record))?;
+    records_from_json(map, "authorityRRs", |name, record| message.add_name_servers(name, record))?;
+    records_from_json(map, "additionalRRs", |name, record| message.add_additional_records(name, record))?;
+
+    Ok(message)
+}
+
+impl Serialize for MessageBase {
+
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        message_to_json(self).map_err(S::Error::custom)?.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for MessageBase {
+
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map = Map::deserialize(deserializer)?;
+        message_from_json(&map).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for DnsQuery {
+
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        query_to_json(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DnsQuery {
+
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map = Map::deserialize(deserializer)?;
+        query_from_json(&map, "").map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::net::IpAddr;
+    use serde_json::{json, Value};
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::response_codes::ResponseCodes;
+    use crate::messages::inter::types::Types;
+    use crate::messages::message_base::MessageBase;
+    use crate::messages::message_serde::{message_from_json, message_to_json};
+    use crate::records::a_record::ARecord;
+    use crate::records::mx_record::MxRecord;
+    use crate::records::ns_record::NsRecord;
+    use crate::utils::dns_query::DnsQuery;
+
+    fn response() -> MessageBase {
+        let mut message = MessageBase::new(0x1234);
+        message.set_qr(true);
+        message.set_authoritative(true);
+        message.set_recursion_desired(true);
+        message.set_response_code(ResponseCodes::NameError);
+        message.add_query(DnsQuery::new("example.com", Types::Mx, DnsClasses::In));
+        message.add_answers("example.com", Box::new(MxRecord::new(DnsClasses::In, 300, 10, "mail.example.com")));
+        message.add_name_servers("example.com", Box::new(NsRecord::new(DnsClasses::In, 3600, "ns.example.com")));
+        message.add_additional_records("mail.example.com", Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 25]))));
+        message
+    }
+
+    #[test]
+    fn message_round_trip() {
+        let message = response();
+
+        //WITHOUT THE OCTETS THE MESSAGE HAS TO BE REBUILT FROM THE PARSED MEMBERS
+        let mut map = message_to_json(&message).unwrap();
+        assert_eq!(map["QNAME"], json!("example.com."));
+        assert_eq!(map["ANCOUNT"], json!(1));
+        map.remove("messageOctetsHEX");
+        assert_eq!(message_from_json(&map).unwrap().encode(), message.encode());
+
+        let text = serde_json::to_string(&message).unwrap();
+        assert_eq!(serde_json::from_str::<MessageBase>(&text).unwrap().encode(), message.encode());
+    }
+
+    #[test]
+    fn flat_question_members() {
+        let json = json!({"ID": 7, "RD": 1, "QNAME": "example.org.", "QTYPEname": "AAAA"});
+        let message = message_from_json(json.as_object().unwrap()).unwrap();
+
+        let query = &message.get_queries()[0];
+        assert_eq!(message.get_id(), 7);
+        assert!(message.is_recursion_desired());
+        assert_eq!(query.get_query().unwrap(), "example.org");
+        assert_eq!(query.get_type(), Types::Aaaa);
+        assert_eq!(query.get_dns_class(), DnsClasses::In);
+    }
+
+    #[test]
+    fn message_octets_win_over_parsed_members() {
+        let message = response();
+
+        let mut map = message_to_json(&message).unwrap();
+        map.insert("ID".to_string(), json!(1));
+        map.insert("QR".to_string(), json!(false));
+        map.insert("answerRRs".to_string(), Value::Array(Vec::new()));
+
+        let decoded = message_from_json(&map).unwrap();
+        assert_eq!(decoded.get_id(), 0x1234);
+        assert!(decoded.is_qr());
+        assert_eq!(decoded.encode(), message.encode());
+
+        map.insert("messageOctetsHEX".to_string(), json!("1234"));
+        assert!(message_from_json(&map).is_err());
+    }
+}
@@ -1,2 +1,5 @@
 pub mod inter;
 pub mod message_base;
+pub mod message_dump;
+#[cfg(feature = "serde")]
+pub mod message_serde;
@@ -0,0 +1,250 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::SystemTime;
+use crate::messages::inter::transports::Transports;
+use crate::messages::message_base::MessageBase;
+use crate::pcap::packet_parser::{PacketParser, TCP_FIN, TCP_RST, TCP_SYN};
+use crate::pcap::pcap_reader::PcapReader;
+use crate::pcap::tcp_flow::TcpFlow;
+
+pub type CapturedMessage = (SystemTime, SocketAddr, SocketAddr, MessageBase);
+
+//DNS AND MDNS, ANYTHING ELSE HAS TO BE ADDED WITH add_port
+const DEFAULT_PORTS: [u16; 2] = [53, 5353];
+
+pub struct DnsCapture<R: Read> {
+    reader: PcapReader<R>,
+    parser: PacketParser,
+    flows: HashMap<(SocketAddr, SocketAddr), TcpFlow>,
+    ports: Vec<u16>,
+    pending: VecDeque<Result<CapturedMessage, String>>,
+    frame: usize,
+    done: bool
+}
+
+impl DnsCapture<BufReader<File>> {
+
+    pub fn open(path: &Path) -> Result<Self, String> {
+        Ok(Self::from_reader(PcapReader::open(path)?))
+    }
+}
+
+impl<R: Read> DnsCapture<R> {
+
+    pub fn new(reader: R) -> Result<Self, String> {
+        Ok(Self::from_reader(PcapReader::new(reader)?))
+    }
+
+    fn from_reader(reader: PcapReader<R>) -> Self {
+        Self {
+            reader,
+            parser: PacketParser::new(),
+            flows: HashMap::new(),
+            ports: DEFAULT_PORTS.to_vec(),
+            pending: VecDeque::new(),
+            frame: 0,
+            done: false
+        }
+    }
+
+    pub fn add_port(&mut self, port: u16) {
+        if !self.ports.contains(&port) {
+            self.ports.push(port);
+        }
+    }
+
+    pub fn get_ports(&self) -> &[u16] {
+        &self.ports
+    }
+
+    //ONE FRAME CAN FINISH SEVERAL PIPELINED TCP MESSAGES OR NONE AT ALL
+    fn read_frame(&mut self) -> Result<bool, String> {
+        let frame = match self.reader.next_frame()? {
+            Some(frame) => frame,
+            None => return Ok(false)
+        };
+
+        self.frame += 1;
+
+        let packet = match self.parser.parse(frame.get_link_type(), frame.get_data()) {
+            Ok(Some(packet)) => packet,
+            Ok(None) => return Ok(true),
+            Err(e) => {
+                self.pending.push_back(Err(format!("Frame {}: {}", self.frame, e)));
+                return Ok(true);
+            }
+        };
+
+        let source = packet.get_source();
+        let destination = packet.get_destination();
+
+        if !self.ports.contains(&source.port()) && !self.ports.contains(&destination.port()) {
+            return Ok(true);
+        }
+
+        match packet.get_transport() {
+            Transports::Udp => {
+                self.decode(frame.get_timestamp(), source, destination, packet.get_payload());
+            }
+            Transports::Tcp => {
+                let flags = packet.get_flags();
+                let flow = self.flows.entry((source, destination)).or_default();
+                flow.push(packet.get_sequence(), (flags & TCP_SYN) != 0, packet.get_payload());
+
+                let mut messages = Vec::new();
+                while let Some(message) = flow.next_message() {
+                    messages.push(message);
+                }
+
+                if (flags & (TCP_FIN | TCP_RST)) != 0 {
+                    self.flows.remove(&(source, destination));
+                }
+
+                for message in messages {
+                    self.decode(frame.get_timestamp(), source, destination, &message);
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn decode(&mut self, timestamp: SystemTime, source: SocketAddr, destination: SocketAddr, buf: &[u8]) {
+        let message = MessageBase::from_bytes(buf, 0).map(|mut message| {
+            message.set_origin(source);
+            message.set_destination(destination);
+            (timestamp, source, destination, message)
+        }).map_err(|e| format!("Frame {}: {} -> {}: {}", self.frame, source, destination, e));
+
+        self.pending.push_back(message);
+    }
+}
+
+//DECODE ERRORS COME OUT AS Err AND THE WALK CARRIES ON, A BROKEN FILE ENDS IT
+impl<R: Read> Iterator for DnsCapture<R> {
+
+    type Item = Result<CapturedMessage, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(message) = self.pending.pop_front() {
+                return Some(message);
+            }
+
+            if self.done {
+                return None;
+            }
+
+            match self.read_frame() {
+                Ok(true) => {}
+                Ok(false) => self.done = true,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::types::Types;
+    use crate::messages::message_base::MessageBase;
+    use crate::pcap::dns_capture::DnsCapture;
+    use crate::utils::dns_query::DnsQuery;
+
+    //RAW IPV4 LINK TYPE, SO EACH FRAME IS JUST AN IP DATAGRAM
+    fn pcap(frames: &[Vec<u8>]) -> Vec<u8> {
+        let mut buf = 0xa1b2c3d4u32.to_le_bytes().to_vec();
+        buf.extend_from_slice(&[2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 0, 0, 101, 0, 0, 0]);
+
+        for (i, frame) in frames.iter().enumerate() {
+            buf.extend_from_slice(&(i as u32).to_le_bytes());
+            buf.extend_from_slice(&0u32.to_le_bytes());
+            buf.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            buf.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            buf.extend_from_slice(frame);
+        }
+
+        buf
+    }
+
+    fn ipv4(protocol: u8, transport: &[u8]) -> Vec<u8> {
+        let mut buf = vec![0x45, 0];
+        buf.extend_from_slice(&((20+transport.len()) as u16).to_be_bytes());
+        buf.extend_from_slice(&[0, 1, 0, 0, 64, protocol, 0, 0, 192, 0, 2, 1, 192, 0, 2, 53]);
+        buf.extend_from_slice(transport);
+        buf
+    }
+
+    fn udp(source: u16, destination: u16, payload: &[u8]) -> Vec<u8> {
+        let mut buf = source.to_be_bytes().to_vec();
+        buf.extend_from_slice(&destination.to_be_bytes());
+        buf.extend_from_slice(&((8+payload.len()) as u16).to_be_bytes());
+        buf.extend_from_slice(&[0, 0]);
+        buf.extend_from_slice(payload);
+        ipv4(17, &buf)
+    }
+
+    fn tcp(sequence: u32, flags: u8, payload: &[u8]) -> Vec<u8> {
+        let mut buf = 5000u16.to_be_bytes().to_vec();
+        buf.extend_from_slice(&53u16.to_be_bytes());
+        buf.extend_from_slice(&sequence.to_be_bytes());
+        buf.extend_from_slice(&[0, 0, 0, 0, 0x50, flags, 0xff, 0xff, 0, 0, 0, 0]);
+        buf.extend_from_slice(payload);
+        ipv4(6, &buf)
+    }
+
+    fn query(id: u16) -> Vec<u8> {
+        let mut message = MessageBase::new(id);
+        message.add_query(DnsQuery::new("example.com", Types::A, DnsClasses::In));
+        message.encode()
+    }
+
+    #[test]
+    fn udp_and_split_tcp_messages() {
+        let mut stream = (query(2).len() as u16).to_be_bytes().to_vec();
+        stream.extend(query(2));
+
+        let buf = pcap(&[
+            udp(5000, 53, &query(1)),
+            udp(5000, 80, &query(9)),
+            tcp(700, 0x02, &[]),
+            tcp(701, 0x18, &stream[..7]),
+            tcp(708, 0x19, &stream[7..])
+        ]);
+
+        let messages = DnsCapture::new(&buf[..]).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(messages.len(), 2);
+
+        let (_, source, destination, message) = &messages[0];
+        assert_eq!(message.get_id(), 1);
+        assert_eq!(*source, "192.0.2.1:5000".parse().unwrap());
+        assert_eq!(*destination, "192.0.2.53:53".parse().unwrap());
+        assert_eq!(message.get_origin(), Some(*source));
+
+        assert_eq!(messages[1].3.get_id(), 2);
+        assert_eq!(messages[1].3.get_queries()[0].get_query().unwrap(), "example.com");
+    }
+
+    #[test]
+    fn bad_messages_and_truncated_files_come_out_as_errors() {
+        let mut buf = pcap(&[
+            udp(5000, 53, &[0xde, 0xad]),
+            udp(5000, 53, &query(3))
+        ]);
+        buf.extend_from_slice(&[0; 10]);
+
+        let results = DnsCapture::new(&buf[..]).unwrap().collect::<Vec<_>>();
+        assert_eq!(results.len(), 3);
+        assert!(matches!(&results[0], Err(e) if e.starts_with("Frame 1:")));
+        assert_eq!(results[1].as_ref().unwrap().3.get_id(), 3);
+        assert!(results[2].is_err());
+    }
+}
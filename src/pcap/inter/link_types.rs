@@ -0,0 +1,37 @@
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum LinkTypes {
+    Null,
+    Ethernet,
+    Raw,
+    Loop,
+    LinuxSll,
+    Ipv4,
+    Ipv6,
+    LinuxSll2
+}
+
+impl LinkTypes {
+
+    pub fn from_code(code: u32) -> Result<Self, String> {
+        for c in [Self::Null, Self::Ethernet, Self::Raw, Self::Loop, Self::LinuxSll, Self::Ipv4, Self::Ipv6, Self::LinuxSll2] {
+            if c.get_code() == code {
+                return Ok(c);
+            }
+        }
+
+        Err(format!("Couldn't find for code: {}", code))
+    }
+
+    pub fn get_code(&self) -> u32 {
+        match self {
+            Self::Null => 0,
+            Self::Ethernet => 1,
+            Self::Raw => 101,
+            Self::Loop => 108,
+            Self::LinuxSll => 113,
+            Self::Ipv4 => 228,
+            Self::Ipv6 => 229,
+            Self::LinuxSll2 => 276
+        }
+    }
+}
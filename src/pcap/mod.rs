@@ -0,0 +1,5 @@
+pub mod inter;
+pub mod pcap_reader;
+pub mod packet_parser;
+pub mod tcp_flow;
+pub mod dns_capture;
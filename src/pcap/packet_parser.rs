@@ -0,0 +1,468 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use crate::messages::inter::transports::Transports;
+use crate::pcap::inter::link_types::LinkTypes;
+
+const ETHER_TYPE_IPV4: u16 = 0x0800;
+const ETHER_TYPE_IPV6: u16 = 0x86dd;
+const ETHER_TYPE_VLAN: u16 = 0x8100;
+const ETHER_TYPE_QINQ: u16 = 0x88a8;
+
+const PROTOCOL_TCP: u8 = 6;
+const PROTOCOL_UDP: u8 = 17;
+
+const IPV6_HOP_BY_HOP: u8 = 0;
+const IPV6_ROUTING: u8 = 43;
+const IPV6_FRAGMENT: u8 = 44;
+const IPV6_AUTHENTICATION: u8 = 51;
+const IPV6_DESTINATION: u8 = 60;
+
+//HALF FINISHED DATAGRAMS ARE DROPPED PAST THIS, A CAPTURE FULL OF LOST FRAGMENTS SHOULDN'T EAT MEMORY
+const MAX_FRAGMENTED_DATAGRAMS: usize = 1024;
+
+pub const TCP_FIN: u8 = 0x01;
+pub const TCP_SYN: u8 = 0x02;
+pub const TCP_RST: u8 = 0x04;
+
+pub struct Packet {
+    transport: Transports,
+    source: SocketAddr,
+    destination: SocketAddr,
+    sequence: u32,
+    flags: u8,
+    payload: Vec<u8>
+}
+
+impl Packet {
+
+    pub fn get_transport(&self) -> Transports {
+        self.transport
+    }
+
+    pub fn get_source(&self) -> SocketAddr {
+        self.source
+    }
+
+    pub fn get_destination(&self) -> SocketAddr {
+        self.destination
+    }
+
+    pub fn get_sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    pub fn get_flags(&self) -> u8 {
+        self.flags
+    }
+
+    pub fn get_payload(&self) -> &[u8] {
+        &self.payload
+    }
+}
+
+#[derive(Default)]
+struct Fragments {
+    parts: Vec<(usize, Vec<u8>)>,
+    total: Option<usize>
+}
+
+#[derive(Default)]
+pub struct PacketParser {
+    fragments: HashMap<(IpAddr, IpAddr, u32, u8), Fragments>
+}
+
+impl PacketParser {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //Ok(None) IS ANYTHING THAT ISN'T UDP OR TCP OVER IP, OR A FRAGMENT STILL WAITING FOR THE REST
+    pub fn parse(&mut self, link_type: u32, frame: &[u8]) -> Result<Option<Packet>, String> {
+        let ip = match LinkTypes::from_code(link_type) {
+            Ok(LinkTypes::Ethernet) => {
+                let mut off = 12;
+
+                loop {
+                    let ether_type = u16::from_be_bytes(get(frame, off, 2, "Ethernet header")?.try_into().unwrap());
+
+                    match ether_type {
+                        ETHER_TYPE_VLAN | ETHER_TYPE_QINQ => off += 4,
+                        ETHER_TYPE_IPV4 | ETHER_TYPE_IPV6 => break &frame[off+2..],
+                        _ => return Ok(None)
+                    }
+                }
+            }
+            //LOOPBACK FAMILY VALUES DIFFER BY OS, THE IP VERSION NIBBLE DOESN'T
+            Ok(LinkTypes::Null) | Ok(LinkTypes::Loop) => get(frame, 4, frame.len().saturating_sub(4), "loopback header")?,
+            Ok(LinkTypes::LinuxSll) => {
+                match u16::from_be_bytes(get(frame, 14, 2, "Linux cooked header")?.try_into().unwrap()) {
+                    ETHER_TYPE_IPV4 | ETHER_TYPE_IPV6 => &frame[16..],
+                    _ => return Ok(None)
+                }
+            }
+            Ok(LinkTypes::LinuxSll2) => {
+                get(frame, 0, 20, "Linux cooked v2 header")?;
+                match u16::from_be_bytes([frame[0], frame[1]]) {
+                    ETHER_TYPE_IPV4 | ETHER_TYPE_IPV6 => &frame[20..],
+                    _ => return Ok(None)
+                }
+            }
+            Ok(LinkTypes::Raw) | Ok(LinkTypes::Ipv4) | Ok(LinkTypes::Ipv6) => frame,
+            Err(_) => return Err(format!("Unsupported link type: {}", link_type))
+        };
+
+        match ip.first().map(|b| b >> 4) {
+            Some(4) => self.parse_ipv4(ip),
+            Some(6) => self.parse_ipv6(ip),
+            _ => Ok(None)
+        }
+    }
+
+    fn parse_ipv4(&mut self, ip: &[u8]) -> Result<Option<Packet>, String> {
+        let header = get(ip, 0, 20, "IPv4 header")?;
+        let header_length = ((header[0] & 0x0f) as usize)*4;
+        //SEGMENTATION OFFLOAD LEAVES TOTAL LENGTH AT 0 ON FRAMES CAPTURED BEFORE THE NIC SPLITS THEM
+        let total_length = match u16::from_be_bytes([header[2], header[3]]) as usize {
+            0 => ip.len(),
+            total_length => total_length
+        };
+
+        if header_length < 20 || total_length < header_length {
+            return Err(format!("Invalid IPv4 header length: {}", header_length));
+        }
+
+        //ETHERNET PADS SHORT FRAMES, TOTAL LENGTH SAYS WHERE THE DATAGRAM REALLY ENDS
+        let payload = get(ip, header_length, total_length-header_length, "IPv4 payload")?;
+
+        let id = u16::from_be_bytes([header[4], header[5]]) as u32;
+        let fragment = u16::from_be_bytes([header[6], header[7]]);
+        let more_fragments = (fragment & 0x2000) != 0;
+        let fragment_offset = ((fragment & 0x1fff) as usize)*8;
+
+        let protocol = header[9];
+        let source = IpAddr::V4(Ipv4Addr::new(header[12], header[13], header[14], header[15]));
+        let destination = IpAddr::V4(Ipv4Addr::new(header[16], header[17], header[18], header[19]));
+
+        if more_fragments || fragment_offset > 0 {
+            return match self.reassemble((source, destination, id, protocol), fragment_offset, more_fragments, payload) {
+                Some(payload) => parse_transport(protocol, source, destination, &payload),
+                None => Ok(None)
+            };
+        }
+
+        parse_transport(protocol, source, destination, payload)
+    }
+
+    fn parse_ipv6(&mut self, ip: &[u8]) -> Result<Option<Packet>, String> {
+        let header = get(ip, 0, 40, "IPv6 header")?;
+        let payload_length = u16::from_be_bytes([header[4], header[5]]) as usize;
+
+        let source = IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(&header[8..24]).unwrap()));
+        let destination = IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(&header[24..40]).unwrap()));
+
+        let payload = get(ip, 40, payload_length, "IPv6 payload")?;
+        let mut next_header = header[6];
+        let mut off = 0;
+
+        loop {
+            match next_header {
+                IPV6_HOP_BY_HOP | IPV6_ROUTING | IPV6_DESTINATION => {
+                    let extension = get(payload, off, 2, "IPv6 extension header")?;
+                    next_header = extension[0];
+                    off += (extension[1] as usize+1)*8;
+                }
+                IPV6_AUTHENTICATION => {
+                    let extension = get(payload, off, 2, "IPv6 authentication header")?;
+                    next_header = extension[0];
+                    off += (extension[1] as usize+2)*4;
+                }
+                IPV6_FRAGMENT => {
+                    let extension = get(payload, off, 8, "IPv6 fragment header")?;
+                    let protocol = extension[0];
+                    let fragment = u16::from_be_bytes([extension[2], extension[3]]);
+                    let id = u32::from_be_bytes([extension[4], extension[5], extension[6], extension[7]]);
+                    let data = get(payload, off+8, payload.len().saturating_sub(off+8), "IPv6 fragment")?;
+
+                    //THE FIRST FRAGMENT CAN STILL CARRY EXTENSION HEADERS, ONLY UDP AND TCP RIGHT AFTER ARE HANDLED
+                    return match self.reassemble((source, destination, id, protocol), (fragment & 0xfff8) as usize, (fragment & 0x0001) != 0, data) {
+                        Some(payload) => parse_transport(protocol, source, destination, &payload),
+                        None => Ok(None)
+                    };
+                }
+                protocol => return parse_transport(protocol, source, destination, get(payload, off, payload.len().saturating_sub(off), "IPv6 payload")?)
+            }
+        }
+    }
+
+    fn reassemble(&mut self, key: (IpAddr, IpAddr, u32, u8), offset: usize, more: bool, data: &[u8]) -> Option<Vec<u8>> {
+        if self.fragments.len() >= MAX_FRAGMENTED_DATAGRAMS && !self.fragments.contains_key(&key) {
+            self.fragments.clear();
+        }
+
+        let fragments = self.fragments.entry(key).or_default();
+        fragments.parts.push((offset, data.to_vec()));
+
+        if !more {
+            fragments.total = Some(offset+data.len());
+        }
+
+        let total = fragments.total?;
+        fragments.parts.sort_by_key(|(offset, _)| *offset);
+
+        //OVERLAPS ARE ALLOWED, GAPS MEAN WE'RE STILL WAITING
+        let mut covered = 0;
+        for (offset, data) in &fragments.parts {
+            if *offset > covered {
+                return None;
+            }
+            covered = covered.max(offset+data.len());
+        }
+
+        if covered < total {
+            return None;
+        }
+
+        let fragments = self.fragments.remove(&key)?;
+        let mut datagram = vec![0u8; total];
+
+        for (offset, data) in fragments.parts.into_iter().filter(|(offset, _)| *offset < total) {
+            let end = (offset+data.len()).min(total);
+            datagram[offset..end].copy_from_slice(&data[..end-offset]);
+        }
+
+        Some(datagram)
+    }
+}
+
+fn parse_transport(protocol: u8, source: IpAddr, destination: IpAddr, segment: &[u8]) -> Result<Option<Packet>, String> {
+    match protocol {
+        PROTOCOL_UDP => {
+            let header = get(segment, 0, 8, "UDP header")?;
+            let length = u16::from_be_bytes([header[4], header[5]]) as usize;
+
+            if length < 8 {
+                return Err(format!("Invalid UDP length: {}", length));
+            }
+
+            Ok(Some(Packet {
+                transport: Transports::Udp,
+                source: SocketAddr::new(source, u16::from_be_bytes([header[0], header[1]])),
+                destination: SocketAddr::new(destination, u16::from_be_bytes([header[2], header[3]])),
+                sequence: 0,
+                flags: 0,
+                payload: get(segment, 8, length-8, "UDP payload")?.to_vec()
+            }))
+        }
+        PROTOCOL_TCP => {
+            let header = get(segment, 0, 20, "TCP header")?;
+            let data_offset = ((header[12] >> 4) as usize)*4;
+
+            if data_offset < 20 {
+                return Err(format!("Invalid TCP data offset: {}", data_offset));
+            }
+
+            Ok(Some(Packet {
+                transport: Transports::Tcp,
+                source: SocketAddr::new(source, u16::from_be_bytes([header[0], header[1]])),
+                destination: SocketAddr::new(destination, u16::from_be_bytes([header[2], header[3]])),
+                sequence: u32::from_be_bytes([header[4], header[5], header[6], header[7]]),
+                flags: header[13],
+                payload: get(segment, data_offset, segment.len().saturating_sub(data_offset), "TCP payload")?.to_vec()
+            }))
+        }
+        _ => Ok(None)
+    }
+}
+
+//SNAPLEN CUTS FRAMES SHORT, SO EVERY READ IS CHECKED
+fn get<'a>(buf: &'a [u8], off: usize, length: usize, what: &str) -> Result<&'a [u8], String> {
+    buf.get(off..off+length).ok_or(format!("{} is truncated", what))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::net::SocketAddr;
+    use crate::messages::inter::transports::Transports;
+    use crate::pcap::packet_parser::PacketParser;
+
+    fn udp(source: u16, destination: u16, payload: &[u8]) -> Vec<u8> {
+        let mut buf = source.to_be_bytes().to_vec();
+        buf.extend_from_slice(&destination.to_be_bytes());
+        buf.extend_from_slice(&((8+payload.len()) as u16).to_be_bytes());
+        buf.extend_from_slice(&[0, 0]);
+        buf.extend_from_slice(payload);
+        buf
+    }
+
+    fn tcp(source: u16, destination: u16, sequence: u32, flags: u8, payload: &[u8]) -> Vec<u8> {
+        let mut buf = source.to_be_bytes().to_vec();
+        buf.extend_from_slice(&destination.to_be_bytes());
+        buf.extend_from_slice(&sequence.to_be_bytes());
+        buf.extend_from_slice(&[0, 0, 0, 0, 0x50, flags, 0xff, 0xff, 0, 0, 0, 0]);
+        buf.extend_from_slice(payload);
+        buf
+    }
+
+    fn ipv4(protocol: u8, id: u16, fragment: u16, payload: &[u8]) -> Vec<u8> {
+        let mut buf = vec![0x45, 0];
+        buf.extend_from_slice(&((20+payload.len()) as u16).to_be_bytes());
+        buf.extend_from_slice(&id.to_be_bytes());
+        buf.extend_from_slice(&fragment.to_be_bytes());
+        buf.extend_from_slice(&[64, protocol, 0, 0, 192, 0, 2, 1, 192, 0, 2, 53]);
+        buf.extend_from_slice(payload);
+        buf
+    }
+
+    fn ipv6(next_header: u8, payload: &[u8]) -> Vec<u8> {
+        let mut buf = vec![0x60, 0, 0, 0];
+        buf.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        buf.extend_from_slice(&[next_header, 64]);
+        buf.extend_from_slice(&"2001:db8::1".parse::<std::net::Ipv6Addr>().unwrap().octets());
+        buf.extend_from_slice(&"2001:db8::53".parse::<std::net::Ipv6Addr>().unwrap().octets());
+        buf.extend_from_slice(payload);
+        buf
+    }
+
+    fn ethernet(tags: &[u16], ether_type: u16, payload: &[u8]) -> Vec<u8> {
+        let mut buf = vec![0; 12];
+        for tag in tags {
+            buf.extend_from_slice(&tag.to_be_bytes());
+            buf.extend_from_slice(&[0, 1]);
+        }
+        buf.extend_from_slice(&ether_type.to_be_bytes());
+        buf.extend_from_slice(payload);
+        buf
+    }
+
+    fn parse(link_type: u32, frame: &[u8]) -> (Transports, SocketAddr, SocketAddr, Vec<u8>) {
+        let packet = PacketParser::new().parse(link_type, frame).unwrap().unwrap();
+        (packet.get_transport(), packet.get_source(), packet.get_destination(), packet.get_payload().to_vec())
+    }
+
+    #[test]
+    fn every_link_type_reaches_the_udp_payload() {
+        let ip = ipv4(17, 1, 0, &udp(5000, 53, b"dns"));
+        let expected = (Transports::Udp, "192.0.2.1:5000".parse().unwrap(), "192.0.2.53:53".parse().unwrap(), b"dns".to_vec());
+
+        //ETHERNET PADS SHORT FRAMES, THE IP TOTAL LENGTH CUTS THE PADDING OFF
+        let mut padded = ethernet(&[], 0x0800, &ip);
+        padded.resize(64, 0);
+        assert_eq!(parse(1, &padded), expected);
+        assert_eq!(parse(1, &ethernet(&[0x8100], 0x0800, &ip)), expected);
+        assert_eq!(parse(1, &ethernet(&[0x88a8, 0x8100], 0x0800, &ip)), expected);
+
+        let mut null = 2u32.to_le_bytes().to_vec();
+        null.extend_from_slice(&ip);
+        assert_eq!(parse(0, &null), expected);
+
+        let mut sll = vec![0; 14];
+        sll.extend_from_slice(&0x0800u16.to_be_bytes());
+        sll.extend_from_slice(&ip);
+        assert_eq!(parse(113, &sll), expected);
+
+        let mut sll2 = 0x0800u16.to_be_bytes().to_vec();
+        sll2.resize(20, 0);
+        sll2.extend_from_slice(&ip);
+        assert_eq!(parse(276, &sll2), expected);
+
+        assert_eq!(parse(101, &ip), expected);
+    }
+
+    #[test]
+    fn other_traffic_is_skipped() {
+        let mut parser = PacketParser::new();
+
+        //ARP, ICMP AND A SLL FRAME THAT ISN'T IP
+        assert!(parser.parse(1, &ethernet(&[], 0x0806, &[0; 28])).unwrap().is_none());
+        assert!(parser.parse(101, &ipv4(1, 1, 0, &[8, 0, 0, 0])).unwrap().is_none());
+        let mut sll = vec![0; 14];
+        sll.extend_from_slice(&0x0806u16.to_be_bytes());
+        assert!(parser.parse(113, &sll).unwrap().is_none());
+
+        assert!(parser.parse(147, &[0x45]).is_err());
+    }
+
+    #[test]
+    fn ipv6_extension_headers_are_skipped() {
+        //HOP BY HOP (8 BYTES) THEN DESTINATION OPTIONS (16 BYTES) THEN UDP
+        let mut payload = vec![60, 0, 1, 4, 0, 0, 0, 0];
+        payload.extend_from_slice(&[17, 1, 1, 12]);
+        payload.extend_from_slice(&[0; 12]);
+        payload.extend(udp(5353, 5353, b"mdns"));
+
+        let (transport, source, destination, data) = parse(101, &ipv6(0, &payload));
+        assert_eq!(transport, Transports::Udp);
+        assert_eq!(source, "[2001:db8::1]:5353".parse().unwrap());
+        assert_eq!(destination, "[2001:db8::53]:5353".parse().unwrap());
+        assert_eq!(data, b"mdns");
+
+        //AN EXTENSION HEADER THAT CLAIMS MORE THAN THE PAYLOAD HOLDS
+        let payload = vec![17, 8, 0, 0, 0, 0, 0, 0];
+        assert!(PacketParser::new().parse(101, &ipv6(0, &payload)).is_err());
+    }
+
+    #[test]
+    fn ipv4_fragments_are_reassembled_in_any_order() {
+        let datagram = udp(5000, 53, &[7; 32]);
+        let mut parser = PacketParser::new();
+
+        //LAST FRAGMENT FIRST, OFFSETS ARE IN 8 BYTE UNITS
+        assert!(parser.parse(101, &ipv4(17, 9, 3, &datagram[24..])).unwrap().is_none());
+        assert!(parser.parse(101, &ipv4(17, 9, 0x2000, &datagram[..16])).unwrap().is_none());
+        let packet = parser.parse(101, &ipv4(17, 9, 0x2000 | 2, &datagram[16..24])).unwrap().unwrap();
+        assert_eq!(packet.get_payload(), &[7; 32]);
+    }
+
+    #[test]
+    fn ipv6_fragments_are_reassembled() {
+        let datagram = udp(5000, 53, &[9; 24]);
+        let mut parser = PacketParser::new();
+
+        let fragment = |offset: u16, more: bool, data: &[u8]| {
+            let mut payload = vec![17, 0];
+            payload.extend_from_slice(&(offset | more as u16).to_be_bytes());
+            payload.extend_from_slice(&77u32.to_be_bytes());
+            payload.extend_from_slice(data);
+            ipv6(44, &payload)
+        };
+
+        assert!(parser.parse(101, &fragment(0, true, &datagram[..16])).unwrap().is_none());
+        let packet = parser.parse(101, &fragment(16, false, &datagram[16..])).unwrap().unwrap();
+        assert_eq!(packet.get_payload(), &[9; 24]);
+    }
+
+    #[test]
+    fn tcp_sequence_and_flags() {
+        let packet = PacketParser::new().parse(101, &ipv4(6, 1, 0, &tcp(5000, 53, 1000, 0x18, b"abc"))).unwrap().unwrap();
+        assert_eq!(packet.get_transport(), Transports::Tcp);
+        assert_eq!(packet.get_sequence(), 1000);
+        assert_eq!(packet.get_flags(), 0x18);
+        assert_eq!(packet.get_payload(), b"abc");
+    }
+
+    #[test]
+    fn truncated_frames_are_errors_not_panics() {
+        let frames = [
+            (1, ethernet(&[0x8100], 0x0800, &ipv4(17, 1, 0, &udp(5000, 53, b"dns")))),
+            (1, ethernet(&[], 0x86dd, &ipv6(0, &[6, 0, 0, 0, 0, 0, 0, 0, 0, 0]))),
+            (113, [vec![0; 14], vec![0x08, 0x00], ipv4(6, 1, 0, &tcp(5000, 53, 1, 0x02, b""))].concat()),
+            (276, [vec![0x86, 0xdd], vec![0; 18], ipv6(17, &udp(53, 53, b"x"))].concat())
+        ];
+
+        for (link_type, frame) in frames {
+            for length in 0..frame.len() {
+                let _ = PacketParser::new().parse(link_type, &frame[..length]);
+            }
+        }
+
+        assert!(PacketParser::new().parse(1, &[0; 10]).is_err());
+        assert!(PacketParser::new().parse(101, &[0x45, 0, 0, 20]).is_err());
+
+        //UDP LENGTH SHORTER THAN ITS OWN HEADER
+        let mut bad = udp(5000, 53, b"");
+        bad[5] = 4;
+        assert!(PacketParser::new().parse(101, &ipv4(17, 1, 0, &bad)).is_err());
+    }
+}
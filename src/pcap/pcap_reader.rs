@@ -0,0 +1,502 @@
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const PCAP_MAGIC_MICROS: u32 = 0xa1b2c3d4;
+const PCAP_MAGIC_NANOS: u32 = 0xa1b23c4d;
+
+const PCAPNG_SECTION_HEADER: u32 = 0x0a0d0d0a;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b3c4d;
+const PCAPNG_INTERFACE_DESCRIPTION: u32 = 1;
+const PCAPNG_PACKET: u32 = 2;
+const PCAPNG_SIMPLE_PACKET: u32 = 3;
+const PCAPNG_ENHANCED_PACKET: u32 = 6;
+
+const PCAPNG_OPTION_END: u16 = 0;
+const PCAPNG_OPTION_TS_RESOLUTION: u16 = 9;
+
+//NOTHING WE CARE ABOUT COMES CLOSE, ANYTHING BIGGER IS A CORRUPT LENGTH
+const MAX_RECORD_LENGTH: usize = 16*1024*1024;
+
+#[derive(Clone)]
+pub struct Frame {
+    timestamp: SystemTime,
+    link_type: u32,
+    data: Vec<u8>
+}
+
+impl Frame {
+
+    pub fn get_timestamp(&self) -> SystemTime {
+        self.timestamp
+    }
+
+    pub fn get_link_type(&self) -> u32 {
+        self.link_type
+    }
+
+    pub fn get_data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Interface {
+    link_type: u32,
+    //IF_TSRESOL - TOP BIT CLEAR IS 10^-N, SET IS 2^-N
+    resolution: u8
+}
+
+pub struct PcapReader<R: Read> {
+    reader: R,
+    big_endian: bool,
+    pcapng: bool,
+    nanos: bool,
+    link_type: u32,
+    interfaces: Vec<Interface>
+}
+
+impl PcapReader<BufReader<File>> {
+
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("Couldn't open {}: {}", path.display(), e))?;
+        Self::new(BufReader::new(file))
+    }
+}
+
+impl<R: Read> PcapReader<R> {
+
+    //PCAP AND PCAPNG ARE TOLD APART BY THE FIRST FOUR BYTES
+    pub fn new(reader: R) -> Result<Self, String> {
+        let mut reader = Self {
+            reader,
+            big_endian: false,
+            pcapng: false,
+            nanos: false,
+            link_type: 0,
+            interfaces: Vec::new()
+        };
+
+        let mut magic = [0u8; 4];
+        if !reader.read_exact_or_eof(&mut magic)? {
+            return Err("Capture file is empty".to_string());
+        }
+
+        if u32::from_le_bytes(magic) == PCAPNG_SECTION_HEADER {
+            reader.pcapng = true;
+            reader.read_section_header()?;
+            return Ok(reader);
+        }
+
+        match (u32::from_le_bytes(magic), u32::from_be_bytes(magic)) {
+            (PCAP_MAGIC_MICROS, _) => {}
+            (PCAP_MAGIC_NANOS, _) => reader.nanos = true,
+            (_, PCAP_MAGIC_MICROS) => reader.big_endian = true,
+            (_, PCAP_MAGIC_NANOS) => {
+                reader.big_endian = true;
+                reader.nanos = true;
+            }
+            _ => return Err(format!("Not a pcap or pcapng file, magic: {:02x?}", magic))
+        }
+
+        //VERSION, THISZONE, SIGFIGS AND SNAPLEN AREN'T NEEDED TO READ THE RECORDS
+        let mut header = [0u8; 20];
+        reader.read_exact(&mut header)?;
+        reader.link_type = reader.u32(&header[16..20]);
+
+        Ok(reader)
+    }
+
+    pub fn next_frame(&mut self) -> Result<Option<Frame>, String> {
+        match self.pcapng {
+            true => self.next_block(),
+            false => self.next_record()
+        }
+    }
+
+    fn next_record(&mut self) -> Result<Option<Frame>, String> {
+        let mut header = [0u8; 16];
+        if !self.read_exact_or_eof(&mut header)? {
+            return Ok(None);
+        }
+
+        let seconds = self.u32(&header[0..4]) as u64;
+        let fraction = self.u32(&header[4..8]) as u64;
+        let length = self.u32(&header[8..12]) as usize;
+
+        if length > MAX_RECORD_LENGTH {
+            return Err(format!("Invalid pcap record length: {}", length));
+        }
+
+        let mut data = vec![0u8; length];
+        self.read_exact(&mut data)?;
+
+        let timestamp = match self.nanos {
+            true => UNIX_EPOCH+Duration::new(seconds, 0)+Duration::from_nanos(fraction),
+            false => UNIX_EPOCH+Duration::new(seconds, 0)+Duration::from_micros(fraction)
+        };
+
+        Ok(Some(Frame {
+            timestamp,
+            link_type: self.link_type,
+            data
+        }))
+    }
+
+    fn next_block(&mut self) -> Result<Option<Frame>, String> {
+        loop {
+            let mut header = [0u8; 4];
+            if !self.read_exact_or_eof(&mut header)? {
+                return Ok(None);
+            }
+
+            if u32::from_le_bytes(header) == PCAPNG_SECTION_HEADER {
+                self.read_section_header()?;
+                continue;
+            }
+
+            let block_type = self.u32(&header);
+            let body = self.read_block_body()?;
+
+            match block_type {
+                PCAPNG_INTERFACE_DESCRIPTION => self.read_interface(&body)?,
+                PCAPNG_ENHANCED_PACKET => return self.read_packet(&body, false).map(Some),
+                PCAPNG_PACKET => return self.read_packet(&body, true).map(Some),
+                PCAPNG_SIMPLE_PACKET => return self.read_simple_packet(&body).map(Some),
+                _ => {}
+            }
+        }
+    }
+
+    //EACH SECTION CAN SWITCH BYTE ORDER AND STARTS WITH NO INTERFACES
+    fn read_section_header(&mut self) -> Result<(), String> {
+        let mut header = [0u8; 8];
+        self.read_exact(&mut header)?;
+
+        self.big_endian = match (u32::from_le_bytes([header[4], header[5], header[6], header[7]]), u32::from_be_bytes([header[4], header[5], header[6], header[7]])) {
+            (PCAPNG_BYTE_ORDER_MAGIC, _) => false,
+            (_, PCAPNG_BYTE_ORDER_MAGIC) => true,
+            _ => return Err("Invalid pcapng byte order magic".to_string())
+        };
+
+        let length = self.u32(&header[0..4]) as usize;
+        if length < 28 || !length.is_multiple_of(4) || length > MAX_RECORD_LENGTH {
+            return Err(format!("Invalid pcapng section header length: {}", length));
+        }
+
+        let mut rest = vec![0u8; length-12];
+        self.read_exact(&mut rest)?;
+        self.interfaces.clear();
+
+        Ok(())
+    }
+
+    //BODY WITHOUT THE TYPE AND THE TWO LENGTHS
+    fn read_block_body(&mut self) -> Result<Vec<u8>, String> {
+        let mut length = [0u8; 4];
+        self.read_exact(&mut length)?;
+
+        let length = self.u32(&length) as usize;
+        if length < 12 || !length.is_multiple_of(4) || length > MAX_RECORD_LENGTH {
+            return Err(format!("Invalid pcapng block length: {}", length));
+        }
+
+        let mut body = vec![0u8; length-8];
+        self.read_exact(&mut body)?;
+        body.truncate(length-12);
+
+        Ok(body)
+    }
+
+    fn read_interface(&mut self, body: &[u8]) -> Result<(), String> {
+        if body.len() < 8 {
+            return Err("Invalid pcapng interface description".to_string());
+        }
+
+        let mut interface = Interface {
+            link_type: self.u16(&body[0..2]) as u32,
+            resolution: 6
+        };
+
+        let mut off = 8;
+        while off+4 <= body.len() {
+            let code = self.u16(&body[off..off+2]);
+            let length = self.u16(&body[off+2..off+4]) as usize;
+
+            if code == PCAPNG_OPTION_END || off+4+length > body.len() {
+                break;
+            }
+
+            if code == PCAPNG_OPTION_TS_RESOLUTION && length == 1 {
+                interface.resolution = body[off+4];
+            }
+
+            off += 4+length.div_ceil(4)*4;
+        }
+
+        self.interfaces.push(interface);
+
+        Ok(())
+    }
+
+    //ENHANCED AND OBSOLETE PACKET BLOCKS SHARE THE LAYOUT, THE OBSOLETE ONE ONLY HAS 16 BITS FOR THE INTERFACE
+    fn read_packet(&self, body: &[u8], obsolete: bool) -> Result<Frame, String> {
+        if body.len() < 20 {
+            return Err("Invalid pcapng packet block".to_string());
+        }
+
+        let interface_id = match obsolete {
+            true => self.u16(&body[0..2]) as usize,
+            false => self.u32(&body[0..4]) as usize
+        };
+
+        let length = self.u32(&body[12..16]) as usize;
+        if 20+length > body.len() {
+            return Err("pcapng packet block runs past its length".to_string());
+        }
+
+        let interface = self.get_interface(interface_id)?;
+        let ticks = ((self.u32(&body[4..8]) as u64) << 32) | self.u32(&body[8..12]) as u64;
+
+        Ok(Frame {
+            timestamp: to_time(ticks, interface.resolution),
+            link_type: interface.link_type,
+            data: body[20..20+length].to_vec()
+        })
+    }
+
+    //SIMPLE PACKET BLOCKS HAVE NO TIMESTAMP, THE CAPTURE IS CUT TO WHAT THE BLOCK HOLDS
+    fn read_simple_packet(&self, body: &[u8]) -> Result<Frame, String> {
+        if body.len() < 4 {
+            return Err("Invalid pcapng simple packet block".to_string());
+        }
+
+        let interface = self.get_interface(0)?;
+        let length = (self.u32(&body[0..4]) as usize).min(body.len()-4);
+
+        Ok(Frame {
+            timestamp: UNIX_EPOCH,
+            link_type: interface.link_type,
+            data: body[4..4+length].to_vec()
+        })
+    }
+
+    fn get_interface(&self, id: usize) -> Result<Interface, String> {
+        self.interfaces.get(id).copied().ok_or(format!("pcapng packet for undescribed interface: {}", id))
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), String> {
+        self.reader.read_exact(buf).map_err(|e| match e.kind() {
+            ErrorKind::UnexpectedEof => "Capture file is truncated".to_string(),
+            _ => e.to_string()
+        })
+    }
+
+    //A CLEAN EOF IS ONLY ALLOWED BEFORE THE FIRST BYTE OF A RECORD
+    fn read_exact_or_eof(&mut self, buf: &mut [u8]) -> Result<bool, String> {
+        let mut read = 0;
+
+        while read < buf.len() {
+            match self.reader.read(&mut buf[read..]) {
+                Ok(0) if read == 0 => return Ok(false),
+                Ok(0) => return Err("Capture file is truncated".to_string()),
+                Ok(n) => read += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.to_string())
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn u16(&self, buf: &[u8]) -> u16 {
+        match self.big_endian {
+            true => u16::from_be_bytes([buf[0], buf[1]]),
+            false => u16::from_le_bytes([buf[0], buf[1]])
+        }
+    }
+
+    fn u32(&self, buf: &[u8]) -> u32 {
+        match self.big_endian {
+            true => u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]),
+            false => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]])
+        }
+    }
+}
+
+fn to_time(ticks: u64, resolution: u8) -> SystemTime {
+    let exponent = (resolution & 0x7f) as u32;
+
+    let nanos = match resolution & 0x80 {
+        0 => (ticks as u128*1_000_000_000)/10u128.pow(exponent.min(38)),
+        _ => (ticks as u128*1_000_000_000) >> exponent.min(127)
+    };
+
+    UNIX_EPOCH+Duration::from_nanos(nanos.min(u64::MAX as u128) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::time::{Duration, UNIX_EPOCH};
+    use crate::pcap::pcap_reader::PcapReader;
+
+    fn pcap(big_endian: bool, magic: u32, link_type: u32, records: &[(u32, u32, &[u8])]) -> Vec<u8> {
+        let u32 = |value: u32| if big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+        let u16 = |value: u16| if big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+
+        let mut buf = u32(magic).to_vec();
+        buf.extend_from_slice(&u16(2));
+        buf.extend_from_slice(&u16(4));
+        buf.extend_from_slice(&[0; 8]);
+        buf.extend_from_slice(&u32(65535));
+        buf.extend_from_slice(&u32(link_type));
+
+        for (seconds, fraction, data) in records {
+            buf.extend_from_slice(&u32(*seconds));
+            buf.extend_from_slice(&u32(*fraction));
+            buf.extend_from_slice(&u32(data.len() as u32));
+            buf.extend_from_slice(&u32(data.len() as u32));
+            buf.extend_from_slice(data);
+        }
+
+        buf
+    }
+
+    //LITTLE ENDIAN BLOCK WITH THE BODY PADDED TO 4 BYTES AND THE LENGTH ON BOTH ENDS
+    fn block(block_type: u32, body: &[u8]) -> Vec<u8> {
+        let length = 12+body.len().div_ceil(4)*4;
+
+        let mut buf = block_type.to_le_bytes().to_vec();
+        buf.extend_from_slice(&(length as u32).to_le_bytes());
+        buf.extend_from_slice(body);
+        buf.resize(length-4, 0);
+        buf.extend_from_slice(&(length as u32).to_le_bytes());
+        buf
+    }
+
+    fn section_header() -> Vec<u8> {
+        let mut body = 0x1a2b3c4du32.to_le_bytes().to_vec();
+        body.extend_from_slice(&[1, 0, 0, 0]);
+        body.extend_from_slice(&u64::MAX.to_le_bytes());
+        block(0x0a0d0d0a, &body)
+    }
+
+    fn interface(link_type: u16, resolution: Option<u8>) -> Vec<u8> {
+        let mut body = link_type.to_le_bytes().to_vec();
+        body.extend_from_slice(&[0, 0]);
+        body.extend_from_slice(&65535u32.to_le_bytes());
+
+        if let Some(resolution) = resolution {
+            body.extend_from_slice(&[9, 0, 1, 0, resolution, 0, 0, 0]);
+            body.extend_from_slice(&[0, 0, 0, 0]);
+        }
+
+        block(1, &body)
+    }
+
+    fn enhanced_packet(interface: u32, ticks: u64, data: &[u8]) -> Vec<u8> {
+        let mut body = interface.to_le_bytes().to_vec();
+        body.extend_from_slice(&((ticks >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(ticks as u32).to_le_bytes());
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        body.extend_from_slice(data);
+        block(6, &body)
+    }
+
+    fn frames(buf: &[u8]) -> Result<Vec<(Duration, u32, Vec<u8>)>, String> {
+        let mut reader = PcapReader::new(buf)?;
+        let mut frames = Vec::new();
+
+        while let Some(frame) = reader.next_frame()? {
+            frames.push((frame.get_timestamp().duration_since(UNIX_EPOCH).unwrap(), frame.get_link_type(), frame.get_data().to_vec()));
+        }
+
+        Ok(frames)
+    }
+
+    #[test]
+    fn classic_pcap_in_either_byte_order() {
+        let buf = pcap(false, 0xa1b2c3d4, 1, &[(1, 500_000, &[1, 2, 3]), (2, 0, &[4])]);
+        assert_eq!(frames(&buf).unwrap(), vec![
+            (Duration::from_millis(1500), 1, vec![1, 2, 3]),
+            (Duration::from_secs(2), 1, vec![4])
+        ]);
+
+        //NANOSECOND MAGIC CHANGES WHAT THE FRACTION MEANS
+        let buf = pcap(true, 0xa1b23c4d, 101, &[(1, 500, &[5, 6])]);
+        assert_eq!(frames(&buf).unwrap(), vec![(Duration::new(1, 500), 101, vec![5, 6])]);
+    }
+
+    #[test]
+    fn pcapng_with_several_interfaces() {
+        let mut buf = section_header();
+        buf.extend(interface(1, None));
+        buf.extend(interface(101, Some(9)));
+        buf.extend(block(0xbad, &[1, 2, 3, 4]));
+        buf.extend(enhanced_packet(1, 1_500_000_000, &[1, 2, 3]));
+        buf.extend(enhanced_packet(0, 2_000_000, &[4, 5]));
+
+        //SIMPLE PACKETS BELONG TO THE FIRST INTERFACE AND HAVE NO TIMESTAMP
+        let mut simple = 1u32.to_le_bytes().to_vec();
+        simple.push(6);
+        buf.extend(block(3, &simple));
+
+        assert_eq!(frames(&buf).unwrap(), vec![
+            (Duration::from_millis(1500), 101, vec![1, 2, 3]),
+            (Duration::from_secs(2), 1, vec![4, 5]),
+            (Duration::ZERO, 1, vec![6])
+        ]);
+    }
+
+    #[test]
+    fn pcapng_new_section_forgets_interfaces() {
+        let mut buf = section_header();
+        buf.extend(interface(1, None));
+        buf.extend(enhanced_packet(0, 0, &[1]));
+        buf.extend(section_header());
+        buf.extend(enhanced_packet(0, 0, &[2]));
+
+        let mut reader = PcapReader::new(&buf[..]).unwrap();
+        assert!(reader.next_frame().unwrap().is_some());
+        assert!(reader.next_frame().is_err());
+    }
+
+    #[test]
+    fn truncated_or_garbage_input_is_an_error() {
+        assert!(PcapReader::new(&[][..]).is_err());
+        assert!(PcapReader::new(&b"not a capture file"[..]).is_err());
+
+        //CUT ANYWHERE INSIDE A FILE, READING IT MUST FAIL CLEANLY
+        let classic = pcap(false, 0xa1b2c3d4, 1, &[(1, 0, &[1, 2, 3, 4])]);
+        for length in 1..classic.len() {
+            if length != 24 {
+                assert!(frames(&classic[..length]).is_err(), "classic cut at {}", length);
+            }
+        }
+
+        let mut pcapng = section_header();
+        pcapng.extend(interface(1, Some(9)));
+        pcapng.extend(enhanced_packet(0, 0, &[1, 2, 3, 4]));
+        for length in 1..pcapng.len() {
+            if length != 28 && length != 60 {
+                assert!(frames(&pcapng[..length]).is_err(), "pcapng cut at {}", length);
+            }
+        }
+
+        //A BLOCK LENGTH THAT ISN'T A MULTIPLE OF 4, AND A PACKET LONGER THAN ITS BLOCK
+        let mut bad_length = section_header();
+        bad_length.extend_from_slice(&6u32.to_le_bytes());
+        bad_length.extend_from_slice(&13u32.to_le_bytes());
+        assert!(frames(&bad_length).is_err());
+
+        let mut overrun = section_header();
+        overrun.extend(interface(1, None));
+        let mut packet = enhanced_packet(0, 0, &[1, 2, 3, 4]);
+        packet[20..24].copy_from_slice(&100u32.to_le_bytes());
+        overrun.extend(packet);
+        assert!(frames(&overrun).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+//OUT OF ORDER SEGMENTS AND UNFINISHED MESSAGES PAST THESE ARE A BROKEN CAPTURE, THE FLOW IS RESET
+const MAX_PENDING_SEGMENTS: usize = 256;
+const MAX_BUFFERED: usize = 1024*1024;
+
+//RFC 1035 4.2.2 - ONE DIRECTION OF A DNS TCP CONNECTION, EACH MESSAGE HAS A 2 BYTE LENGTH IN FRONT
+#[derive(Default)]
+pub struct TcpFlow {
+    next_sequence: Option<u32>,
+    buf: Vec<u8>,
+    pending: HashMap<u32, Vec<u8>>
+}
+
+impl TcpFlow {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, sequence: u32, syn: bool, payload: &[u8]) {
+        //SYN TAKES UP A SEQUENCE NUMBER, A CAPTURE STARTED MID CONNECTION SYNCS ON THE FIRST SEGMENT
+        let sequence = match syn {
+            true => {
+                self.reset();
+                sequence.wrapping_add(1)
+            }
+            false => sequence
+        };
+
+        if payload.is_empty() {
+            if self.next_sequence.is_none() {
+                self.next_sequence = Some(sequence);
+            }
+            return;
+        }
+
+        let next = *self.next_sequence.get_or_insert(sequence);
+
+        //SIGNED DISTANCE SO WRAPPING SEQUENCE NUMBERS COMPARE RIGHT
+        if (sequence.wrapping_sub(next) as i32) > 0 {
+            if self.pending.len() >= MAX_PENDING_SEGMENTS {
+                self.reset();
+                return;
+            }
+
+            self.pending.insert(sequence, payload.to_vec());
+            return;
+        }
+
+        self.append(sequence, payload);
+
+        while let Some(sequence) = self.pending.keys().copied().find(|&sequence| (sequence.wrapping_sub(self.next_sequence.unwrap()) as i32) <= 0) {
+            let payload = self.pending.remove(&sequence).unwrap();
+            self.append(sequence, &payload);
+        }
+
+        if self.buf.len() > MAX_BUFFERED {
+            self.reset();
+        }
+    }
+
+    pub fn next_message(&mut self) -> Option<Vec<u8>> {
+        if self.buf.len() < 2 {
+            return None;
+        }
+
+        let length = u16::from_be_bytes([self.buf[0], self.buf[1]]) as usize;
+        if self.buf.len() < 2+length {
+            return None;
+        }
+
+        let message = self.buf[2..2+length].to_vec();
+        self.buf.drain(..2+length);
+
+        Some(message)
+    }
+
+    //RETRANSMITTED BYTES WE ALREADY HAVE ARE CUT OFF THE FRONT
+    fn append(&mut self, sequence: u32, payload: &[u8]) {
+        let next = self.next_sequence.unwrap();
+        let overlap = next.wrapping_sub(sequence) as usize;
+
+        if overlap < payload.len() {
+            self.buf.extend_from_slice(&payload[overlap..]);
+            self.next_sequence = Some(sequence.wrapping_add(payload.len() as u32));
+        }
+    }
+
+    fn reset(&mut self) {
+        self.next_sequence = None;
+        self.buf.clear();
+        self.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::pcap::tcp_flow::TcpFlow;
+
+    fn framed(message: &[u8]) -> Vec<u8> {
+        let mut buf = (message.len() as u16).to_be_bytes().to_vec();
+        buf.extend_from_slice(message);
+        buf
+    }
+
+    #[test]
+    fn message_split_across_segments() {
+        let buf = framed(b"hello world");
+        let mut flow = TcpFlow::new();
+
+        flow.push(100, true, &[]);
+        flow.push(101, false, &buf[..1]);
+        assert_eq!(flow.next_message(), None);
+        flow.push(102, false, &buf[1..6]);
+        assert_eq!(flow.next_message(), None);
+        flow.push(107, false, &buf[6..]);
+        assert_eq!(flow.next_message(), Some(b"hello world".to_vec()));
+        assert_eq!(flow.next_message(), None);
+    }
+
+    #[test]
+    fn out_of_order_and_retransmitted_segments() {
+        let buf = [framed(b"first"), framed(b"second")].concat();
+        let mut flow = TcpFlow::new();
+
+        //A CAPTURE STARTED MID CONNECTION SYNCS ON THE FIRST SEGMENT IT SEES
+        flow.push(5000, false, &buf[..4]);
+        flow.push(5010, false, &buf[10..]);
+        flow.push(5002, false, &buf[2..10]);
+        flow.push(5000, false, &buf[..4]);
+
+        assert_eq!(flow.next_message(), Some(b"first".to_vec()));
+        assert_eq!(flow.next_message(), Some(b"second".to_vec()));
+        assert_eq!(flow.next_message(), None);
+    }
+
+    #[test]
+    fn sequence_numbers_wrap() {
+        let buf = framed(b"wrapped");
+        let mut flow = TcpFlow::new();
+
+        flow.push(u32::MAX-2, false, &buf[..3]);
+        flow.push(0, false, &buf[3..]);
+        assert_eq!(flow.next_message(), Some(b"wrapped".to_vec()));
+    }
+
+    #[test]
+    fn syn_starts_over() {
+        let mut flow = TcpFlow::new();
+        flow.push(10, false, &[0, 9, 1, 2]);
+
+        flow.push(500, true, &[]);
+        flow.push(501, false, &framed(b"new"));
+        assert_eq!(flow.next_message(), Some(b"new".to_vec()));
+    }
+}
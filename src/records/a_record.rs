@@ -27,27 +27,27 @@ impl Default for ARecord {
 
 impl RecordBase for ARecord {
 
-    fn from_bytes(buf: &[u8], off: usize) -> Self {
+    fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
         let dns_class = u16::from_be_bytes([buf[off], buf[off+1]]);
         let cache_flush = (dns_class & 0x8000) != 0;
-        let dns_class = Some(DnsClasses::from_code(dns_class & 0x7FFF).unwrap());
+        let dns_class = Some(DnsClasses::from_code(dns_class & 0x7FFF)?);
         let ttl = u32::from_be_bytes([buf[off+2], buf[off+3], buf[off+4], buf[off+5]]);
 
         let length = u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
         let record = &buf[off + 8..off + 8 + length];
 
         let address = match record.len() {
-            4 => IpAddr::from(<[u8; 4]>::try_from(record).expect("Invalid IPv4 address")),
-            16 => IpAddr::from(<[u8; 16]>::try_from(record).expect("Invalid IPv6 address")),
-            _ => panic!("Invalid Inet Address")
+            4 => IpAddr::from(<[u8; 4]>::try_from(record).unwrap()),
+            16 => IpAddr::from(<[u8; 16]>::try_from(record).unwrap()),
+            _ => return Err(format!("Invalid Inet Address length: {}", length))
         };
 
-        Self {
+        Ok(Self {
             dns_class,
             cache_flush,
             ttl,
             address: Some(address)
-        }
+        })
     }
 
     fn to_bytes(&self, label_map: &mut HashMap<String, usize>, off: usize) -> Result<Vec<u8>, String> {
@@ -83,6 +83,17 @@ impl RecordBase for ARecord {
         Types::A
     }
 
+    fn get_dns_class(&self) -> Result<DnsClasses, String> {
+        match self.dns_class {
+            Some(ref dns_class) => Ok(dns_class.clone()),
+            None => Err("No dns class returned".to_string())
+        }
+    }
+
+    fn get_ttl(&self) -> u32 {
+        self.ttl
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -103,8 +114,8 @@ impl RecordBase for ARecord {
         Box::new(self.clone())
     }
 
-    fn to_string(&self) -> String {
-        format!("[RECORD] type {:?}, class {:?}, addr: {}", self.get_type(), self.dns_class.unwrap(), self.address.unwrap().to_string())
+    fn rdata_to_string(&self) -> String {
+        self.address.unwrap().to_string()
     }
 }
 
@@ -123,21 +134,10 @@ impl ARecord {
         self.dns_class = Some(dns_class);
     }
 
-    pub fn get_dns_class(&self) -> Result<DnsClasses, String> {
-        match self.dns_class {
-            Some(ref dns_class) => Ok(dns_class.clone()),
-            None => Err("No dns class returned".to_string())
-        }
-    }
-
     pub fn set_ttl(&mut self, ttl: u32) {
         self.ttl = ttl;
     }
 
-    pub fn get_ttl(&self) -> u32 {
-        self.ttl
-    }
-
     pub fn set_address(&mut self, address: IpAddr) {
         self.address = Some(address);
     }
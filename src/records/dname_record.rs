@@ -0,0 +1,115 @@
+use std::any::Any;
+use std::collections::HashMap;
+use crate::messages::inter::dns_classes::DnsClasses;
+use crate::messages::inter::types::Types;
+use crate::records::inter::record_base::RecordBase;
+use crate::utils::domain_utils::{pack_domain_uncompressed, to_fqdn, unpack_domain};
+
+#[derive(Clone, Default)]
+pub struct DNameRecord {
+    dns_class: Option<DnsClasses>,
+    ttl: u32,
+    domain: Option<String>
+}
+
+impl RecordBase for DNameRecord {
+
+    fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
+        let dns_class = Some(DnsClasses::from_code(u16::from_be_bytes([buf[off], buf[off+1]]))?);
+        let ttl = u32::from_be_bytes([buf[off+2], buf[off+3], buf[off+4], buf[off+5]]);
+
+        let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
+
+        let (domain, length) = unpack_domain(buf, off+8)?;
+        if off+8+length != data_length {
+            return Err(format!("Invalid {} RDATA length: {}", Types::Dname.get_name(), data_length-off-8));
+        }
+
+        Ok(Self {
+            dns_class,
+            ttl,
+            domain: Some(domain)
+        })
+    }
+
+    //RFC 6672 2.5 - THE TARGET IS NEVER COMPRESSED, SERVERS THAT DON'T KNOW DNAME COULDN'T FOLLOW THE POINTER
+    fn to_bytes(&self, _label_map: &mut HashMap<String, usize>, _off: usize) -> Result<Vec<u8>, String> {
+        let mut buf = vec![0u8; 10];
+
+        buf.splice(0..2, self.get_type().get_code().to_be_bytes());
+        buf.splice(2..4, self.dns_class.unwrap().get_code().to_be_bytes());
+        buf.splice(4..8, self.ttl.to_be_bytes());
+
+        buf.extend_from_slice(&pack_domain_uncompressed(self.domain.as_ref().unwrap())?);
+
+        buf.splice(8..10, ((buf.len()-10) as u16).to_be_bytes());
+
+        Ok(buf)
+    }
+
+    fn get_type(&self) -> Types {
+        Types::Dname
+    }
+
+    fn get_dns_class(&self) -> Result<DnsClasses, String> {
+        match self.dns_class {
+            Some(dns_class) => Ok(dns_class),
+            None => Err("No dns class returned".to_string())
+        }
+    }
+
+    fn get_ttl(&self) -> u32 {
+        self.ttl
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn upcast(&self) -> &dyn RecordBase {
+        self
+    }
+
+    fn upcast_mut(&mut self) -> &mut dyn RecordBase {
+        self
+    }
+
+    fn dyn_clone(&self) -> Box<dyn RecordBase> {
+        Box::new(self.clone())
+    }
+
+    fn rdata_to_string(&self) -> String {
+        to_fqdn(self.domain.as_ref().unwrap())
+    }
+}
+
+impl DNameRecord {
+
+    pub fn new(dns_classes: DnsClasses, ttl: u32, domain: &str) -> Self {
+        Self {
+            dns_class: Some(dns_classes),
+            ttl,
+            domain: Some(domain.to_string())
+        }
+    }
+
+    pub fn set_dns_class(&mut self, dns_class: DnsClasses) {
+        self.dns_class = Some(dns_class);
+    }
+
+    pub fn set_ttl(&mut self, ttl: u32) {
+        self.ttl = ttl;
+    }
+
+    pub fn set_domain(&mut self, domain: &str) {
+        self.domain = Some(domain.to_string());
+    }
+
+    pub fn get_domain(&self) -> Option<String> {
+        self.domain.clone()
+    }
+}
@@ -2,7 +2,9 @@ use std::any::Any;
 use std::collections::HashMap;
 use crate::messages::inter::dns_classes::DnsClasses;
 use crate::messages::inter::types::Types;
+use crate::records::inter::dnssec_algorithms::DnsSecAlgorithms;
 use crate::records::inter::record_base::RecordBase;
+use crate::utils::base64;
 
 #[derive(Clone)]
 pub struct DNSKeyRecord {
@@ -30,12 +32,17 @@ impl Default for DNSKeyRecord {
 
 impl RecordBase for DNSKeyRecord {
 
-    fn from_bytes(buf: &[u8], off: usize) -> Self {
+    fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
         let mut off = off;
 
-        let dns_class = Some(DnsClasses::from_code(u16::from_be_bytes([buf[off], buf[off+1]])).unwrap());
+        let dns_class = Some(DnsClasses::from_code(u16::from_be_bytes([buf[off], buf[off+1]]))?);
         let ttl = u32::from_be_bytes([buf[off+2], buf[off+3], buf[off+4], buf[off+5]]);
 
+        let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
+        if data_length < off+12 {
+            return Err(format!("Invalid DNSKEY RDATA length: {}", data_length-off-8));
+        }
+
         let flags = u16::from_be_bytes([buf[off+8], buf[off+9]]);
         /*
         Flags: 0x0100
@@ -48,19 +55,18 @@ impl RecordBase for DNSKeyRecord {
         let protocol = buf[off+10];
         let algorithm = buf[off+11];
 
-        let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
         off += 12;
 
         let public_key = buf[off..data_length].to_vec();
 
-        Self {
+        Ok(Self {
             dns_class,
             ttl,
             flags,
             protocol,
             algorithm,
             public_key
-        }
+        })
     }
 
     fn to_bytes(&self, label_map: &mut HashMap<String, usize>, off: usize) -> Result<Vec<u8>, String> {
@@ -85,6 +91,17 @@ impl RecordBase for DNSKeyRecord {
         Types::DnsKey
     }
 
+    fn get_dns_class(&self) -> Result<DnsClasses, String> {
+        match self.dns_class {
+            Some(ref dns_class) => Ok(dns_class.clone()),
+            None => Err("No dns class returned".to_string())
+        }
+    }
+
+    fn get_ttl(&self) -> u32 {
+        self.ttl
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -105,8 +122,8 @@ impl RecordBase for DNSKeyRecord {
         Box::new(self.clone())
     }
 
-    fn to_string(&self) -> String {
-        format!("[RECORD] type {:?}, class {:?}", self.get_type(), self.dns_class.unwrap())
+    fn rdata_to_string(&self) -> String {
+        format!("{} {} {} {}", self.flags, self.protocol, self.algorithm, base64::encode(&self.public_key))
     }
 }
 
@@ -127,18 +144,15 @@ impl DNSKeyRecord {
         self.dns_class = Some(dns_class);
     }
 
-    pub fn get_dns_class(&self) -> Result<DnsClasses, String> {
-        match self.dns_class {
-            Some(ref dns_class) => Ok(dns_class.clone()),
-            None => Err("No dns class returned".to_string())
-        }
-    }
-
     pub fn set_ttl(&mut self, ttl: u32) {
         self.ttl = ttl;
     }
 
-    pub fn get_ttl(&self) -> u32 {
-        self.ttl
+    pub fn set_algorithm(&mut self, algorithm: DnsSecAlgorithms) {
+        self.algorithm = algorithm.get_code();
+    }
+
+    pub fn get_algorithm(&self) -> Result<DnsSecAlgorithms, String> {
+        DnsSecAlgorithms::from_code(self.algorithm)
     }
 }
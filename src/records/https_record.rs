@@ -1,10 +1,14 @@
 use std::any::Any;
 use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
 use crate::messages::inter::dns_classes::DnsClasses;
 use crate::messages::inter::types::Types;
 use crate::records::inter::record_base::RecordBase;
-use crate::utils::domain_utils::{pack_domain, unpack_domain};
+use crate::records::inter::svc_param_keys::SvcParamKeys;
+use crate::utils::domain_utils::{pack_domain, to_fqdn, unpack_domain};
+use crate::utils::base64;
 use crate::utils::ordered_map::OrderedMap;
+use crate::utils::string_utils::escape_string;
 
 #[derive(Clone)]
 pub struct HttpsRecord {
@@ -30,34 +34,50 @@ impl Default for HttpsRecord {
 
 impl RecordBase for HttpsRecord {
 
-    fn from_bytes(buf: &[u8], off: usize) -> Self {
+    fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
         let mut off = off;
 
-        let dns_class = Some(DnsClasses::from_code(u16::from_be_bytes([buf[off], buf[off+1]])).unwrap());
+        let dns_class = Some(DnsClasses::from_code(u16::from_be_bytes([buf[off], buf[off+1]]))?);
         let ttl = u32::from_be_bytes([buf[off+2], buf[off+3], buf[off+4], buf[off+5]]);
 
-        let svc_priority = u16::from_be_bytes([buf[off+8], buf[off+9]]);
+        let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
+        if data_length < off+11 {
+            return Err(format!("Invalid HTTPS RDATA length: {}", data_length-off-8));
+        }
 
-        let (target, length) = unpack_domain(&buf, off+10);
+        let svc_priority = u16::from_be_bytes([buf[off+8], buf[off+9]]);
 
-        let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
+        let (target, length) = unpack_domain(buf, off+10)?;
         off += length+10;
 
         let mut params = OrderedMap::new();
         while off < data_length {
+            if off+4 > data_length {
+                return Err("HTTPS SvcParam runs past RDATA".to_string());
+            }
+
             let key = u16::from_be_bytes([buf[off], buf[off+1]]);
             let length = u16::from_be_bytes([buf[off+2], buf[off+3]]) as usize;
+
+            if off+4+length > data_length {
+                return Err("HTTPS SvcParam runs past RDATA".to_string());
+            }
+
             params.insert(key, buf[off + 4..off + 4 + length].to_vec());
             off += length+4;
         }
 
-        Self {
+        if off != data_length {
+            return Err(format!("Invalid HTTPS RDATA length: {}", data_length));
+        }
+
+        Ok(Self {
             dns_class,
             ttl,
             svc_priority,
             target: Some(target),
             params
-        }
+        })
     }
 
     fn to_bytes(&self, label_map: &mut HashMap<String, usize>, off: usize) -> Result<Vec<u8>, String> {
@@ -69,7 +89,7 @@ impl RecordBase for HttpsRecord {
 
         buf.splice(10..12, self.svc_priority.to_be_bytes());
 
-        let target = pack_domain(self.target.as_ref().unwrap().as_str(), label_map, off+12);
+        let target = pack_domain(self.target.as_ref().unwrap().as_str(), label_map, off+12)?;
         buf.extend_from_slice(&target);
 
         for (key, value) in self.params.iter() {
@@ -87,6 +107,17 @@ impl RecordBase for HttpsRecord {
         Types::Https
     }
 
+    fn get_dns_class(&self) -> Result<DnsClasses, String> {
+        match self.dns_class {
+            Some(ref dns_class) => Ok(dns_class.clone()),
+            None => Err("No dns class returned".to_string())
+        }
+    }
+
+    fn get_ttl(&self) -> u32 {
+        self.ttl
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -107,8 +138,15 @@ impl RecordBase for HttpsRecord {
         Box::new(self.clone())
     }
 
-    fn to_string(&self) -> String {
-        format!("[RECORD] type {:?}, class {:?}", self.get_type(), self.dns_class.unwrap())
+    fn rdata_to_string(&self) -> String {
+        let mut record = format!("{} {}", self.svc_priority, to_fqdn(self.target.as_ref().unwrap()));
+
+        for (key, value) in self.params.iter() {
+            record.push(' ');
+            record.push_str(&param_to_string(*key, value));
+        }
+
+        record
     }
 }
 
@@ -128,18 +166,60 @@ impl HttpsRecord {
         self.dns_class = Some(dns_class);
     }
 
-    pub fn get_dns_class(&self) -> Result<DnsClasses, String> {
-        match self.dns_class {
-            Some(ref dns_class) => Ok(dns_class.clone()),
-            None => Err("No dns class returned".to_string())
-        }
-    }
-
     pub fn set_ttl(&mut self, ttl: u32) {
         self.ttl = ttl;
     }
+}
 
-    pub fn get_ttl(&self) -> u32 {
-        self.ttl
+fn key_to_string(key: u16) -> String {
+    match SvcParamKeys::from_code(key) {
+        Ok(key) => key.get_name().to_string(),
+        Err(_) => format!("key{}", key)
+    }
+}
+
+//RFC 9460 2.1 - VALUES THAT DON'T FIT THE KEY'S FORMAT FALL BACK TO THE GENERIC keyNNNN FORM
+fn param_to_string(key: u16, value: &[u8]) -> String {
+    let formatted = match SvcParamKeys::from_code(key) {
+        Ok(SvcParamKeys::Mandatory) if !value.is_empty() && value.len().is_multiple_of(2) => {
+            Some(value.chunks(2).map(|code| key_to_string(u16::from_be_bytes([code[0], code[1]]))).collect::<Vec<_>>().join(","))
+        }
+        Ok(SvcParamKeys::Alpn) if !value.is_empty() => {
+            let mut ids = Vec::new();
+            let mut off = 0;
+
+            while off < value.len() {
+                let length = value[off] as usize;
+                if length == 0 || off+1+length > value.len() {
+                    break;
+                }
+                ids.push(escape_string(&value[off+1..off+1+length], b","));
+                off += length+1;
+            }
+
+            if off == value.len() { Some(ids.join(",")) } else { None }
+        }
+        Ok(SvcParamKeys::NoDefaultAlpn) if value.is_empty() => {
+            return key_to_string(key);
+        }
+        Ok(SvcParamKeys::Port) if value.len() == 2 => {
+            Some(u16::from_be_bytes([value[0], value[1]]).to_string())
+        }
+        Ok(SvcParamKeys::Ipv4Hint) if !value.is_empty() && value.len().is_multiple_of(4) => {
+            Some(value.chunks(4).map(|a| Ipv4Addr::new(a[0], a[1], a[2], a[3]).to_string()).collect::<Vec<_>>().join(","))
+        }
+        Ok(SvcParamKeys::Ipv6Hint) if !value.is_empty() && value.len().is_multiple_of(16) => {
+            Some(value.chunks(16).map(|a| Ipv6Addr::from(<[u8; 16]>::try_from(a).unwrap()).to_string()).collect::<Vec<_>>().join(","))
+        }
+        Ok(SvcParamKeys::Ech) if !value.is_empty() => {
+            Some(base64::encode(value))
+        }
+        _ => None
+    };
+
+    match formatted {
+        Some(formatted) => format!("{}={}", key_to_string(key), formatted),
+        None if value.is_empty() => format!("key{}", key),
+        None => format!("key{}={}", key, escape_string(value, b""))
     }
 }
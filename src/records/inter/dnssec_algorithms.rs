@@ -0,0 +1,74 @@
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum DnsSecAlgorithms {
+    RsaMd5,
+    Dh,
+    Dsa,
+    RsaSha1,
+    DsaNsec3Sha1,
+    RsaSha1Nsec3Sha1,
+    RsaSha256,
+    RsaSha512,
+    EccGost,
+    EcdsaP256Sha256,
+    EcdsaP384Sha384,
+    Ed25519,
+    Ed448,
+    Indirect,
+    PrivateDns,
+    PrivateOid
+}
+
+impl DnsSecAlgorithms {
+
+    pub fn from_code(code: u8) -> Result<Self, String> {
+        for c in [Self::RsaMd5, Self::Dh, Self::Dsa, Self::RsaSha1, Self::DsaNsec3Sha1, Self::RsaSha1Nsec3Sha1, Self::RsaSha256, Self::RsaSha512, Self::EccGost, Self::EcdsaP256Sha256, Self::EcdsaP384Sha384, Self::Ed25519, Self::Ed448, Self::Indirect, Self::PrivateDns, Self::PrivateOid] {
+            if c.get_code() == code {
+                return Ok(c);
+            }
+        }
+
+        Err(format!("Couldn't find for code: {}", code))
+    }
+
+    pub fn get_code(&self) -> u8 {
+        match self {
+            Self::RsaMd5 => 1,
+            Self::Dh => 2,
+            Self::Dsa => 3,
+            Self::RsaSha1 => 5,
+            Self::DsaNsec3Sha1 => 6,
+            Self::RsaSha1Nsec3Sha1 => 7,
+            Self::RsaSha256 => 8,
+            Self::RsaSha512 => 10,
+            Self::EccGost => 12,
+            Self::EcdsaP256Sha256 => 13,
+            Self::EcdsaP384Sha384 => 14,
+            Self::Ed25519 => 15,
+            Self::Ed448 => 16,
+            Self::Indirect => 252,
+            Self::PrivateDns => 253,
+            Self::PrivateOid => 254
+        }
+    }
+
+    pub fn get_mnemonic(&self) -> &str {
+        match self {
+            Self::RsaMd5 => "RSAMD5",
+            Self::Dh => "DH",
+            Self::Dsa => "DSA",
+            Self::RsaSha1 => "RSASHA1",
+            Self::DsaNsec3Sha1 => "DSA-NSEC3-SHA1",
+            Self::RsaSha1Nsec3Sha1 => "RSASHA1-NSEC3-SHA1",
+            Self::RsaSha256 => "RSASHA256",
+            Self::RsaSha512 => "RSASHA512",
+            Self::EccGost => "ECC-GOST",
+            Self::EcdsaP256Sha256 => "ECDSAP256SHA256",
+            Self::EcdsaP384Sha384 => "ECDSAP384SHA384",
+            Self::Ed25519 => "ED25519",
+            Self::Ed448 => "ED448",
+            Self::Indirect => "INDIRECT",
+            Self::PrivateDns => "PRIVATEDNS",
+            Self::PrivateOid => "PRIVATEOID"
+        }
+    }
+}
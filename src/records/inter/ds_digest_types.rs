@@ -0,0 +1,38 @@
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum DsDigestTypes {
+    Sha1,
+    Sha256,
+    Gost,
+    Sha384
+}
+
+impl DsDigestTypes {
+
+    pub fn from_code(code: u8) -> Result<Self, String> {
+        for c in [Self::Sha1, Self::Sha256, Self::Gost, Self::Sha384] {
+            if c.get_code() == code {
+                return Ok(c);
+            }
+        }
+
+        Err(format!("Couldn't find for code: {}", code))
+    }
+
+    pub fn get_code(&self) -> u8 {
+        match self {
+            Self::Sha1 => 1,
+            Self::Sha256 => 2,
+            Self::Gost => 3,
+            Self::Sha384 => 4
+        }
+    }
+
+    pub fn get_mnemonic(&self) -> &str {
+        match self {
+            Self::Sha1 => "SHA-1",
+            Self::Sha256 => "SHA-256",
+            Self::Gost => "GOST",
+            Self::Sha384 => "SHA-384"
+        }
+    }
+}
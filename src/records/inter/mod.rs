@@ -1,2 +1,8 @@
 pub mod record_base;
 pub mod opt_codes;
+pub mod option_base;
+pub mod dnssec_algorithms;
+pub mod ds_digest_types;
+pub mod nsec3_hash_algorithms;
+pub mod svc_param_keys;
+pub mod understood_algorithm;
@@ -0,0 +1,29 @@
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Nsec3HashAlgorithms {
+    Sha1
+}
+
+impl Nsec3HashAlgorithms {
+
+    pub fn from_code(code: u8) -> Result<Self, String> {
+        for c in [Self::Sha1] {
+            if c.get_code() == code {
+                return Ok(c);
+            }
+        }
+
+        Err(format!("Couldn't find for code: {}", code))
+    }
+
+    pub fn get_code(&self) -> u8 {
+        match self {
+            Self::Sha1 => 1
+        }
+    }
+
+    pub fn get_mnemonic(&self) -> &str {
+        match self {
+            Self::Sha1 => "SHA-1"
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use std::any::Any;
+use crate::records::inter::opt_codes::OptCodes;
+
+pub trait OptionBase {
+
+    fn from_bytes(buf: &[u8], off: usize, length: usize) -> Result<Self, String> where Self: Sized;
+
+    fn to_bytes(&self) -> Result<Vec<u8>, String>;
+
+    fn get_code(&self) -> OptCodes;
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn dyn_clone(&self) -> Box<dyn OptionBase>;
+
+    fn to_string(&self) -> String;
+}
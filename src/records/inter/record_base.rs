@@ -3,14 +3,18 @@ use std::collections::HashMap;
 use crate::messages::inter::dns_classes::DnsClasses;
 use crate::messages::inter::types::Types;
 
-pub trait RecordBase {
+pub trait RecordBase: Send + Sync {
 
-    fn from_bytes(buf: &[u8], off: usize) -> Self where Self: Sized;
+    fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> where Self: Sized;
 
     fn to_bytes(&self, label_map: &mut HashMap<String, usize>, off: usize) -> Result<Vec<u8>, String>;
 
     fn get_type(&self) -> Types;
 
+    fn get_dns_class(&self) -> Result<DnsClasses, String>;
+
+    fn get_ttl(&self) -> u32;
+
     fn as_any(&self) -> &dyn Any;
 
     fn as_any_mut(&mut self) -> &mut dyn Any;
@@ -21,5 +25,11 @@ pub trait RecordBase {
 
     fn dyn_clone(&self) -> Box<dyn RecordBase>;
 
-    fn to_string(&self) -> String;
+    //THE RDATA ALONE IN PRESENTATION FORM, WHAT COMES AFTER THE TYPE IN A ZONE FILE
+    fn rdata_to_string(&self) -> String;
+
+    //TTL CLASS TYPE RDATA, THE WAY DIG AND ZONE FILES PRINT A RECORD AFTER ITS OWNER
+    fn to_string(&self) -> String {
+        format!("{} {} {} {}", self.get_ttl(), self.get_dns_class().unwrap().get_name(), self.get_type(), self.rdata_to_string())
+    }
 }
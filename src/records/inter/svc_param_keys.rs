@@ -0,0 +1,57 @@
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum SvcParamKeys {
+    Mandatory,
+    Alpn,
+    NoDefaultAlpn,
+    Port,
+    Ipv4Hint,
+    Ech,
+    Ipv6Hint
+}
+
+impl SvcParamKeys {
+
+    pub fn from_code(code: u16) -> Result<Self, String> {
+        for c in [Self::Mandatory, Self::Alpn, Self::NoDefaultAlpn, Self::Port, Self::Ipv4Hint, Self::Ech, Self::Ipv6Hint] {
+            if c.get_code() == code {
+                return Ok(c);
+            }
+        }
+
+        Err(format!("Couldn't find for code: {}", code))
+    }
+
+    pub fn get_code(&self) -> u16 {
+        match self {
+            Self::Mandatory => 0,
+            Self::Alpn => 1,
+            Self::NoDefaultAlpn => 2,
+            Self::Port => 3,
+            Self::Ipv4Hint => 4,
+            Self::Ech => 5,
+            Self::Ipv6Hint => 6
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        for c in [Self::Mandatory, Self::Alpn, Self::NoDefaultAlpn, Self::Port, Self::Ipv4Hint, Self::Ech, Self::Ipv6Hint] {
+            if c.get_name() == name {
+                return Ok(c);
+            }
+        }
+
+        Err(format!("Couldn't find for name: {}", name))
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            Self::Mandatory => "mandatory",
+            Self::Alpn => "alpn",
+            Self::NoDefaultAlpn => "no-default-alpn",
+            Self::Port => "port",
+            Self::Ipv4Hint => "ipv4hint",
+            Self::Ech => "ech",
+            Self::Ipv6Hint => "ipv6hint"
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use std::fmt::Debug;
+use crate::records::inter::opt_codes::OptCodes;
+
+//RFC 6975 - WHAT ONE OF THE DAU, DHU AND N3U OPTIONS LISTS, EACH ONLY DIFFERS IN ITS CODE AND THE ALGORITHM NUMBERS IT CARRIES
+pub trait UnderstoodAlgorithm: Copy + Eq + Debug + 'static {
+
+    const OPT_CODE: OptCodes;
+
+    const NAME: &'static str;
+
+    fn from_code(code: u8) -> Result<Self, String>;
+
+    fn get_code(&self) -> u8;
+
+    fn get_mnemonic(&self) -> &str;
+}
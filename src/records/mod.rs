@@ -1,7 +1,9 @@
 pub mod inter;
+pub mod options;
 pub mod a_record;
 pub mod aaaa_record;
 pub mod cname_record;
+pub mod dname_record;
 pub mod mx_record;
 pub mod ns_record;
 pub mod ptr_record;
@@ -15,3 +17,6 @@ pub mod dnskey_record;
 pub mod rrsig_record;
 pub mod srv_record;
 pub mod https_record;
+pub mod unknown_record;
+#[cfg(feature = "serde")]
+pub mod record_serde;
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use crate::messages::inter::dns_classes::DnsClasses;
 use crate::messages::inter::types::Types;
 use crate::records::inter::record_base::RecordBase;
-use crate::utils::domain_utils::{pack_domain, unpack_domain};
+use crate::utils::domain_utils::{pack_domain, to_fqdn, unpack_domain};
 
 #[derive(Clone)]
 pub struct MxRecord {
@@ -27,22 +27,28 @@ impl Default for MxRecord {
 
 impl RecordBase for MxRecord {
 
-    fn from_bytes(buf: &[u8], off: usize) -> Self {
-        let dns_class = Some(DnsClasses::from_code(u16::from_be_bytes([buf[off], buf[off+1]])).unwrap());
+    fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
+        let dns_class = Some(DnsClasses::from_code(u16::from_be_bytes([buf[off], buf[off+1]]))?);
         let ttl = u32::from_be_bytes([buf[off+2], buf[off+3], buf[off+4], buf[off+5]]);
 
-        let z = u16::from_be_bytes([buf[off+6], buf[off+7]]);
+        let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
+        if data_length < off+11 {
+            return Err(format!("Invalid MX RDATA length: {}", data_length-off-8));
+        }
 
         let priority = u16::from_be_bytes([buf[off+8], buf[off+9]]);
 
-        let (domain, _) = unpack_domain(buf, off+10);
+        let (domain, length) = unpack_domain(buf, off+10)?;
+        if off+10+length != data_length {
+            return Err(format!("Invalid MX RDATA length: {}", data_length-off-8));
+        }
 
-        Self {
+        Ok(Self {
             dns_class,
             ttl,
             priority,
             domain: Some(domain)
-        }
+        })
     }
 
     fn to_bytes(&self, label_map: &mut HashMap<String, usize>, off: usize) -> Result<Vec<u8>, String> {
@@ -54,7 +60,7 @@ impl RecordBase for MxRecord {
 
         buf.splice(10..12, self.priority.to_be_bytes());
 
-        buf.extend_from_slice(&pack_domain(self.domain.as_ref().unwrap().as_str(), label_map, off+14));
+        buf.extend_from_slice(&pack_domain(self.domain.as_ref().unwrap().as_str(), label_map, off+12)?);
 
         buf.splice(8..10, ((buf.len()-10) as u16).to_be_bytes());
 
@@ -65,6 +71,17 @@ impl RecordBase for MxRecord {
         Types::Mx
     }
 
+    fn get_dns_class(&self) -> Result<DnsClasses, String> {
+        match self.dns_class {
+            Some(ref dns_class) => Ok(dns_class.clone()),
+            None => Err("No dns class returned".to_string())
+        }
+    }
+
+    fn get_ttl(&self) -> u32 {
+        self.ttl
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -85,8 +102,8 @@ impl RecordBase for MxRecord {
         Box::new(self.clone())
     }
 
-    fn to_string(&self) -> String {
-        format!("[RECORD] type {:?}, class {:?}, priority {}, domain {}", self.get_type(), self.dns_class.unwrap(), self.priority, self.domain.as_ref().unwrap())
+    fn rdata_to_string(&self) -> String {
+        format!("{} {}", self.priority, to_fqdn(self.domain.as_ref().unwrap()))
     }
 }
 
@@ -105,19 +122,16 @@ impl MxRecord {
         self.dns_class = Some(dns_class);
     }
 
-    pub fn get_dns_class(&self) -> Result<DnsClasses, String> {
-        match self.dns_class {
-            Some(ref dns_class) => Ok(dns_class.clone()),
-            None => Err("No dns class returned".to_string())
-        }
-    }
-
     pub fn set_ttl(&mut self, ttl: u32) {
         self.ttl = ttl;
     }
 
-    pub fn get_ttl(&self) -> u32 {
-        self.ttl
+    pub fn set_priority(&mut self, priority: u16) {
+        self.priority = priority;
+    }
+
+    pub fn get_priority(&self) -> u16 {
+        self.priority
     }
 
     pub fn set_domain(&mut self, domain: &str) {
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use crate::messages::inter::dns_classes::DnsClasses;
 use crate::messages::inter::types::Types;
 use crate::records::inter::record_base::RecordBase;
-use crate::utils::domain_utils::{pack_domain, unpack_domain};
+use crate::utils::domain_utils::{pack_domain, to_fqdn, unpack_domain};
 
 #[derive(Clone)]
 pub struct NsRecord {
@@ -25,19 +25,22 @@ impl Default for NsRecord {
 
 impl RecordBase for NsRecord {
 
-    fn from_bytes(buf: &[u8], off: usize) -> Self {
-        let dns_class = Some(DnsClasses::from_code(u16::from_be_bytes([buf[off], buf[off+1]])).unwrap());
+    fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
+        let dns_class = Some(DnsClasses::from_code(u16::from_be_bytes([buf[off], buf[off+1]]))?);
         let ttl = u32::from_be_bytes([buf[off+2], buf[off+3], buf[off+4], buf[off+5]]);
 
-        let z = u16::from_be_bytes([buf[off+6], buf[off+7]]);
+        let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
 
-        let (domain, _) = unpack_domain(buf, off+8);
+        let (domain, length) = unpack_domain(buf, off+8)?;
+        if off+8+length != data_length {
+            return Err(format!("Invalid {} RDATA length: {}", Types::Ns.get_name(), data_length-off-8));
+        }
 
-        Self {
+        Ok(Self {
             dns_class,
             ttl,
             domain: Some(domain)
-        }
+        })
     }
 
     fn to_bytes(&self, label_map: &mut HashMap<String, usize>, off: usize) -> Result<Vec<u8>, String> {
@@ -47,7 +50,7 @@ impl RecordBase for NsRecord {
         buf.splice(2..4, self.dns_class.unwrap().get_code().to_be_bytes());
         buf.splice(4..8, self.ttl.to_be_bytes());
 
-        buf.extend_from_slice(&pack_domain(self.domain.as_ref().unwrap().as_str(), label_map, off+12));
+        buf.extend_from_slice(&pack_domain(self.domain.as_ref().unwrap().as_str(), label_map, off+10)?);
 
         buf.splice(8..10, ((buf.len()-10) as u16).to_be_bytes());
 
@@ -58,6 +61,17 @@ impl RecordBase for NsRecord {
         Types::Ns
     }
 
+    fn get_dns_class(&self) -> Result<DnsClasses, String> {
+        match self.dns_class {
+            Some(ref dns_class) => Ok(dns_class.clone()),
+            None => Err("No dns class returned".to_string())
+        }
+    }
+
+    fn get_ttl(&self) -> u32 {
+        self.ttl
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -78,8 +92,8 @@ impl RecordBase for NsRecord {
         Box::new(self.clone())
     }
 
-    fn to_string(&self) -> String {
-        format!("[RECORD] type {:?}, class {:?}, domain {}", self.get_type(), self.dns_class.unwrap(), self.domain.as_ref().unwrap())
+    fn rdata_to_string(&self) -> String {
+        to_fqdn(self.domain.as_ref().unwrap())
     }
 }
 
@@ -97,21 +111,10 @@ impl NsRecord {
         self.dns_class = Some(dns_class);
     }
 
-    pub fn get_dns_class(&self) -> Result<DnsClasses, String> {
-        match self.dns_class {
-            Some(ref dns_class) => Ok(dns_class.clone()),
-            None => Err("No dns class returned".to_string())
-        }
-    }
-
     pub fn set_ttl(&mut self, ttl: u32) {
         self.ttl = ttl;
     }
 
-    pub fn get_ttl(&self) -> u32 {
-        self.ttl
-    }
-
     pub fn set_domain(&mut self, domain: &str) {
         self.domain = Some(domain.to_string());
     }
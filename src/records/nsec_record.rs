@@ -3,7 +3,7 @@ use std::collections::{BTreeMap, HashMap};
 use crate::messages::inter::dns_classes::DnsClasses;
 use crate::messages::inter::types::Types;
 use crate::records::inter::record_base::RecordBase;
-use crate::utils::domain_utils::{pack_domain, unpack_domain};
+use crate::utils::domain_utils::{pack_domain, to_fqdn, unpack_domain};
 
 #[derive(Clone)]
 pub struct NsecRecord {
@@ -29,27 +29,35 @@ impl Default for NsecRecord {
 
 impl RecordBase for NsecRecord {
 
-    fn from_bytes(buf: &[u8], off: usize) -> Self {
+    fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
         let mut off = off;
 
         let dns_class = u16::from_be_bytes([buf[off], buf[off+1]]);
         let cache_flush = (dns_class & 0x8000) != 0;
-        let dns_class = Some(DnsClasses::from_code(dns_class & 0x7FFF).unwrap());
+        let dns_class = Some(DnsClasses::from_code(dns_class & 0x7FFF)?);
         let ttl = u32::from_be_bytes([buf[off+2], buf[off+3], buf[off+4], buf[off+5]]);
 
-        let (domain, length) = unpack_domain(buf, off+8);
-
         let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
+
+        let (domain, length) = unpack_domain(buf, off+8)?;
         off += length+8;
 
+        if off > data_length {
+            return Err("NSEC next domain runs past RDATA".to_string());
+        }
+
         let mut rr_types = Vec::new();
 
         while off < data_length {
+            if off+2 > data_length {
+                return Err("NSEC type bitmap runs past RDATA".to_string());
+            }
+
             let window = buf[off];
             let length = buf[off + 1] as usize;
 
             if off+2+length > data_length {
-                break;
+                return Err("NSEC type bitmap runs past RDATA".to_string());
             }
 
             let bitmap = &buf[off + 2..off + 2 + length];
@@ -65,13 +73,13 @@ impl RecordBase for NsecRecord {
             off += 2+length;
         }
 
-        Self {
+        Ok(Self {
             dns_class,
             cache_flush,
             ttl,
             domain: Some(domain),
             rr_types
-        }
+        })
     }
 
     fn to_bytes(&self, label_map: &mut HashMap<String, usize>, off: usize) -> Result<Vec<u8>, String> {
@@ -87,7 +95,7 @@ impl RecordBase for NsecRecord {
         buf.splice(2..4, dns_class.to_be_bytes());
         buf.splice(4..8, self.ttl.to_be_bytes());
 
-        buf.extend_from_slice(&pack_domain(self.domain.as_ref().unwrap().as_str(), label_map, off+12));
+        buf.extend_from_slice(&pack_domain(self.domain.as_ref().unwrap().as_str(), label_map, off+10)?);
 
         let mut windows: BTreeMap<u8, Vec<u8>> = BTreeMap::new();
 
@@ -119,6 +127,17 @@ impl RecordBase for NsecRecord {
         Types::Nsec
     }
 
+    fn get_dns_class(&self) -> Result<DnsClasses, String> {
+        match self.dns_class {
+            Some(ref dns_class) => Ok(dns_class.clone()),
+            None => Err("No dns class returned".to_string())
+        }
+    }
+
+    fn get_ttl(&self) -> u32 {
+        self.ttl
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -139,8 +158,15 @@ impl RecordBase for NsecRecord {
         Box::new(self.clone())
     }
 
-    fn to_string(&self) -> String {
-        format!("[RECORD] type {:?}, class {:?}, domain {}", self.get_type(), self.dns_class.unwrap(), self.domain.as_ref().unwrap())
+    fn rdata_to_string(&self) -> String {
+        let mut record = to_fqdn(self.domain.as_ref().unwrap());
+
+        for code in &self.rr_types {
+            record.push(' ');
+            record.push_str(&Types::from_code(*code).unwrap_or(Types::Unknown(*code)).to_string());
+        }
+
+        record
     }
 }
 
@@ -160,21 +186,10 @@ impl NsecRecord {
         self.dns_class = Some(dns_class);
     }
 
-    pub fn get_dns_class(&self) -> Result<DnsClasses, String> {
-        match self.dns_class {
-            Some(ref dns_class) => Ok(dns_class.clone()),
-            None => Err("No dns class returned".to_string())
-        }
-    }
-
     pub fn set_ttl(&mut self, ttl: u32) {
         self.ttl = ttl;
     }
 
-    pub fn get_ttl(&self) -> u32 {
-        self.ttl
-    }
-
     pub fn set_domain(&mut self, domain: &str) {
         self.domain = Some(domain.to_string());
     }
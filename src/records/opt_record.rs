@@ -1,9 +1,20 @@
 use std::any::Any;
 use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use crate::messages::inter::dns_classes::DnsClasses;
 use crate::messages::inter::types::Types;
 use crate::records::inter::opt_codes::OptCodes;
+use crate::records::inter::option_base::OptionBase;
 use crate::records::inter::record_base::RecordBase;
-use crate::utils::ordered_map::OrderedMap;
+use crate::records::options::chain_option::ChainOption;
+use crate::records::options::dau_option::DauOption;
+use crate::records::options::dhu_option::DhuOption;
+use crate::records::options::expire_option::ExpireOption;
+use crate::records::options::key_tag_option::KeyTagOption;
+use crate::records::options::n3u_option::N3uOption;
+use crate::records::options::nsid_option::NsidOption;
+use crate::records::options::tcp_keepalive_option::TcpKeepaliveOption;
+use crate::utils::hex;
 
 #[derive(Clone)]
 pub struct OptRecord {
@@ -11,7 +22,7 @@ pub struct OptRecord {
     ext_rcode: u8,
     edns_version: u8,
     flags: u16,
-    options: OrderedMap<OptCodes, Vec<u8>>
+    options: Vec<(u16, Vec<u8>)>
 }
 
 impl Default for OptRecord {
@@ -22,14 +33,14 @@ impl Default for OptRecord {
             ext_rcode: 0,
             edns_version: 0,
             flags: 0x8000,
-            options: OrderedMap::new()
+            options: Vec::new()
         }
     }
 }
 
 impl RecordBase for OptRecord {
 
-    fn from_bytes(buf: &[u8], off: usize) -> Self {
+    fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
         let payload_size = u16::from_be_bytes([buf[off], buf[off+1]]);
         let ext_rcode = buf[off+2];
         let edns_version = buf[off+3];
@@ -37,23 +48,33 @@ impl RecordBase for OptRecord {
 
         let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
         let mut off = off+8;
-        let mut options = OrderedMap::new();
+        let mut options = Vec::new();
 
+        //CODES ARE KEPT RAW SO UNKNOWN AND REPEATED OPTIONS SURVIVE A ROUND TRIP
         while off < data_length {
-            let opt_code = OptCodes::from_code(u16::from_be_bytes([buf[off], buf[off+1]])).unwrap();
+            if off+4 > data_length {
+                return Err("OPT option runs past RDATA".to_string());
+            }
+
+            let code = u16::from_be_bytes([buf[off], buf[off+1]]);
             let length = u16::from_be_bytes([buf[off+2], buf[off+3]]) as usize;
-            options.insert(opt_code, buf[off + 4..off + 4 + length].to_vec());
+
+            if off+4+length > data_length {
+                return Err(format!("OPT option {} runs past RDATA", code));
+            }
+
+            options.push((code, buf[off + 4..off + 4 + length].to_vec()));
 
             off += 4+length;
         }
 
-        Self {
+        Ok(Self {
             payload_size,
             ext_rcode,
             edns_version,
             flags,
             options
-        }
+        })
     }
 
     fn to_bytes(&self, label_map: &mut HashMap<String, usize>, off: usize) -> Result<Vec<u8>, String> {
@@ -67,8 +88,8 @@ impl RecordBase for OptRecord {
 
         buf.splice(6..8, self.flags.to_be_bytes());
 
-        for (code, option) in self.options.iter() {
-            buf.extend_from_slice(&code.get_code().to_be_bytes());
+        for (code, option) in &self.options {
+            buf.extend_from_slice(&code.to_be_bytes());
             buf.extend_from_slice(&(option.len() as u16).to_be_bytes());
             buf.extend_from_slice(&option);
         }
@@ -82,6 +103,17 @@ impl RecordBase for OptRecord {
         Types::Opt
     }
 
+    //RFC 6891 6.1.2 - THE CLASS FIELD IS THE PAYLOAD SIZE, OPT HAS NO CLASS OF ITS OWN
+    fn get_dns_class(&self) -> Result<DnsClasses, String> {
+        Err("OPT has no dns class".to_string())
+    }
+
+    //RFC 6891 6.1.3 - THE TTL FIELD CARRIES THE EXTENDED RCODE, VERSION AND FLAGS
+    fn get_ttl(&self) -> u32 {
+        let flags = self.flags.to_be_bytes();
+        u32::from_be_bytes([self.ext_rcode, self.edns_version, flags[0], flags[1]])
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -102,8 +134,12 @@ impl RecordBase for OptRecord {
         Box::new(self.clone())
     }
 
+    fn rdata_to_string(&self) -> String {
+        self.options.iter().map(|(code, option)| Self::option_to_string(*code, option)).collect::<Vec<_>>().join("; ")
+    }
+
     fn to_string(&self) -> String {
-        format!("[RECORD] type {:?} payload_size {}", self.get_type(), self.payload_size)
+        format!("EDNS: version: {}, flags:{}; udp: {}", self.edns_version, if self.is_dnssec_ok() { " do" } else { "" }, self.payload_size)
     }
 }
 
@@ -115,7 +151,241 @@ impl OptRecord {
             ext_rcode,
             edns_version,
             flags,
-            options: OrderedMap::new()
+            options: Vec::new()
         }
     }
+
+    pub fn set_payload_size(&mut self, payload_size: u16) {
+        self.payload_size = payload_size;
+    }
+
+    pub fn get_payload_size(&self) -> u16 {
+        self.payload_size
+    }
+
+    pub fn set_ext_rcode(&mut self, ext_rcode: u8) {
+        self.ext_rcode = ext_rcode;
+    }
+
+    pub fn get_ext_rcode(&self) -> u8 {
+        self.ext_rcode
+    }
+
+    pub fn set_edns_version(&mut self, edns_version: u8) {
+        self.edns_version = edns_version;
+    }
+
+    pub fn get_edns_version(&self) -> u8 {
+        self.edns_version
+    }
+
+    pub fn set_flags(&mut self, flags: u16) {
+        self.flags = flags;
+    }
+
+    pub fn get_flags(&self) -> u16 {
+        self.flags
+    }
+
+    //RFC 3225 - DO IS THE TOP BIT OF THE EDNS FLAGS
+    pub fn set_dnssec_ok(&mut self, dnssec_ok: bool) {
+        self.flags = if dnssec_ok { self.flags | 0x8000 } else { self.flags & 0x7FFF };
+    }
+
+    pub fn is_dnssec_ok(&self) -> bool {
+        (self.flags & 0x8000) != 0
+    }
+
+    pub fn add_option(&mut self, code: OptCodes, option: Vec<u8>) {
+        self.options.push((code.get_code(), option));
+    }
+
+    pub fn add_raw_option(&mut self, code: u16, option: Vec<u8>) {
+        self.options.push((code, option));
+    }
+
+    pub fn set_option(&mut self, code: OptCodes, option: Vec<u8>) {
+        let code = code.get_code();
+
+        match self.options.iter().position(|(c, _)| *c == code) {
+            Some(i) => {
+                self.options[i].1 = option;
+
+                let mut i = i+1;
+                while i < self.options.len() {
+                    if self.options[i].0 == code {
+                        self.options.remove(i);
+                        continue;
+                    }
+                    i += 1;
+                }
+            }
+            None => self.options.push((code, option))
+        }
+    }
+
+    pub fn get_option(&self, code: &OptCodes) -> Option<&Vec<u8>> {
+        self.options.iter().find(|(c, _)| *c == code.get_code()).map(|(_, option)| option)
+    }
+
+    pub fn get_options(&self, code: &OptCodes) -> Vec<&Vec<u8>> {
+        self.options.iter().filter(|(c, _)| *c == code.get_code()).map(|(_, option)| option).collect()
+    }
+
+    pub fn remove_option(&mut self, code: &OptCodes) -> Vec<Vec<u8>> {
+        let code = code.get_code();
+        let mut removed = Vec::new();
+
+        self.options.retain(|(c, option)| {
+            if *c == code {
+                removed.push(option.clone());
+                return false;
+            }
+            true
+        });
+
+        removed
+    }
+
+    pub fn get_raw_options(&self) -> &Vec<(u16, Vec<u8>)> {
+        &self.options
+    }
+
+    pub fn get_unknown_options(&self) -> Vec<&(u16, Vec<u8>)> {
+        self.options.iter().filter(|(code, _)| OptCodes::from_code(*code).is_err()).collect()
+    }
+
+    pub fn add_typed_option(&mut self, option: &dyn OptionBase) -> Result<(), String> {
+        self.options.push((option.get_code().get_code(), option.to_bytes()?));
+        Ok(())
+    }
+
+    pub fn get_typed_options(&self) -> Vec<Result<Box<dyn OptionBase>, String>> {
+        self.options.iter().filter_map(|(code, option)| Self::decode_option(*code, option)).collect()
+    }
+
+    //NONE WHEN THERE IS NO TYPED CODEC FOR THE CODE
+    pub fn decode_option(code: u16, option: &[u8]) -> Option<Result<Box<dyn OptionBase>, String>> {
+        let option = match OptCodes::from_code(code).ok()? {
+            OptCodes::Nsid => NsidOption::from_bytes(option, 0, option.len()).map(|o| o.dyn_clone()),
+            OptCodes::Dau => DauOption::from_bytes(option, 0, option.len()).map(|o| o.dyn_clone()),
+            OptCodes::Dhu => DhuOption::from_bytes(option, 0, option.len()).map(|o| o.dyn_clone()),
+            OptCodes::N3u => N3uOption::from_bytes(option, 0, option.len()).map(|o| o.dyn_clone()),
+            OptCodes::Expire => ExpireOption::from_bytes(option, 0, option.len()).map(|o| o.dyn_clone()),
+            OptCodes::TcpKeepalive => TcpKeepaliveOption::from_bytes(option, 0, option.len()).map(|o| o.dyn_clone()),
+            OptCodes::Chain => ChainOption::from_bytes(option, 0, option.len()).map(|o| o.dyn_clone()),
+            OptCodes::KeyTag => KeyTagOption::from_bytes(option, 0, option.len()).map(|o| o.dyn_clone()),
+            _ => return None
+        };
+
+        Some(option)
+    }
+
+    //ONE LINE PER OPTION THE WAY DIG PRINTS THE OPT PSEUDOSECTION
+    pub fn option_to_string(code: u16, option: &[u8]) -> String {
+        match Self::decode_option(code, option) {
+            Some(Ok(option)) => return option.to_string(),
+            Some(Err(e)) => return format!("OPT={}: {} ({})", code, hex::encode(option), e),
+            None => {}
+        }
+
+        match OptCodes::from_code(code) {
+            Ok(OptCodes::Cookie) => format!("COOKIE: {}", hex::encode(option)),
+            Ok(OptCodes::Padding) => format!("PADDING: {} bytes", option.len()),
+            Ok(OptCodes::Ecs) if option.len() >= 4 => {
+                let family = u16::from_be_bytes([option[0], option[1]]);
+                let address = &option[4..];
+
+                let address = match family {
+                    1 if address.len() <= 4 => {
+                        let mut octets = [0u8; 4];
+                        octets[..address.len()].copy_from_slice(address);
+                        Ipv4Addr::from(octets).to_string()
+                    }
+                    2 if address.len() <= 16 => {
+                        let mut octets = [0u8; 16];
+                        octets[..address.len()].copy_from_slice(address);
+                        Ipv6Addr::from(octets).to_string()
+                    }
+                    _ => hex::encode(address)
+                };
+
+                format!("CLIENT-SUBNET: {}/{}/{}", address, option[2], option[3])
+            }
+            Ok(OptCodes::EdnsError) if option.len() >= 2 => {
+                format!("EDE: {} ({})", u16::from_be_bytes([option[0], option[1]]), String::from_utf8_lossy(&option[2..]))
+            }
+            _ => format!("OPT={}: {}", code, hex::encode(option))
+        }
+    }
+
+    pub fn get_typed_option<O: OptionBase + Clone + 'static>(&self) -> Option<O> {
+        self.get_typed_options()
+            .into_iter()
+            .flatten()
+            .find_map(|option| option.as_any().downcast_ref::<O>().cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashMap;
+    use crate::records::inter::opt_codes::OptCodes;
+    use crate::records::inter::record_base::RecordBase;
+    use crate::records::opt_record::OptRecord;
+    use crate::records::options::nsid_option::NsidOption;
+
+    //TYPE, UDP 1232, DO SET, THEN NSID "a", AN UNKNOWN 65001, A SECOND NSID "b" AND COOKIE
+    const WIRE: [u8; 35] = [
+        0x00, 0x29, 0x04, 0xd0, 0x00, 0x00, 0x80, 0x00, 0x00, 0x19,
+        0x00, 0x03, 0x00, 0x01, b'a',
+        0xfd, 0xe9, 0x00, 0x02, 0xbe, 0xef,
+        0x00, 0x03, 0x00, 0x01, b'b',
+        0x00, 0x0a, 0x00, 0x05, 0x01, 0x02, 0x03, 0x04, 0x05
+    ];
+
+    #[test]
+    fn options_keep_wire_order() {
+        let record = OptRecord::from_bytes(&WIRE, 2).unwrap();
+        assert_eq!(record.get_payload_size(), 1232);
+        assert!(record.is_dnssec_ok());
+
+        assert_eq!(record.get_raw_options().iter().map(|(code, _)| *code).collect::<Vec<_>>(), vec![3, 65001, 3, 10]);
+        assert_eq!(record.get_options(&OptCodes::Nsid), vec![&b"a".to_vec(), &b"b".to_vec()]);
+        assert_eq!(record.get_option(&OptCodes::Nsid), Some(&b"a".to_vec()));
+        assert_eq!(record.get_unknown_options(), vec![&(65001, vec![0xbe, 0xef])]);
+        assert_eq!(record.get_typed_option::<NsidOption>(), Some(NsidOption::new(b"a")));
+    }
+
+    #[test]
+    fn to_bytes_gives_back_the_input() {
+        let record = OptRecord::from_bytes(&WIRE, 2).unwrap();
+        assert_eq!(record.to_bytes(&mut HashMap::new(), 0).unwrap(), WIRE.to_vec());
+    }
+
+    #[test]
+    fn set_option_collapses_duplicates() {
+        let mut record = OptRecord::from_bytes(&WIRE, 2).unwrap();
+        record.set_option(OptCodes::Nsid, b"c".to_vec());
+
+        //THE FIRST ONE TAKES THE NEW VALUE IN PLACE, THE REST ARE GONE
+        assert_eq!(record.get_raw_options(), &vec![(3, b"c".to_vec()), (65001, vec![0xbe, 0xef]), (10, vec![1, 2, 3, 4, 5])]);
+
+        record.set_option(OptCodes::Padding, vec![0; 3]);
+        assert_eq!(record.get_raw_options().last(), Some(&(12, vec![0; 3])));
+
+        assert_eq!(record.remove_option(&OptCodes::Nsid), vec![b"c".to_vec()]);
+        assert!(record.get_options(&OptCodes::Nsid).is_empty());
+    }
+
+    #[test]
+    fn options_past_rdata_are_errors() {
+        let mut wire = WIRE;
+        wire[9] = 0x18;
+        assert!(OptRecord::from_bytes(&wire, 2).is_err());
+
+        wire[9] = 0x02;
+        assert!(OptRecord::from_bytes(&wire, 2).is_err());
+    }
 }
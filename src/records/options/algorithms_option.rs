@@ -0,0 +1,169 @@
+use std::any::Any;
+use std::marker::PhantomData;
+use crate::records::inter::opt_codes::OptCodes;
+use crate::records::inter::option_base::OptionBase;
+use crate::records::inter::understood_algorithm::UnderstoodAlgorithm;
+
+//THE ALGORITHM NUMBERS ARE KEPT AS SENT, ONES WE DON'T KNOW STILL GO BACK OUT
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AlgorithmsOption<A: UnderstoodAlgorithm> {
+    algorithms: Vec<u8>,
+    _algorithm: PhantomData<A>
+}
+
+impl<A: UnderstoodAlgorithm> Default for AlgorithmsOption<A> {
+
+    fn default() -> Self {
+        Self {
+            algorithms: Vec::new(),
+            _algorithm: PhantomData
+        }
+    }
+}
+
+impl<A: UnderstoodAlgorithm> OptionBase for AlgorithmsOption<A> {
+
+    fn from_bytes(buf: &[u8], off: usize, length: usize) -> Result<Self, String> {
+        if off+length > buf.len() {
+            return Err(format!("{} option runs past the end of the buffer", A::NAME));
+        }
+
+        Ok(Self::from_codes(&buf[off..off + length]))
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        Ok(self.algorithms.clone())
+    }
+
+    fn get_code(&self) -> OptCodes {
+        A::OPT_CODE
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn dyn_clone(&self) -> Box<dyn OptionBase> {
+        Box::new(self.clone())
+    }
+
+    fn to_string(&self) -> String {
+        let algorithms: Vec<String> = self.algorithms.iter().map(|&code| {
+            match A::from_code(code) {
+                Ok(algorithm) => algorithm.get_mnemonic().to_string(),
+                Err(_) => code.to_string()
+            }
+        }).collect();
+
+        format!("{}: {}", A::NAME, algorithms.join(" "))
+    }
+}
+
+impl<A: UnderstoodAlgorithm> AlgorithmsOption<A> {
+
+    pub fn new(algorithms: &[A]) -> Self {
+        Self {
+            algorithms: algorithms.iter().map(|algorithm| algorithm.get_code()).collect(),
+            _algorithm: PhantomData
+        }
+    }
+
+    pub fn from_codes(algorithms: &[u8]) -> Self {
+        Self {
+            algorithms: algorithms.to_vec(),
+            _algorithm: PhantomData
+        }
+    }
+
+    pub fn add_algorithm(&mut self, algorithm: A) {
+        self.algorithms.push(algorithm.get_code());
+    }
+
+    pub fn get_codes(&self) -> &Vec<u8> {
+        &self.algorithms
+    }
+
+    pub fn get_algorithms(&self) -> Vec<A> {
+        self.algorithms.iter().filter_map(|&code| A::from_code(code).ok()).collect()
+    }
+
+    pub fn is_understood(&self, algorithm: A) -> bool {
+        self.algorithms.contains(&algorithm.get_code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::records::inter::dnssec_algorithms::DnsSecAlgorithms;
+    use crate::records::inter::ds_digest_types::DsDigestTypes;
+    use crate::records::inter::nsec3_hash_algorithms::Nsec3HashAlgorithms;
+    use crate::records::inter::opt_codes::OptCodes;
+    use crate::records::inter::option_base::OptionBase;
+    use crate::records::options::dau_option::DauOption;
+    use crate::records::options::dhu_option::DhuOption;
+    use crate::records::options::n3u_option::N3uOption;
+
+    #[test]
+    fn dau_round_trip() {
+        let option = DauOption::new(&[DnsSecAlgorithms::RsaSha256, DnsSecAlgorithms::EcdsaP256Sha256, DnsSecAlgorithms::Ed25519]);
+        let buf = option.to_bytes().unwrap();
+        assert_eq!(buf, vec![8, 13, 15]);
+
+        let decoded = DauOption::from_bytes(&buf, 0, buf.len()).unwrap();
+        assert_eq!(decoded, option);
+        assert_eq!(decoded.get_code(), OptCodes::Dau);
+        assert!(decoded.is_understood(DnsSecAlgorithms::Ed25519));
+        assert!(!decoded.is_understood(DnsSecAlgorithms::RsaSha1));
+        assert_eq!(decoded.to_string(), "DAU: RSASHA256 ECDSAP256SHA256 ED25519");
+    }
+
+    #[test]
+    fn dhu_round_trip() {
+        let mut option = DhuOption::default();
+        option.add_algorithm(DsDigestTypes::Sha256);
+        option.add_algorithm(DsDigestTypes::Sha384);
+
+        let buf = option.to_bytes().unwrap();
+        assert_eq!(buf, vec![2, 4]);
+        assert_eq!(DhuOption::from_bytes(&buf, 0, buf.len()).unwrap().get_algorithms(), vec![DsDigestTypes::Sha256, DsDigestTypes::Sha384]);
+        assert_eq!(option.get_code(), OptCodes::Dhu);
+        assert_eq!(option.to_string(), "DHU: SHA-256 SHA-384");
+    }
+
+    #[test]
+    fn n3u_round_trip() {
+        let option = N3uOption::new(&[Nsec3HashAlgorithms::Sha1]);
+        let buf = option.to_bytes().unwrap();
+        assert_eq!(buf, vec![1]);
+        assert_eq!(N3uOption::from_bytes(&buf, 0, buf.len()).unwrap(), option);
+        assert_eq!(option.get_code(), OptCodes::N3u);
+        assert_eq!(option.to_string(), "N3U: SHA-1");
+    }
+
+    #[test]
+    fn unknown_algorithms_are_kept() {
+        let buf = [0xff, 0xff, 8, 200, 2];
+        let option = DauOption::from_bytes(&buf, 2, 3).unwrap();
+
+        assert_eq!(option.get_codes(), &vec![8, 200, 2]);
+        assert_eq!(option.get_algorithms(), vec![DnsSecAlgorithms::RsaSha256, DnsSecAlgorithms::Dh]);
+        assert_eq!(option.to_bytes().unwrap(), vec![8, 200, 2]);
+        assert_eq!(option.to_string(), "DAU: RSASHA256 200 DH");
+
+        let option = N3uOption::from_bytes(&[2], 0, 1).unwrap();
+        assert!(option.get_algorithms().is_empty());
+        assert_eq!(option.to_bytes().unwrap(), vec![2]);
+        assert_eq!(option.to_string(), "N3U: 2");
+    }
+
+    #[test]
+    fn empty_and_truncated() {
+        let option = DhuOption::from_bytes(&[], 0, 0).unwrap();
+        assert!(option.get_codes().is_empty());
+        assert!(option.to_bytes().unwrap().is_empty());
+
+        assert!(DauOption::from_bytes(&[8, 13], 0, 3).is_err());
+        assert!(DhuOption::from_bytes(&[2], 1, 1).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use std::any::Any;
+use crate::records::inter::opt_codes::OptCodes;
+use crate::records::inter::option_base::OptionBase;
+use crate::utils::domain_utils::{pack_domain_uncompressed, unpack_domain};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainOption {
+    closest_trust_point: String
+}
+
+impl Default for ChainOption {
+
+    fn default() -> Self {
+        Self {
+            closest_trust_point: String::new()
+        }
+    }
+}
+
+impl OptionBase for ChainOption {
+
+    fn from_bytes(buf: &[u8], off: usize, length: usize) -> Result<Self, String> {
+        if off+length > buf.len() {
+            return Err("CHAIN option runs past the end of the buffer".to_string());
+        }
+
+        //RFC 7901 - THE NAME IS NEVER COMPRESSED, SO IT MUST FIT THE OPTION EXACTLY
+        let option = &buf[off..off + length];
+        if Self::has_pointer(option) {
+            return Err("CHAIN closest trust point must not be compressed".to_string());
+        }
+
+        let (closest_trust_point, read) = unpack_domain(option, 0)?;
+        if read != length {
+            return Err(format!("Invalid CHAIN length: {}", length));
+        }
+
+        Ok(Self {
+            closest_trust_point
+        })
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        pack_domain_uncompressed(&self.closest_trust_point)
+    }
+
+    fn get_code(&self) -> OptCodes {
+        OptCodes::Chain
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn dyn_clone(&self) -> Box<dyn OptionBase> {
+        Box::new(self.clone())
+    }
+
+    fn to_string(&self) -> String {
+        format!("CHAIN: {}.", self.closest_trust_point)
+    }
+}
+
+impl ChainOption {
+
+    pub fn new(closest_trust_point: &str) -> Self {
+        Self {
+            closest_trust_point: closest_trust_point.trim_end_matches('.').to_string()
+        }
+    }
+
+    pub fn set_closest_trust_point(&mut self, closest_trust_point: &str) {
+        self.closest_trust_point = closest_trust_point.trim_end_matches('.').to_string();
+    }
+
+    pub fn get_closest_trust_point(&self) -> &str {
+        &self.closest_trust_point
+    }
+
+    fn has_pointer(buf: &[u8]) -> bool {
+        let mut off = 0;
+
+        while off < buf.len() {
+            let length = buf[off];
+
+            if length & 0xC0 == 0xC0 {
+                return true;
+            }
+
+            if length == 0 {
+                break;
+            }
+
+            off += 1+length as usize;
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::records::inter::option_base::OptionBase;
+    use crate::records::options::chain_option::ChainOption;
+
+    #[test]
+    fn round_trip() {
+        let option = ChainOption::new("example.com.");
+        let buf = option.to_bytes().unwrap();
+        assert_eq!(buf, b"\x07example\x03com\x00".to_vec());
+        assert_eq!(ChainOption::from_bytes(&buf, 0, buf.len()).unwrap(), option);
+        assert_eq!(option.to_string(), "CHAIN: example.com.");
+    }
+
+    #[test]
+    fn root() {
+        let option = ChainOption::new(".");
+        assert_eq!(option.to_bytes().unwrap(), vec![0]);
+        assert_eq!(ChainOption::from_bytes(&[0], 0, 1).unwrap(), option);
+        assert_eq!(option.to_string(), "CHAIN: .");
+    }
+
+    #[test]
+    fn bad_lengths() {
+        //A TRAILING BYTE AFTER THE NAME, A NAME CUT SHORT AND ONE RUNNING PAST THE BUFFER
+        assert!(ChainOption::from_bytes(b"\x03com\x00\x00", 0, 6).is_err());
+        assert!(ChainOption::from_bytes(b"\x07example", 0, 8).is_err());
+        assert!(ChainOption::from_bytes(b"\x03com\x00", 0, 6).is_err());
+    }
+
+    #[test]
+    fn compressed_name_is_refused() {
+        assert!(ChainOption::from_bytes(&[0x03, b'c', b'o', b'm', 0xc0, 0x00], 0, 6).is_err());
+    }
+}
@@ -0,0 +1,26 @@
+use crate::records::inter::dnssec_algorithms::DnsSecAlgorithms;
+use crate::records::inter::opt_codes::OptCodes;
+use crate::records::inter::understood_algorithm::UnderstoodAlgorithm;
+use crate::records::options::algorithms_option::AlgorithmsOption;
+
+//RFC 6975 - DNSSEC SIGNING ALGORITHMS THE RESOLVER CAN VALIDATE
+pub type DauOption = AlgorithmsOption<DnsSecAlgorithms>;
+
+impl UnderstoodAlgorithm for DnsSecAlgorithms {
+
+    const OPT_CODE: OptCodes = OptCodes::Dau;
+
+    const NAME: &'static str = "DAU";
+
+    fn from_code(code: u8) -> Result<Self, String> {
+        DnsSecAlgorithms::from_code(code)
+    }
+
+    fn get_code(&self) -> u8 {
+        DnsSecAlgorithms::get_code(self)
+    }
+
+    fn get_mnemonic(&self) -> &str {
+        DnsSecAlgorithms::get_mnemonic(self)
+    }
+}
@@ -0,0 +1,26 @@
+use crate::records::inter::ds_digest_types::DsDigestTypes;
+use crate::records::inter::opt_codes::OptCodes;
+use crate::records::inter::understood_algorithm::UnderstoodAlgorithm;
+use crate::records::options::algorithms_option::AlgorithmsOption;
+
+//RFC 6975 - DS HASH ALGORITHMS THE RESOLVER CAN VALIDATE
+pub type DhuOption = AlgorithmsOption<DsDigestTypes>;
+
+impl UnderstoodAlgorithm for DsDigestTypes {
+
+    const OPT_CODE: OptCodes = OptCodes::Dhu;
+
+    const NAME: &'static str = "DHU";
+
+    fn from_code(code: u8) -> Result<Self, String> {
+        DsDigestTypes::from_code(code)
+    }
+
+    fn get_code(&self) -> u8 {
+        DsDigestTypes::get_code(self)
+    }
+
+    fn get_mnemonic(&self) -> &str {
+        DsDigestTypes::get_mnemonic(self)
+    }
+}
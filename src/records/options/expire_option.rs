@@ -0,0 +1,112 @@
+use std::any::Any;
+use crate::records::inter::opt_codes::OptCodes;
+use crate::records::inter::option_base::OptionBase;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpireOption {
+    expire: Option<u32>
+}
+
+impl Default for ExpireOption {
+
+    fn default() -> Self {
+        Self {
+            expire: None
+        }
+    }
+}
+
+impl OptionBase for ExpireOption {
+
+    fn from_bytes(buf: &[u8], off: usize, length: usize) -> Result<Self, String> {
+        let expire = match length {
+            0 => None,
+            4 if off+4 <= buf.len() => Some(u32::from_be_bytes([buf[off], buf[off+1], buf[off+2], buf[off+3]])),
+            _ => return Err(format!("Invalid EXPIRE length: {}", length))
+        };
+
+        Ok(Self {
+            expire
+        })
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        Ok(match self.expire {
+            Some(expire) => expire.to_be_bytes().to_vec(),
+            None => Vec::new()
+        })
+    }
+
+    fn get_code(&self) -> OptCodes {
+        OptCodes::Expire
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn dyn_clone(&self) -> Box<dyn OptionBase> {
+        Box::new(self.clone())
+    }
+
+    fn to_string(&self) -> String {
+        match self.expire {
+            Some(expire) => format!("EXPIRE: {}", expire),
+            None => "EXPIRE".to_string()
+        }
+    }
+}
+
+impl ExpireOption {
+
+    //RFC 7314 - SECONDS REMAINING BEFORE THE ZONE EXPIRES ON THE RESPONDING SERVER
+    pub fn new(expire: u32) -> Self {
+        Self {
+            expire: Some(expire)
+        }
+    }
+
+    pub fn request() -> Self {
+        Self::default()
+    }
+
+    pub fn set_expire(&mut self, expire: Option<u32>) {
+        self.expire = expire;
+    }
+
+    pub fn get_expire(&self) -> Option<u32> {
+        self.expire
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::records::inter::option_base::OptionBase;
+    use crate::records::options::expire_option::ExpireOption;
+
+    #[test]
+    fn empty_form() {
+        let option = ExpireOption::from_bytes(&[], 0, 0).unwrap();
+        assert_eq!(option, ExpireOption::request());
+        assert_eq!(option.get_expire(), None);
+        assert!(option.to_bytes().unwrap().is_empty());
+        assert_eq!(option.to_string(), "EXPIRE");
+    }
+
+    #[test]
+    fn four_byte_form() {
+        let option = ExpireOption::new(604800);
+        let buf = option.to_bytes().unwrap();
+        assert_eq!(buf, vec![0x00, 0x09, 0x3a, 0x80]);
+        assert_eq!(ExpireOption::from_bytes(&buf, 0, 4).unwrap(), option);
+        assert_eq!(option.to_string(), "EXPIRE: 604800");
+    }
+
+    #[test]
+    fn bad_lengths() {
+        assert!(ExpireOption::from_bytes(&[0x00, 0x09], 0, 2).is_err());
+        assert!(ExpireOption::from_bytes(&[0x00, 0x09, 0x3a, 0x80, 0x00], 0, 5).is_err());
+        assert!(ExpireOption::from_bytes(&[0x00, 0x09, 0x3a], 0, 4).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use std::any::Any;
+use crate::records::inter::opt_codes::OptCodes;
+use crate::records::inter::option_base::OptionBase;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyTagOption {
+    key_tags: Vec<u16>
+}
+
+impl Default for KeyTagOption {
+
+    fn default() -> Self {
+        Self {
+            key_tags: Vec::new()
+        }
+    }
+}
+
+impl OptionBase for KeyTagOption {
+
+    fn from_bytes(buf: &[u8], off: usize, length: usize) -> Result<Self, String> {
+        if length == 0 || !length.is_multiple_of(2) || off+length > buf.len() {
+            return Err(format!("Invalid edns-key-tag length: {}", length));
+        }
+
+        let key_tags = buf[off..off + length]
+            .chunks(2)
+            .map(|tag| u16::from_be_bytes([tag[0], tag[1]]))
+            .collect();
+
+        Ok(Self {
+            key_tags
+        })
+    }
+
+    //AN EMPTY LIST ISN'T A VALID OPTION, SO IT'S NEVER SENT EITHER
+    fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        if self.key_tags.is_empty() {
+            return Err("edns-key-tag needs at least one key tag".to_string());
+        }
+
+        Ok(self.key_tags.iter().flat_map(|tag| tag.to_be_bytes()).collect())
+    }
+
+    fn get_code(&self) -> OptCodes {
+        OptCodes::KeyTag
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn dyn_clone(&self) -> Box<dyn OptionBase> {
+        Box::new(self.clone())
+    }
+
+    fn to_string(&self) -> String {
+        format!("KEY-TAG: {}", self.key_tags.iter().map(|tag| tag.to_string()).collect::<Vec<_>>().join(", "))
+    }
+}
+
+impl KeyTagOption {
+
+    //RFC 8145 - THE KEY TAGS OF THE TRUST ANCHORS THE RESOLVER IS USING FOR THE QUERIED ZONE
+    pub fn new(key_tags: Vec<u16>) -> Self {
+        Self {
+            key_tags
+        }
+    }
+
+    pub fn add_key_tag(&mut self, key_tag: u16) {
+        self.key_tags.push(key_tag);
+    }
+
+    pub fn get_key_tags(&self) -> &Vec<u16> {
+        &self.key_tags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::records::inter::option_base::OptionBase;
+    use crate::records::options::key_tag_option::KeyTagOption;
+
+    #[test]
+    fn round_trip() {
+        let option = KeyTagOption::new(vec![20326, 38696]);
+        let buf = option.to_bytes().unwrap();
+        assert_eq!(buf, vec![0x4f, 0x66, 0x97, 0x28]);
+        assert_eq!(KeyTagOption::from_bytes(&buf, 0, buf.len()).unwrap(), option);
+        assert_eq!(option.to_string(), "KEY-TAG: 20326, 38696");
+    }
+
+    #[test]
+    fn empty_list_is_refused_both_ways() {
+        assert!(KeyTagOption::new(Vec::new()).to_bytes().is_err());
+        assert!(KeyTagOption::default().to_bytes().is_err());
+        assert!(KeyTagOption::from_bytes(&[], 0, 0).is_err());
+    }
+
+    #[test]
+    fn bad_lengths() {
+        assert!(KeyTagOption::from_bytes(&[0x4f, 0x66, 0x97], 0, 3).is_err());
+        assert!(KeyTagOption::from_bytes(&[0x4f, 0x66], 0, 4).is_err());
+        assert!(KeyTagOption::from_bytes(&[0x4f, 0x66], 1, 2).is_err());
+    }
+}
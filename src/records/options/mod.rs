@@ -0,0 +1,9 @@
+pub mod nsid_option;
+pub mod tcp_keepalive_option;
+pub mod expire_option;
+pub mod chain_option;
+pub mod key_tag_option;
+pub mod algorithms_option;
+pub mod dau_option;
+pub mod dhu_option;
+pub mod n3u_option;
@@ -0,0 +1,26 @@
+use crate::records::inter::nsec3_hash_algorithms::Nsec3HashAlgorithms;
+use crate::records::inter::opt_codes::OptCodes;
+use crate::records::inter::understood_algorithm::UnderstoodAlgorithm;
+use crate::records::options::algorithms_option::AlgorithmsOption;
+
+//RFC 6975 - NSEC3 HASH ALGORITHMS THE RESOLVER CAN VALIDATE
+pub type N3uOption = AlgorithmsOption<Nsec3HashAlgorithms>;
+
+impl UnderstoodAlgorithm for Nsec3HashAlgorithms {
+
+    const OPT_CODE: OptCodes = OptCodes::N3u;
+
+    const NAME: &'static str = "N3U";
+
+    fn from_code(code: u8) -> Result<Self, String> {
+        Nsec3HashAlgorithms::from_code(code)
+    }
+
+    fn get_code(&self) -> u8 {
+        Nsec3HashAlgorithms::get_code(self)
+    }
+
+    fn get_mnemonic(&self) -> &str {
+        Nsec3HashAlgorithms::get_mnemonic(self)
+    }
+}
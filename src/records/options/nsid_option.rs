@@ -0,0 +1,111 @@
+use std::any::Any;
+use crate::records::inter::opt_codes::OptCodes;
+use crate::records::inter::option_base::OptionBase;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NsidOption {
+    nsid: Vec<u8>
+}
+
+impl Default for NsidOption {
+
+    fn default() -> Self {
+        Self {
+            nsid: Vec::new()
+        }
+    }
+}
+
+impl OptionBase for NsidOption {
+
+    fn from_bytes(buf: &[u8], off: usize, length: usize) -> Result<Self, String> {
+        if off+length > buf.len() {
+            return Err("NSID option runs past the end of the buffer".to_string());
+        }
+
+        Ok(Self {
+            nsid: buf[off..off + length].to_vec()
+        })
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        Ok(self.nsid.clone())
+    }
+
+    fn get_code(&self) -> OptCodes {
+        OptCodes::Nsid
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn dyn_clone(&self) -> Box<dyn OptionBase> {
+        Box::new(self.clone())
+    }
+
+    fn to_string(&self) -> String {
+        let hex: String = self.nsid.iter().map(|b| format!("{:02x}", b)).collect();
+
+        match std::str::from_utf8(&self.nsid) {
+            Ok(nsid) if nsid.chars().all(|c| c.is_ascii_graphic() || c == ' ') => format!("NSID: {} (\"{}\")", hex, nsid),
+            _ => format!("NSID: {}", hex)
+        }
+    }
+}
+
+impl NsidOption {
+
+    pub fn new(nsid: &[u8]) -> Self {
+        Self {
+            nsid: nsid.to_vec()
+        }
+    }
+
+    //RFC 5001 - A CLIENT ASKS BY SENDING THE OPTION EMPTY
+    pub fn request() -> Self {
+        Self::default()
+    }
+
+    pub fn is_request(&self) -> bool {
+        self.nsid.is_empty()
+    }
+
+    pub fn set_nsid(&mut self, nsid: &[u8]) {
+        self.nsid = nsid.to_vec();
+    }
+
+    pub fn get_nsid(&self) -> &[u8] {
+        &self.nsid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::records::inter::option_base::OptionBase;
+    use crate::records::options::nsid_option::NsidOption;
+
+    #[test]
+    fn round_trip() {
+        let option = NsidOption::new(b"ns1");
+        let buf = option.to_bytes().unwrap();
+        assert_eq!(buf, b"ns1".to_vec());
+        assert_eq!(NsidOption::from_bytes(&buf, 0, buf.len()).unwrap(), option);
+        assert_eq!(option.to_string(), "NSID: 6e7331 (\"ns1\")");
+        assert_eq!(NsidOption::new(&[0x00, 0xff]).to_string(), "NSID: 00ff");
+    }
+
+    #[test]
+    fn empty_is_a_request() {
+        let option = NsidOption::from_bytes(&[], 0, 0).unwrap();
+        assert!(option.is_request());
+        assert!(NsidOption::request().to_bytes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn bad_lengths() {
+        assert!(NsidOption::from_bytes(b"ns1", 0, 4).is_err());
+        assert!(NsidOption::from_bytes(b"ns1", 2, 2).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use std::any::Any;
+use std::time::Duration;
+use crate::records::inter::opt_codes::OptCodes;
+use crate::records::inter::option_base::OptionBase;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TcpKeepaliveOption {
+    timeout: Option<u16>
+}
+
+impl Default for TcpKeepaliveOption {
+
+    fn default() -> Self {
+        Self {
+            timeout: None
+        }
+    }
+}
+
+impl OptionBase for TcpKeepaliveOption {
+
+    fn from_bytes(buf: &[u8], off: usize, length: usize) -> Result<Self, String> {
+        let timeout = match length {
+            0 => None,
+            2 if off+2 <= buf.len() => Some(u16::from_be_bytes([buf[off], buf[off+1]])),
+            _ => return Err(format!("Invalid edns-tcp-keepalive length: {}", length))
+        };
+
+        Ok(Self {
+            timeout
+        })
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        Ok(match self.timeout {
+            Some(timeout) => timeout.to_be_bytes().to_vec(),
+            None => Vec::new()
+        })
+    }
+
+    fn get_code(&self) -> OptCodes {
+        OptCodes::TcpKeepalive
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn dyn_clone(&self) -> Box<dyn OptionBase> {
+        Box::new(self.clone())
+    }
+
+    fn to_string(&self) -> String {
+        match self.timeout {
+            Some(timeout) => format!("KEEPALIVE: {}.{} secs", timeout / 10, timeout % 10),
+            None => "KEEPALIVE".to_string()
+        }
+    }
+}
+
+impl TcpKeepaliveOption {
+
+    //RFC 7828 - TIMEOUT IS IN UNITS OF 100 MILLISECONDS
+    pub fn new(timeout: u16) -> Self {
+        Self {
+            timeout: Some(timeout)
+        }
+    }
+
+    pub fn from_duration(timeout: Duration) -> Self {
+        Self::new((timeout.as_millis() / 100).min(u16::MAX as u128) as u16)
+    }
+
+    //CLIENTS MUST SEND THE OPTION WITHOUT A TIMEOUT
+    pub fn request() -> Self {
+        Self::default()
+    }
+
+    pub fn set_timeout(&mut self, timeout: Option<u16>) {
+        self.timeout = timeout;
+    }
+
+    pub fn get_timeout(&self) -> Option<u16> {
+        self.timeout
+    }
+
+    pub fn get_duration(&self) -> Option<Duration> {
+        self.timeout.map(|timeout| Duration::from_millis(timeout as u64 * 100))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::records::inter::option_base::OptionBase;
+    use crate::records::options::tcp_keepalive_option::TcpKeepaliveOption;
+
+    #[test]
+    fn empty_form() {
+        let option = TcpKeepaliveOption::from_bytes(&[], 0, 0).unwrap();
+        assert_eq!(option, TcpKeepaliveOption::default());
+        assert!(option.to_bytes().unwrap().is_empty());
+        assert_eq!(option.to_string(), "KEEPALIVE");
+    }
+
+    #[test]
+    fn timeout_form() {
+        let option = TcpKeepaliveOption::from_bytes(&[0x01, 0x2c], 0, 2).unwrap();
+        assert_eq!(option.to_bytes().unwrap(), vec![0x01, 0x2c]);
+        assert_eq!(option.to_string(), "KEEPALIVE: 30.0 secs");
+    }
+
+    #[test]
+    fn bad_lengths() {
+        assert!(TcpKeepaliveOption::from_bytes(&[0x01], 0, 1).is_err());
+        assert!(TcpKeepaliveOption::from_bytes(&[0x01, 0x2c, 0x00], 0, 3).is_err());
+        assert!(TcpKeepaliveOption::from_bytes(&[0x01], 0, 2).is_err());
+    }
+}
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use crate::messages::inter::dns_classes::DnsClasses;
 use crate::messages::inter::types::Types;
 use crate::records::inter::record_base::RecordBase;
-use crate::utils::domain_utils::{pack_domain, unpack_domain};
+use crate::utils::domain_utils::{pack_domain, to_fqdn, unpack_domain};
 
 #[derive(Clone)]
 pub struct PtrRecord {
@@ -27,22 +27,25 @@ impl Default for PtrRecord {
 
 impl RecordBase for PtrRecord {
 
-    fn from_bytes(buf: &[u8], off: usize) -> Self {
+    fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
         let dns_class = u16::from_be_bytes([buf[off], buf[off+1]]);
         let cache_flush = (dns_class & 0x8000) != 0;
-        let dns_class = Some(DnsClasses::from_code(dns_class & 0x7FFF).unwrap());
+        let dns_class = Some(DnsClasses::from_code(dns_class & 0x7FFF)?);
         let ttl = u32::from_be_bytes([buf[off+2], buf[off+3], buf[off+4], buf[off+5]]);
 
-        let z = u16::from_be_bytes([buf[off+6], buf[off+7]]);
+        let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
 
-        let (domain, _) = unpack_domain(buf, off+8);
+        let (domain, length) = unpack_domain(buf, off+8)?;
+        if off+8+length != data_length {
+            return Err(format!("Invalid {} RDATA length: {}", Types::Ptr.get_name(), data_length-off-8));
+        }
 
-        Self {
+        Ok(Self {
             dns_class,
             cache_flush,
             ttl,
             domain: Some(domain)
-        }
+        })
     }
 
     fn to_bytes(&self, label_map: &mut HashMap<String, usize>, off: usize) -> Result<Vec<u8>, String> {
@@ -58,7 +61,7 @@ impl RecordBase for PtrRecord {
         buf.splice(2..4, dns_class.to_be_bytes());
         buf.splice(4..8, self.ttl.to_be_bytes());
 
-        buf.extend_from_slice(&pack_domain(self.domain.as_ref().unwrap().as_str(), label_map, off+12));
+        buf.extend_from_slice(&pack_domain(self.domain.as_ref().unwrap().as_str(), label_map, off+10)?);
 
         buf.splice(8..10, ((buf.len()-10) as u16).to_be_bytes());
 
@@ -69,6 +72,17 @@ impl RecordBase for PtrRecord {
         Types::Ptr
     }
 
+    fn get_dns_class(&self) -> Result<DnsClasses, String> {
+        match self.dns_class {
+            Some(ref dns_class) => Ok(dns_class.clone()),
+            None => Err("No dns class returned".to_string())
+        }
+    }
+
+    fn get_ttl(&self) -> u32 {
+        self.ttl
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -89,8 +103,8 @@ impl RecordBase for PtrRecord {
         Box::new(self.clone())
     }
 
-    fn to_string(&self) -> String {
-        format!("[RECORD] type {:?}, class {:?}, domain {}", self.get_type(), self.dns_class.unwrap(), self.domain.as_ref().unwrap())
+    fn rdata_to_string(&self) -> String {
+        to_fqdn(self.domain.as_ref().unwrap())
     }
 }
 
@@ -109,21 +123,10 @@ impl PtrRecord {
         self.dns_class = Some(dns_class);
     }
 
-    pub fn get_dns_class(&self) -> Result<DnsClasses, String> {
-        match self.dns_class {
-            Some(ref dns_class) => Ok(dns_class.clone()),
-            None => Err("No dns class returned".to_string())
-        }
-    }
-
     pub fn set_ttl(&mut self, ttl: u32) {
         self.ttl = ttl;
     }
 
-    pub fn get_ttl(&self) -> u32 {
-        self.ttl
-    }
-
     pub fn set_domain(&mut self, domain: &str) {
         self.domain = Some(domain.to_string());
     }
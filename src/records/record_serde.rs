@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use crate::messages::inter::dns_classes::DnsClasses;
+use crate::messages::inter::types::Types;
+use crate::messages::message_base::MessageBase;
+use crate::records::a_record::ARecord;
+use crate::records::aaaa_record::AAAARecord;
+use crate::records::cname_record::CNameRecord;
+use crate::records::dname_record::DNameRecord;
+use crate::records::dnskey_record::DNSKeyRecord;
+use crate::records::https_record::HttpsRecord;
+use crate::records::inter::record_base::RecordBase;
+use crate::records::mx_record::MxRecord;
+use crate::records::ns_record::NsRecord;
+use crate::records::nsec_record::NsecRecord;
+use crate::records::opt_record::OptRecord;
+use crate::records::ptr_record::PtrRecord;
+use crate::records::rrsig_record::RRSigRecord;
+use crate::records::soa_record::SoaRecord;
+use crate::records::srv_record::SrvRecord;
+use crate::records::txt_record::TxtRecord;
+use crate::records::unknown_record::UnknownRecord;
+use crate::utils::domain_utils::{from_fqdn, to_fqdn};
+use crate::utils::hex;
+use crate::zone::rdata_parser::parse_rdata_str;
+
+//RFC 8427 2.2 - THE RECORD DOESN'T KNOW ITS OWN NAME, WHOEVER HOLDS IT PASSES IT IN
+pub fn record_to_json(name: Option<&str>, record: &dyn RecordBase) -> Result<Map<String, Value>, String> {
+    //THE FIXED PART OF THE WIRE FORM IS NEVER COMPRESSED, SO TYPE, CLASS AND TTL COME FROM THERE
+    let wire = record.to_bytes(&mut HashMap::new(), 0)?;
+    let _type = record.get_type();
+    let dns_class = u16::from_be_bytes([wire[2], wire[3]]);
+
+    let mut map = Map::new();
+
+    if let Some(name) = name {
+        map.insert("NAME".to_string(), Value::from(to_fqdn(name)));
+    }
+
+    map.insert("TYPE".to_string(), Value::from(_type.get_code()));
+    map.insert("TYPEname".to_string(), Value::from(_type.to_string()));
+    map.insert("CLASS".to_string(), Value::from(dns_class));
+
+    //OPT REUSES CLASS FOR THE PAYLOAD SIZE
+    if _type != Types::Opt {
+        if let Ok(dns_class) = DnsClasses::from_code(dns_class) {
+            map.insert("CLASSname".to_string(), Value::from(dns_class.get_name()));
+        }
+    }
+
+    map.insert("TTL".to_string(), Value::from(u32::from_be_bytes([wire[4], wire[5], wire[6], wire[7]])));
+
+    //RDATA WITHOUT NAMES CAN'T BE COMPRESSED, SO THE HEX IS EXACT
+    if _type == Types::Opt || record.as_any().is::<UnknownRecord>() {
+        map.insert("RDLENGTH".to_string(), Value::from(wire.len()-10));
+        map.insert("RDATAHEX".to_string(), Value::from(hex::encode(&wire[10..])));
+        return Ok(map);
+    }
+
+    map.insert(format!("rdata{}", _type), Value::from(record.rdata_to_string()));
+
+    Ok(map)
+}
+
+pub fn record_from_json(map: &Map<String, Value>) -> Result<(String, Box<dyn RecordBase>), String> {
+    let name = match map.get("NAME") {
+        Some(Value::String(name)) => from_fqdn(name).map_err(|e| format!("Invalid NAME: {}", e))?,
+        Some(_) => return Err("NAME must be a string".to_string()),
+        None => String::new()
+    };
+
+    let _type = match (map.get("TYPE"), map.get("TYPEname")) {
+        (Some(Value::Number(code)), _) => {
+            let code = code.as_u64().and_then(|code| u16::try_from(code).ok()).ok_or("TYPE must be 0-65535")?;
+            Types::from_code(code).unwrap_or(Types::Unknown(code))
+        }
+        (None, Some(Value::String(name))) => Types::from_name(name)?,
+        _ => return Err("RR needs a TYPE or TYPEname".to_string())
+    };
+
+    let dns_class = match (map.get("CLASS"), map.get("CLASSname")) {
+        (Some(Value::Number(code)), _) => code.as_u64().and_then(|code| u16::try_from(code).ok()).ok_or("CLASS must be 0-65535")?,
+        (None, Some(Value::String(name))) => DnsClasses::from_name(name)?.get_code(),
+        (None, None) => DnsClasses::In.get_code(),
+        _ => return Err("CLASS must be a number".to_string())
+    };
+
+    let ttl = match map.get("TTL") {
+        Some(ttl) => ttl.as_u64().and_then(|ttl| u32::try_from(ttl).ok()).ok_or("TTL must be 0-4294967295")?,
+        None => 0
+    };
+
+    let rdata = map.get(&format!("rdata{}", _type)).and_then(Value::as_str);
+    let rdata_hex = map.get("RDATAHEX").and_then(Value::as_str);
+
+    if _type == Types::Opt {
+        let data = hex::decode(rdata_hex.ok_or("OPT needs RDATAHEX")?)?;
+
+        let mut wire = Vec::new();
+        wire.extend_from_slice(&_type.get_code().to_be_bytes());
+        wire.extend_from_slice(&dns_class.to_be_bytes());
+        wire.extend_from_slice(&ttl.to_be_bytes());
+        wire.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        wire.extend_from_slice(&data);
+
+        return Ok((name, MessageBase::record_from_bytes(&wire, 0)?));
+    }
+
+    //EVERYTHING ELSE GOES THROUGH THE PRESENTATION PARSER SO THE RULES ARE THE SAME AS A ZONE FILE
+    let cache_flush = (dns_class & 0x8000) != 0;
+    let dns_class = DnsClasses::from_code(dns_class & 0x7FFF)?;
+    let record = match (rdata, rdata_hex) {
+        (Some(rdata), _) => parse_rdata_str(_type, dns_class, ttl, rdata)?,
+        (None, Some(rdata_hex)) => {
+            let data = hex::decode(rdata_hex)?;
+            parse_rdata_str(_type, dns_class, ttl, &format!("\\# {} {}", data.len(), hex::encode(&data)))?
+        }
+        (None, None) => return Err(format!("RR needs rdata{} or RDATAHEX", _type))
+    };
+
+    //MDNS CACHE FLUSH (RFC 6762 10.2) HAS NO PRESENTATION FORM, PUT IT BACK ON THE WIRE FORM
+    if cache_flush {
+        let mut wire = record.to_bytes(&mut HashMap::new(), 0)?;
+        wire[2] |= 0x80;
+        return Ok((name, MessageBase::record_from_bytes(&wire, 0)?));
+    }
+
+    Ok((name, record))
+}
+
+impl Serialize for dyn RecordBase + '_ {
+
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let map = record_to_json(None, self).map_err(S::Error::custom)?;
+        map.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Box<dyn RecordBase> {
+
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map = Map::deserialize(deserializer)?;
+        record_from_json(&map).map(|(_, record)| record).map_err(D::Error::custom)
+    }
+}
+
+macro_rules! record_serde {
+    ($($record:ty),*) => {
+        $(
+            impl Serialize for $record {
+
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    self.upcast().serialize(serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $record {
+
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let record = Box::<dyn RecordBase>::deserialize(deserializer)?;
+                    record.as_any().downcast_ref::<$record>().cloned()
+                        .ok_or_else(|| D::Error::custom(format!("Expected {}, found a {} record", stringify!($record), record.get_type())))
+                }
+            }
+        )*
+    };
+}
+
+record_serde!(ARecord, AAAARecord, CNameRecord, DNameRecord, DNSKeyRecord, HttpsRecord, MxRecord, NsRecord, NsecRecord, OptRecord,
+        PtrRecord, RRSigRecord, SoaRecord, SrvRecord, TxtRecord, UnknownRecord);
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashMap;
+    use serde_json::{json, Value};
+    use crate::messages::inter::types::Types;
+    use crate::records::inter::record_base::RecordBase;
+    use crate::records::mx_record::MxRecord;
+    use crate::records::record_serde::{record_from_json, record_to_json};
+
+    fn from_json(value: Value) -> Result<(String, Box<dyn RecordBase>), String> {
+        record_from_json(value.as_object().unwrap())
+    }
+
+    #[test]
+    fn record_from_rdata_type() {
+        let (name, record) = from_json(json!({"NAME": "mail.example.", "TYPE": 15, "CLASS": 1, "TTL": 300, "rdataMX": "10 mx.example."})).unwrap();
+        assert_eq!(name, "mail.example");
+        assert_eq!(record.as_any().downcast_ref::<MxRecord>().unwrap().get_domain().unwrap(), "mx.example");
+
+        assert_eq!(Value::Object(record_to_json(Some(&name), record.as_ref()).unwrap()), json!({
+            "NAME": "mail.example.", "TYPE": 15, "TYPEname": "MX", "CLASS": 1, "CLASSname": "IN", "TTL": 300, "rdataMX": "10 mx.example."
+        }));
+
+        //TYPEname AND CLASSname STAND IN FOR THE NUMBERS
+        let (_, record) = from_json(json!({"NAME": "mail.example.", "TYPEname": "MX", "CLASSname": "CH", "TTL": 300, "rdataMX": "10 mx.example."})).unwrap();
+        assert_eq!(record.to_string(), "300 CH MX 10 mx.example.");
+    }
+
+    #[test]
+    fn record_from_rdatahex() {
+        let (_, record) = from_json(json!({"NAME": "a.example.", "TYPEname": "A", "TTL": 60, "RDATAHEX": "C0000201"})).unwrap();
+        assert_eq!(record.to_bytes(&mut HashMap::new(), 0).unwrap(), [0, 1, 0, 1, 0, 0, 0, 60, 0, 4, 192, 0, 2, 1]);
+
+        //TYPES WE CAN'T DECODE ONLY HAVE THE HEX FORM, AND THAT'S WHAT GOES BACK OUT
+        let json = json!({"NAME": "x.example.", "TYPE": 65280, "TYPEname": "TYPE65280", "CLASS": 1, "CLASSname": "IN", "TTL": 0, "RDLENGTH": 2, "RDATAHEX": "beef"});
+        let (name, record) = from_json(json.clone()).unwrap();
+        assert_eq!(record.get_type(), Types::Unknown(65280));
+        assert_eq!(Value::Object(record_to_json(Some(&name), record.as_ref()).unwrap()), json);
+    }
+
+    #[test]
+    fn cache_flush_rides_on_the_class() {
+        let (_, record) = from_json(json!({"NAME": "printer.local.", "TYPE": 1, "CLASS": 0x8001, "TTL": 120, "rdataA": "192.0.2.9"})).unwrap();
+        assert_eq!(&record.to_bytes(&mut HashMap::new(), 0).unwrap()[2..4], [0x80, 1]);
+        assert_eq!(record_to_json(None, record.as_ref()).unwrap()["CLASS"], json!(0x8001));
+    }
+
+    #[test]
+    fn bad_records_are_errors() {
+        assert_eq!(from_json(json!({"NAME": "a.example.", "TYPE": 1})).err().unwrap(), "RR needs rdataA or RDATAHEX");
+        assert!(from_json(json!({"NAME": "a.example.", "rdataA": "192.0.2.1"})).is_err());
+        assert!(from_json(json!({"NAME": "a..example.", "TYPE": 1, "rdataA": "192.0.2.1"})).is_err());
+        assert!(from_json(json!({"NAME": "a.example.", "TYPE": 1, "rdataA": "not an address"})).is_err());
+        assert!(from_json(json!({"NAME": "a.example.", "TYPE": 41, "rdataOPT": ""})).is_err());
+        assert!(from_json(json!({"NAME": "a.example.", "TYPE": 1, "RDATAHEX": "c00002"})).is_err());
+    }
+}
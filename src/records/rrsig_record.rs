@@ -2,8 +2,11 @@ use std::any::Any;
 use std::collections::HashMap;
 use crate::messages::inter::dns_classes::DnsClasses;
 use crate::messages::inter::types::Types;
+use crate::records::inter::dnssec_algorithms::DnsSecAlgorithms;
 use crate::records::inter::record_base::RecordBase;
-use crate::utils::domain_utils::{pack_domain_uncompressed, unpack_domain};
+use crate::utils::base64;
+use crate::utils::domain_utils::{pack_domain_uncompressed, to_fqdn, unpack_domain};
+use crate::utils::time_utils::to_timestamp;
 
 #[derive(Clone)]
 pub struct RRSigRecord {
@@ -41,12 +44,17 @@ impl Default for RRSigRecord {
 
 impl RecordBase for RRSigRecord {
 
-    fn from_bytes(buf: &[u8], off: usize) -> Self {
+    fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
         let mut off = off;
 
-        let dns_class = Some(DnsClasses::from_code(u16::from_be_bytes([buf[off], buf[off+1]])).unwrap());
+        let dns_class = Some(DnsClasses::from_code(u16::from_be_bytes([buf[off], buf[off+1]]))?);
         let ttl = u32::from_be_bytes([buf[off+2], buf[off+3], buf[off+4], buf[off+5]]);
 
+        let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
+        if data_length < off+27 {
+            return Err(format!("Invalid RRSIG RDATA length: {}", data_length-off-8));
+        }
+
         let type_covered = u16::from_be_bytes([buf[off+8], buf[off+9]]);
 
         let algorithm = buf[off+10];
@@ -57,14 +65,16 @@ impl RecordBase for RRSigRecord {
         let signature_inception = u32::from_be_bytes([buf[off+20], buf[off+21], buf[off+22], buf[off+23]]);
         let key_tag = u16::from_be_bytes([buf[off+24], buf[off+25]]);
 
-        let (signer_name, length) = unpack_domain(buf, off+26);
-
-        let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
+        let (signer_name, length) = unpack_domain(buf, off+26)?;
         off += length+26;
 
+        if off > data_length {
+            return Err("RRSIG signer name runs past RDATA".to_string());
+        }
+
         let signature = buf[off..data_length].to_vec();
 
-        Self {
+        Ok(Self {
             dns_class,
             ttl,
             type_covered,
@@ -76,7 +86,7 @@ impl RecordBase for RRSigRecord {
             key_tag,
             signer_name: Some(signer_name),
             signature
-        }
+        })
     }
 
     fn to_bytes(&self, label_map: &mut HashMap<String, usize>, off: usize) -> Result<Vec<u8>, String> {
@@ -96,7 +106,7 @@ impl RecordBase for RRSigRecord {
         buf.splice(22..26, self.signature_inception.to_be_bytes());
         buf.splice(26..28, self.key_tag.to_be_bytes());
 
-        buf.extend_from_slice(&pack_domain_uncompressed(self.signer_name.as_ref().unwrap()));
+        buf.extend_from_slice(&pack_domain_uncompressed(self.signer_name.as_ref().unwrap())?);
 
         buf.extend_from_slice(&self.signature);
 
@@ -109,6 +119,17 @@ impl RecordBase for RRSigRecord {
         Types::Rrsig
     }
 
+    fn get_dns_class(&self) -> Result<DnsClasses, String> {
+        match self.dns_class {
+            Some(ref dns_class) => Ok(dns_class.clone()),
+            None => Err("No dns class returned".to_string())
+        }
+    }
+
+    fn get_ttl(&self) -> u32 {
+        self.ttl
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -129,8 +150,11 @@ impl RecordBase for RRSigRecord {
         Box::new(self.clone())
     }
 
-    fn to_string(&self) -> String {
-        format!("[RECORD] type {:?}, class {:?}", self.get_type(), self.dns_class.unwrap())
+    fn rdata_to_string(&self) -> String {
+        let type_covered = Types::from_code(self.type_covered).unwrap_or(Types::Unknown(self.type_covered));
+        format!("{} {} {} {} {} {} {} {} {}", type_covered, self.algorithm, self.labels, self.original_ttl,
+                to_timestamp(self.signature_expiration), to_timestamp(self.signature_inception), self.key_tag,
+                to_fqdn(self.signer_name.as_ref().unwrap()), base64::encode(&self.signature))
     }
 }
 
@@ -156,18 +180,15 @@ impl RRSigRecord {
         self.dns_class = Some(dns_class);
     }
 
-    pub fn get_dns_class(&self) -> Result<DnsClasses, String> {
-        match self.dns_class {
-            Some(ref dns_class) => Ok(dns_class.clone()),
-            None => Err("No dns class returned".to_string())
-        }
-    }
-
     pub fn set_ttl(&mut self, ttl: u32) {
         self.ttl = ttl;
     }
 
-    pub fn get_ttl(&self) -> u32 {
-        self.ttl
+    pub fn set_algorithm(&mut self, algorithm: DnsSecAlgorithms) {
+        self.algorithm = algorithm.get_code();
+    }
+
+    pub fn get_algorithm(&self) -> Result<DnsSecAlgorithms, String> {
+        DnsSecAlgorithms::from_code(self.algorithm)
     }
 }
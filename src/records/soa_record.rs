@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use crate::messages::inter::dns_classes::DnsClasses;
 use crate::messages::inter::types::Types;
 use crate::records::inter::record_base::RecordBase;
-use crate::utils::domain_utils::{pack_domain, unpack_domain};
+use crate::utils::domain_utils::{pack_domain, to_fqdn, unpack_domain};
 
 #[derive(Clone)]
 pub struct SoaRecord {
@@ -37,27 +37,31 @@ impl Default for SoaRecord {
 
 impl RecordBase for SoaRecord {
 
-    fn from_bytes(buf: &[u8], off: usize) -> Self {
+    fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
         let mut off = off;
 
-        let dns_class = Some(DnsClasses::from_code(u16::from_be_bytes([buf[off], buf[off+1]])).unwrap());
+        let dns_class = Some(DnsClasses::from_code(u16::from_be_bytes([buf[off], buf[off+1]]))?);
         let ttl = u32::from_be_bytes([buf[off+2], buf[off+3], buf[off+4], buf[off+5]]);
 
-        let z = u16::from_be_bytes([buf[off+6], buf[off+7]]);
+        let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
 
-        let (domain, length) = unpack_domain(buf, off+8);
+        let (domain, length) = unpack_domain(buf, off+8)?;
         off += length+8;
 
-        let (mailbox, length) = unpack_domain(buf, off);
+        let (mailbox, length) = unpack_domain(buf, off)?;
         off += length;
 
+        if off+20 != data_length {
+            return Err(format!("Invalid SOA RDATA length: {}", data_length));
+        }
+
         let serial_number = u32::from_be_bytes([buf[off], buf[off+1], buf[off+2], buf[off+3]]);
         let refresh_interval = u32::from_be_bytes([buf[off+4], buf[off+5], buf[off+6], buf[off+7]]);
         let retry_interval = u32::from_be_bytes([buf[off+8], buf[off+9], buf[off+10], buf[off+11]]);
         let expire_limit = u32::from_be_bytes([buf[off+12], buf[off+13], buf[off+14], buf[off+15]]);
         let minimum_ttl = u32::from_be_bytes([buf[off+16], buf[off+17], buf[off+18], buf[off+19]]);
 
-        Self {
+        Ok(Self {
             dns_class,
             ttl,
             domain: Some(domain),
@@ -67,7 +71,7 @@ impl RecordBase for SoaRecord {
             retry_interval,
             expire_limit,
             minimum_ttl
-        }
+        })
     }
 
     fn to_bytes(&self, label_map: &mut HashMap<String, usize>, off: usize) -> Result<Vec<u8>, String> {
@@ -79,12 +83,12 @@ impl RecordBase for SoaRecord {
         buf.splice(2..4, self.dns_class.unwrap().get_code().to_be_bytes());
         buf.splice(4..8, self.ttl.to_be_bytes());
 
-        let domain = pack_domain(self.domain.as_ref().unwrap().as_str(), label_map, off+12);
+        let domain = pack_domain(self.domain.as_ref().unwrap().as_str(), label_map, off+10)?;
         buf.extend_from_slice(&domain);
 
-        off += 12+domain.len();
+        off += 10+domain.len();
 
-        let mailbox = pack_domain(self.mailbox.as_ref().unwrap().as_str(), label_map, off+12);
+        let mailbox = pack_domain(self.mailbox.as_ref().unwrap().as_str(), label_map, off)?;
         buf.extend_from_slice(&mailbox);
 
         buf.extend_from_slice(&self.serial_number.to_be_bytes());
@@ -102,6 +106,17 @@ impl RecordBase for SoaRecord {
         Types::Soa
     }
 
+    fn get_dns_class(&self) -> Result<DnsClasses, String> {
+        match self.dns_class {
+            Some(ref dns_class) => Ok(dns_class.clone()),
+            None => Err("No dns class returned".to_string())
+        }
+    }
+
+    fn get_ttl(&self) -> u32 {
+        self.ttl
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -122,8 +137,8 @@ impl RecordBase for SoaRecord {
         Box::new(self.clone())
     }
 
-    fn to_string(&self) -> String {
-        format!("[RECORD] type {:?}, class {:?}, domain {}", self.get_type(), self.dns_class.unwrap(), self.domain.as_ref().unwrap())
+    fn rdata_to_string(&self) -> String {
+        format!("{} {} {} {} {} {} {}", to_fqdn(self.domain.as_ref().unwrap()), to_fqdn(self.mailbox.as_ref().unwrap()), self.serial_number, self.refresh_interval, self.retry_interval, self.expire_limit, self.minimum_ttl)
     }
 }
 
@@ -147,21 +162,10 @@ impl SoaRecord {
         self.dns_class = Some(dns_class);
     }
 
-    pub fn get_dns_class(&self) -> Result<DnsClasses, String> {
-        match self.dns_class {
-            Some(ref dns_class) => Ok(dns_class.clone()),
-            None => Err("No dns class returned".to_string())
-        }
-    }
-
     pub fn set_ttl(&mut self, ttl: u32) {
         self.ttl = ttl;
     }
 
-    pub fn get_ttl(&self) -> u32 {
-        self.ttl
-    }
-
     pub fn set_domain(&mut self, domain: &str) {
         self.domain = Some(domain.to_string());
     }
@@ -169,4 +173,12 @@ impl SoaRecord {
     pub fn get_domain(&self) -> Option<String> {
         self.domain.clone()
     }
+
+    pub fn set_minimum_ttl(&mut self, minimum_ttl: u32) {
+        self.minimum_ttl = minimum_ttl;
+    }
+
+    pub fn get_minimum_ttl(&self) -> u32 {
+        self.minimum_ttl
+    }
 }
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use crate::messages::inter::dns_classes::DnsClasses;
 use crate::messages::inter::types::Types;
 use crate::records::inter::record_base::RecordBase;
-use crate::utils::domain_utils::{pack_domain, unpack_domain};
+use crate::utils::domain_utils::{pack_domain, to_fqdn, unpack_domain};
 
 #[derive(Clone)]
 pub struct SrvRecord {
@@ -33,21 +33,27 @@ impl Default for SrvRecord {
 
 impl RecordBase for SrvRecord {
 
-    fn from_bytes(buf: &[u8], off: usize) -> Self {
+    fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
         let dns_class = u16::from_be_bytes([buf[off], buf[off+1]]);
         let cache_flush = (dns_class & 0x8000) != 0;
-        let dns_class = Some(DnsClasses::from_code(dns_class & 0x7FFF).unwrap());
+        let dns_class = Some(DnsClasses::from_code(dns_class & 0x7FFF)?);
         let ttl = u32::from_be_bytes([buf[off+2], buf[off+3], buf[off+4], buf[off+5]]);
 
-        let z = u16::from_be_bytes([buf[off+6], buf[off+7]]);
+        let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
+        if data_length < off+15 {
+            return Err(format!("Invalid SRV RDATA length: {}", data_length-off-8));
+        }
 
         let priority = u16::from_be_bytes([buf[off+8], buf[off+9]]);
         let weight = u16::from_be_bytes([buf[off+10], buf[off+11]]);
         let port = u16::from_be_bytes([buf[off+12], buf[off+13]]);
 
-        let (target, _) = unpack_domain(buf, off+14);
+        let (target, length) = unpack_domain(buf, off+14)?;
+        if off+14+length != data_length {
+            return Err(format!("Invalid SRV RDATA length: {}", data_length-off-8));
+        }
 
-        Self {
+        Ok(Self {
             dns_class,
             cache_flush,
             ttl,
@@ -55,7 +61,7 @@ impl RecordBase for SrvRecord {
             weight,
             port,
             target: Some(target)
-        }
+        })
     }
 
     fn to_bytes(&self, label_map: &mut HashMap<String, usize>, off: usize) -> Result<Vec<u8>, String> {
@@ -75,7 +81,7 @@ impl RecordBase for SrvRecord {
         buf.splice(12..14, self.weight.to_be_bytes());
         buf.splice(14..16, self.port.to_be_bytes());
 
-        buf.extend_from_slice(&pack_domain(self.target.as_ref().unwrap().as_str(), label_map, off+16));
+        buf.extend_from_slice(&pack_domain(self.target.as_ref().unwrap().as_str(), label_map, off+16)?);
 
         buf.splice(8..10, ((buf.len()-10) as u16).to_be_bytes());
 
@@ -86,6 +92,17 @@ impl RecordBase for SrvRecord {
         Types::Srv
     }
 
+    fn get_dns_class(&self) -> Result<DnsClasses, String> {
+        match self.dns_class {
+            Some(ref dns_class) => Ok(dns_class.clone()),
+            None => Err("No dns class returned".to_string())
+        }
+    }
+
+    fn get_ttl(&self) -> u32 {
+        self.ttl
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -106,8 +123,8 @@ impl RecordBase for SrvRecord {
         Box::new(self.clone())
     }
 
-    fn to_string(&self) -> String {
-        format!("[RECORD] type {:?}, class {:?}, target {}", self.get_type(), self.dns_class.unwrap(), self.target.as_ref().unwrap())
+    fn rdata_to_string(&self) -> String {
+        format!("{} {} {} {}", self.priority, self.weight, self.port, to_fqdn(self.target.as_ref().unwrap()))
     }
 }
 
@@ -129,18 +146,39 @@ impl SrvRecord {
         self.dns_class = Some(dns_class);
     }
 
-    pub fn get_dns_class(&self) -> Result<DnsClasses, String> {
-        match self.dns_class {
-            Some(ref dns_class) => Ok(dns_class.clone()),
-            None => Err("No dns class returned".to_string())
-        }
-    }
-
     pub fn set_ttl(&mut self, ttl: u32) {
         self.ttl = ttl;
     }
 
-    pub fn get_ttl(&self) -> u32 {
-        self.ttl
+    pub fn set_priority(&mut self, priority: u16) {
+        self.priority = priority;
+    }
+
+    pub fn get_priority(&self) -> u16 {
+        self.priority
+    }
+
+    pub fn set_weight(&mut self, weight: u16) {
+        self.weight = weight;
+    }
+
+    pub fn get_weight(&self) -> u16 {
+        self.weight
+    }
+
+    pub fn set_port(&mut self, port: u16) {
+        self.port = port;
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn set_target(&mut self, target: &str) {
+        self.target = Some(target.to_string());
+    }
+
+    pub fn get_target(&self) -> Option<String> {
+        self.target.clone()
     }
 }
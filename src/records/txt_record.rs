@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use crate::messages::inter::dns_classes::DnsClasses;
 use crate::messages::inter::types::Types;
 use crate::records::inter::record_base::RecordBase;
+use crate::utils::string_utils::quote_string;
 
 #[derive(Clone)]
 pub struct TxtRecord {
@@ -26,12 +27,12 @@ impl Default for TxtRecord {
 
 impl RecordBase for TxtRecord {
 
-    fn from_bytes(buf: &[u8], off: usize) -> Self {
+    fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
         let mut off = off;
 
         let dns_class = u16::from_be_bytes([buf[off], buf[off+1]]);
         let cache_flush = (dns_class & 0x8000) != 0;
-        let dns_class = Some(DnsClasses::from_code(dns_class & 0x7FFF).unwrap());
+        let dns_class = Some(DnsClasses::from_code(dns_class & 0x7FFF)?);
         let ttl = u32::from_be_bytes([buf[off+2], buf[off+3], buf[off+4], buf[off+5]]);
 
         let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
@@ -41,17 +42,22 @@ impl RecordBase for TxtRecord {
 
         while off < data_length {
             let length = buf[off] as usize;
-            let record = String::from_utf8(buf[off + 1..off + 1 + length].to_vec()).unwrap();
-            records.push(record);
+
+            if off+1+length > data_length {
+                return Err("TXT string runs past RDATA".to_string());
+            }
+
+            //TXT IS ANY BYTES, WHATEVER ISN'T UTF-8 BECOMES U+FFFD INSTEAD OF FAILING THE MESSAGE
+            records.push(String::from_utf8_lossy(&buf[off + 1..off + 1 + length]).into_owned());
             off += length+1;
         }
 
-        Self {
+        Ok(Self {
             dns_class,
             cache_flush,
             ttl,
             records
-        }
+        })
     }
 
     fn to_bytes(&self, label_map: &mut HashMap<String, usize>, off: usize) -> Result<Vec<u8>, String> {
@@ -81,6 +87,17 @@ impl RecordBase for TxtRecord {
         Types::Txt
     }
 
+    fn get_dns_class(&self) -> Result<DnsClasses, String> {
+        match self.dns_class {
+            Some(ref dns_class) => Ok(dns_class.clone()),
+            None => Err("No dns class returned".to_string())
+        }
+    }
+
+    fn get_ttl(&self) -> u32 {
+        self.ttl
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -101,8 +118,9 @@ impl RecordBase for TxtRecord {
         Box::new(self.clone())
     }
 
-    fn to_string(&self) -> String {
-        format!("[RECORD] type {:?}, class {:?}", self.get_type(), self.dns_class.unwrap())
+    fn rdata_to_string(&self) -> String {
+        let records: Vec<String> = self.records.iter().map(|record| quote_string(record.as_bytes())).collect();
+        records.join(" ")
     }
 }
 
@@ -121,18 +139,7 @@ impl TxtRecord {
         self.dns_class = Some(dns_class);
     }
 
-    pub fn get_dns_class(&self) -> Result<DnsClasses, String> {
-        match self.dns_class {
-            Some(ref dns_class) => Ok(dns_class.clone()),
-            None => Err("No dns class returned".to_string())
-        }
-    }
-
     pub fn set_ttl(&mut self, ttl: u32) {
         self.ttl = ttl;
     }
-
-    pub fn get_ttl(&self) -> u32 {
-        self.ttl
-    }
 }
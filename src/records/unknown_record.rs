@@ -0,0 +1,186 @@
+use std::any::Any;
+use std::collections::HashMap;
+use crate::messages::inter::dns_classes::DnsClasses;
+use crate::messages::inter::types::Types;
+use crate::records::inter::record_base::RecordBase;
+use crate::utils::hex;
+
+//RFC 3597 - ANY TYPE WE CAN'T DECODE IS CARRIED AS OPAQUE RDATA
+#[derive(Clone)]
+pub struct UnknownRecord {
+    _type: u16,
+    dns_class: Option<DnsClasses>,
+    cache_flush: bool,
+    ttl: u32,
+    data: Vec<u8>
+}
+
+impl Default for UnknownRecord {
+
+    fn default() -> Self {
+        Self {
+            _type: 0,
+            dns_class: None,
+            cache_flush: false,
+            ttl: 0,
+            data: Vec::new()
+        }
+    }
+}
+
+impl RecordBase for UnknownRecord {
+
+    fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
+        //THE TYPE SITS RIGHT BEFORE THE CLASS
+        let _type = u16::from_be_bytes([buf[off-2], buf[off-1]]);
+        let dns_class = u16::from_be_bytes([buf[off], buf[off+1]]);
+        let cache_flush = (dns_class & 0x8000) != 0;
+        let dns_class = Some(DnsClasses::from_code(dns_class & 0x7FFF)?);
+        let ttl = u32::from_be_bytes([buf[off+2], buf[off+3], buf[off+4], buf[off+5]]);
+
+        let data_length = off+8+u16::from_be_bytes([buf[off+6], buf[off+7]]) as usize;
+        let data = buf[off+8..data_length].to_vec();
+
+        Ok(Self {
+            _type,
+            dns_class,
+            cache_flush,
+            ttl,
+            data
+        })
+    }
+
+    fn to_bytes(&self, _label_map: &mut HashMap<String, usize>, _off: usize) -> Result<Vec<u8>, String> {
+        let mut buf = vec![0u8; 10];
+
+        buf.splice(0..2, self._type.to_be_bytes());
+
+        let mut dns_class = self.dns_class.unwrap().get_code();
+        if self.cache_flush {
+            dns_class |= 0x8000;
+        }
+
+        buf.splice(2..4, dns_class.to_be_bytes());
+        buf.splice(4..8, self.ttl.to_be_bytes());
+
+        buf.extend_from_slice(&self.data);
+
+        buf.splice(8..10, ((buf.len()-10) as u16).to_be_bytes());
+
+        Ok(buf)
+    }
+
+    fn get_type(&self) -> Types {
+        Types::from_code(self._type).unwrap_or(Types::Unknown(self._type))
+    }
+
+    fn get_dns_class(&self) -> Result<DnsClasses, String> {
+        match self.dns_class {
+            Some(ref dns_class) => Ok(dns_class.clone()),
+            None => Err("No dns class returned".to_string())
+        }
+    }
+
+    fn get_ttl(&self) -> u32 {
+        self.ttl
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn upcast(&self) -> &dyn RecordBase {
+        self
+    }
+
+    fn upcast_mut(&mut self) -> &mut dyn RecordBase {
+        self
+    }
+
+    fn dyn_clone(&self) -> Box<dyn RecordBase> {
+        Box::new(self.clone())
+    }
+
+    fn rdata_to_string(&self) -> String {
+        let mut record = format!("\\# {}", self.data.len());
+
+        if !self.data.is_empty() {
+            record.push(' ');
+            record.push_str(&hex::encode(&self.data));
+        }
+
+        record
+    }
+}
+
+impl UnknownRecord {
+
+    pub fn new(dns_classes: DnsClasses, ttl: u32, _type: u16, data: &[u8]) -> Self {
+        Self {
+            _type,
+            dns_class: Some(dns_classes),
+            cache_flush: false,
+            ttl,
+            data: data.to_vec()
+        }
+    }
+
+    pub fn set_dns_class(&mut self, dns_class: DnsClasses) {
+        self.dns_class = Some(dns_class);
+    }
+
+    pub fn set_ttl(&mut self, ttl: u32) {
+        self.ttl = ttl;
+    }
+
+    pub fn set_data(&mut self, data: &[u8]) {
+        self.data = data.to_vec();
+    }
+
+    pub fn get_data(&self) -> &Vec<u8> {
+        &self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashMap;
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::types::Types;
+    use crate::messages::message_base::MessageBase;
+    use crate::records::inter::record_base::RecordBase;
+    use crate::records::unknown_record::UnknownRecord;
+
+    #[test]
+    fn wire_round_trip() {
+        let record = UnknownRecord::new(DnsClasses::In, 300, 65280, &[0xde, 0xad, 0xbe, 0xef]);
+        let buf = record.to_bytes(&mut HashMap::new(), 0).unwrap();
+        assert_eq!(buf, [0xff, 0x00, 0, 1, 0, 0, 1, 0x2c, 0, 4, 0xde, 0xad, 0xbe, 0xef]);
+
+        let decoded = MessageBase::record_from_bytes(&buf, 0).unwrap();
+        assert_eq!(decoded.get_type(), Types::Unknown(65280));
+        assert_eq!(decoded.rdata_to_string(), "\\# 4 deadbeef");
+        assert_eq!(decoded.to_string(), "300 IN TYPE65280 \\# 4 deadbeef");
+    }
+
+    #[test]
+    fn empty_rdata() {
+        let record = UnknownRecord::new(DnsClasses::In, 0, 65280, &[]);
+        assert_eq!(record.rdata_to_string(), "\\# 0");
+    }
+
+    //MDNS RESPONSES SET THE TOP CLASS BIT ON RECORDS THAT REPLACE WHAT A CACHE HOLDS
+    #[test]
+    fn cache_flush_bit_is_kept_off_the_class() {
+        let buf = [0xff, 0x00, 0x80, 1, 0, 0, 0, 120, 0, 1, 7];
+
+        let record = MessageBase::record_from_bytes(&buf, 0).unwrap();
+        assert_eq!(record.get_dns_class().unwrap(), DnsClasses::In);
+        assert_eq!(record.to_bytes(&mut HashMap::new(), 0).unwrap(), buf);
+    }
+}
@@ -0,0 +1,225 @@
+use std::cmp::Ordering;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+use crate::records::a_record::ARecord;
+use crate::records::aaaa_record::AAAARecord;
+use crate::records::inter::record_base::RecordBase;
+use crate::utils::ordered_map::OrderedMap;
+
+//RFC 6724 2.1 - THE DEFAULT POLICY TABLE, PREFIX, LENGTH, PRECEDENCE, LABEL - LONGEST MATCHING PREFIX WINS
+const POLICY_TABLE: [(Ipv6Addr, u8, u8, u8); 9] = [
+    (Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1), 128, 50, 0),
+    (Ipv6Addr::new(0, 0, 0, 0, 0, 0xffff, 0, 0), 96, 35, 4),
+    (Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0), 96, 1, 3),
+    (Ipv6Addr::new(0x2001, 0, 0, 0, 0, 0, 0, 0), 32, 5, 5),
+    (Ipv6Addr::new(0x2002, 0, 0, 0, 0, 0, 0, 0), 16, 30, 2),
+    (Ipv6Addr::new(0x3ffe, 0, 0, 0, 0, 0, 0, 0), 16, 1, 12),
+    (Ipv6Addr::new(0xfec0, 0, 0, 0, 0, 0, 0, 0), 10, 1, 11),
+    (Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 0), 7, 3, 13),
+    (Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0), 0, 40, 1)
+];
+
+//RFC 4291 2.7 - THE SCOPES THAT MATTER HERE, BIGGER IS WIDER
+const SCOPE_LINK_LOCAL: u8 = 0x2;
+const SCOPE_SITE_LOCAL: u8 = 0x5;
+const SCOPE_GLOBAL: u8 = 0xe;
+
+//RFC 6724 6 - ORDERS addresses THE WAY A CONNECTING CLIENT SHOULD TRY THEM, THE SOURCE FOR EACH IS WHAT THE ROUTING TABLE PICKS
+//CONNECTING A UDP SOCKET SENDS NOTHING, IT ONLY ASKS THE KERNEL WHICH LOCAL ADDRESS WOULD BE USED
+pub fn sort_addresses(addresses: &mut [IpAddr]) {
+    sort_addresses_with(addresses, source_for);
+}
+
+//THE SAME RULES WITH THE SOURCE SELECTION HANDED IN, None IS A DESTINATION THIS HOST CAN'T REACH AT ALL
+pub fn sort_addresses_with<F: Fn(&IpAddr) -> Option<IpAddr>>(addresses: &mut [IpAddr], source_for: F) {
+    let mut candidates: Vec<(IpAddr, Option<IpAddr>)> = addresses.iter().map(|address| (*address, source_for(address))).collect();
+
+    //STABLE, RULE 10 - WHEN NOTHING ELSE DECIDES, THE ORDER THE ANSWER CAME IN STAYS
+    candidates.sort_by(compare);
+
+    for (address, (sorted, _)) in addresses.iter_mut().zip(candidates) {
+        *address = sorted;
+    }
+}
+
+//THE ADDRESS OF AN A OR AAAA RECORD, None FOR ANY OTHER TYPE
+pub fn get_address(record: &dyn RecordBase) -> Option<IpAddr> {
+    match record.as_any().downcast_ref::<ARecord>() {
+        Some(record) => record.get_address(),
+        None => record.as_any().downcast_ref::<AAAARecord>()?.get_address()
+    }
+}
+
+//A AND AAAA OWNED BY host ITSELF - RFC 2181 10.3, AN MX OR SRV TARGET MUST NOT BE AN ALIAS SO A CNAME IS NEVER FOLLOWED
+pub fn get_owned_addresses(records: &OrderedMap<String, Vec<Box<dyn RecordBase>>>, host: &str) -> Vec<IpAddr> {
+    records.iter()
+        .filter(|(owner, _)| owner.trim_end_matches('.').eq_ignore_ascii_case(host))
+        .flat_map(|(_, records)| records.iter())
+        .filter_map(|record| get_address(record.as_ref()))
+        .collect()
+}
+
+fn source_for(destination: &IpAddr) -> Option<IpAddr> {
+    let socket = match destination {
+        IpAddr::V4(_) => UdpSocket::bind(SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0))),
+        IpAddr::V6(_) => UdpSocket::bind(SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)))
+    }.ok()?;
+
+    socket.connect(SocketAddr::new(*destination, 53)).ok()?;
+    Some(socket.local_addr().ok()?.ip())
+}
+
+//LESS IS BETTER, RULES 3, 4 AND 7 NEED STATE THE KERNEL DOESN'T SHARE SO THEY'RE SKIPPED
+fn compare((da, sa): &(IpAddr, Option<IpAddr>), (db, sb): &(IpAddr, Option<IpAddr>)) -> Ordering {
+    let (da, db) = (to_v6(da), to_v6(db));
+
+    //RULE 1 - AVOID UNUSABLE DESTINATIONS
+    let (sa, sb) = match (sa, sb) {
+        (Some(sa), Some(sb)) => (to_v6(sa), to_v6(sb)),
+        (Some(_), None) => return Ordering::Less,
+        (None, Some(_)) => return Ordering::Greater,
+        (None, None) => return Ordering::Equal
+    };
+
+    //RULE 2 - PREFER MATCHING SCOPE
+    let a_scope = scope(&da) == scope(&sa);
+    let b_scope = scope(&db) == scope(&sb);
+    if a_scope != b_scope {
+        return b_scope.cmp(&a_scope);
+    }
+
+    let (da_precedence, da_label) = policy(&da);
+    let (db_precedence, db_label) = policy(&db);
+
+    //RULE 5 - PREFER MATCHING LABEL
+    let a_label = da_label == policy(&sa).1;
+    let b_label = db_label == policy(&sb).1;
+    if a_label != b_label {
+        return b_label.cmp(&a_label);
+    }
+
+    //RULE 6 - PREFER HIGHER PRECEDENCE
+    if da_precedence != db_precedence {
+        return db_precedence.cmp(&da_precedence);
+    }
+
+    //RULE 8 - PREFER SMALLER SCOPE
+    if scope(&da) != scope(&db) {
+        return scope(&da).cmp(&scope(&db));
+    }
+
+    //RULE 9 - USE LONGEST MATCHING PREFIX, IPV6 ONLY LIKE GLIBC, FOR IPV4 IT DEFEATS ROUND ROBIN
+    if da.to_ipv4_mapped().is_none() && db.to_ipv4_mapped().is_none() {
+        return common_prefix(&db, &sb).cmp(&common_prefix(&da, &sa));
+    }
+
+    Ordering::Equal
+}
+
+//RFC 6724 2.1 - IPV4 GOES THROUGH THE TABLE AS ITS MAPPED FORM
+fn to_v6(address: &IpAddr) -> Ipv6Addr {
+    match address {
+        IpAddr::V4(v4) => v4.to_ipv6_mapped(),
+        IpAddr::V6(v6) => *v6
+    }
+}
+
+fn policy(address: &Ipv6Addr) -> (u8, u8) {
+    POLICY_TABLE.iter()
+        .find(|(prefix, length, _, _)| common_prefix(address, prefix) >= *length as u32)
+        .map(|(_, _, precedence, label)| (*precedence, *label))
+        .unwrap_or((40, 1))
+}
+
+//RFC 6724 3.2 - IPV4 LOOPBACK AND AUTOCONFIGURED ADDRESSES ARE LINK LOCAL, EVERY OTHER IPV4 ADDRESS IS GLOBAL, PRIVATE ONES INCLUDED
+fn scope(address: &Ipv6Addr) -> u8 {
+    if let Some(v4) = address.to_ipv4_mapped() {
+        return match v4.is_loopback() || v4.is_link_local() {
+            true => SCOPE_LINK_LOCAL,
+            false => SCOPE_GLOBAL
+        };
+    }
+
+    let segments = address.segments();
+
+    if address.is_multicast() {
+        return (segments[0] & 0x000f) as u8;
+    }
+
+    if address.is_loopback() || segments[0] & 0xffc0 == 0xfe80 {
+        return SCOPE_LINK_LOCAL;
+    }
+
+    if segments[0] & 0xffc0 == 0xfec0 {
+        return SCOPE_SITE_LOCAL;
+    }
+
+    SCOPE_GLOBAL
+}
+
+fn common_prefix(a: &Ipv6Addr, b: &Ipv6Addr) -> u32 {
+    (a.to_bits() ^ b.to_bits()).leading_zeros()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::net::IpAddr;
+    use crate::resolver::address_selection::sort_addresses_with;
+
+    //NAME, DESTINATIONS WITH THE SOURCE EACH WOULD USE, THE ORDER THEY SHOULD END UP IN
+    type Case = (&'static str, &'static [(&'static str, &'static str)], &'static [&'static str]);
+
+    //"" IS A DESTINATION WITH NO SOURCE, ONE THIS HOST CAN'T REACH
+    fn sorted(candidates: &[(&str, &str)]) -> Vec<String> {
+        let sources: Vec<(IpAddr, Option<IpAddr>)> = candidates.iter()
+            .map(|(destination, source)| (destination.parse().unwrap(), source.parse().ok()))
+            .collect();
+
+        let mut addresses: Vec<IpAddr> = sources.iter().map(|(destination, _)| *destination).collect();
+        sort_addresses_with(&mut addresses, |destination| sources.iter().find(|(d, _)| d == destination).and_then(|(_, source)| *source));
+        addresses.iter().map(IpAddr::to_string).collect()
+    }
+
+    //RFC 6724 10.2 AND A FEW OF OUR OWN
+    #[test]
+    fn destination_address_selection() {
+        let cases: [Case; 9] = [
+            ("rule 1, unreachable last",
+                &[("2001:db8:1::1", ""), ("198.51.100.121", "198.51.100.117")],
+                &["198.51.100.121", "2001:db8:1::1"]),
+            ("rule 2, matching scope",
+                &[("2001:db8:1::1", "fe80::1"), ("198.51.100.121", "198.51.100.117")],
+                &["198.51.100.121", "2001:db8:1::1"]),
+            ("rule 5, matching label",
+                &[("2001:db8:1::1", "2002:c633:6401::2"), ("2002:c633:6401::1", "2002:c633:6401::2")],
+                &["2002:c633:6401::1", "2001:db8:1::1"]),
+            ("rule 6, ::1 before 127.0.0.1",
+                &[("127.0.0.1", "127.0.0.1"), ("::1", "::1")],
+                &["::1", "127.0.0.1"]),
+            ("rule 6, native IPv6 before 6to4",
+                &[("2002:c633:6401::1", "2002:c633:6401::2"), ("2001:db8:1::1", "2001:db8:1::2")],
+                &["2001:db8:1::1", "2002:c633:6401::1"]),
+            ("rule 6, global before ULA",
+                &[("fd11::1", "fd11::2"), ("2001:db8::1", "2001:db8::2")],
+                &["2001:db8::1", "fd11::1"]),
+            ("rule 8, smaller scope",
+                &[("2001:db8:1::1", "2001:db8:1::2"), ("fe80::1", "fe80::2")],
+                &["fe80::1", "2001:db8:1::1"]),
+            ("rule 9, longest matching prefix",
+                &[("2001:db8:3ffe::1", "2001:db8:3f44::2"), ("2001:db8:1::1", "2001:db8:1::2")],
+                &["2001:db8:1::1", "2001:db8:3ffe::1"]),
+            ("rule 10, IPv4 keeps the order it came in",
+                &[("192.0.2.200", "192.0.2.1"), ("192.0.2.2", "192.0.2.1")],
+                &["192.0.2.200", "192.0.2.2"])
+        ];
+
+        for (name, candidates, expected) in cases {
+            assert_eq!(sorted(candidates), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn sort_is_stable_when_nothing_is_reachable() {
+        assert_eq!(sorted(&[("2001:db8::1", ""), ("192.0.2.1", ""), ("::1", "")]), ["2001:db8::1", "192.0.2.1", "::1"]);
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use crate::client::resolve_error::ResolveError;
+use crate::messages::inter::dns_classes::DnsClasses;
+use crate::messages::inter::types::Types;
+use crate::messages::message_base::MessageBase;
+use crate::records::inter::record_base::RecordBase;
+
+const DEFAULT_MAX_ENTRIES: usize = 4096;
+
+//RFC 8767 4 - NOTHING IS TRUSTED FOR LONGER THAN A WEEK, WHATEVER THE TTL SAYS
+const DEFAULT_MAX_TTL: u32 = 604800;
+
+//RFC 2308 5 - A NAME THAT DIDN'T EXIST MAY SOON, NEGATIVE ANSWERS ARE KEPT 3 HOURS AT MOST
+const MAX_NEGATIVE_TTL: u32 = 10800;
+
+struct Entry {
+    //EACH RECORD AS IT SITS IN A MESSAGE, TYPE THROUGH RDATA, SO THE TTL CAN BE REWRITTEN IN PLACE ON THE WAY OUT
+    records: Vec<Vec<u8>>,
+    //NO RECORDS AND NOT NXDOMAIN IS NODATA, THE NAME EXISTS BUT NOT WITH THIS TYPE
+    nx_domain: bool,
+    expires: Instant
+}
+
+pub struct Cache {
+    entries: HashMap<(String, Types, DnsClasses), Entry>,
+    max_entries: usize,
+    max_ttl: u32
+}
+
+impl Default for Cache {
+
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            max_entries: DEFAULT_MAX_ENTRIES,
+            max_ttl: DEFAULT_MAX_TTL
+        }
+    }
+}
+
+impl Cache {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    //ONCE FULL, EXPIRED ENTRIES GO FIRST, THEN WHATEVER WAS ABOUT TO EXPIRE ANYWAY
+    pub fn set_max_entries(&mut self, max_entries: usize) {
+        self.max_entries = max_entries;
+    }
+
+    pub fn get_max_entries(&self) -> usize {
+        self.max_entries
+    }
+
+    pub fn set_max_ttl(&mut self, max_ttl: u32) {
+        self.max_ttl = max_ttl;
+    }
+
+    pub fn get_max_ttl(&self) -> u32 {
+        self.max_ttl
+    }
+
+    //RFC 2181 5.2 - THE WHOLE RRSET GOES IN AT ONCE AND LIVES AS LONG AS ITS SHORTEST TTL, A TTL OF 0 IS NEVER CACHED
+    pub fn insert(&mut self, name: &str, _type: Types, dns_class: DnsClasses, records: &[Box<dyn RecordBase>], now: Instant) {
+        let mut encoded = Vec::with_capacity(records.len());
+
+        for record in records {
+            match record.to_bytes(&mut HashMap::new(), 0) {
+                Ok(buf) if buf.len() >= 10 => encoded.push(buf),
+                _ => return
+            }
+        }
+
+        let ttl = match encoded.iter().map(|buf| u32::from_be_bytes([buf[4], buf[5], buf[6], buf[7]])).min() {
+            Some(ttl) => ttl.min(self.max_ttl),
+            None => return
+        };
+
+        self.put(name, _type, dns_class, Entry {
+            records: encoded,
+            nx_domain: false,
+            expires: now+Duration::from_secs(ttl as u64)
+        }, ttl, now);
+    }
+
+    //RFC 2308 5 - ttl IS WHAT THE SOA IN THE AUTHORITY SECTION ALLOWS, THE LOWER OF ITS OWN TTL AND ITS MINIMUM
+    pub fn insert_negative(&mut self, name: &str, _type: Types, dns_class: DnsClasses, nx_domain: bool, ttl: u32, now: Instant) {
+        let ttl = ttl.min(self.max_ttl).min(MAX_NEGATIVE_TTL);
+
+        self.put(name, _type, dns_class, Entry {
+            records: Vec::new(),
+            nx_domain,
+            expires: now+Duration::from_secs(ttl as u64)
+        }, ttl, now);
+    }
+
+    //RECORDS COME BACK WITH WHAT'S LEFT OF THEIR TTL, A CACHED NXDOMAIN COMES BACK AS THE ERROR IT WAS
+    pub fn get(&mut self, name: &str, _type: Types, dns_class: DnsClasses, now: Instant) -> Option<Result<Vec<Box<dyn RecordBase>>, ResolveError>> {
+        let key = key(name, _type, dns_class);
+        let entry = self.entries.get(&key)?;
+
+        if entry.expires <= now {
+            self.entries.remove(&key);
+            return None;
+        }
+
+        if entry.nx_domain {
+            return Some(Err(ResolveError::NxDomain));
+        }
+
+        let remaining = entry.expires.duration_since(now).as_secs() as u32;
+        let mut records = Vec::with_capacity(entry.records.len());
+
+        for buf in &entry.records {
+            let mut buf = buf.clone();
+            buf.splice(4..8, remaining.to_be_bytes());
+
+            match MessageBase::record_from_bytes(&buf, 0) {
+                Ok(record) => records.push(record),
+                Err(_) => {
+                    self.entries.remove(&key);
+                    return None;
+                }
+            }
+        }
+
+        Some(Ok(records))
+    }
+
+    pub fn remove(&mut self, name: &str, _type: Types, dns_class: DnsClasses) {
+        self.entries.remove(&key(name, _type, dns_class));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn put(&mut self, name: &str, _type: Types, dns_class: DnsClasses, entry: Entry, ttl: u32, now: Instant) {
+        let key = key(name, _type, dns_class);
+
+        if ttl == 0 || self.max_entries == 0 {
+            self.entries.remove(&key);
+            return;
+        }
+
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.max_entries {
+            self.entries.retain(|_, entry| entry.expires > now);
+
+            if self.entries.len() >= self.max_entries {
+                let soonest = self.entries.iter()
+                    .min_by_key(|(_, entry)| entry.expires)
+                    .map(|(key, _)| key.clone());
+
+                if let Some(soonest) = soonest {
+                    self.entries.remove(&soonest);
+                }
+            }
+        }
+
+        self.entries.insert(key, entry);
+    }
+}
+
+//RFC 4343 - NAMES COMPARE CASE INSENSITIVE, AND WITH OR WITHOUT THE TRAILING DOT THEY'RE THE SAME NAME
+fn key(name: &str, _type: Types, dns_class: DnsClasses) -> (String, Types, DnsClasses) {
+    (name.trim_end_matches('.').to_ascii_lowercase(), _type, dns_class)
+}
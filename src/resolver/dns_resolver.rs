@@ -0,0 +1,812 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use crate::client::resolve_error::ResolveError;
+use crate::messages::inter::transports::Transports;
+use crate::messages::inter::types::Types;
+use crate::messages::message_base::MessageBase;
+use crate::records::cname_record::CNameRecord;
+use crate::records::inter::record_base::RecordBase;
+use crate::records::opt_record::OptRecord;
+use crate::records::soa_record::SoaRecord;
+use crate::resolver::cache::Cache;
+use crate::resolver::inter::resolver_events::ResolverEvents;
+use crate::utils::dns_query::DnsQuery;
+use crate::utils::ordered_map::OrderedMap;
+use crate::utils::random;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_RETRIES: u32 = 2;
+
+//DNS FLAG DAY 2020 - BIG ENOUGH FOR MOST ANSWERS, SMALL ENOUGH TO NOT FRAGMENT
+const DEFAULT_PAYLOAD_SIZE: u16 = 1232;
+
+//A LONGER CHAIN THAN THIS IS ALMOST ALWAYS A LOOP SOMEBODY MISSED
+const DEFAULT_MAX_CNAME_DEPTH: usize = 8;
+
+pub type Chain = Vec<(String, Box<dyn RecordBase>)>;
+
+struct Lookup {
+    query: DnsQuery,
+    //WHERE THE CNAMES FOLLOWED SO FAR LEAD, WHAT'S ACTUALLY BEING ASKED FOR
+    name: String,
+    chain: Chain,
+    request: MessageBase,
+    server: SocketAddr,
+    transport: Transports,
+    //WHICH SERVER THE FIRST ATTEMPT GOES TO, ALWAYS THE FIRST ONE UNLESS rotate IS SET
+    offset: usize,
+    attempt: u32,
+    deadline: Instant,
+    //WHAT THE CALLER GETS IF THIS WAS THE LAST ATTEMPT
+    error: ResolveError
+}
+
+//NO SOCKETS AND NO CLOCK - THE CALLER SENDS WHAT COMES OUT OF poll_event, FEEDS BACK WHATEVER ARRIVES WITH receive
+//AND CALLS tick ONCE poll_timeout HAS PASSED, SO THE SAME INPUTS ALWAYS GIVE THE SAME EVENTS
+pub struct Resolver {
+    servers: Vec<SocketAddr>,
+    timeout: Duration,
+    retries: u32,
+    payload_size: Option<u16>,
+    tcp_only: bool,
+    rotate: bool,
+    max_cname_depth: usize,
+    cache: Cache,
+    lookups: BTreeMap<usize, Lookup>,
+    next_lookup: usize,
+    events: VecDeque<ResolverEvents>
+}
+
+impl Resolver {
+
+    pub fn new(server: SocketAddr) -> Self {
+        Self {
+            servers: vec![server],
+            timeout: DEFAULT_TIMEOUT,
+            retries: DEFAULT_RETRIES,
+            payload_size: Some(DEFAULT_PAYLOAD_SIZE),
+            tcp_only: false,
+            rotate: false,
+            max_cname_depth: DEFAULT_MAX_CNAME_DEPTH,
+            cache: Cache::new(),
+            lookups: BTreeMap::new(),
+            next_lookup: 0,
+            events: VecDeque::new()
+        }
+    }
+
+    //RETRIES GO ROUND THE SERVERS IN THE ORDER THEY WERE ADDED
+    pub fn add_server(&mut self, server: SocketAddr) {
+        self.servers.push(server);
+    }
+
+    pub fn get_servers(&self) -> Vec<SocketAddr> {
+        self.servers.clone()
+    }
+
+    //PER ATTEMPT, EVERY CNAME THAT HAS TO BE ASKED FOR SEPARATELY STARTS ITS OWN ATTEMPTS
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn set_retries(&mut self, retries: u32) {
+        self.retries = retries;
+    }
+
+    pub fn get_retries(&self) -> u32 {
+        self.retries
+    }
+
+    //None SENDS QUERIES WITHOUT AN OPT RECORD
+    pub fn set_payload_size(&mut self, payload_size: Option<u16>) {
+        self.payload_size = payload_size;
+    }
+
+    pub fn get_payload_size(&self) -> Option<u16> {
+        self.payload_size
+    }
+
+    //SKIP UDP ENTIRELY, OTHERWISE TCP IS ONLY USED WHEN A UDP ANSWER COMES BACK TRUNCATED
+    pub fn set_tcp_only(&mut self, tcp_only: bool) {
+        self.tcp_only = tcp_only;
+    }
+
+    pub fn is_tcp_only(&self) -> bool {
+        self.tcp_only
+    }
+
+    //EACH LOOKUP STARTS ONE SERVER ALONG FROM THE LAST, SO THE LOAD IS SPREAD INSTEAD OF ALWAYS HITTING THE FIRST
+    pub fn set_rotate(&mut self, rotate: bool) {
+        self.rotate = rotate;
+    }
+
+    pub fn is_rotate(&self) -> bool {
+        self.rotate
+    }
+
+    pub fn set_max_cname_depth(&mut self, max_cname_depth: usize) {
+        self.max_cname_depth = max_cname_depth;
+    }
+
+    pub fn get_max_cname_depth(&self) -> usize {
+        self.max_cname_depth
+    }
+
+    pub fn get_cache(&self) -> &Cache {
+        &self.cache
+    }
+
+    pub fn get_cache_mut(&mut self) -> &mut Cache {
+        &mut self.cache
+    }
+
+    //NOTHING GOES OUT HERE, THE LOOKUP'S FIRST QUERY - OR ITS ANSWER, IF THE CACHE HAD IT - WAITS IN poll_event
+    pub fn resolve(&mut self, query: DnsQuery, now: Instant) -> usize {
+        let lookup = self.next_lookup;
+        self.next_lookup = self.next_lookup.wrapping_add(1);
+
+        let name = query.get_query().unwrap_or_default();
+        self.advance(lookup, query, name, Vec::new(), now);
+        lookup
+    }
+
+    //ANYTHING THAT ISN'T THE ANSWER TO A QUESTION IN FLIGHT, FROM THE SERVER IT WAS ASKED OF, OVER THE TRANSPORT IT WAS ASKED OVER, IS DROPPED
+    //OVER TCP buf IS ONE MESSAGE WITH ITS LENGTH PREFIX ALREADY TAKEN OFF
+    pub fn receive(&mut self, buf: &[u8], source: SocketAddr, transport: Transports, now: Instant) {
+        if buf.len() < 2 {
+            return;
+        }
+
+        let id = u16::from_be_bytes([buf[0], buf[1]]);
+
+        let Some((&lookup, state)) = self.lookups.iter_mut().find(|(_, state)| {
+            state.request.get_id() == id && state.server == source && state.transport == transport
+        }) else {
+            return;
+        };
+
+        let mut response = match MessageBase::from_bytes(buf, 0) {
+            Ok(response) => response,
+            //KEEP WAITING, A BROKEN PACKET WITH OUR ID MIGHT NOT BE THE SERVER'S
+            Err(e) => {
+                state.error = ResolveError::Malformed(e);
+                return;
+            }
+        };
+
+        if !response.is_response_to(&state.request) {
+            return;
+        }
+
+        response.set_origin(source);
+
+        if let Some(state) = self.lookups.remove(&lookup) {
+            self.handle(lookup, state, response, now);
+        }
+    }
+
+    //EVERY ATTEMPT WHOSE TIMEOUT HAS PASSED MOVES ON TO THE NEXT SERVER, OR GIVES UP
+    pub fn tick(&mut self, now: Instant) {
+        let expired: Vec<usize> = self.lookups.iter()
+            .filter(|(_, state)| state.deadline <= now)
+            .map(|(lookup, _)| *lookup)
+            .collect();
+
+        for lookup in expired {
+            if let Some(state) = self.lookups.remove(&lookup) {
+                self.retry(lookup, state, now);
+            }
+        }
+    }
+
+    //WHEN tick NEXT HAS SOMETHING TO DO, None WHILE NOTHING IS IN FLIGHT
+    pub fn poll_timeout(&self) -> Option<Instant> {
+        self.lookups.values().map(|state| state.deadline).min()
+    }
+
+    pub fn poll_event(&mut self) -> Option<ResolverEvents> {
+        self.events.pop_front()
+    }
+
+    //THE LOOKUP IS DROPPED WITHOUT AN ANSWER EVENT, A LATE RESPONSE FOR IT IS IGNORED LIKE ANY OTHER STRAY
+    pub fn cancel(&mut self, lookup: usize) {
+        self.lookups.remove(&lookup);
+        self.events.retain(|event| match event {
+            ResolverEvents::Send { lookup: l, .. } | ResolverEvents::Answer { lookup: l, .. } => *l != lookup
+        });
+    }
+
+    pub fn is_pending(&self, lookup: usize) -> bool {
+        self.lookups.contains_key(&lookup)
+    }
+
+    //ANSWER FROM THE CACHE AS FAR AS IT GOES, FOLLOWING CACHED CNAMES, AND ASK FOR WHATEVER IT DOESN'T HAVE
+    fn advance(&mut self, lookup: usize, query: DnsQuery, mut name: String, mut chain: Chain, now: Instant) {
+        let _type = query.get_type();
+        let dns_class = query.get_dns_class();
+
+        loop {
+            match self.cache.get(&name, _type, dns_class, now) {
+                Some(Ok(records)) => {
+                    let response = answer(&query, chain, &name, records, None);
+                    return self.finish(lookup, Ok(response));
+                }
+                Some(Err(e)) => return self.finish(lookup, Err(e)),
+                None => {}
+            }
+
+            if _type == Types::Cname {
+                break;
+            }
+
+            let Some(Ok(cnames)) = self.cache.get(&name, Types::Cname, dns_class, now) else {
+                break;
+            };
+
+            let Some(target) = cname_target(&cnames) else {
+                break;
+            };
+
+            if let Err(e) = self.follow(&mut chain, &name, &cnames, &target) {
+                return self.finish(lookup, Err(e));
+            }
+
+            name = target;
+        }
+
+        let state = Lookup {
+            request: self.new_request(&name, &query),
+            query,
+            name,
+            chain,
+            server: self.servers[0],
+            transport: if self.tcp_only { Transports::Tcp } else { Transports::Udp },
+            offset: if self.rotate { lookup % self.servers.len() } else { 0 },
+            attempt: 0,
+            deadline: now,
+            error: ResolveError::Timeout
+        };
+
+        self.transmit(lookup, state, now);
+    }
+
+    fn handle(&mut self, lookup: usize, mut state: Lookup, response: MessageBase, now: Instant) {
+        //RFC 7766 5 - THE WHOLE ANSWER DIDN'T FIT, ASK THE SAME SERVER AGAIN OVER TCP
+        if response.is_truncated() && state.transport == Transports::Udp {
+            state.transport = Transports::Tcp;
+            return self.transmit(lookup, state, now);
+        }
+
+        let nx_domain = match ResolveError::from_response_code(response.get_response_code()) {
+            None => false,
+            Some(ResolveError::NxDomain) => true,
+            //ANOTHER SERVER, OR THE SAME ONE A MOMENT LATER, MAY DO BETTER
+            Some(ResolveError::ServFail) => {
+                state.error = ResolveError::ServFail;
+                return self.retry(lookup, state, now);
+            }
+            Some(e) => return self.finish(lookup, Err(e))
+        };
+
+        let _type = state.query.get_type();
+        let dns_class = state.query.get_dns_class();
+        let mut followed = false;
+
+        //ONLY RECORDS ON THE WAY FROM THE NAME ASKED FOR GET CACHED, ANYTHING ELSE IN THE ANSWER IS NOT OURS TO TRUST
+        loop {
+            let records = rrset(response.get_answers(), &state.name, _type);
+
+            if !records.is_empty() {
+                self.cache.insert(&state.name, _type, dns_class, &records, now);
+                let response = answer(&state.query, state.chain, &state.name, records, Some(&response));
+                return self.finish(lookup, Ok(response));
+            }
+
+            if _type == Types::Cname {
+                break;
+            }
+
+            let cnames = rrset(response.get_answers(), &state.name, Types::Cname);
+
+            let Some(target) = cname_target(&cnames) else {
+                break;
+            };
+
+            self.cache.insert(&state.name, Types::Cname, dns_class, &cnames, now);
+
+            if let Err(e) = self.follow(&mut state.chain, &state.name, &cnames, &target) {
+                return self.finish(lookup, Err(e));
+            }
+
+            state.name = target;
+            followed = true;
+        }
+
+        //RFC 2308 3 - AN SOA IN THE AUTHORITY SECTION MAKES IT A NEGATIVE ANSWER FOR WHERE THE CHAIN ENDED, RFC 6604 2.1
+        let negative_ttl = response.get_name_servers().iter()
+            .flat_map(|(_, records)| records.iter())
+            .find_map(|record| record.as_any().downcast_ref::<SoaRecord>())
+            .map(|soa| soa.get_ttl().min(soa.get_minimum_ttl()));
+
+        if let Some(ttl) = negative_ttl {
+            self.cache.insert_negative(&state.name, _type, dns_class, nx_domain, ttl, now);
+        }
+
+        if nx_domain {
+            return self.finish(lookup, Err(ResolveError::NxDomain));
+        }
+
+        //THE SERVER STOPPED PART WAY DOWN THE CHAIN WITHOUT SAYING THE END IS EMPTY, ASK FOR THE REST OURSELVES
+        if followed && negative_ttl.is_none() {
+            return self.advance(lookup, state.query, state.name, state.chain, now);
+        }
+
+        let response = answer(&state.query, state.chain, &state.name, Vec::new(), Some(&response));
+        self.finish(lookup, Ok(response))
+    }
+
+    fn retry(&mut self, lookup: usize, mut state: Lookup, now: Instant) {
+        state.attempt += 1;
+
+        if state.attempt > self.retries {
+            return self.finish(lookup, Err(state.error));
+        }
+
+        //A NEW ATTEMPT IS A NEW QUESTION, A LATE ANSWER TO THE OLD ONE SHOULDN'T COUNT FOR IT
+        state.request = self.new_request(&state.name, &state.query);
+        state.transport = if self.tcp_only { Transports::Tcp } else { Transports::Udp };
+        self.transmit(lookup, state, now);
+    }
+
+    fn transmit(&mut self, lookup: usize, mut state: Lookup, now: Instant) {
+        state.server = self.servers[(state.offset+state.attempt as usize) % self.servers.len()];
+        state.deadline = now+self.timeout;
+        state.error = ResolveError::Timeout;
+
+        self.events.push_back(ResolverEvents::Send {
+            lookup,
+            server: state.server,
+            transport: state.transport,
+            query: state.request.encode()
+        });
+
+        self.lookups.insert(lookup, state);
+    }
+
+    fn finish(&mut self, lookup: usize, response: Result<MessageBase, ResolveError>) {
+        self.events.push_back(ResolverEvents::Answer {
+            lookup,
+            response: response.map(Box::new)
+        });
+    }
+
+    //THE CNAME GOES ON THE CHAIN, UNLESS IT LEADS SOMEWHERE THE CHAIN HAS ALREADY BEEN OR THE CHAIN IS ALREADY TOO LONG
+    fn follow(&self, chain: &mut Chain, name: &str, cnames: &[Box<dyn RecordBase>], target: &str) -> Result<(), ResolveError> {
+        chain.push((name.to_string(), cnames[0].dyn_clone()));
+
+        if chain.len() > self.max_cname_depth {
+            return Err(ResolveError::Loop(format!("More than {} CNAMEs from {}", self.max_cname_depth, chain[0].0)));
+        }
+
+        if chain.iter().any(|(owner, _)| same_name(owner, target)) {
+            return Err(ResolveError::Loop(format!("CNAME loop at {}", target)));
+        }
+
+        Ok(())
+    }
+
+    //THE ID IS RANDOM LIKE ANY OTHER QUERY'S, AND NEVER ONE ALREADY IN FLIGHT, OR TWO ANSWERS COULD BE MIXED UP
+    fn new_request(&self, name: &str, query: &DnsQuery) -> MessageBase {
+        let id = loop {
+            let id = u16::from_be_bytes(random::gen_array());
+
+            if !self.lookups.values().any(|state| state.request.get_id() == id) {
+                break id;
+            }
+        };
+
+        let mut request = MessageBase::new(id);
+        request.set_recursion_desired(true);
+        request.add_query(DnsQuery::new(name, query.get_type(), query.get_dns_class()));
+
+        if let Some(payload_size) = self.payload_size {
+            request.add_additional_records("", Box::new(OptRecord::new(payload_size, 0, 0, 0)));
+        }
+
+        request
+    }
+}
+
+//WHAT THE CALLER GETS BACK - THE QUESTION AS ASKED, EVERY CNAME FOLLOWED ON THE WAY, THEN THE RECORDS WHERE THEY LED
+//WITH THE HEADER, AUTHORITY AND ADDITIONAL SECTIONS OF THE LAST RESPONSE, IF THE ANSWER DIDN'T COME ALL FROM THE CACHE
+pub fn answer(query: &DnsQuery, chain: Chain, name: &str, records: Vec<Box<dyn RecordBase>>, last: Option<&MessageBase>) -> MessageBase {
+    let mut response = MessageBase::new(0);
+    response.set_qr(true);
+    response.set_recursion_desired(true);
+    response.set_recursion_available(true);
+
+    if let Some(last) = last {
+        response.set_id(last.get_id());
+        response.set_authoritative(last.is_authoritative());
+        response.set_recursion_available(last.is_recursion_available());
+        response.set_authenticated_data(last.is_authenticated_data());
+        response.set_checking_disabled(last.is_checking_disabled());
+
+        if let Some(origin) = last.get_origin() {
+            response.set_origin(origin);
+        }
+
+        for (owner, records) in last.get_name_servers().iter() {
+            for record in records {
+                response.add_name_servers(owner, record.dyn_clone());
+            }
+        }
+
+        for (owner, records) in last.get_additional_records().iter() {
+            for record in records {
+                response.add_additional_records(owner, record.dyn_clone());
+            }
+        }
+    }
+
+    response.add_query(query.clone());
+
+    for (owner, record) in chain {
+        response.add_answers(&owner, record);
+    }
+
+    for record in records {
+        response.add_answers(name, record);
+    }
+
+    response
+}
+
+//EVERY RECORD OF _type OWNED BY name, HOWEVER THE SERVER CHOSE TO CASE IT
+pub fn rrset(section: &OrderedMap<String, Vec<Box<dyn RecordBase>>>, name: &str, _type: Types) -> Vec<Box<dyn RecordBase>> {
+    section.iter()
+        .filter(|(owner, _)| same_name(owner, name))
+        .flat_map(|(_, records)| records.iter())
+        .filter(|record| record.get_type() == _type)
+        .map(|record| record.dyn_clone())
+        .collect()
+}
+
+//RFC 2181 10.1 - THERE'S ONLY EVER ONE CNAME FOR A NAME
+pub fn cname_target(cnames: &[Box<dyn RecordBase>]) -> Option<String> {
+    cnames.first()?.as_any().downcast_ref::<CNameRecord>()?.get_domain()
+}
+
+pub fn same_name(a: &str, b: &str) -> bool {
+    a.trim_end_matches('.').eq_ignore_ascii_case(b.trim_end_matches('.'))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::net::{IpAddr, SocketAddr};
+    use std::time::{Duration, Instant};
+    use crate::client::resolve_error::ResolveError;
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::response_codes::ResponseCodes;
+    use crate::messages::inter::transports::Transports;
+    use crate::messages::inter::types::Types;
+    use crate::messages::message_base::MessageBase;
+    use crate::records::a_record::ARecord;
+    use crate::records::cname_record::CNameRecord;
+    use crate::records::soa_record::SoaRecord;
+    use crate::resolver::dns_resolver::Resolver;
+    use crate::resolver::inter::resolver_events::ResolverEvents;
+    use crate::utils::dns_query::DnsQuery;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn server(last: u8) -> SocketAddr {
+        SocketAddr::from(([192, 0, 2, last], 53))
+    }
+
+    fn resolver(servers: u8) -> Resolver {
+        let mut resolver = Resolver::new(server(1));
+        for last in 2..=servers {
+            resolver.add_server(server(last));
+        }
+
+        resolver.set_timeout(TIMEOUT);
+        resolver
+    }
+
+    //THE NEXT EVENT HAS TO BE A QUERY, DECODED
+    fn sent(resolver: &mut Resolver) -> (SocketAddr, Transports, MessageBase) {
+        match resolver.poll_event() {
+            Some(ResolverEvents::Send { server, transport, query, .. }) => (server, transport, MessageBase::from_bytes(&query, 0).unwrap()),
+            Some(ResolverEvents::Answer { .. }) => panic!("expected a query, got an answer"),
+            None => panic!("expected a query, got nothing")
+        }
+    }
+
+    //THE NEXT EVENT HAS TO BE THE ANSWER TO lookup
+    fn answered(resolver: &mut Resolver, lookup: usize) -> Result<Box<MessageBase>, ResolveError> {
+        match resolver.poll_event() {
+            Some(ResolverEvents::Answer { lookup: l, response }) => {
+                assert_eq!(l, lookup);
+                response
+            }
+            Some(ResolverEvents::Send { .. }) => panic!("expected an answer, got a query"),
+            None => panic!("expected an answer, got nothing")
+        }
+    }
+
+    fn reply(request: &MessageBase, response_code: ResponseCodes) -> MessageBase {
+        let mut response = MessageBase::new(request.get_id());
+        response.set_qr(true);
+        response.set_response_code(response_code);
+
+        for query in request.get_queries() {
+            response.add_query(query);
+        }
+
+        response
+    }
+
+    fn a(last: u8) -> Box<ARecord> {
+        Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([198, 51, 100, last])))
+    }
+
+    fn cname(target: &str) -> Box<CNameRecord> {
+        Box::new(CNameRecord::new(DnsClasses::In, 300, target))
+    }
+
+    fn soa() -> Box<SoaRecord> {
+        Box::new(SoaRecord::new(DnsClasses::In, 300, "ns.example.com", "hostmaster.example.com", 1, 3600, 600, 86400, 60))
+    }
+
+    fn query(name: &str) -> DnsQuery {
+        DnsQuery::new(name, Types::A, DnsClasses::In)
+    }
+
+    fn addresses(response: &MessageBase) -> Vec<String> {
+        response.get_answers().iter()
+            .flat_map(|(owner, records)| records.iter().map(move |record| format!("{} {}", owner, record.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn timeout_moves_on_to_the_next_server_until_the_retries_run_out() {
+        let mut resolver = resolver(2);
+        resolver.set_retries(2);
+        let now = Instant::now();
+
+        let lookup = resolver.resolve(query("example.com"), now);
+        let (first, _, request) = sent(&mut resolver);
+        assert_eq!(first, server(1));
+        assert_eq!(resolver.poll_timeout(), Some(now+TIMEOUT));
+
+        resolver.tick(now+TIMEOUT-Duration::from_millis(1));
+        assert!(resolver.poll_event().is_none());
+
+        resolver.tick(now+TIMEOUT);
+        let (second, _, retried) = sent(&mut resolver);
+        assert_eq!(second, server(2));
+        assert_ne!(retried.get_id(), request.get_id());
+
+        resolver.tick(now+TIMEOUT*2);
+        let (third, _, _) = sent(&mut resolver);
+        assert_eq!(third, server(1));
+
+        resolver.tick(now+TIMEOUT*3);
+        assert_eq!(answered(&mut resolver, lookup).err(), Some(ResolveError::Timeout));
+        assert!(!resolver.is_pending(lookup));
+        assert_eq!(resolver.poll_timeout(), None);
+    }
+
+    #[test]
+    fn truncated_answer_is_asked_again_over_tcp_of_the_same_server() {
+        let mut resolver = resolver(2);
+        let now = Instant::now();
+
+        let lookup = resolver.resolve(query("example.com"), now);
+        let (first, transport, request) = sent(&mut resolver);
+        assert_eq!(transport, Transports::Udp);
+
+        let mut truncated = reply(&request, ResponseCodes::NoError);
+        truncated.set_truncated(true);
+        resolver.receive(&truncated.encode(), first, Transports::Udp, now);
+
+        let (again, transport, request) = sent(&mut resolver);
+        assert_eq!(again, first);
+        assert_eq!(transport, Transports::Tcp);
+
+        let mut response = reply(&request, ResponseCodes::NoError);
+        response.add_answers("example.com", a(1));
+
+        //THE UDP ATTEMPT IS OVER, THE SAME ANSWER OVER UDP IS A STRAY NOW
+        resolver.receive(&response.encode(), first, Transports::Udp, now);
+        assert!(resolver.poll_event().is_none());
+
+        resolver.receive(&response.encode(), first, Transports::Tcp, now);
+        let response = answered(&mut resolver, lookup).unwrap();
+        assert_eq!(addresses(&response), vec!["example.com 300 IN A 198.51.100.1".to_string()]);
+    }
+
+    #[test]
+    fn servfail_is_retried_on_the_next_server() {
+        let mut resolver = resolver(2);
+        resolver.set_retries(1);
+        let now = Instant::now();
+
+        let lookup = resolver.resolve(query("example.com"), now);
+        let (first, _, request) = sent(&mut resolver);
+        resolver.receive(&reply(&request, ResponseCodes::ServerFailure).encode(), first, Transports::Udp, now);
+
+        let (second, _, request) = sent(&mut resolver);
+        assert_eq!(second, server(2));
+        resolver.receive(&reply(&request, ResponseCodes::ServerFailure).encode(), second, Transports::Udp, now);
+
+        assert_eq!(answered(&mut resolver, lookup).err(), Some(ResolveError::ServFail));
+    }
+
+    #[test]
+    fn cname_chain_is_followed_and_the_rest_asked_for() {
+        let mut resolver = resolver(1);
+        let now = Instant::now();
+
+        let lookup = resolver.resolve(query("www.example.com"), now);
+        let (first, _, request) = sent(&mut resolver);
+
+        //THE SERVER ONLY KNOWS THE FIRST TWO HOPS
+        let mut response = reply(&request, ResponseCodes::NoError);
+        response.add_answers("www.example.com", cname("web.example.com"));
+        response.add_answers("web.example.com", cname("cdn.example.net"));
+        resolver.receive(&response.encode(), first, Transports::Udp, now);
+
+        let (_, _, request) = sent(&mut resolver);
+        assert_eq!(request.get_queries()[0].get_query().unwrap(), "cdn.example.net");
+
+        let mut response = reply(&request, ResponseCodes::NoError);
+        response.add_answers("cdn.example.net", a(1));
+        resolver.receive(&response.encode(), first, Transports::Udp, now);
+
+        let response = answered(&mut resolver, lookup).unwrap();
+        assert_eq!(response.get_queries()[0].get_query().unwrap(), "www.example.com");
+        assert_eq!(addresses(&response), vec![
+            "www.example.com 300 IN CNAME web.example.com.".to_string(),
+            "web.example.com 300 IN CNAME cdn.example.net.".to_string(),
+            "cdn.example.net 300 IN A 198.51.100.1".to_string()
+        ]);
+
+        //EVERY HOP IS CACHED NOW, NOTHING GOES OUT THE SECOND TIME
+        let lookup = resolver.resolve(query("www.example.com"), now);
+        assert_eq!(addresses(&answered(&mut resolver, lookup).unwrap()).len(), 3);
+    }
+
+    #[test]
+    fn cname_loop_is_an_error() {
+        let mut resolver = resolver(1);
+        let now = Instant::now();
+
+        let lookup = resolver.resolve(query("a.example.com"), now);
+        let (first, _, request) = sent(&mut resolver);
+
+        let mut response = reply(&request, ResponseCodes::NoError);
+        response.add_answers("a.example.com", cname("b.example.com"));
+        response.add_answers("b.example.com", cname("a.example.com"));
+        resolver.receive(&response.encode(), first, Transports::Udp, now);
+
+        assert!(matches!(answered(&mut resolver, lookup), Err(ResolveError::Loop(_))));
+    }
+
+    #[test]
+    fn cname_chain_longer_than_the_limit_is_an_error() {
+        let mut resolver = resolver(1);
+        resolver.set_max_cname_depth(2);
+        let now = Instant::now();
+
+        let lookup = resolver.resolve(query("a.example.com"), now);
+        let (first, _, request) = sent(&mut resolver);
+
+        let mut response = reply(&request, ResponseCodes::NoError);
+        response.add_answers("a.example.com", cname("b.example.com"));
+        response.add_answers("b.example.com", cname("c.example.com"));
+        response.add_answers("c.example.com", cname("d.example.com"));
+        resolver.receive(&response.encode(), first, Transports::Udp, now);
+
+        assert!(matches!(answered(&mut resolver, lookup), Err(ResolveError::Loop(_))));
+    }
+
+    #[test]
+    fn negative_answers_are_cached_for_the_soa_minimum() {
+        let mut resolver = resolver(1);
+        let now = Instant::now();
+
+        let lookup = resolver.resolve(query("empty.example.com"), now);
+        let (first, _, request) = sent(&mut resolver);
+        let mut response = reply(&request, ResponseCodes::NoError);
+        response.add_name_servers("example.com", soa());
+        resolver.receive(&response.encode(), first, Transports::Udp, now);
+        assert!(addresses(&answered(&mut resolver, lookup).unwrap()).is_empty());
+
+        let lookup = resolver.resolve(query("missing.example.com"), now);
+        let (first, _, request) = sent(&mut resolver);
+        let mut response = reply(&request, ResponseCodes::NameError);
+        response.add_name_servers("example.com", soa());
+        resolver.receive(&response.encode(), first, Transports::Udp, now);
+        assert_eq!(answered(&mut resolver, lookup).err(), Some(ResolveError::NxDomain));
+
+        //THE SOA'S MINIMUM OF 60 IS LOWER THAN ITS TTL OF 300, SO IT'S THE ONE THAT COUNTS
+        let later = now+Duration::from_secs(59);
+
+        let lookup = resolver.resolve(query("empty.example.com"), later);
+        assert!(addresses(&answered(&mut resolver, lookup).unwrap()).is_empty());
+
+        let lookup = resolver.resolve(query("missing.example.com"), later);
+        assert_eq!(answered(&mut resolver, lookup).err(), Some(ResolveError::NxDomain));
+
+        let expired = now+Duration::from_secs(60);
+
+        resolver.resolve(query("empty.example.com"), expired);
+        sent(&mut resolver);
+
+        resolver.resolve(query("missing.example.com"), expired);
+        sent(&mut resolver);
+    }
+
+    #[test]
+    fn answer_with_the_wrong_id_source_or_transport_is_ignored() {
+        let mut resolver = resolver(2);
+        let now = Instant::now();
+
+        let lookup = resolver.resolve(query("example.com"), now);
+        let (first, _, request) = sent(&mut resolver);
+
+        let mut response = reply(&request, ResponseCodes::NoError);
+        response.add_answers("example.com", a(1));
+
+        let mut wrong_id = MessageBase::from_bytes(&response.encode(), 0).unwrap();
+        wrong_id.set_id(request.get_id().wrapping_add(1));
+        resolver.receive(&wrong_id.encode(), first, Transports::Udp, now);
+
+        resolver.receive(&response.encode(), server(2), Transports::Udp, now);
+        resolver.receive(&response.encode(), SocketAddr::from(([192, 0, 2, 1], 5353)), Transports::Udp, now);
+        resolver.receive(&response.encode(), first, Transports::Tcp, now);
+
+        //SAME ID, BUT THE QUESTION ISN'T THE ONE ASKED
+        let mut other = MessageBase::new(request.get_id());
+        other.set_qr(true);
+        other.add_query(query("example.net"));
+        resolver.receive(&other.encode(), first, Transports::Udp, now);
+
+        assert!(resolver.poll_event().is_none());
+        assert!(resolver.is_pending(lookup));
+
+        resolver.receive(&response.encode(), first, Transports::Udp, now);
+        assert_eq!(addresses(&answered(&mut resolver, lookup).unwrap()).len(), 1);
+    }
+
+    #[test]
+    fn cancelled_lookup_sends_and_answers_nothing() {
+        let mut resolver = resolver(1);
+        let now = Instant::now();
+
+        let lookup = resolver.resolve(query("example.com"), now);
+        resolver.cancel(lookup);
+        assert!(resolver.poll_event().is_none());
+        assert!(!resolver.is_pending(lookup));
+
+        let lookup = resolver.resolve(query("example.com"), now);
+        let (first, _, request) = sent(&mut resolver);
+        resolver.cancel(lookup);
+
+        let mut response = reply(&request, ResponseCodes::NoError);
+        response.add_answers("example.com", a(1));
+        resolver.receive(&response.encode(), first, Transports::Udp, now);
+        resolver.tick(now+TIMEOUT);
+
+        assert!(resolver.poll_event().is_none());
+        assert_eq!(resolver.poll_timeout(), None);
+    }
+}
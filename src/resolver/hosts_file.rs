@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use crate::messages::inter::dns_classes::DnsClasses;
+use crate::messages::inter::types::Types;
+use crate::messages::message_base::MessageBase;
+use crate::records::a_record::ARecord;
+use crate::records::aaaa_record::AAAARecord;
+use crate::records::inter::record_base::RecordBase;
+use crate::records::ptr_record::PtrRecord;
+use crate::utils::dns_query::DnsQuery;
+use crate::utils::name::Name;
+
+//NOTHING TO CACHE, THE FILE IS THE SOURCE AND IT'S READ AGAIN THE MOMENT IT CHANGES
+const HOSTS_TTL: u32 = 0;
+
+//HOSTS(5) - AN ADDRESS, ITS CANONICAL NAME, THEN ANY ALIASES, # TO THE END OF THE LINE IS A COMMENT
+pub struct HostsFile {
+    path: PathBuf,
+    //WHAT THE FILE LOOKED LIKE WHEN IT WAS LAST READ, None IF IT COULDN'T BE
+    modified: Option<(SystemTime, u64)>,
+    addresses: HashMap<String, Vec<IpAddr>>,
+    names: HashMap<IpAddr, Vec<String>>
+}
+
+impl HostsFile {
+
+    //A MISSING OR UNREADABLE FILE IS JUST EMPTY, IT'S PICKED UP ONCE IT APPEARS
+    pub fn new(path: &Path) -> Self {
+        let mut hosts_file = Self {
+            path: path.to_path_buf(),
+            modified: None,
+            addresses: HashMap::new(),
+            names: HashMap::new()
+        };
+
+        hosts_file.reload();
+        hosts_file
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    //CHEAP WHEN NOTHING CHANGED - ONE STAT, COMPARING MODIFIED TIME AND SIZE
+    pub fn reload_if_changed(&mut self) -> bool {
+        if stat(&self.path) == self.modified {
+            return false;
+        }
+
+        self.reload();
+        true
+    }
+
+    pub fn reload(&mut self) {
+        self.modified = stat(&self.path);
+        self.addresses.clear();
+        self.names.clear();
+
+        if let Ok(text) = fs::read_to_string(&self.path) {
+            self.parse(&text);
+        }
+    }
+
+    //EVERY ADDRESS FOR name, IN THE ORDER THE FILE LISTS THEM
+    pub fn get_addresses(&self, name: &str) -> Vec<IpAddr> {
+        self.addresses.get(&key(name)).cloned().unwrap_or_default()
+    }
+
+    //THE CANONICAL NAME COMES FIRST, LIKE GETHOSTBYADDR
+    pub fn get_names(&self, address: &IpAddr) -> Vec<String> {
+        self.names.get(&canonical(*address)).cloned().unwrap_or_default()
+    }
+
+    //A, AAAA AND PTR IN CLASS IN ARE ANSWERED IF THE FILE HAS SOMETHING, None MEANS ASK THE NETWORK
+    //THE FILE IS CHECKED FOR CHANGES FIRST, SO AN EDIT IS SEEN BY THE VERY NEXT QUERY
+    pub fn query(&mut self, query: &DnsQuery) -> Option<MessageBase> {
+        if query.get_dns_class() != DnsClasses::In {
+            return None;
+        }
+
+        self.reload_if_changed();
+
+        let name = query.get_query().ok()?;
+        let name = name.trim_end_matches('.');
+
+        let records: Vec<Box<dyn RecordBase>> = match query.get_type() {
+            Types::A => self.get_addresses(name).into_iter()
+                .filter(|address| address.is_ipv4())
+                .map(|address| ARecord::new(DnsClasses::In, false, HOSTS_TTL, address).dyn_clone())
+                .collect(),
+            Types::Aaaa => self.get_addresses(name).into_iter()
+                .filter(|address| address.is_ipv6())
+                .map(|address| AAAARecord::new(DnsClasses::In, false, HOSTS_TTL, address).dyn_clone())
+                .collect(),
+            Types::Ptr => self.get_names(&Name::new(name).to_full_ip()?).into_iter()
+                .map(|name| PtrRecord::new(DnsClasses::In, false, HOSTS_TTL, &name).dyn_clone())
+                .collect(),
+            _ => return None
+        };
+
+        if records.is_empty() {
+            return None;
+        }
+
+        let mut response = MessageBase::new(0);
+        response.set_qr(true);
+        response.set_authoritative(true);
+        response.set_recursion_desired(true);
+        response.set_recursion_available(true);
+        response.add_query(query.clone());
+
+        for record in records {
+            response.add_answers(name, record);
+        }
+
+        Some(response)
+    }
+
+    fn parse(&mut self, text: &str) {
+        for line in text.lines() {
+            let line = match line.find('#') {
+                Some(i) => &line[..i],
+                None => line
+            };
+
+            let mut tokens = line.split_whitespace();
+
+            let Some(address) = tokens.next().and_then(parse_address) else {
+                continue;
+            };
+
+            for name in tokens {
+                let name = name.trim_end_matches('.');
+
+                let addresses = self.addresses.entry(key(name)).or_default();
+                if !addresses.contains(&address) {
+                    addresses.push(address);
+                }
+
+                let names = self.names.entry(address).or_default();
+                if !names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+}
+
+fn key(name: &str) -> String {
+    name.trim_end_matches('.').to_ascii_lowercase()
+}
+
+fn stat(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+//A LINK LOCAL fe80::1%eth0 IS STILL fe80::1 AS FAR AS NAMES GO
+fn parse_address(address: &str) -> Option<IpAddr> {
+    let address = address.split('%').next()?;
+    address.parse::<IpAddr>().ok().map(canonical)
+}
+
+//::ffff:1.2.3.4 AND 1.2.3.4 ARE THE SAME HOST
+fn canonical(address: IpAddr) -> IpAddr {
+    match address {
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => address
+        },
+        address => address
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use std::net::IpAddr;
+    use std::path::PathBuf;
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::types::Types;
+    use crate::messages::message_base::MessageBase;
+    use crate::records::ptr_record::PtrRecord;
+    use crate::resolver::address_selection::get_address;
+    use crate::resolver::hosts_file::HostsFile;
+    use crate::utils::dns_query::DnsQuery;
+
+    const HOSTS: &str = "\
+# A COMMENT LINE
+127.0.0.1   localhost
+192.0.2.7   Printer.example printer   # TRAILING COMMENT
+2001:db8::7 printer.example
+::ffff:192.0.2.8 mapped.example
+fe80::1%eth0 router.example
+not-an-address ignored.example
+";
+
+    fn hosts_file(name: &str, text: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("find9-{}-{}.hosts", std::process::id(), name));
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    fn addresses(response: &MessageBase) -> Vec<IpAddr> {
+        response.get_answers().iter()
+            .flat_map(|(_, records)| records.iter())
+            .filter_map(|record| get_address(record.as_ref()))
+            .collect()
+    }
+
+    #[test]
+    fn names_aliases_and_comments() {
+        let path = hosts_file("names", HOSTS);
+        let hosts_file = HostsFile::new(&path);
+
+        assert_eq!(hosts_file.get_addresses("printer"), vec![IpAddr::from([192, 0, 2, 7])]);
+        assert_eq!(hosts_file.get_addresses("PRINTER.example."), vec![IpAddr::from([192, 0, 2, 7]), "2001:db8::7".parse().unwrap()]);
+        assert_eq!(hosts_file.get_names(&IpAddr::from([192, 0, 2, 7])), vec!["Printer.example", "printer"]);
+        assert!(hosts_file.get_addresses("ignored.example").is_empty());
+        assert!(hosts_file.get_addresses("comment").is_empty());
+        assert!(hosts_file.get_addresses("trailing").is_empty());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn mapped_addresses_and_zones_are_canonicalised() {
+        let path = hosts_file("canonical", HOSTS);
+        let hosts_file = HostsFile::new(&path);
+
+        assert_eq!(hosts_file.get_addresses("mapped.example"), vec![IpAddr::from([192, 0, 2, 8])]);
+        assert_eq!(hosts_file.get_names(&"::ffff:192.0.2.8".parse().unwrap()), vec!["mapped.example"]);
+        assert_eq!(hosts_file.get_addresses("router.example"), vec!["fe80::1".parse::<IpAddr>().unwrap()]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn query_filters_by_family() {
+        let path = hosts_file("family", HOSTS);
+        let mut hosts_file = HostsFile::new(&path);
+
+        let a = hosts_file.query(&DnsQuery::new("printer.example.", Types::A, DnsClasses::In)).unwrap();
+        assert_eq!(addresses(&a), vec![IpAddr::from([192, 0, 2, 7])]);
+        assert!(a.is_authoritative());
+
+        let aaaa = hosts_file.query(&DnsQuery::new("printer.example.", Types::Aaaa, DnsClasses::In)).unwrap();
+        assert_eq!(addresses(&aaaa), vec!["2001:db8::7".parse::<IpAddr>().unwrap()]);
+
+        //ONLY AN IPV4 ADDRESS, THE AAAA QUESTION GOES TO THE NETWORK
+        assert!(hosts_file.query(&DnsQuery::new("localhost", Types::Aaaa, DnsClasses::In)).is_none());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn query_answers_ptr() {
+        let path = hosts_file("ptr", HOSTS);
+        let mut hosts_file = HostsFile::new(&path);
+
+        let response = hosts_file.query(&DnsQuery::new("7.2.0.192.in-addr.arpa.", Types::Ptr, DnsClasses::In)).unwrap();
+        let names: Vec<String> = response.get_answers().iter()
+            .flat_map(|(_, records)| records.iter())
+            .filter_map(|record| record.as_any().downcast_ref::<PtrRecord>()?.get_domain())
+            .collect();
+        assert_eq!(names, vec!["Printer.example", "printer"]);
+
+        let v6 = "7.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa";
+        assert!(hosts_file.query(&DnsQuery::new(v6, Types::Ptr, DnsClasses::In)).is_some());
+
+        //A PARTIAL REVERSE NAME ISN'T AN ADDRESS
+        assert!(hosts_file.query(&DnsQuery::new("2.0.192.in-addr.arpa", Types::Ptr, DnsClasses::In)).is_none());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn other_types_and_classes_go_to_the_network() {
+        let path = hosts_file("other", HOSTS);
+        let mut hosts_file = HostsFile::new(&path);
+
+        assert!(hosts_file.query(&DnsQuery::new("printer", Types::Mx, DnsClasses::In)).is_none());
+        assert!(hosts_file.query(&DnsQuery::new("printer", Types::A, DnsClasses::Ch)).is_none());
+        assert!(hosts_file.query(&DnsQuery::new("unknown.example", Types::A, DnsClasses::In)).is_none());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rewrite_is_seen_by_the_next_query() {
+        let path = hosts_file("rewrite", "192.0.2.1 box\n");
+        let mut hosts_file = HostsFile::new(&path);
+        let query = DnsQuery::new("box", Types::A, DnsClasses::In);
+
+        assert_eq!(addresses(&hosts_file.query(&query).unwrap()), vec![IpAddr::from([192, 0, 2, 1])]);
+
+        //A DIFFERENT SIZE, SO IT'S NOTICED EVEN WHERE THE MODIFIED TIME IS COARSE
+        fs::write(&path, "192.0.2.200 box\n").unwrap();
+        assert_eq!(addresses(&hosts_file.query(&query).unwrap()), vec![IpAddr::from([192, 0, 2, 200])]);
+
+        fs::remove_file(&path).unwrap();
+        assert!(hosts_file.query(&query).is_none());
+    }
+}
@@ -0,0 +1 @@
+pub mod resolver_events;
@@ -0,0 +1,19 @@
+use std::net::SocketAddr;
+use crate::client::resolve_error::ResolveError;
+use crate::messages::inter::transports::Transports;
+use crate::messages::message_base::MessageBase;
+
+pub enum ResolverEvents {
+    //PUT query ON THE WIRE TO server, OVER TCP IT STILL NEEDS ITS 2 BYTE LENGTH PREFIX
+    Send {
+        lookup: usize,
+        server: SocketAddr,
+        transport: Transports,
+        query: Vec<u8>
+    },
+    //THE LOOKUP IS DONE AND FORGOTTEN, NOTHING MORE FOR IT WILL COME OUT
+    Answer {
+        lookup: usize,
+        response: Result<Box<MessageBase>, ResolveError>
+    }
+}
@@ -0,0 +1,831 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+use crate::client::dns_client::Client;
+use crate::client::resolve_error::ResolveError;
+use crate::messages::inter::dns_classes::DnsClasses;
+use crate::messages::inter::response_codes::ResponseCodes;
+use crate::messages::inter::types::Types;
+use crate::messages::message_base::MessageBase;
+use crate::records::cname_record::CNameRecord;
+use crate::records::dname_record::DNameRecord;
+use crate::records::inter::record_base::RecordBase;
+use crate::records::ns_record::NsRecord;
+use crate::records::soa_record::SoaRecord;
+use crate::resolver::address_selection::{get_address, sort_addresses};
+use crate::resolver::cache::Cache;
+use crate::resolver::dns_resolver::{answer, cname_target, rrset, same_name, Chain};
+use crate::resolver::root_hints::get_root_hints;
+use crate::resolver::stub_resolver::get_records;
+use crate::utils::dns_query::DnsQuery;
+
+const DEFAULT_PORT: u16 = 53;
+
+//AUTHORITATIVE SERVERS ANSWER FROM MEMORY, ONE THIS SLOW IS BETTER SKIPPED FOR THE NEXT ONE IN THE DELEGATION
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+const DEFAULT_RETRIES: u32 = 0;
+
+//DNS FLAG DAY 2020 - BIG ENOUGH FOR MOST ANSWERS, SMALL ENOUGH TO NOT FRAGMENT
+const DEFAULT_PAYLOAD_SIZE: u16 = 1232;
+
+//PLENTY FOR ANY REAL NAME, GLUELESS SERVERS AND ALL, TOO FEW FOR ONE QUESTION TO BE TURNED INTO A FLOOD
+const DEFAULT_MAX_QUERIES: usize = 64;
+
+//GLUELESS NAME SERVERS WHOSE OWN SERVERS ARE GLUELESS, AND SO ON
+const DEFAULT_MAX_DEPTH: usize = 4;
+
+//A LONGER CHAIN THAN THIS IS ALMOST ALWAYS A LOOP SOMEBODY MISSED
+const DEFAULT_MAX_CNAME_DEPTH: usize = 8;
+
+//A SERVER THAT WAS LAME FOR A ZONE ISN'T ASKED ABOUT IT AGAIN FOR THIS LONG
+const LAME_TTL: Duration = Duration::from_secs(900);
+
+//EACH NAME SERVER'S NAME WITH THE ADDRESSES KNOWN FOR IT, EMPTY IF IT CAME WITHOUT GLUE
+type Servers = Vec<(String, Vec<IpAddr>)>;
+
+//WHAT A RESPONSE MEANS FOR THE NAME BEING ASKED FOR
+enum Steps {
+    Answer(Vec<Box<dyn RecordBase>>, MessageBase),
+    NoData(MessageBase),
+    //WHAT GOES ON THE CHAIN, THEN THE NAME IT LEADS TO
+    Alias(Chain, String),
+    //THE ZONE CUT AND ITS SERVERS
+    Referral(String, Servers)
+}
+
+//SHARED BY EVERYTHING ONE resolve SETS OFF, THE GLUELESS SERVER LOOKUPS INCLUDED
+struct Iteration {
+    queries: usize,
+    //NAME SERVERS WHOSE ADDRESSES ARE BEING LOOKED UP, THE INNERMOST LAST
+    pending: Vec<String>
+}
+
+//NO UPSTREAM, EVERY NAME IS FOLLOWED DOWN FROM THE ROOT, OR FROM THE DEEPEST DELEGATION THE CACHE ALREADY HAS
+//EVERY QUERY GOES OUT WITHOUT RD AND ONLY WHAT A SERVER IS AUTHORITATIVE FOR IS TAKEN FROM IT
+pub struct IterativeResolver {
+    root_hints: Servers,
+    port: u16,
+    timeout: Duration,
+    retries: u32,
+    payload_size: Option<u16>,
+    tcp_only: bool,
+    max_queries: usize,
+    max_depth: usize,
+    max_cname_depth: usize,
+    cache: Cache,
+    lame: HashMap<(IpAddr, String), Instant>
+}
+
+impl Default for IterativeResolver {
+
+    fn default() -> Self {
+        Self::new(get_root_hints())
+    }
+}
+
+impl IterativeResolver {
+
+    pub fn new(root_hints: Vec<(String, Vec<IpAddr>)>) -> Self {
+        Self {
+            root_hints,
+            port: DEFAULT_PORT,
+            timeout: DEFAULT_TIMEOUT,
+            retries: DEFAULT_RETRIES,
+            payload_size: Some(DEFAULT_PAYLOAD_SIZE),
+            tcp_only: false,
+            max_queries: DEFAULT_MAX_QUERIES,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_cname_depth: DEFAULT_MAX_CNAME_DEPTH,
+            cache: Cache::new(),
+            lame: HashMap::new()
+        }
+    }
+
+    pub fn get_root_hints(&self) -> &Vec<(String, Vec<IpAddr>)> {
+        &self.root_hints
+    }
+
+    //GLUE ONLY CARRIES ADDRESSES, SO EVERY SERVER IS ASKED ON THE SAME PORT - ANYTHING BUT 53 IS FOR A TEST HIERARCHY
+    pub fn set_port(&mut self, port: u16) {
+        self.port = port;
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    //PER SERVER, A DELEGATION'S OTHER SERVERS ARE WHAT GET TRIED NEXT
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn set_retries(&mut self, retries: u32) {
+        self.retries = retries;
+    }
+
+    pub fn get_retries(&self) -> u32 {
+        self.retries
+    }
+
+    //None SENDS QUERIES WITHOUT AN OPT RECORD
+    pub fn set_payload_size(&mut self, payload_size: Option<u16>) {
+        self.payload_size = payload_size;
+    }
+
+    pub fn get_payload_size(&self) -> Option<u16> {
+        self.payload_size
+    }
+
+    //SKIP UDP ENTIRELY, OTHERWISE TCP IS ONLY USED WHEN A UDP ANSWER COMES BACK TRUNCATED
+    pub fn set_tcp_only(&mut self, tcp_only: bool) {
+        self.tcp_only = tcp_only;
+    }
+
+    pub fn is_tcp_only(&self) -> bool {
+        self.tcp_only
+    }
+
+    //EVERY QUERY ONE resolve SENDS COUNTS, THE ONES FOR GLUELESS SERVERS AND EVERY RETRY INCLUDED
+    pub fn set_max_queries(&mut self, max_queries: usize) {
+        self.max_queries = max_queries;
+    }
+
+    pub fn get_max_queries(&self) -> usize {
+        self.max_queries
+    }
+
+    //HOW MANY GLUELESS SERVER LOOKUPS CAN BE NESTED INSIDE EACH OTHER
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    pub fn get_max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    //A DNAME COUNTS ONCE, FOR THE CNAME IT IMPLIES
+    pub fn set_max_cname_depth(&mut self, max_cname_depth: usize) {
+        self.max_cname_depth = max_cname_depth;
+    }
+
+    pub fn get_max_cname_depth(&self) -> usize {
+        self.max_cname_depth
+    }
+
+    pub fn get_cache(&self) -> &Cache {
+        &self.cache
+    }
+
+    pub fn get_cache_mut(&mut self) -> &mut Cache {
+        &mut self.cache
+    }
+
+    pub fn is_lame(&self, address: IpAddr, zone: &str) -> bool {
+        self.lame.get(&(address, zone.trim_end_matches('.').to_ascii_lowercase())).is_some_and(|expires| *expires > Instant::now())
+    }
+
+    pub fn clear_lame(&mut self) {
+        self.lame.clear();
+    }
+
+    pub fn lookup(&mut self, name: &str, _type: Types) -> Result<MessageBase, ResolveError> {
+        self.resolve(&DnsQuery::new(name, _type, DnsClasses::In))
+    }
+
+    //THE SAME SHAPE OF ANSWER AS Resolver GIVES - THE QUESTION, EVERY CNAME AND DNAME FOLLOWED, THEN THE RECORDS WHERE THEY LED
+    pub fn resolve(&mut self, query: &DnsQuery) -> Result<MessageBase, ResolveError> {
+        let mut iteration = Iteration {
+            queries: 0,
+            pending: Vec::new()
+        };
+
+        self.iterate(query, &mut iteration)
+    }
+
+    fn iterate(&mut self, query: &DnsQuery, iteration: &mut Iteration) -> Result<MessageBase, ResolveError> {
+        let _type = query.get_type();
+        let dns_class = query.get_dns_class();
+        let mut name = query.get_query().map_err(ResolveError::Malformed)?.trim_end_matches('.').to_string();
+        let mut chain = Chain::new();
+
+        loop {
+            let now = Instant::now();
+
+            match self.cache.get(&name, _type, dns_class, now) {
+                Some(Ok(records)) => return Ok(answer(query, chain, &name, records, None)),
+                Some(Err(e)) => return Err(e),
+                None => {}
+            }
+
+            let cached = match _type {
+                Types::Cname => None,
+                _ => match self.cache.get(&name, Types::Cname, dns_class, now) {
+                    Some(Ok(cnames)) => cname_target(&cnames).map(|target| (vec![(name.clone(), cnames[0].dyn_clone())], target)),
+                    _ => None
+                }
+            };
+
+            let (aliases, target) = match cached {
+                Some(alias) => alias,
+                None => {
+                    let (mut zone, mut servers) = self.get_closest_servers(&name, dns_class);
+
+                    //EVERY REFERRAL IS A CUT FURTHER DOWN TOWARDS name, SO THIS ENDS BEFORE THE LABELS DO
+                    loop {
+                        match self.ask(&zone, &servers, &name, _type, dns_class, iteration)? {
+                            Steps::Answer(records, response) => return Ok(answer(query, chain, &name, records, Some(&response))),
+                            Steps::NoData(response) => return Ok(answer(query, chain, &name, Vec::new(), Some(&response))),
+                            Steps::Alias(aliases, target) => break (aliases, target),
+                            Steps::Referral(cut, delegation) => {
+                                zone = cut;
+                                servers = delegation;
+                            }
+                        }
+                    }
+                }
+            };
+
+            chain.extend(aliases);
+
+            if chain.iter().filter(|(_, record)| record.get_type() == Types::Cname).count() > self.max_cname_depth {
+                return Err(ResolveError::Loop(format!("More than {} CNAMEs from {}", self.max_cname_depth, chain[0].0)));
+            }
+
+            if chain.iter().any(|(owner, _)| same_name(owner, &target)) {
+                return Err(ResolveError::Loop(format!("CNAME loop at {}", target)));
+            }
+
+            name = target;
+        }
+    }
+
+    //THE SERVERS FOR zone IN TURN UNTIL ONE GIVES AN ANSWER THAT MEANS SOMETHING, THE ONES WITH GLUE FIRST
+    //A GLUELESS SERVER IS ONLY LOOKED UP ONCE ALL OF THOSE HAVE FAILED
+    fn ask(&mut self, zone: &str, servers: &Servers, name: &str, _type: Types, dns_class: DnsClasses, iteration: &mut Iteration) -> Result<Steps, ResolveError> {
+        let mut error = ResolveError::LameDelegation(format!("{}.", zone));
+        let mut glueless = Vec::new();
+
+        for (host, addresses) in servers {
+            if addresses.is_empty() {
+                glueless.push(host);
+                continue;
+            }
+
+            if let Some(step) = self.ask_server(zone, addresses, name, _type, dns_class, iteration, &mut error)? {
+                return Ok(step);
+            }
+        }
+
+        for host in glueless {
+            let addresses = match self.get_glueless_addresses(host, dns_class, iteration) {
+                Ok(addresses) => addresses,
+                Err(e) => {
+                    self.check_budget(iteration)?;
+                    error = e;
+                    continue;
+                }
+            };
+
+            if let Some(step) = self.ask_server(zone, &addresses, name, _type, dns_class, iteration, &mut error)? {
+                return Ok(step);
+            }
+        }
+
+        Err(error)
+    }
+
+    //ONE SERVER AT EACH OF ITS ADDRESSES, None IF NONE OF THEM GAVE ANYTHING, WITH error SAYING WHY
+    #[allow(clippy::too_many_arguments)]
+    fn ask_server(&mut self, zone: &str, addresses: &[IpAddr], name: &str, _type: Types, dns_class: DnsClasses, iteration: &mut Iteration, error: &mut ResolveError) -> Result<Option<Steps>, ResolveError> {
+        let mut addresses = addresses.to_vec();
+        sort_addresses(&mut addresses);
+
+        for address in addresses {
+            if self.is_lame(address, zone) {
+                continue;
+            }
+
+            self.check_budget(iteration)?;
+            iteration.queries += 1;
+
+            let mut request = MessageBase::new(0);
+            request.add_query(DnsQuery::new(name, _type, dns_class));
+
+            match self.get_client(address).query(request) {
+                Ok(response) => match self.classify(zone, name, _type, dns_class, response) {
+                    Some(step) => return Ok(Some(step)),
+                    None => {
+                        self.set_lame(address, zone);
+                        *error = ResolveError::LameDelegation(format!("{}.", zone));
+                    }
+                },
+                //ONLY A SERVER FOR THE ZONE CAN SAY THE NAME ISN'T IN IT
+                Err(ResolveError::NxDomain) => return Err(ResolveError::NxDomain),
+                Err(ResolveError::ErrorResponse(ResponseCodes::Refused)) => {
+                    self.set_lame(address, zone);
+                    *error = ResolveError::LameDelegation(format!("{}.", zone));
+                }
+                Err(e) => *error = e
+            }
+        }
+
+        Ok(None)
+    }
+
+    //WHAT A RESPONSE FROM A SERVER FOR zone SAYS ABOUT name - None IF IT ISN'T REALLY SERVING zone, A LAME DELEGATION
+    //ONLY RECORDS AT OR BELOW zone ARE TAKEN, A SERVER ONLY GETS TO SPEAK FOR WHAT IT WAS DELEGATED
+    fn classify(&mut self, zone: &str, name: &str, _type: Types, dns_class: DnsClasses, response: MessageBase) -> Option<Steps> {
+        let now = Instant::now();
+        let records = rrset(response.get_answers(), name, _type);
+
+        if !records.is_empty() {
+            self.cache.insert(name, _type, dns_class, &records, now);
+            return Some(Steps::Answer(records, response));
+        }
+
+        if _type != Types::Cname {
+            let cnames = rrset(response.get_answers(), name, Types::Cname);
+
+            if let Some(target) = cname_target(&cnames) {
+                self.cache.insert(name, Types::Cname, dns_class, &cnames, now);
+                return Some(Steps::Alias(vec![(name.to_string(), cnames[0].dyn_clone())], target));
+            }
+        }
+
+        //RFC 6672 2.2 - A DNAME ABOVE name SWAPS ITS OWNER FOR ITS TARGET AT THE END OF name, THE CNAME THAT MAKES GOES ON THE CHAIN AFTER IT
+        if _type != Types::Dname {
+            for (owner, records) in response.get_answers().iter() {
+                let owner = owner.trim_end_matches('.');
+
+                if !is_subdomain(owner, zone) || same_name(owner, name) || !is_subdomain(name, owner) {
+                    continue;
+                }
+
+                let Some(dname) = records.iter().find_map(|record| record.as_any().downcast_ref::<DNameRecord>()) else {
+                    continue;
+                };
+
+                let target = format!("{}{}", &name[..name.len()-owner.len()], dname.get_domain()?.trim_end_matches('.'));
+                let cname = CNameRecord::new(dns_class, dname.get_ttl(), &target);
+
+                self.cache.insert(owner, Types::Dname, dns_class, &[dname.dyn_clone()], now);
+                return Some(Steps::Alias(vec![(owner.to_string(), dname.dyn_clone()), (name.to_string(), cname.dyn_clone())], target));
+            }
+        }
+
+        let cut = response.get_name_servers().iter()
+            .find(|(_, records)| records.iter().any(|record| record.get_type() == Types::Ns))
+            .map(|(owner, _)| owner.trim_end_matches('.').to_string());
+
+        if let Some(cut) = cut.filter(|_| !response.is_authoritative()) {
+            //A CUT THAT ISN'T BELOW zone, OR DOESN'T LEAD TO name, GOES SIDEWAYS OR BACK UP - FOLLOWING IT COULD GO ROUND FOREVER
+            if same_name(&cut, zone) || !is_subdomain(&cut, zone) || !is_subdomain(name, &cut) {
+                return None;
+            }
+
+            let ns = rrset(response.get_name_servers(), &cut, Types::Ns);
+            self.cache.insert(&cut, Types::Ns, dns_class, &ns, now);
+
+            let servers = ns.iter()
+                .filter_map(|record| record.as_any().downcast_ref::<NsRecord>()?.get_domain())
+                .map(|host| {
+                    let host = host.trim_end_matches('.').to_string();
+
+                    //GLUE FOR A NAME OUTSIDE zone IS THE SERVER VOUCHING FOR SOMETHING IT DOESN'T SERVE, IT'S LOOKED UP INSTEAD
+                    if is_subdomain(&host, zone) {
+                        for _type in [Types::A, Types::Aaaa] {
+                            let glue = rrset(response.get_additional_records(), &host, _type);
+
+                            if !glue.is_empty() {
+                                self.cache.insert(&host, _type, dns_class, &glue, now);
+                            }
+                        }
+                    }
+
+                    let addresses = self.get_cached_addresses(&host, dns_class);
+                    (host, addresses)
+                })
+                .collect();
+
+            return Some(Steps::Referral(cut, servers));
+        }
+
+        //RFC 2308 2.2 - NOERROR WITH NOTHING FOR name, FROM THE ZONE ITSELF
+        let soa = response.get_name_servers().iter()
+            .flat_map(|(_, records)| records.iter())
+            .find_map(|record| record.as_any().downcast_ref::<SoaRecord>());
+
+        if !response.is_authoritative() && soa.is_none() {
+            return None;
+        }
+
+        if let Some(soa) = soa {
+            self.cache.insert_negative(name, _type, dns_class, false, soa.get_ttl().min(soa.get_minimum_ttl()), now);
+        }
+
+        Some(Steps::NoData(response))
+    }
+
+    //THE DEEPEST ZONE CUT THE CACHE KNOWS ABOVE name, WITH WHATEVER ADDRESSES IT HAS FOR THE SERVERS, OTHERWISE THE ROOT HINTS
+    fn get_closest_servers(&mut self, name: &str, dns_class: DnsClasses) -> (String, Servers) {
+        let mut zone = name;
+
+        while !zone.is_empty() {
+            if let Some(Ok(records)) = self.cache.get(zone, Types::Ns, dns_class, Instant::now()) {
+                let servers: Servers = records.iter()
+                    .filter_map(|record| record.as_any().downcast_ref::<NsRecord>()?.get_domain())
+                    .map(|host| {
+                        let host = host.trim_end_matches('.').to_string();
+                        let addresses = self.get_cached_addresses(&host, dns_class);
+                        (host, addresses)
+                    })
+                    .collect();
+
+                if !servers.is_empty() {
+                    return (zone.to_string(), servers);
+                }
+            }
+
+            zone = zone.split_once('.').map(|(_, parent)| parent).unwrap_or_default();
+        }
+
+        (String::new(), self.root_hints.clone())
+    }
+
+    fn get_cached_addresses(&mut self, host: &str, dns_class: DnsClasses) -> Vec<IpAddr> {
+        let mut addresses = Vec::new();
+
+        for _type in [Types::A, Types::Aaaa] {
+            if let Some(Ok(records)) = self.cache.get(host, _type, dns_class, Instant::now()) {
+                addresses.extend(records.iter().filter_map(|record| get_address(record.as_ref())));
+            }
+        }
+
+        addresses
+    }
+
+    //A GLUELESS SERVER'S NAME IS RESOLVED FROM THE TOP LIKE ANY OTHER, ON THE SAME QUERY BUDGET
+    //ONE THAT'S ALREADY BEING LOOKED UP FURTHER OUT CAN ONLY BE FOUND THROUGH ITSELF
+    fn get_glueless_addresses(&mut self, host: &str, dns_class: DnsClasses, iteration: &mut Iteration) -> Result<Vec<IpAddr>, ResolveError> {
+        if iteration.pending.iter().any(|pending| same_name(pending, host)) {
+            return Err(ResolveError::Loop(format!("Name server {} can only be found through itself", host)));
+        }
+
+        if iteration.pending.len() >= self.max_depth {
+            return Err(ResolveError::Loop(format!("Name servers without glue nested more than {} deep at {}", self.max_depth, host)));
+        }
+
+        iteration.pending.push(host.to_string());
+
+        let mut addresses = Vec::new();
+        let mut error = None;
+
+        for _type in [Types::A, Types::Aaaa] {
+            match self.iterate(&DnsQuery::new(host, _type, dns_class), iteration) {
+                Ok(response) => addresses.extend(get_records(&response).into_iter().filter_map(get_address)),
+                Err(e) => error = Some(e)
+            }
+
+            if !addresses.is_empty() || self.check_budget(iteration).is_err() {
+                break;
+            }
+        }
+
+        iteration.pending.pop();
+
+        match error {
+            Some(e) if addresses.is_empty() => Err(e),
+            _ => Ok(addresses)
+        }
+    }
+
+    fn check_budget(&self, iteration: &Iteration) -> Result<(), ResolveError> {
+        if iteration.queries >= self.max_queries {
+            return Err(ResolveError::Loop(format!("More than {} queries", self.max_queries)));
+        }
+
+        Ok(())
+    }
+
+    fn set_lame(&mut self, address: IpAddr, zone: &str) {
+        self.lame.insert((address, zone.to_ascii_lowercase()), Instant::now()+LAME_TTL);
+    }
+
+    fn get_client(&self, address: IpAddr) -> Client {
+        let mut client = Client::new(SocketAddr::new(address, self.port));
+        client.set_timeout(self.timeout);
+        client.set_retries(self.retries);
+        client.set_payload_size(self.payload_size);
+        client.set_tcp_only(self.tcp_only);
+        client
+    }
+}
+
+//THE ROOT IS ABOVE EVERYTHING, OTHERWISE zone HAS TO BE WHOLE LABELS AT THE END OF name
+fn is_subdomain(name: &str, zone: &str) -> bool {
+    let name = name.trim_end_matches('.').to_ascii_lowercase();
+    let zone = zone.trim_end_matches('.').to_ascii_lowercase();
+    zone.is_empty() || name == zone || name.ends_with(&format!(".{}", zone))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::net::{IpAddr, SocketAddr, UdpSocket};
+    use std::sync::OnceLock;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use crate::client::resolve_error::ResolveError;
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::response_codes::ResponseCodes;
+    use crate::messages::inter::types::Types;
+    use crate::messages::message_base::MessageBase;
+    use crate::records::inter::record_base::RecordBase;
+    use crate::records::ns_record::NsRecord;
+    use crate::resolver::dns_resolver::same_name;
+    use crate::resolver::iterative_resolver::{is_subdomain, IterativeResolver};
+    use crate::server::inter::handler_base::{dispatch, HandlerBase};
+    use crate::zone::zone_file::ZoneFile;
+
+    const ROOT: &str = "\
+@ 86400 IN SOA a.root. hostmaster.root. 1 3600 600 86400 60
+test. 86400 IN NS ns.test.
+ns.test. 86400 IN A 127.0.0.2
+";
+
+    //ONLY provider.test ITSELF KNOWS WHERE ns2.provider.test IS, SO glueless.test COMES WITHOUT GLUE
+    //lame.test HAS THREE SERVERS THAT DON'T SERVE IT BEFORE THE ONE THAT DOES, dead.test ONLY HAS ONE THAT DOESN'T
+    const TEST: &str = "\
+@ 3600 IN SOA ns.test. hostmaster.test. 1 3600 600 86400 60
+ns 3600 IN A 127.0.0.2
+example 3600 IN NS ns.example
+ns.example 3600 IN A 127.0.0.3
+provider 3600 IN NS ns.provider
+ns.provider 3600 IN A 127.0.0.4
+glueless 3600 IN NS ns2.provider
+lame 3600 IN NS refused.lame
+lame 3600 IN NS upward.lame
+lame 3600 IN NS sideways.lame
+lame 3600 IN NS ns.lame
+refused.lame 3600 IN A 127.0.0.5
+upward.lame 3600 IN A 127.0.0.6
+sideways.lame 3600 IN A 127.0.0.7
+ns.lame 3600 IN A 127.0.0.4
+dead 3600 IN NS refused.lame
+";
+
+    const EXAMPLE: &str = "\
+@ 300 IN SOA ns.example.test. hostmaster.example.test. 1 3600 600 86400 60
+www 300 IN A 127.0.1.1
+ext 300 IN CNAME www.provider.test.
+dn 300 IN DNAME provider.test.
+loop1 300 IN CNAME loop2
+loop2 300 IN CNAME loop1
+";
+
+    const PROVIDER: &str = "\
+@ 300 IN SOA ns.provider.test. hostmaster.provider.test. 1 3600 600 86400 60
+ns2 300 IN A 127.0.0.4
+www 300 IN A 127.0.2.2
+";
+
+    const GLUELESS: &str = "\
+@ 300 IN SOA ns.provider.test. hostmaster.provider.test. 1 3600 600 86400 60
+host 300 IN A 127.0.3.3
+";
+
+    const LAME: &str = "\
+@ 300 IN SOA ns.lame.test. hostmaster.lame.test. 1 3600 600 86400 60
+www 300 IN A 127.0.4.4
+";
+
+    //ANSWERS FROM THE DEEPEST OF zones THE NAME IS IN - A REFERRAL AT A CUT, OTHERWISE AUTHORITATIVELY
+    //REFUSED FOR ANYTHING IN NONE OF THEM
+    fn authority(zones: Vec<ZoneFile>) -> impl HandlerBase {
+        move |request: &MessageBase| {
+            let question = request.get_queries().remove(0);
+            let name = question.get_query().unwrap();
+
+            let mut response = MessageBase::new(request.get_id());
+            response.set_qr(true);
+            response.add_query(question.clone());
+
+            let Some(zone) = zones.iter().filter(|zone| is_subdomain(&name, zone.get_origin())).max_by_key(|zone| zone.get_origin().len()) else {
+                response.set_response_code(ResponseCodes::Refused);
+                return Some(response);
+            };
+
+            let records = zone.get_records();
+            let find = |owner: &str, _type: Types| -> Vec<Box<dyn RecordBase>> {
+                records.iter()
+                    .filter(|(o, _)| same_name(o, owner))
+                    .flat_map(|(_, records)| records.iter())
+                    .filter(|record| record.get_type() == _type)
+                    .map(|record| record.dyn_clone())
+                    .collect()
+            };
+            let above = |_type: Types| records.iter()
+                .find(|(owner, records)| !same_name(owner, zone.get_origin()) && is_subdomain(&name, owner) && records.iter().any(|record| record.get_type() == _type))
+                .map(|(owner, _)| owner.clone());
+
+            if let Some(cut) = above(Types::Ns) {
+                for ns in find(&cut, Types::Ns) {
+                    let host = ns.as_any().downcast_ref::<NsRecord>().unwrap().get_domain().unwrap();
+
+                    for glue in find(&host, Types::A) {
+                        response.add_additional_records(&host, glue);
+                    }
+
+                    response.add_name_servers(&cut, ns);
+                }
+
+                return Some(response);
+            }
+
+            response.set_authoritative(true);
+
+            match above(Types::Dname).filter(|owner| !same_name(owner, &name) && question.get_type() != Types::Dname) {
+                Some(owner) => find(&owner, Types::Dname).into_iter().for_each(|record| response.add_answers(&owner, record)),
+                None => {
+                    let mut records = find(&name, question.get_type());
+
+                    if records.is_empty() {
+                        records = find(&name, Types::Cname);
+                    }
+
+                    if records.is_empty() {
+                        if !zone.get_records().iter().any(|(owner, _)| is_subdomain(owner, &name)) {
+                            response.set_response_code(ResponseCodes::NameError);
+                        }
+
+                        find(zone.get_origin(), Types::Soa).into_iter().for_each(|record| response.add_name_servers(zone.get_origin(), record));
+                    }
+
+                    records.into_iter().for_each(|record| response.add_answers(&name, record));
+                }
+            }
+
+            Some(response)
+        }
+    }
+
+    //A SERVER THAT ALWAYS HANDS BACK THE SAME REFERRAL, WHATEVER IT'S ASKED
+    fn referral(cut: &'static str, host: &'static str) -> impl HandlerBase {
+        move |request: &MessageBase| {
+            let mut response = MessageBase::new(request.get_id());
+            response.set_qr(true);
+            response.add_query(request.get_queries().remove(0));
+            response.add_name_servers(cut, Box::new(NsRecord::new(DnsClasses::In, 3600, host)));
+            Some(response)
+        }
+    }
+
+    fn serve(socket: UdpSocket, handler: impl HandlerBase + 'static) {
+        let local = socket.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+
+            while let Ok((size, source)) = socket.recv_from(&mut buf) {
+                if let Some((_, response)) = dispatch(&handler, &buf[..size], source, local) {
+                    socket.send_to(&response.encode(), source).unwrap();
+                }
+            }
+        });
+    }
+
+    fn zone(text: &str, origin: &str) -> ZoneFile {
+        ZoneFile::parse(text, origin).unwrap()
+    }
+
+    //ONE HIERARCHY FOR EVERY TEST, ALL ON THE PORT THE ROOT GOT - 127.0.0.1 IS THE ROOT, .2 test, .3 example.test,
+    //.4 provider.test, glueless.test AND lame.test, .5 REFUSES, .6 REFERS BACK UP TO test AND .7 SIDEWAYS TO example.test
+    fn port() -> u16 {
+        static PORT: OnceLock<u16> = OnceLock::new();
+
+        *PORT.get_or_init(|| {
+            let root = UdpSocket::bind("127.0.0.1:0").unwrap();
+            let port = root.local_addr().unwrap().port();
+            let bind = |last: u8| UdpSocket::bind(SocketAddr::from(([127, 0, 0, last], port))).unwrap();
+
+            serve(root, authority(vec![zone(ROOT, ".")]));
+            serve(bind(2), authority(vec![zone(TEST, "test")]));
+            serve(bind(3), authority(vec![zone(EXAMPLE, "example.test")]));
+            serve(bind(4), authority(vec![zone(PROVIDER, "provider.test"), zone(GLUELESS, "glueless.test"), zone(LAME, "lame.test")]));
+            serve(bind(5), authority(Vec::new()));
+            serve(bind(6), referral("test", "ns.test"));
+            serve(bind(7), referral("example.test", "ns.example.test"));
+            port
+        })
+    }
+
+    fn resolver() -> IterativeResolver {
+        let mut resolver = IterativeResolver::new(vec![("a.root".to_string(), vec![IpAddr::from([127, 0, 0, 1])])]);
+        resolver.set_port(port());
+        resolver.set_timeout(Duration::from_secs(1));
+        resolver
+    }
+
+    fn answers(response: &MessageBase) -> Vec<String> {
+        response.get_answers().iter()
+            .flat_map(|(owner, records)| records.iter().map(move |record| format!("{} {}", owner, record.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn referrals_with_glue_are_followed_down_to_the_answer() {
+        let mut resolver = resolver();
+
+        let response = resolver.lookup("www.example.test", Types::A).unwrap();
+        assert!(response.is_authoritative());
+        assert_eq!(answers(&response), vec!["www.example.test 300 IN A 127.0.1.1".to_string()]);
+
+        //THE CUTS ON THE WAY ARE REMEMBERED
+        assert!(matches!(resolver.get_cache_mut().get("example.test", Types::Ns, DnsClasses::In, Instant::now()), Some(Ok(_))));
+
+        assert!(resolver.lookup("www.example.test", Types::Aaaa).unwrap().get_answers().is_empty());
+        assert_eq!(resolver.lookup("missing.example.test", Types::A).err(), Some(ResolveError::NxDomain));
+    }
+
+    #[test]
+    fn glueless_name_server_is_resolved_from_the_root() {
+        let mut resolver = resolver();
+
+        let response = resolver.lookup("host.glueless.test", Types::A).unwrap();
+        assert_eq!(answers(&response), vec!["host.glueless.test 300 IN A 127.0.3.3".to_string()]);
+    }
+
+    #[test]
+    fn cname_is_followed_into_another_zone() {
+        let mut resolver = resolver();
+
+        let response = resolver.lookup("ext.example.test", Types::A).unwrap();
+        assert_eq!(answers(&response), vec![
+            "ext.example.test 300 IN CNAME www.provider.test.".to_string(),
+            "www.provider.test 300 IN A 127.0.2.2".to_string()
+        ]);
+    }
+
+    #[test]
+    fn dname_is_followed_with_the_cname_it_makes() {
+        let mut resolver = resolver();
+
+        let response = resolver.lookup("www.dn.example.test", Types::A).unwrap();
+        assert_eq!(answers(&response), vec![
+            "dn.example.test 300 IN DNAME provider.test.".to_string(),
+            "www.dn.example.test 300 IN CNAME www.provider.test.".to_string(),
+            "www.provider.test 300 IN A 127.0.2.2".to_string()
+        ]);
+    }
+
+    #[test]
+    fn cname_loop_is_an_error() {
+        let mut resolver = resolver();
+        assert!(matches!(resolver.lookup("loop1.example.test", Types::A), Err(ResolveError::Loop(_))));
+    }
+
+    #[test]
+    fn lame_servers_are_skipped_and_remembered() {
+        let mut resolver = resolver();
+
+        let response = resolver.lookup("www.lame.test", Types::A).unwrap();
+        assert_eq!(answers(&response), vec!["www.lame.test 300 IN A 127.0.4.4".to_string()]);
+
+        //REFUSED, A REFERRAL BACK UP TO test AND ONE SIDEWAYS TO example.test
+        for last in [5, 6, 7] {
+            assert!(resolver.is_lame(IpAddr::from([127, 0, 0, last]), "lame.test"));
+        }
+        assert!(!resolver.is_lame(IpAddr::from([127, 0, 0, 4]), "lame.test"));
+
+        assert_eq!(resolver.lookup("www.dead.test", Types::A).err(), Some(ResolveError::LameDelegation("dead.test.".to_string())));
+    }
+
+    #[test]
+    fn query_budget_stops_the_walk() {
+        let mut resolver = resolver();
+        resolver.set_max_queries(2);
+
+        match resolver.lookup("host.glueless.test", Types::A) {
+            Err(ResolveError::Loop(e)) => assert_eq!(e, "More than 2 queries"),
+            _ => panic!("expected the query budget to run out")
+        }
+    }
+
+    #[test]
+    fn glueless_depth_stops_the_walk() {
+        let mut resolver = resolver();
+        resolver.set_max_depth(0);
+
+        match resolver.lookup("host.glueless.test", Types::A) {
+            Err(ResolveError::Loop(e)) => assert!(e.starts_with("Name servers without glue nested more than 0 deep")),
+            _ => panic!("expected the glueless depth to run out")
+        }
+
+        //WITH GLUE ALL THE WAY DOWN THE DEPTH NEVER COMES INTO IT
+        assert!(resolver.lookup("www.example.test", Types::A).is_ok());
+    }
+}
@@ -0,0 +1,11 @@
+pub mod inter;
+pub mod cache;
+pub mod dns_resolver;
+pub mod root_hints;
+pub mod iterative_resolver;
+pub mod resolv_conf;
+pub mod hosts_file;
+pub mod address_selection;
+pub mod srv_selection;
+pub mod mx_selection;
+pub mod stub_resolver;
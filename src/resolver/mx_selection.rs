@@ -0,0 +1,190 @@
+use std::net::IpAddr;
+use crate::client::resolve_error::ResolveError;
+use crate::messages::message_base::MessageBase;
+use crate::records::mx_record::MxRecord;
+use crate::resolver::address_selection::get_owned_addresses;
+use crate::resolver::stub_resolver::get_records;
+use crate::utils::random;
+
+//ONE MAIL EXCHANGER, addresses ARE ONLY WHAT'S OWNED BY host ITSELF, EMPTY IF NOTHING WAS FOUND
+pub struct MxTarget {
+    preference: u16,
+    host: String,
+    addresses: Vec<IpAddr>
+}
+
+impl MxTarget {
+
+    //RFC 5321 5.1 - NO MX RECORDS AT ALL, THE DOMAIN IS ITS OWN MAIL EXCHANGER AT PREFERENCE 0
+    pub fn implicit(domain: &str, addresses: Vec<IpAddr>) -> Self {
+        Self {
+            preference: 0,
+            host: domain.trim_end_matches('.').to_string(),
+            addresses
+        }
+    }
+
+    pub fn get_preference(&self) -> u16 {
+        self.preference
+    }
+
+    pub fn get_host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn set_addresses(&mut self, addresses: Vec<IpAddr>) {
+        self.addresses = addresses;
+    }
+
+    pub fn get_addresses(&self) -> &Vec<IpAddr> {
+        &self.addresses
+    }
+}
+
+//THE MX ANSWER IN THE ORDER ITS HOSTS SHOULD BE TRIED, EACH WITH WHATEVER ADDRESSES THE ADDITIONAL SECTION GAVE FOR IT
+//RFC 7505 - A NULL MX, A LONE "." AT PREFERENCE 0, MEANS THE DOMAIN TAKES NO MAIL, THAT'S NoService AND NOT AN EMPTY LIST
+//ANY OTHER "." SHOULDN'T BE THERE AND IS DROPPED, NO MX RECORDS GIVES AN EMPTY LIST FOR THE IMPLICIT MX TO TAKE OVER
+pub fn get_mx_targets(response: &MessageBase) -> Result<Vec<MxTarget>, ResolveError> {
+    let records: Vec<MxRecord> = get_records(response).into_iter()
+        .filter_map(|record| record.as_any().downcast_ref::<MxRecord>())
+        .cloned()
+        .collect();
+
+    let (null, records): (Vec<MxRecord>, Vec<MxRecord>) = records.into_iter()
+        .partition(|record| record.get_domain().unwrap_or_default().trim_end_matches('.').is_empty());
+
+    if records.is_empty() && null.iter().any(|record| record.get_priority() == 0) {
+        return Err(ResolveError::NoService);
+    }
+
+    Ok(order_mx(records).into_iter().map(|record| {
+        let host = record.get_domain().unwrap_or_default().trim_end_matches('.').to_string();
+
+        MxTarget {
+            preference: record.get_priority(),
+            addresses: get_owned_addresses(response.get_additional_records(), &host),
+            host
+        }
+    }).collect())
+}
+
+pub fn order_mx(records: Vec<MxRecord>) -> Vec<MxRecord> {
+    order_mx_with(records, |max| u32::from_be_bytes(random::gen_array()) % (max+1))
+}
+
+//RFC 5321 5.1 - LOWEST PREFERENCE FIRST, HOSTS SHARING A PREFERENCE SHUFFLED SO THE LOAD SPREADS OVER THEM
+//random(max) GIVES A NUMBER FROM 0 TO max, EACH GROUP IS A FISHER-YATES SHUFFLE DRIVEN BY IT
+pub fn order_mx_with<R: FnMut(u32) -> u32>(mut records: Vec<MxRecord>, mut random: R) -> Vec<MxRecord> {
+    records.sort_by_key(|record| record.get_priority());
+
+    let mut start = 0;
+
+    while start < records.len() {
+        let priority = records[start].get_priority();
+        let end = records[start..].iter().position(|record| record.get_priority() != priority).map_or(records.len(), |i| start+i);
+
+        for i in (start+1..end).rev() {
+            let j = start+random((i-start) as u32) as usize;
+            records.swap(i, j.min(i));
+        }
+
+        start = end;
+    }
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::net::IpAddr;
+    use crate::client::resolve_error::ResolveError;
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::types::Types;
+    use crate::messages::message_base::MessageBase;
+    use crate::records::a_record::ARecord;
+    use crate::records::cname_record::CNameRecord;
+    use crate::records::mx_record::MxRecord;
+    use crate::resolver::mx_selection::{get_mx_targets, order_mx_with};
+    use crate::utils::dns_query::DnsQuery;
+
+    fn mx(preference: u16, host: &str) -> MxRecord {
+        MxRecord::new(DnsClasses::In, 300, preference, host)
+    }
+
+    fn hosts(records: &[MxRecord]) -> Vec<String> {
+        records.iter().map(|record| record.get_domain().unwrap()).collect()
+    }
+
+    fn response(records: Vec<MxRecord>) -> MessageBase {
+        let mut response = MessageBase::new(1);
+        response.set_qr(true);
+        response.add_query(DnsQuery::new("example.com", Types::Mx, DnsClasses::In));
+
+        for record in records {
+            response.add_answers("example.com", Box::new(record));
+        }
+
+        response
+    }
+
+    #[test]
+    fn lowest_preference_first_and_shuffled_within_it() {
+        let records = vec![mx(10, "a.example.com"), mx(20, "x.example.com"), mx(10, "b.example.com"), mx(5, "y.example.com"), mx(10, "c.example.com")];
+
+        //THE 10 GROUP IS a b c, SWAPPING THE LAST WITH THE FIRST THEN LEAVING THE MIDDLE GIVES c b a
+        let mut picks = vec![0, 1].into_iter();
+        let mut maxes = Vec::new();
+        let ordered = order_mx_with(records, |max| {
+            maxes.push(max);
+            picks.next().unwrap()
+        });
+
+        assert_eq!(hosts(&ordered), vec!["y.example.com", "c.example.com", "b.example.com", "a.example.com", "x.example.com"]);
+        assert_eq!(maxes, vec![2, 1]);
+    }
+
+    #[test]
+    fn lone_preferences_never_ask_for_a_random_number() {
+        let records = vec![mx(30, "c.example.com"), mx(10, "a.example.com"), mx(20, "b.example.com")];
+        let ordered = order_mx_with(records, |_| unreachable!());
+        assert_eq!(hosts(&ordered), vec!["a.example.com", "b.example.com", "c.example.com"]);
+    }
+
+    #[test]
+    fn null_mx_is_no_service() {
+        let response = response(vec![mx(0, ".")]);
+        assert_eq!(get_mx_targets(&response).err(), Some(ResolveError::NoService));
+    }
+
+    #[test]
+    fn dot_beside_real_hosts_is_dropped() {
+        let mixed = response(vec![mx(10, "."), mx(20, "mail.example.com")]);
+        let targets = get_mx_targets(&mixed).unwrap();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].get_host(), "mail.example.com");
+        assert_eq!(targets[0].get_preference(), 20);
+
+        //A "." THAT ISN'T AT PREFERENCE 0 ISN'T A NULL MX, IT'S JUST DROPPED
+        let stray = response(vec![mx(10, ".")]);
+        assert!(get_mx_targets(&stray).unwrap().is_empty());
+    }
+
+    #[test]
+    fn no_mx_leaves_it_to_the_implicit_mx() {
+        assert!(get_mx_targets(&response(Vec::new())).unwrap().is_empty());
+    }
+
+    #[test]
+    fn cname_at_the_host_is_not_followed() {
+        let mut response = response(vec![mx(10, "alias.example.com."), mx(20, "mail.example.com.")]);
+        response.add_additional_records("alias.example.com.", Box::new(CNameRecord::new(DnsClasses::In, 300, "real.example.com.")));
+        response.add_additional_records("real.example.com.", Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 1]))));
+        response.add_additional_records("mail.example.com.", Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 2]))));
+
+        let targets = get_mx_targets(&response).unwrap();
+        assert_eq!(targets[0].get_host(), "alias.example.com");
+        assert!(targets[0].get_addresses().is_empty());
+        assert_eq!(targets[1].get_addresses(), &vec![IpAddr::from([192, 0, 2, 2])]);
+    }
+}
@@ -0,0 +1,321 @@
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV6};
+use std::path::Path;
+use std::time::Duration;
+use crate::messages::inter::dns_classes::DnsClasses;
+use crate::messages::inter::types::Types;
+use crate::utils::dns_query::DnsQuery;
+
+//RESOLV.CONF(5) - THE DEFAULTS AND CEILINGS GLIBC USES
+const DEFAULT_NDOTS: u8 = 1;
+const MAX_NDOTS: u8 = 15;
+const DEFAULT_TIMEOUT: u64 = 5;
+const MAX_TIMEOUT: u64 = 30;
+const DEFAULT_ATTEMPTS: u32 = 2;
+const MAX_ATTEMPTS: u32 = 5;
+
+//MAXNS - ANY nameserver LINE PAST THE THIRD IS IGNORED
+const MAX_NAMESERVERS: usize = 3;
+
+#[derive(Clone, Debug)]
+pub struct ResolvConf {
+    nameservers: Vec<SocketAddr>,
+    search: Vec<String>,
+    ndots: u8,
+    timeout: Duration,
+    attempts: u32,
+    rotate: bool,
+    edns0: bool,
+    use_vc: bool
+}
+
+impl Default for ResolvConf {
+
+    //WHAT GLIBC FALLS BACK TO WITHOUT A FILE, A NAMESERVER ON THIS MACHINE
+    fn default() -> Self {
+        Self {
+            nameservers: vec![SocketAddr::from((Ipv4Addr::LOCALHOST, 53))],
+            search: Vec::new(),
+            ndots: DEFAULT_NDOTS,
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT),
+            attempts: DEFAULT_ATTEMPTS,
+            rotate: false,
+            edns0: false,
+            use_vc: false
+        }
+    }
+}
+
+impl ResolvConf {
+
+    //LIKE GLIBC, A LINE THAT DOESN'T MAKE SENSE IS SKIPPED RATHER THAN FAILING THE WHOLE FILE
+    pub fn parse(text: &str) -> Self {
+        let mut conf = Self {
+            nameservers: Vec::new(),
+            ..Default::default()
+        };
+
+        for line in text.lines() {
+            let line = match line.find(['#', ';']) {
+                Some(i) => &line[..i],
+                None => line
+            };
+
+            let mut tokens = line.split_whitespace();
+
+            match tokens.next() {
+                Some("nameserver") => {
+                    if let Some(nameserver) = tokens.next().and_then(parse_nameserver) {
+                        if conf.nameservers.len() < MAX_NAMESERVERS {
+                            conf.nameservers.push(nameserver);
+                        }
+                    }
+                }
+                //domain AND search REPLACE EACH OTHER, THE LAST ONE IN THE FILE WINS
+                Some("domain") => {
+                    if let Some(domain) = tokens.next() {
+                        conf.search = vec![domain.trim_end_matches('.').to_string()];
+                    }
+                }
+                Some("search") => {
+                    conf.search = tokens.map(|domain| domain.trim_end_matches('.').to_string()).collect();
+                }
+                Some("options") => {
+                    for option in tokens {
+                        conf.parse_option(option);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if conf.nameservers.is_empty() {
+            conf.nameservers = Self::default().nameservers;
+        }
+
+        conf
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+        Ok(Self::parse(&text))
+    }
+
+    pub fn add_nameserver(&mut self, nameserver: SocketAddr) {
+        self.nameservers.push(nameserver);
+    }
+
+    pub fn get_nameservers(&self) -> &Vec<SocketAddr> {
+        &self.nameservers
+    }
+
+    pub fn set_search(&mut self, search: Vec<String>) {
+        self.search = search;
+    }
+
+    pub fn get_search(&self) -> &Vec<String> {
+        &self.search
+    }
+
+    pub fn set_ndots(&mut self, ndots: u8) {
+        self.ndots = ndots.min(MAX_NDOTS);
+    }
+
+    pub fn get_ndots(&self) -> u8 {
+        self.ndots
+    }
+
+    //PER ATTEMPT AT ONE NAMESERVER
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    //HOW MANY TIMES EACH NAMESERVER IS TRIED BEFORE GIVING UP
+    pub fn set_attempts(&mut self, attempts: u32) {
+        self.attempts = attempts;
+    }
+
+    pub fn get_attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    //SPREAD LOOKUPS OVER THE NAMESERVERS INSTEAD OF ALWAYS ASKING THE FIRST ONE FIRST
+    pub fn set_rotate(&mut self, rotate: bool) {
+        self.rotate = rotate;
+    }
+
+    pub fn is_rotate(&self) -> bool {
+        self.rotate
+    }
+
+    pub fn set_edns0(&mut self, edns0: bool) {
+        self.edns0 = edns0;
+    }
+
+    pub fn is_edns0(&self) -> bool {
+        self.edns0
+    }
+
+    pub fn set_use_vc(&mut self, use_vc: bool) {
+        self.use_vc = use_vc;
+    }
+
+    pub fn is_use_vc(&self) -> bool {
+        self.use_vc
+    }
+
+    //GLIBC RES_NSEARCH - A NAME WITH A TRAILING DOT IS ONLY EVER ITSELF, ONE WITH AT LEAST ndots DOTS IS TRIED AS IT IS
+    //BEFORE THE SEARCH LIST, ANYTHING SHORTER ONLY AFTER EVERY SEARCH DOMAIN HAS FAILED
+    pub fn get_candidates(&self, name: &str) -> Vec<String> {
+        if name.ends_with('.') {
+            return vec![name.trim_end_matches('.').to_string()];
+        }
+
+        let dots = name.matches('.').count();
+        let mut candidates = Vec::new();
+
+        if dots >= self.ndots as usize {
+            candidates.push(name.to_string());
+        }
+
+        for domain in &self.search {
+            let candidate = match domain.is_empty() {
+                true => name.to_string(),
+                false => format!("{}.{}", name, domain)
+            };
+
+            if !candidates.iter().any(|c: &String| c.eq_ignore_ascii_case(&candidate)) {
+                candidates.push(candidate);
+            }
+        }
+
+        if dots < self.ndots as usize && !candidates.iter().any(|c| c.eq_ignore_ascii_case(name)) {
+            candidates.push(name.to_string());
+        }
+
+        candidates
+    }
+
+    //ONE QUERY PER CANDIDATE, IN THE ORDER THEY SHOULD BE ASKED
+    pub fn get_queries(&self, name: &str, _type: Types, dns_class: DnsClasses) -> Vec<DnsQuery> {
+        self.get_candidates(name).iter()
+            .map(|candidate| DnsQuery::new(candidate, _type, dns_class))
+            .collect()
+    }
+
+    fn parse_option(&mut self, option: &str) {
+        let (name, value) = match option.split_once(':') {
+            Some((name, value)) => (name, value.parse::<u64>().ok()),
+            None => (option, None)
+        };
+
+        match (name, value) {
+            ("ndots", Some(ndots)) => self.ndots = ndots.min(MAX_NDOTS as u64) as u8,
+            ("timeout", Some(timeout)) => self.timeout = Duration::from_secs(timeout.clamp(1, MAX_TIMEOUT)),
+            ("attempts", Some(attempts)) => self.attempts = attempts.clamp(1, MAX_ATTEMPTS as u64) as u32,
+            ("rotate", _) => self.rotate = true,
+            ("edns0", _) => self.edns0 = true,
+            ("use-vc", _) => self.use_vc = true,
+            _ => {}
+        }
+    }
+}
+
+//AN IPV6 LINK LOCAL ADDRESS CAN CARRY ITS SCOPE - fe80::1%2, AN INTERFACE NAME CAN'T BE LOOKED UP FROM HERE SO IT'S DROPPED
+fn parse_nameserver(address: &str) -> Option<SocketAddr> {
+    let (address, scope) = match address.split_once('%') {
+        Some((address, scope)) => (address, scope.parse::<u32>().unwrap_or(0)),
+        None => (address, 0)
+    };
+
+    match address.parse::<IpAddr>().ok()? {
+        IpAddr::V4(address) => Some(SocketAddr::from((address, 53))),
+        IpAddr::V6(address) => Some(SocketAddr::V6(SocketAddrV6::new(address, 53, 0, scope)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::net::SocketAddr;
+    use std::time::Duration;
+    use crate::resolver::resolv_conf::ResolvConf;
+
+    #[test]
+    fn parse_skips_comments_and_extra_nameservers() {
+        let conf = ResolvConf::parse("\
+# a comment
+; another
+nameserver 192.0.2.1 # trailing comment
+nameserver not-an-address
+nameserver 2001:db8::1
+nameserver 192.0.2.3
+nameserver 192.0.2.4
+");
+
+        assert_eq!(conf.get_nameservers(), &vec![
+            "192.0.2.1:53".parse::<SocketAddr>().unwrap(),
+            "[2001:db8::1]:53".parse().unwrap(),
+            "192.0.2.3:53".parse().unwrap()
+        ]);
+
+        //NO USABLE NAMESERVER FALLS BACK TO THIS MACHINE
+        assert_eq!(ResolvConf::parse("nameserver bogus").get_nameservers(), &vec!["127.0.0.1:53".parse::<SocketAddr>().unwrap()]);
+    }
+
+    #[test]
+    fn nameserver_scope() {
+        let conf = ResolvConf::parse("nameserver fe80::1%2\nnameserver fe80::2%eth0");
+
+        match conf.get_nameservers()[..] {
+            [SocketAddr::V6(first), SocketAddr::V6(second)] => {
+                assert_eq!(first.scope_id(), 2);
+                assert_eq!(second.scope_id(), 0);
+                assert_eq!(second.ip().to_string(), "fe80::2");
+            }
+            _ => panic!("expected two IPv6 nameservers")
+        }
+    }
+
+    #[test]
+    fn last_domain_or_search_wins() {
+        assert_eq!(ResolvConf::parse("search a.example. b.example\ndomain c.example").get_search(), &vec!["c.example".to_string()]);
+        assert_eq!(ResolvConf::parse("domain c.example\nsearch a.example b.example.").get_search(), &vec!["a.example".to_string(), "b.example".to_string()]);
+    }
+
+    #[test]
+    fn options_are_clamped() {
+        let conf = ResolvConf::parse("options ndots:40 timeout:0 attempts:9 rotate edns0 use-vc bogus:1");
+        assert_eq!(conf.get_ndots(), 15);
+        assert_eq!(conf.get_timeout(), Duration::from_secs(1));
+        assert_eq!(conf.get_attempts(), 5);
+        assert!(conf.is_rotate() && conf.is_edns0() && conf.is_use_vc());
+
+        let conf = ResolvConf::parse("options timeout:90 attempts:0 ndots:x");
+        assert_eq!(conf.get_timeout(), Duration::from_secs(30));
+        assert_eq!(conf.get_attempts(), 1);
+        assert_eq!(conf.get_ndots(), 1);
+        assert!(!conf.is_rotate());
+    }
+
+    #[test]
+    fn candidates_follow_ndots() {
+        let conf = ResolvConf::parse("search a.example b.example\noptions ndots:2");
+
+        //FEWER DOTS THAN ndots - THE SEARCH LIST FIRST
+        assert_eq!(conf.get_candidates("www.host"), vec!["www.host.a.example", "www.host.b.example", "www.host"]);
+
+        //AT LEAST ndots - AS IT IS FIRST
+        assert_eq!(conf.get_candidates("www.host.net"), vec!["www.host.net", "www.host.net.a.example", "www.host.net.b.example"]);
+
+        //A TRAILING DOT IS ONLY EVER ITSELF
+        assert_eq!(conf.get_candidates("www.host."), vec!["www.host"]);
+
+        let conf = ResolvConf::parse("search a.example\noptions ndots:0");
+        assert_eq!(conf.get_candidates("host"), vec!["host", "host.a.example"]);
+    }
+}
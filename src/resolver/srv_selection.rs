@@ -0,0 +1,199 @@
+use std::net::IpAddr;
+use crate::client::resolve_error::ResolveError;
+use crate::messages::message_base::MessageBase;
+use crate::records::srv_record::SrvRecord;
+use crate::resolver::address_selection::get_owned_addresses;
+use crate::resolver::stub_resolver::get_records;
+use crate::utils::random;
+
+//ONE SRV RECORD READY TO CONNECT TO, addresses ARE WHAT THE ADDITIONAL SECTION HAD FOR THE TARGET, EMPTY IF NOTHING
+pub struct SrvTarget {
+    priority: u16,
+    weight: u16,
+    port: u16,
+    target: String,
+    addresses: Vec<IpAddr>
+}
+
+impl SrvTarget {
+
+    pub fn get_priority(&self) -> u16 {
+        self.priority
+    }
+
+    pub fn get_weight(&self) -> u16 {
+        self.weight
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn get_target(&self) -> &str {
+        &self.target
+    }
+
+    pub fn set_addresses(&mut self, addresses: Vec<IpAddr>) {
+        self.addresses = addresses;
+    }
+
+    pub fn get_addresses(&self) -> &Vec<IpAddr> {
+        &self.addresses
+    }
+}
+
+//THE SRV ANSWER IN THE ORDER ITS TARGETS SHOULD BE TRIED, EACH WITH WHATEVER ADDRESSES THE ADDITIONAL SECTION GAVE FOR IT
+//RFC 2782 - A LONE TARGET OF "." MEANS THE SERVICE IS DECIDEDLY NOT AVAILABLE, THAT'S NoService AND NOT AN EMPTY LIST
+pub fn get_srv_targets(response: &MessageBase) -> Result<Vec<SrvTarget>, ResolveError> {
+    let records: Vec<SrvRecord> = get_records(response).into_iter()
+        .filter_map(|record| record.as_any().downcast_ref::<SrvRecord>())
+        .cloned()
+        .collect();
+
+    let (unavailable, records): (Vec<SrvRecord>, Vec<SrvRecord>) = records.into_iter()
+        .partition(|record| record.get_target().unwrap_or_default().trim_end_matches('.').is_empty());
+
+    if records.is_empty() && !unavailable.is_empty() {
+        return Err(ResolveError::NoService);
+    }
+
+    Ok(order_srv(records).into_iter().map(|record| {
+        let target = record.get_target().unwrap_or_default().trim_end_matches('.').to_string();
+
+        SrvTarget {
+            priority: record.get_priority(),
+            weight: record.get_weight(),
+            port: record.get_port(),
+            //ONLY RECORDS OWNED BY THE TARGET ITSELF, RFC 2782 SAYS THE TARGET MUST NOT BE AN ALIAS
+            addresses: get_owned_addresses(response.get_additional_records(), &target),
+            target
+        }
+    }).collect())
+}
+
+pub fn order_srv(records: Vec<SrvRecord>) -> Vec<SrvRecord> {
+    order_srv_with(records, |max| u32::from_be_bytes(random::gen_array()) % (max+1))
+}
+
+//RFC 2782 - LOWEST PRIORITY FIRST, AND WITHIN A PRIORITY A RUNNING SUM OF WEIGHTS WITH THE ZERO WEIGHTS AT THE FRONT,
+//EACH PICK IS THE FIRST RECORD WHOSE RUNNING SUM REACHES A RANDOM NUMBER FROM 0 TO THE TOTAL - random(max) GIVES THAT NUMBER
+pub fn order_srv_with<R: FnMut(u32) -> u32>(mut records: Vec<SrvRecord>, mut random: R) -> Vec<SrvRecord> {
+    records.sort_by_key(|record| (record.get_priority(), record.get_weight() != 0));
+
+    let mut ordered = Vec::with_capacity(records.len());
+
+    while !records.is_empty() {
+        let priority = records[0].get_priority();
+        let end = records.iter().position(|record| record.get_priority() != priority).unwrap_or(records.len());
+        let mut group: Vec<SrvRecord> = records.drain(..end).collect();
+
+        while !group.is_empty() {
+            let total: u32 = group.iter().map(|record| record.get_weight() as u32).sum();
+            let pick = random(total);
+
+            let mut sum = 0;
+            let i = group.iter().position(|record| {
+                sum += record.get_weight() as u32;
+                sum >= pick
+            }).unwrap_or(0);
+
+            ordered.push(group.remove(i));
+        }
+    }
+
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::net::IpAddr;
+    use crate::client::resolve_error::ResolveError;
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::types::Types;
+    use crate::messages::message_base::MessageBase;
+    use crate::records::a_record::ARecord;
+    use crate::records::cname_record::CNameRecord;
+    use crate::records::srv_record::SrvRecord;
+    use crate::resolver::srv_selection::{get_srv_targets, order_srv_with};
+    use crate::utils::dns_query::DnsQuery;
+
+    fn srv(priority: u16, weight: u16, target: &str) -> SrvRecord {
+        SrvRecord::new(DnsClasses::In, false, 300, priority, weight, 5060, target)
+    }
+
+    fn targets(records: &[SrvRecord]) -> Vec<String> {
+        records.iter().map(|record| record.get_target().unwrap()).collect()
+    }
+
+    fn response(records: Vec<SrvRecord>) -> MessageBase {
+        let mut response = MessageBase::new(1);
+        response.set_qr(true);
+        response.add_query(DnsQuery::new("_sip._tcp.example.com", Types::Srv, DnsClasses::In));
+
+        for record in records {
+            response.add_answers("_sip._tcp.example.com", Box::new(record));
+        }
+
+        response
+    }
+
+    #[test]
+    fn lowest_priority_first() {
+        let records = vec![srv(20, 0, "b.example.com"), srv(30, 0, "c.example.com"), srv(10, 0, "a.example.com")];
+        let ordered = order_srv_with(records, |_| 0);
+        assert_eq!(targets(&ordered), vec!["a.example.com", "b.example.com", "c.example.com"]);
+    }
+
+    #[test]
+    fn zero_weights_lead_their_priority() {
+        let records = vec![srv(10, 5, "heavy.example.com"), srv(10, 0, "zero1.example.com"), srv(10, 0, "zero2.example.com")];
+        let ordered = order_srv_with(records, |_| 0);
+        assert_eq!(targets(&ordered), vec!["zero1.example.com", "zero2.example.com", "heavy.example.com"]);
+    }
+
+    #[test]
+    fn weighted_pick_follows_the_running_sum() {
+        let records = vec![srv(10, 10, "b.example.com"), srv(10, 20, "c.example.com"), srv(10, 0, "a.example.com")];
+
+        //RUNNING SUMS ARE a=0 b=10 c=30, 15 LANDS ON c, THEN 0 ON a AND b IS ALL THAT'S LEFT
+        let mut picks = vec![15, 0, 10].into_iter();
+        let mut totals = Vec::new();
+        let ordered = order_srv_with(records, |max| {
+            totals.push(max);
+            picks.next().unwrap()
+        });
+
+        assert_eq!(targets(&ordered), vec!["c.example.com", "a.example.com", "b.example.com"]);
+        assert_eq!(totals, vec![30, 10, 10]);
+    }
+
+    #[test]
+    fn lone_dot_is_no_service() {
+        let response = response(vec![srv(0, 0, ".")]);
+        assert_eq!(get_srv_targets(&response).err(), Some(ResolveError::NoService));
+    }
+
+    #[test]
+    fn dot_beside_real_targets_is_dropped() {
+        let response = response(vec![srv(0, 0, "."), srv(10, 0, "sip.example.com")]);
+        let targets = get_srv_targets(&response).unwrap();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].get_target(), "sip.example.com");
+        assert_eq!(targets[0].get_port(), 5060);
+    }
+
+    #[test]
+    fn additional_addresses_only_for_their_owner() {
+        let mut response = response(vec![srv(10, 0, "a.example.com."), srv(20, 0, "b.example.com.")]);
+        response.add_additional_records("A.example.com.", Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 1]))));
+        response.add_additional_records("b.example.com.", Box::new(CNameRecord::new(DnsClasses::In, 300, "c.example.com.")));
+        response.add_additional_records("c.example.com.", Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 3]))));
+
+        let targets = get_srv_targets(&response).unwrap();
+        assert_eq!(targets[0].get_target(), "a.example.com");
+        assert_eq!(targets[0].get_addresses(), &vec![IpAddr::from([192, 0, 2, 1])]);
+        assert_eq!(targets[1].get_target(), "b.example.com");
+        assert!(targets[1].get_addresses().is_empty());
+    }
+}
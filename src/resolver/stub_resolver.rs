@@ -0,0 +1,664 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::{IpAddr, SocketAddr, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::client::resolve_error::ResolveError;
+use crate::client::tcp_transport::{connect, map_error, read_message, remaining};
+use crate::client::udp_transport;
+use crate::messages::inter::dns_classes::DnsClasses;
+use crate::messages::inter::transports::Transports;
+use crate::messages::inter::types::Types;
+use crate::messages::message_base::MessageBase;
+use crate::records::a_record::ARecord;
+use crate::records::aaaa_record::AAAARecord;
+use crate::records::cname_record::CNameRecord;
+use crate::records::inter::record_base::RecordBase;
+use crate::records::ptr_record::PtrRecord;
+use crate::resolver::address_selection::{get_owned_addresses, sort_addresses};
+use crate::resolver::dns_resolver::Resolver;
+use crate::resolver::hosts_file::HostsFile;
+use crate::resolver::inter::resolver_events::ResolverEvents;
+use crate::resolver::mx_selection::{get_mx_targets, MxTarget};
+use crate::resolver::resolv_conf::ResolvConf;
+use crate::resolver::srv_selection::{get_srv_targets, SrvTarget};
+use crate::utils::dns_query::DnsQuery;
+use crate::utils::name::Name;
+
+//DNS FLAG DAY 2020 - ONLY ADVERTISED WHEN RESOLV.CONF ASKS FOR EDNS0
+const EDNS0_PAYLOAD_SIZE: u16 = 1232;
+
+const SYSTEM_RESOLV_CONF: &str = "/etc/resolv.conf";
+const SYSTEM_HOSTS_FILE: &str = "/etc/hosts";
+
+type Received = (Vec<u8>, SocketAddr, Transports);
+
+//ONE IDLE TCP CONNECTION PER NAMESERVER, KEPT BETWEEN LOOKUPS
+type Connections = Arc<Mutex<HashMap<SocketAddr, TcpStream>>>;
+
+//THE SYSTEM'S VIEW OF DNS - RESOLV.CONF'S NAMESERVERS, OPTIONS AND SEARCH LIST, WITH THE SANS-IO Resolver DOING THE WORK
+//EVERY QUERY ON THE WIRE IS ITS OWN SHORT LIVED THREAD, SO LOOKUPS ASKED FOR TOGETHER REALLY ARE IN FLIGHT TOGETHER
+pub struct StubResolver {
+    resolver: Resolver,
+    resolv_conf: ResolvConf,
+    hosts_file: Option<HostsFile>,
+    connections: Connections
+}
+
+impl StubResolver {
+
+    pub fn new(resolv_conf: ResolvConf) -> Self {
+        let nameservers = resolv_conf.get_nameservers();
+
+        let mut resolver = Resolver::new(nameservers[0]);
+        for nameserver in &nameservers[1..] {
+            resolver.add_server(*nameserver);
+        }
+
+        //GLIBC TRIES EVERY NAMESERVER attempts TIMES, THE RESOLVER COUNTS RETRIES ACROSS ALL OF THEM
+        resolver.set_retries((resolv_conf.get_attempts().max(1)*nameservers.len() as u32).saturating_sub(1));
+        resolver.set_timeout(resolv_conf.get_timeout());
+        resolver.set_rotate(resolv_conf.is_rotate());
+        resolver.set_tcp_only(resolv_conf.is_use_vc());
+        resolver.set_payload_size(match resolv_conf.is_edns0() {
+            true => Some(EDNS0_PAYLOAD_SIZE),
+            false => None
+        });
+
+        Self {
+            resolver,
+            resolv_conf,
+            hosts_file: None,
+            connections: Arc::new(Mutex::new(HashMap::new()))
+        }
+    }
+
+    pub fn from_resolv_conf(path: &Path) -> Result<Self, String> {
+        Ok(Self::new(ResolvConf::from_file(path)?))
+    }
+
+    //WHAT GETADDRINFO WOULD USE - /etc/hosts FIRST, THEN /etc/resolv.conf, OR A NAMESERVER ON THIS MACHINE WITHOUT ONE
+    pub fn system() -> Self {
+        let mut resolver = Self::new(ResolvConf::from_file(Path::new(SYSTEM_RESOLV_CONF)).unwrap_or_default());
+        resolver.set_hosts_file(Some(HostsFile::new(Path::new(SYSTEM_HOSTS_FILE))));
+        resolver
+    }
+
+    //ASKED BEFORE THE NETWORK FOR A, AAAA AND PTR, None GOES STRAIGHT TO THE NAMESERVERS
+    pub fn set_hosts_file(&mut self, hosts_file: Option<HostsFile>) {
+        self.hosts_file = hosts_file;
+    }
+
+    pub fn get_hosts_file(&self) -> Option<&HostsFile> {
+        self.hosts_file.as_ref()
+    }
+
+    pub fn get_resolv_conf(&self) -> &ResolvConf {
+        &self.resolv_conf
+    }
+
+    //FOR THE CACHE, OR ANYTHING ELSE RESOLV.CONF HAS NO SAY IN
+    pub fn get_resolver_mut(&mut self) -> &mut Resolver {
+        &mut self.resolver
+    }
+
+    //THE HOSTS FILE SEES name EXACTLY AS GIVEN, ONLY THE NETWORK GOES THROUGH THE SEARCH LIST
+    pub fn lookup(&mut self, name: &str, _type: Types) -> Result<MessageBase, ResolveError> {
+        if let Some(hosts_file) = self.hosts_file.as_mut() {
+            if let Some(response) = hosts_file.query(&DnsQuery::new(name, _type, DnsClasses::In)) {
+                return Ok(response);
+            }
+        }
+
+        Ok(self.search(name, &[_type])?.remove(0))
+    }
+
+    //A AND AAAA ASKED TOGETHER, CNAMES IN THE ANSWER FOLLOWED, DUPLICATES DROPPED AND THE REST IN RFC 6724 ORDER
+    //AN ADDRESS IS ITS OWN ANSWER AND A NAME THAT EXISTS WITHOUT ANY ADDRESSES IS AN EMPTY ONE, NOT AN ERROR
+    pub fn lookup_ip(&mut self, name: &str) -> Result<Vec<IpAddr>, ResolveError> {
+        if let Ok(address) = name.parse::<IpAddr>() {
+            return Ok(vec![address]);
+        }
+
+        let mut responses = Vec::new();
+
+        if let Some(hosts_file) = self.hosts_file.as_mut() {
+            responses.extend(hosts_file.query(&DnsQuery::new(name, Types::A, DnsClasses::In)));
+            responses.extend(hosts_file.query(&DnsQuery::new(name, Types::Aaaa, DnsClasses::In)));
+        }
+
+        if responses.is_empty() {
+            responses = self.search(name, &[Types::A, Types::Aaaa])?;
+        }
+
+        let mut addresses: Vec<IpAddr> = Vec::new();
+
+        for response in &responses {
+            for record in get_records(response) {
+                let address = match record.get_type() {
+                    Types::A => record.as_any().downcast_ref::<ARecord>().and_then(|record| record.get_address()),
+                    Types::Aaaa => record.as_any().downcast_ref::<AAAARecord>().and_then(|record| record.get_address()),
+                    _ => None
+                };
+
+                if let Some(address) = address {
+                    if !addresses.contains(&address) {
+                        addresses.push(address);
+                    }
+                }
+            }
+        }
+
+        sort_addresses(&mut addresses);
+        Ok(addresses)
+    }
+
+    //WHAT ToSocketAddrs GIVES FOR (name, port), IN THE ORDER THEY SHOULD BE TRIED
+    pub fn lookup_socket_addrs(&mut self, name: &str, port: u16) -> Result<Vec<SocketAddr>, ResolveError> {
+        Ok(self.lookup_ip(name)?.into_iter().map(|address| SocketAddr::new(address, port)).collect())
+    }
+
+    //THE PTR TARGETS FOR address, ASKED AS AN ABSOLUTE NAME SO THE SEARCH LIST NEVER GETS TACKED ON
+    //RFC 2317 DELEGATIONS ANSWER WITH A CNAME INTO THE CLASSLESS ZONE, THE PTRS ARE WHEREVER IT LEADS
+    pub fn reverse_lookup(&mut self, address: IpAddr) -> Result<Vec<String>, ResolveError> {
+        let response = self.lookup(&format!("{}.", Name::from_ip(address)), Types::Ptr)?;
+
+        Ok(get_records(&response).iter()
+            .filter_map(|record| record.as_any().downcast_ref::<PtrRecord>())
+            .filter_map(|record| record.get_domain())
+            .collect())
+    }
+
+    //_service._protocol.name IN THE ORDER TO TRY IT, A TARGET THE ADDITIONAL SECTION SAID NOTHING ABOUT IS LOOKED UP ON ITS OWN
+    //NO SRV RECORDS IS AN EMPTY LIST, SO THE CALLER CAN FALL BACK TO WHATEVER THE PROTOCOL DOES WITHOUT THEM
+    pub fn lookup_srv(&mut self, service: &str, protocol: &str, name: &str) -> Result<Vec<SrvTarget>, ResolveError> {
+        let name = format!("_{}._{}.{}", service.trim_start_matches('_'), protocol.trim_start_matches('_'), name);
+        let response = self.lookup(&name, Types::Srv)?;
+        let mut targets = get_srv_targets(&response)?;
+
+        for target in targets.iter_mut() {
+            let mut addresses = target.get_addresses().clone();
+
+            match addresses.is_empty() {
+                true => addresses = self.lookup_ip(&format!("{}.", target.get_target())).unwrap_or_default(),
+                false => sort_addresses(&mut addresses)
+            }
+
+            target.set_addresses(addresses);
+        }
+
+        Ok(targets)
+    }
+
+    //RFC 5321 5.1 - WHERE MAIL FOR domain GOES, IN THE ORDER TO TRY IT, ALWAYS ASKED AS AN ABSOLUTE NAME
+    //NO MX RECORDS FALLS BACK TO THE DOMAIN'S OWN ADDRESSES, A HOST WITH NO ADDRESSES STAYS IN THE LIST BUT IF NONE HAVE ANY IT'S NoAddress
+    pub fn lookup_mx(&mut self, domain: &str) -> Result<Vec<MxTarget>, ResolveError> {
+        let domain = domain.trim_end_matches('.');
+        let response = self.lookup(&format!("{}.", domain), Types::Mx)?;
+        let mut targets = get_mx_targets(&response)?;
+
+        if targets.is_empty() {
+            let addresses = self.lookup_ip(&format!("{}.", domain))?;
+
+            if addresses.is_empty() {
+                return Err(ResolveError::NoAddress(domain.to_string()));
+            }
+
+            return Ok(vec![MxTarget::implicit(domain, addresses)]);
+        }
+
+        for target in targets.iter_mut() {
+            let mut addresses = target.get_addresses().clone();
+
+            if addresses.is_empty() {
+                addresses = self.lookup_owned_ip(target.get_host());
+            }
+
+            sort_addresses(&mut addresses);
+            target.set_addresses(addresses);
+        }
+
+        if targets.iter().all(|target| target.get_addresses().is_empty()) {
+            return Err(ResolveError::NoAddress(domain.to_string()));
+        }
+
+        Ok(targets)
+    }
+
+    //EVERY QUERY IS IN FLIGHT AT ONCE, AS IT IS - NO SEARCH LIST - THE RESULTS COME BACK IN THE ORDER THE QUERIES WENT IN
+    pub fn exchange(&mut self, queries: Vec<DnsQuery>) -> Vec<Result<MessageBase, ResolveError>> {
+        let (sender, receiver) = mpsc::channel::<Received>();
+        let timeout = self.resolver.get_timeout();
+
+        let now = Instant::now();
+        let lookups: Vec<usize> = queries.into_iter().map(|query| self.resolver.resolve(query, now)).collect();
+        let mut answers = HashMap::new();
+
+        loop {
+            while let Some(event) = self.resolver.poll_event() {
+                match event {
+                    ResolverEvents::Send { server, transport, query, .. } => {
+                        let sender = sender.clone();
+                        let connections = self.connections.clone();
+
+                        thread::spawn(move || {
+                            let _ = match transport {
+                                Transports::Udp => exchange_udp(server, &query, timeout, &sender),
+                                Transports::Tcp => exchange_tcp(server, &query, timeout, &connections, &sender)
+                            };
+                        });
+                    }
+                    ResolverEvents::Answer { lookup, response } => {
+                        answers.insert(lookup, response.map(|response| *response));
+                    }
+                }
+            }
+
+            if lookups.iter().all(|lookup| answers.contains_key(lookup)) {
+                break;
+            }
+
+            let Some(deadline) = self.resolver.poll_timeout() else {
+                break;
+            };
+
+            if let Ok((buf, source, transport)) = receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                self.resolver.receive(&buf, source, transport, Instant::now());
+            }
+
+            self.resolver.tick(Instant::now());
+        }
+
+        lookups.iter()
+            .map(|lookup| answers.remove(lookup).unwrap_or(Err(ResolveError::Timeout)))
+            .collect()
+    }
+
+    //A AND AAAA OWNED BY host ITSELF, A CNAME IN THE WAY MEANS NO ADDRESSES RATHER THAN WHEREVER IT POINTS
+    //A HOST THAT CAN'T BE RESOLVED IS JUST ONE WITHOUT ADDRESSES, THE NEXT ONE IN THE LIST MAY STILL BE
+    fn lookup_owned_ip(&mut self, host: &str) -> Vec<IpAddr> {
+        let absolute = format!("{}.", host);
+        let mut responses = Vec::new();
+
+        if let Some(hosts_file) = self.hosts_file.as_mut() {
+            responses.extend(hosts_file.query(&DnsQuery::new(&absolute, Types::A, DnsClasses::In)));
+            responses.extend(hosts_file.query(&DnsQuery::new(&absolute, Types::Aaaa, DnsClasses::In)));
+        }
+
+        if responses.is_empty() {
+            responses = self.search(&absolute, &[Types::A, Types::Aaaa]).unwrap_or_default();
+        }
+
+        let mut addresses: Vec<IpAddr> = Vec::new();
+
+        for address in responses.iter().flat_map(|response| get_owned_addresses(response.get_answers(), host)) {
+            if !addresses.contains(&address) {
+                addresses.push(address);
+            }
+        }
+
+        addresses
+    }
+
+    //THE SEARCH LIST, EVERY ONE OF types ASKED FOR AT ONCE FOR EACH CANDIDATE - THE FIRST CANDIDATE WITH AN ANSWER FOR ANY OF THEM WINS
+    //NXDOMAIN, NODATA AND SERVFAIL MOVE ON TO THE NEXT, IF NONE HAD AN ANSWER AN EMPTY ONE BEATS SERVFAIL, WHICH BEATS NXDOMAIN
+    fn search(&mut self, name: &str, types: &[Types]) -> Result<Vec<MessageBase>, ResolveError> {
+        let mut no_data = None;
+        let mut serv_fail = false;
+
+        for candidate in self.resolv_conf.get_candidates(name) {
+            let queries = types.iter().map(|_type| DnsQuery::new(&candidate, *_type, DnsClasses::In)).collect();
+            let mut responses = Vec::new();
+            let mut error = None;
+
+            for result in self.exchange(queries) {
+                match result {
+                    Ok(response) => responses.push(response),
+                    Err(ResolveError::NxDomain) => {}
+                    Err(ResolveError::ServFail) => serv_fail = true,
+                    Err(e) => error = Some(e)
+                }
+            }
+
+            //ONE FAMILY TIMING OUT DOESN'T TAKE AWAY THE OTHER'S ANSWER
+            if responses.iter().any(|response| !response.get_answers().is_empty()) {
+                return Ok(responses);
+            }
+
+            if let Some(e) = error {
+                return Err(e);
+            }
+
+            if no_data.is_none() && !responses.is_empty() {
+                no_data = Some(responses);
+            }
+        }
+
+        match (no_data, serv_fail) {
+            (Some(responses), _) => Ok(responses),
+            (None, true) => Err(ResolveError::ServFail),
+            (None, false) => Err(ResolveError::NxDomain)
+        }
+    }
+}
+
+//THE RECORDS AT THE END OF THE CNAME CHAIN STARTING AT THE NAME ASKED FOR, RECORDS FOR ANY OTHER NAME AREN'T OURS
+pub fn get_records(response: &MessageBase) -> Vec<&dyn RecordBase> {
+    let Some(Ok(mut name)) = response.get_queries().first().map(|query| query.get_query()) else {
+        return Vec::new();
+    };
+
+    let answers = response.get_answers();
+
+    //A CHAIN CAN'T BE LONGER THAN THE ANSWER HAS NAMES, ANYTHING MORE IS A LOOP
+    for _ in 0..=answers.len() {
+        let records: Vec<&dyn RecordBase> = answers.iter()
+            .filter(|(owner, _)| owner.trim_end_matches('.').eq_ignore_ascii_case(name.trim_end_matches('.')))
+            .flat_map(|(_, records)| records.iter().map(|record| record.as_ref()))
+            .collect();
+
+        let target = match records.iter().all(|record| record.get_type() == Types::Cname) {
+            true => records.iter().find_map(|record| record.as_any().downcast_ref::<CNameRecord>()).and_then(|record| record.get_domain()),
+            false => None
+        };
+
+        match target {
+            Some(target) => name = target,
+            None => return records.into_iter().filter(|record| record.get_type() != Types::Cname).collect()
+        }
+    }
+
+    Vec::new()
+}
+
+//ONLY WHAT THE SERVER SENDS IS PASSED ON, THE RESOLVER DECIDES WHAT COUNTS
+fn exchange_udp(server: SocketAddr, query: &[u8], timeout: Duration, sender: &Sender<Received>) -> Result<(), ResolveError> {
+    let socket = udp_transport::bind(server)?;
+    socket.send_to(query, server).map_err(|e| ResolveError::Io(e.to_string()))?;
+
+    let request = MessageBase::from_bytes(query, 0).map_err(ResolveError::Malformed)?;
+
+    udp_transport::receive(&socket, server, Instant::now()+timeout, |buf| {
+        //ONCE THE REAL ANSWER IS THROUGH, OR NOBODY IS LISTENING ANYMORE, THERE'S NOTHING LEFT TO WAIT FOR
+        let done = MessageBase::from_bytes(buf, 0).is_ok_and(|response| response.is_response_to(&request));
+        (sender.send((buf.to_vec(), server, Transports::Udp)).is_err() || done).then_some(())
+    })
+}
+
+//RFC 7766 6.2.1 - A CONNECTION AN EARLIER QUERY LEFT OPEN IS USED FIRST, WHILE ONE THREAD HAS IT THE OTHERS OPEN THEIR OWN
+fn exchange_tcp(server: SocketAddr, query: &[u8], timeout: Duration, connections: &Connections, sender: &Sender<Received>) -> Result<(), ResolveError> {
+    let request = MessageBase::from_bytes(query, 0).map_err(ResolveError::Malformed)?;
+    let deadline = Instant::now()+timeout;
+
+    let mut buf = Vec::with_capacity(2+query.len());
+    buf.extend_from_slice(&(query.len() as u16).to_be_bytes());
+    buf.extend_from_slice(query);
+
+    let idle = connections.lock().map_err(|e| ResolveError::Io(e.to_string()))?.remove(&server);
+
+    let stream = match idle {
+        Some(mut stream) => match exchange_stream(&mut stream, &buf, &request, deadline, sender) {
+            Ok(()) => stream,
+            //THE SERVER MAY HAVE CLOSED IT WHILE IT SAT IDLE, THAT'S WORTH ONE RECONNECT
+            Err(ResolveError::Io(_)) => {
+                let mut stream = connect(server, deadline)?;
+                exchange_stream(&mut stream, &buf, &request, deadline, sender)?;
+                stream
+            }
+            Err(e) => return Err(e)
+        },
+        None => {
+            let mut stream = connect(server, deadline)?;
+            exchange_stream(&mut stream, &buf, &request, deadline, sender)?;
+            stream
+        }
+    };
+
+    connections.lock().map_err(|e| ResolveError::Io(e.to_string()))?.entry(server).or_insert(stream);
+
+    Ok(())
+}
+
+//ANSWERS TO QUERIES AN EARLIER THREAD GAVE UP ON CAN STILL BE ON THE STREAM, THEY'RE PASSED ON UNTIL OURS TURNS UP
+fn exchange_stream(stream: &mut TcpStream, buf: &[u8], request: &MessageBase, deadline: Instant, sender: &Sender<Received>) -> Result<(), ResolveError> {
+    let server = stream.peer_addr().map_err(|e| ResolveError::Io(e.to_string()))?;
+
+    stream.set_write_timeout(Some(remaining(deadline)?)).map_err(|e| ResolveError::Io(e.to_string()))?;
+    stream.write_all(buf).map_err(map_error)?;
+
+    loop {
+        stream.set_read_timeout(Some(remaining(deadline)?)).map_err(|e| ResolveError::Io(e.to_string()))?;
+        let response = read_message(stream, deadline)?;
+
+        let done = MessageBase::from_bytes(&response, 0).is_ok_and(|response| response.is_response_to(request));
+        sender.send((response, server, Transports::Tcp)).map_err(|e| ResolveError::Io(e.to_string()))?;
+
+        if done {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashMap;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+    use std::path::PathBuf;
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+    use crate::client::resolve_error::ResolveError;
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::types::Types;
+    use crate::messages::message_base::MessageBase;
+    use crate::records::a_record::ARecord;
+    use crate::records::cname_record::CNameRecord;
+    use crate::records::mx_record::MxRecord;
+    use crate::resolver::dns_resolver::Resolver;
+    use crate::resolver::hosts_file::HostsFile;
+    use crate::resolver::resolv_conf::ResolvConf;
+    use crate::resolver::stub_resolver::{exchange_tcp, Connections, StubResolver};
+    use crate::utils::dns_query::DnsQuery;
+
+    //A NAMESERVER ON A RANDOM PORT, RESOLV.CONF CAN ONLY NAME PORT 53
+    fn stub(server: SocketAddr) -> StubResolver {
+        let mut stub = StubResolver::new(ResolvConf::parse("options attempts:1"));
+        stub.resolver = Resolver::new(server);
+        stub.resolver.set_timeout(Duration::from_secs(1));
+        stub.resolver.set_retries(0);
+        stub
+    }
+
+    //A NAMESERVER THAT KEEPS ANSWERING, fill ADDS WHATEVER THE QUESTION SHOULD GET
+    fn serve(fill: fn(&str, Types, &mut MessageBase)) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let server = socket.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let mut buf = [0u8; 512];
+
+            loop {
+                let (size, source) = socket.recv_from(&mut buf).unwrap();
+                let mut response = MessageBase::from_bytes(&buf[..size], 0).unwrap();
+                response.set_qr(true);
+                response.set_recursion_available(true);
+
+                let name = response.get_queries()[0].get_query().unwrap();
+                let _type = response.get_queries()[0].get_type();
+                fill(name.trim_end_matches('.'), _type, &mut response);
+
+                socket.send_to(&response.encode(), source).unwrap();
+            }
+        });
+
+        server
+    }
+
+    fn hosts_file(name: &str, text: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("find9-{}-{}.hosts", std::process::id(), name));
+        fs::write(&path, text).unwrap();
+        path
+    }
+
+    fn query(id: u16) -> Vec<u8> {
+        let mut message = MessageBase::new(id);
+        message.add_query(DnsQuery::new("example.com", Types::A, DnsClasses::In));
+        message.encode()
+    }
+
+    fn answer(stream: &mut TcpStream, id: Option<u16>) -> u16 {
+        let mut length = [0u8; 2];
+        stream.read_exact(&mut length).unwrap();
+        let mut buf = vec![0u8; u16::from_be_bytes(length) as usize];
+        stream.read_exact(&mut buf).unwrap();
+
+        let mut response = MessageBase::from_bytes(&buf, 0).unwrap();
+        let asked = response.get_id();
+        response.set_qr(true);
+
+        //A STALE ANSWER TO SOMETHING ASKED EARLIER GOES OUT FIRST
+        if let Some(id) = id {
+            response.set_id(id);
+            let stale = response.encode();
+            stream.write_all(&[&(stale.len() as u16).to_be_bytes()[..], &stale].concat()).unwrap();
+            response.set_id(asked);
+        }
+
+        let encoded = response.encode();
+        stream.write_all(&[&(encoded.len() as u16).to_be_bytes()[..], &encoded].concat()).unwrap();
+        asked
+    }
+
+    #[test]
+    fn tcp_connection_is_reused_then_reopened_after_an_idle_close() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let server: SocketAddr = listener.local_addr().unwrap();
+
+        let handle = thread::spawn(move || {
+            let (mut first, _) = listener.accept().unwrap();
+            let asked = vec![answer(&mut first, None), answer(&mut first, Some(7))];
+            drop(first);
+
+            let (mut second, _) = listener.accept().unwrap();
+            [asked, vec![answer(&mut second, None)]].concat()
+        });
+
+        let connections: Connections = Arc::new(Mutex::new(HashMap::new()));
+        let (sender, receiver) = mpsc::channel();
+        let timeout = Duration::from_secs(2);
+
+        exchange_tcp(server, &query(1), timeout, &connections, &sender).unwrap();
+        exchange_tcp(server, &query(2), timeout, &connections, &sender).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        exchange_tcp(server, &query(3), timeout, &connections, &sender).unwrap();
+
+        assert_eq!(handle.join().unwrap(), vec![1, 2, 3]);
+
+        //THE STALE ANSWER IS PASSED ON TOO, THE RESOLVER IS THE ONE THAT DROPS IT
+        let ids: Vec<u16> = receiver.try_iter().map(|(buf, _, _)| u16::from_be_bytes([buf[0], buf[1]])).collect();
+        assert_eq!(ids, vec![1, 7, 2, 3]);
+        assert!(connections.lock().unwrap().contains_key(&server));
+    }
+
+    #[test]
+    fn lookup_ip_answers_from_the_hosts_file_without_the_network() {
+        //A NAMESERVER THAT NEVER ANSWERS, ASKING IT WOULD TIME OUT
+        let silent = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let path = hosts_file("lookup_ip", "192.0.2.7 printer\n192.0.2.8 printer\n");
+
+        let mut stub = stub(silent.local_addr().unwrap());
+        stub.set_hosts_file(Some(HostsFile::new(&path)));
+
+        assert_eq!(stub.lookup_ip("printer").unwrap(), vec![IpAddr::from([192, 0, 2, 7]), IpAddr::from([192, 0, 2, 8])]);
+        assert_eq!(stub.lookup_ip("192.0.2.99").unwrap(), vec![IpAddr::from([192, 0, 2, 99])]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn lookup_ip_follows_cnames_and_drops_duplicates() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let server = socket.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let mut buf = [0u8; 512];
+
+            for _ in 0..2 {
+                let (size, source) = socket.recv_from(&mut buf).unwrap();
+                let mut response = MessageBase::from_bytes(&buf[..size], 0).unwrap();
+                response.set_qr(true);
+                response.set_recursion_available(true);
+
+                //THE AAAA QUESTION GETS AN EMPTY ANSWER, THE A ONE THE SAME ADDRESS TWICE AT THE END OF A CNAME
+                if response.get_queries()[0].get_type() == Types::A {
+                    response.add_answers("www.example", Box::new(CNameRecord::new(DnsClasses::In, 300, "host.example")));
+                    for address in [[192, 0, 2, 1], [192, 0, 2, 2], [192, 0, 2, 1]] {
+                        response.add_answers("host.example", Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from(address))));
+                    }
+                    response.add_answers("other.example", Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 66]))));
+                }
+
+                socket.send_to(&response.encode(), source).unwrap();
+            }
+        });
+
+        assert_eq!(stub(server).lookup_ip("www.example.").unwrap(), vec![IpAddr::from([192, 0, 2, 1]), IpAddr::from([192, 0, 2, 2])]);
+    }
+
+    #[test]
+    fn lookup_mx_falls_back_to_the_implicit_mx() {
+        let server = serve(|name, _type, response| {
+            if name == "example.com" && _type == Types::A {
+                response.add_answers("example.com", Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 10]))));
+            }
+        });
+
+        let targets = stub(server).lookup_mx("example.com").unwrap();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].get_host(), "example.com");
+        assert_eq!(targets[0].get_preference(), 0);
+        assert_eq!(targets[0].get_addresses(), &vec![IpAddr::from([192, 0, 2, 10])]);
+    }
+
+    #[test]
+    fn lookup_mx_null_mx_is_no_service() {
+        let server = serve(|name, _type, response| {
+            if name == "example.com" && _type == Types::Mx {
+                response.add_answers("example.com", Box::new(MxRecord::new(DnsClasses::In, 300, 0, ".")));
+            }
+        });
+
+        assert_eq!(stub(server).lookup_mx("example.com").err(), Some(ResolveError::NoService));
+    }
+
+    #[test]
+    fn lookup_mx_does_not_follow_a_cname_at_the_host() {
+        let server = serve(|name, _type, response| {
+            match (name, _type) {
+                ("example.com", Types::Mx) => {
+                    response.add_answers("example.com", Box::new(MxRecord::new(DnsClasses::In, 300, 10, "alias.example.com.")));
+                    response.add_answers("example.com", Box::new(MxRecord::new(DnsClasses::In, 300, 20, "mail.example.com.")));
+                }
+                ("alias.example.com", Types::A) => {
+                    response.add_answers("alias.example.com", Box::new(CNameRecord::new(DnsClasses::In, 300, "real.example.com")));
+                    response.add_answers("real.example.com", Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 1]))));
+                }
+                ("mail.example.com", Types::A) => {
+                    response.add_answers("mail.example.com", Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 2]))));
+                }
+                _ => {}
+            }
+        });
+
+        let targets = stub(server).lookup_mx("example.com").unwrap();
+        assert_eq!(targets.iter().map(|target| target.get_host()).collect::<Vec<_>>(), vec!["alias.example.com", "mail.example.com"]);
+        assert!(targets[0].get_addresses().is_empty());
+        assert_eq!(targets[1].get_addresses(), &vec![IpAddr::from([192, 0, 2, 2])]);
+    }
+}
@@ -0,0 +1,35 @@
+use std::net::SocketAddr;
+use crate::messages::message_base::MessageBase;
+
+//WHAT A SERVER HANDS EVERY DECODED QUERY TO, None SENDS NOTHING BACK
+//THE SERVER TAKES CARE OF THE TRANSPORT SIDE OF THE ANSWER - ID, PADDING, FRAMING
+pub trait HandlerBase: Send + Sync {
+
+    fn handle(&self, request: &MessageBase) -> Option<MessageBase>;
+}
+
+impl<F> HandlerBase for F
+where
+    F: Fn(&MessageBase) -> Option<MessageBase> + Send + Sync
+{
+    fn handle(&self, request: &MessageBase) -> Option<MessageBase> {
+        self(request)
+    }
+}
+
+//DECODE, HAND OVER AND ECHO THE ID BACK - ANYTHING THAT ISN'T A QUERY NEVER REACHES THE HANDLER, ANSWERING A RESPONSE IS HOW LOOPS START
+//THE REQUEST COMES BACK WITH THE ANSWER, HOW MUCH OF IT FITS DEPENDS ON WHAT THE REQUEST ADVERTISED
+pub fn dispatch(handler: &dyn HandlerBase, buf: &[u8], origin: SocketAddr, destination: SocketAddr) -> Option<(MessageBase, MessageBase)> {
+    let mut request = MessageBase::from_bytes(buf, 0).ok()?;
+
+    if request.is_qr() {
+        return None;
+    }
+
+    request.set_origin(origin);
+    request.set_destination(destination);
+
+    let mut response = handler.handle(&request)?;
+    response.set_id(request.get_id());
+    Some((request, response))
+}
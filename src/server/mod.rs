@@ -0,0 +1,7 @@
+pub mod inter;
+#[cfg(feature = "quic")]
+pub mod quic_server;
+#[cfg(feature = "async")]
+pub mod udp_server;
+#[cfg(feature = "async")]
+pub mod tcp_server;
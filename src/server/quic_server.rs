@@ -0,0 +1,358 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use quinn::crypto::rustls::QuicServerConfig;
+use quinn::{Connection, Endpoint, Incoming, RecvStream, SendStream, ServerConfig, VarInt};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio::runtime::Runtime;
+use crate::client::inter::quic_error_codes::QuicErrorCodes;
+use crate::client::quic_transport::{DOQ_ALPN, MAX_STREAM_SIZE};
+use crate::messages::inter::padding_policies::PaddingPolicies;
+use crate::messages::message_base::MessageBase;
+use crate::records::inter::opt_codes::OptCodes;
+use crate::server::inter::handler_base::HandlerBase;
+
+//BLOCKING ON THE OUTSIDE LIKE QuicTransport, run() HOLDS THE THREAD UNTIL close() IS CALLED FROM ANOTHER ONE
+pub struct QuicServer {
+    runtime: Runtime,
+    endpoint: Endpoint
+}
+
+impl QuicServer {
+
+    //certificates IS THE DER CHAIN LEAF FIRST, key IS ITS DER PRIVATE KEY - PKCS8, PKCS1 OR SEC1
+    pub fn bind(address: SocketAddr, certificates: Vec<Vec<u8>>, key: Vec<u8>) -> Result<Self, String> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        let certificates = certificates.into_iter().map(CertificateDer::from).collect();
+        let key = PrivateKeyDer::try_from(key).map_err(|e| format!("Invalid private key: {}", e))?;
+
+        //QUIC ONLY EVER RUNS TLS 1.3
+        let mut tls_config = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(|e| e.to_string())?
+            .with_no_client_auth()
+            .with_single_cert(certificates, key)
+            .map_err(|e| format!("Invalid certificate: {}", e))?;
+        tls_config.alpn_protocols = vec![DOQ_ALPN.to_vec()];
+
+        let config = ServerConfig::with_crypto(Arc::new(QuicServerConfig::try_from(tls_config).map_err(|e| e.to_string())?));
+
+        //quinn PICKS UP THE RUNTIME IT'S CREATED IN
+        let endpoint = {
+            let _guard = runtime.enter();
+            Endpoint::server(config, address).map_err(|e| e.to_string())?
+        };
+
+        Ok(Self {
+            runtime,
+            endpoint
+        })
+    }
+
+    //WHAT WAS ACTUALLY BOUND, FOR WHEN address ASKED FOR PORT 0
+    pub fn get_local_addr(&self) -> Result<SocketAddr, String> {
+        self.endpoint.local_addr().map_err(|e| e.to_string())
+    }
+
+    //EVERY CONNECTION AND EVERY STREAM ON IT IS ITS OWN TASK, handler RUNS ON THE BLOCKING POOL SO A SLOW ONE STALLS NOTHING ELSE
+    pub fn run<H: HandlerBase + 'static>(&self, handler: H) {
+        let handler: Arc<dyn HandlerBase> = Arc::new(handler);
+        let local = self.endpoint.local_addr().ok();
+
+        self.runtime.block_on(async {
+            while let Some(incoming) = self.endpoint.accept().await {
+                tokio::spawn(serve_connection(incoming, local, handler.clone()));
+            }
+
+            self.endpoint.wait_idle().await;
+        });
+    }
+
+    //CONNECTIONS STILL OPEN ARE CLOSED WITH DOQ_NO_ERROR, run() RETURNS ONCE THEY ARE
+    pub fn close(&self) {
+        self.endpoint.close(VarInt::from_u32(QuicErrorCodes::NoError.get_code()), b"");
+    }
+}
+
+async fn serve_connection(incoming: Incoming, local: Option<SocketAddr>, handler: Arc<dyn HandlerBase>) {
+    let connection = match incoming.await {
+        Ok(connection) => connection,
+        Err(_) => return
+    };
+
+    //RFC 9250 4.2 - QUERIES ONLY EVER COME ON CLIENT INITIATED BIDIRECTIONAL STREAMS
+    while let Ok((send, recv)) = connection.accept_bi().await {
+        tokio::spawn(serve_stream(connection.clone(), send, recv, local, handler.clone()));
+    }
+}
+
+async fn serve_stream(connection: Connection, mut send: SendStream, mut recv: RecvStream, local: Option<SocketAddr>, handler: Arc<dyn HandlerBase>) {
+    //A CLIENT THAT RESETS ITS OWN STREAM HAS GIVEN UP ON IT, NOTHING TO ANSWER
+    let query = match recv.read_to_end(MAX_STREAM_SIZE).await {
+        Ok(query) => query,
+        Err(quinn::ReadToEndError::TooLong) => {
+            connection.close(VarInt::from_u32(QuicErrorCodes::ProtocolError.get_code()), b"Stream is longer than a DNS message");
+            return;
+        }
+        Err(_) => return
+    };
+
+    let remote = connection.remote_address();
+
+    //MESSAGES STAY ON THE BLOCKING POOL, ONLY BYTES CROSS BACK
+    let response = tokio::task::spawn_blocking(move || answer(&query, remote, local, handler.as_ref())).await;
+
+    let encoded = match response {
+        Ok(Ok(Some(encoded))) => encoded,
+        Ok(Ok(None)) => {
+            let _ = send.reset(VarInt::from_u32(QuicErrorCodes::RequestCancelled.get_code()));
+            return;
+        }
+        Ok(Err(e)) => {
+            //RFC 9250 4.3.3 - A MALFORMED QUERY ENDS THE WHOLE CONNECTION, NOT JUST THE STREAM
+            connection.close(VarInt::from_u32(QuicErrorCodes::ProtocolError.get_code()), e.as_bytes());
+            return;
+        }
+        Err(_) => {
+            let _ = send.reset(VarInt::from_u32(QuicErrorCodes::InternalError.get_code()));
+            return;
+        }
+    };
+
+    if encoded.len() > u16::MAX as usize {
+        let _ = send.reset(VarInt::from_u32(QuicErrorCodes::InternalError.get_code()));
+        return;
+    }
+
+    let mut buf = Vec::with_capacity(2+encoded.len());
+    buf.extend_from_slice(&(encoded.len() as u16).to_be_bytes());
+    buf.extend_from_slice(&encoded);
+
+    if send.write_all(&buf).await.is_ok() {
+        let _ = send.finish();
+        let _ = send.stopped().await;
+    }
+}
+
+//Err IS A PROTOCOL ERROR ON THE CLIENT'S PART, Ok(None) IS THE HANDLER DECLINING TO ANSWER
+fn answer(query: &[u8], remote: SocketAddr, local: Option<SocketAddr>, handler: &dyn HandlerBase) -> Result<Option<Vec<u8>>, String> {
+    if query.len() < 2 || u16::from_be_bytes([query[0], query[1]]) as usize != query.len()-2 {
+        return Err("Stream length doesn't match the length prefix".to_string());
+    }
+
+    let mut request = MessageBase::from_bytes(&query[2..], 0)?;
+
+    if request.get_id() != 0 {
+        return Err(format!("Query ID isn't 0: {}", request.get_id()));
+    }
+
+    //RFC 9250 5.5.2 - QUIC HAS ITS OWN IDLE TIMEOUT, A KEEPALIVE OPTION HAS NO PLACE HERE
+    if request.get_opt_record().is_some_and(|opt| opt.get_option(&OptCodes::TcpKeepalive).is_some()) {
+        return Err("Query carries an edns-tcp-keepalive option".to_string());
+    }
+
+    request.set_origin(remote);
+    if let Some(local) = local {
+        request.set_destination(local);
+    }
+
+    let mut response = match handler.handle(&request) {
+        Some(response) => response,
+        None => return Ok(None)
+    };
+
+    response.set_id(0);
+
+    //RFC 8467 4.1 - ONLY PAD THE ANSWER TO A QUERY THAT WAS PADDED ITSELF
+    let padded = request.get_opt_record().is_some_and(|opt| opt.get_option(&OptCodes::Padding).is_some());
+    if padded && response.get_padding_policy() == PaddingPolicies::None {
+        response.set_padding_policy(PaddingPolicies::Response);
+    }
+
+    Ok(Some(response.encode()))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::net::{IpAddr, SocketAddr};
+    use std::sync::mpsc::{channel, Receiver};
+    use std::sync::Arc;
+    use std::thread;
+    use quinn::crypto::rustls::QuicClientConfig;
+    use quinn::{ClientConfig, ConnectionError, Endpoint, ReadError, ReadToEndError};
+    use rcgen::{CertificateParams, KeyPair};
+    use ring::digest::{digest, SHA256};
+    use crate::client::inter::quic_error_codes::QuicErrorCodes;
+    use crate::client::inter::transport_base::TransportBase;
+    use crate::client::quic_transport::{QuicTransport, DOQ_ALPN, MAX_STREAM_SIZE};
+    use crate::client::resolve_error::ResolveError;
+    use crate::client::tls_config::TlsConfig;
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::types::Types;
+    use crate::messages::message_base::MessageBase;
+    use crate::records::a_record::ARecord;
+    use crate::server::quic_server::QuicServer;
+    use crate::utils::base64;
+    use crate::utils::dns_query::DnsQuery;
+
+    //A SELF SIGNED dns.example, THE CLIENT TRUSTS IT BY ITS SPKI PIN
+    //ANSWERS EVERYTHING BUT declined.example, REPORTS THE ID OF EVERY QUERY IT'S HANDED
+    fn serve() -> (Arc<QuicServer>, SocketAddr, String, Receiver<u16>) {
+        let key = KeyPair::generate().unwrap();
+        let certificate = CertificateParams::new(vec!["dns.example".to_string()]).unwrap().self_signed(&key).unwrap();
+        let pin = base64::encode(digest(&SHA256, &key.public_key_der()).as_ref());
+
+        let server = Arc::new(QuicServer::bind("127.0.0.1:0".parse().unwrap(), vec![certificate.der().to_vec()], key.serialize_der()).unwrap());
+        let address = server.get_local_addr().unwrap();
+        let (tx, rx) = channel();
+
+        let running = server.clone();
+        thread::spawn(move || {
+            running.run(move |request: &MessageBase| {
+                tx.send(request.get_id()).unwrap();
+
+                let question = request.get_queries().remove(0);
+                if question.get_query().unwrap() == "declined.example" {
+                    return None;
+                }
+
+                let mut response = MessageBase::new(request.get_id());
+                response.set_qr(true);
+                response.add_query(question.clone());
+                response.add_answers(&question.get_query().unwrap(), Box::new(ARecord::new(DnsClasses::In, false, 300, IpAddr::from([192, 0, 2, 1]))));
+                Some(response)
+            });
+        });
+
+        (server, address, pin, rx)
+    }
+
+    fn query(name: &str, id: u16) -> MessageBase {
+        let mut message = MessageBase::new(id);
+        message.add_query(DnsQuery::new(name, Types::A, DnsClasses::In));
+        message
+    }
+
+    fn frame(message: &[u8]) -> Vec<u8> {
+        let mut buf = (message.len() as u16).to_be_bytes().to_vec();
+        buf.extend_from_slice(message);
+        buf
+    }
+
+    //ONE STREAM WITH EXACTLY stream ON IT, Err IS THE STREAM RESET OR CONNECTION CLOSE CODE
+    fn exchange_raw(address: SocketAddr, pin: &str, stream: &[u8]) -> Result<Vec<u8>, u64> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+
+        let mut tls_config = TlsConfig::new();
+        tls_config.clear_root_certificates();
+        tls_config.add_spki_pin(pin).unwrap();
+        tls_config.set_alpn_protocols(vec![DOQ_ALPN.to_vec()]);
+        let config = ClientConfig::new(Arc::new(QuicClientConfig::try_from(tls_config.get_client_config().unwrap()).unwrap()));
+
+        runtime.block_on(async {
+            let endpoint = Endpoint::client("127.0.0.1:0".parse().unwrap()).unwrap();
+            let connection = endpoint.connect_with(config, address, "dns.example").unwrap().await.unwrap();
+
+            let (mut send, mut recv) = connection.open_bi().await.unwrap();
+            send.write_all(stream).await.unwrap();
+            send.finish().unwrap();
+
+            match recv.read_to_end(MAX_STREAM_SIZE).await {
+                Ok(response) => Ok(response),
+                Err(ReadToEndError::Read(ReadError::Reset(code))) => Err(code.into_inner()),
+                Err(_) => match connection.closed().await {
+                    ConnectionError::ApplicationClosed(close) => Err(close.error_code.into_inner()),
+                    e => panic!("Connection lost without a DoQ error code: {}", e)
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn transport_queries_server_with_id_zero() {
+        let (server, address, pin, rx) = serve();
+
+        let mut transport = QuicTransport::new(address, "dns.example").unwrap();
+        transport.get_tls_config_mut().clear_root_certificates();
+        transport.get_tls_config_mut().add_spki_pin(&pin).unwrap();
+
+        let mut messages = [query("a.example", 0x1234), query("b.example", 0x5678)];
+        let responses = transport.query_all(&mut messages);
+
+        for (response, name) in responses.iter().zip(["a.example", "b.example"]) {
+            let response = response.as_ref().unwrap();
+            assert_eq!(response.get_id(), 0);
+            assert_eq!(response.get_queries()[0].get_query().unwrap(), name);
+            assert_eq!(response.get_answers().get(&name.to_string()).unwrap().len(), 1);
+        }
+
+        assert_eq!(rx.recv().unwrap(), 0);
+        assert_eq!(rx.recv().unwrap(), 0);
+        server.close();
+    }
+
+    #[test]
+    fn transport_maps_a_stream_reset_to_its_error_code() {
+        let (server, address, pin, _rx) = serve();
+
+        let mut transport = QuicTransport::new(address, "dns.example").unwrap();
+        transport.get_tls_config_mut().clear_root_certificates();
+        transport.get_tls_config_mut().add_spki_pin(&pin).unwrap();
+
+        match transport.query(&mut query("declined.example", 0)) {
+            Err(ResolveError::Io(e)) => assert!(e.ends_with(QuicErrorCodes::RequestCancelled.get_name())),
+            _ => panic!("A declined query should reset its stream")
+        }
+
+        server.close();
+    }
+
+    #[test]
+    fn stream_carries_one_length_prefixed_message() {
+        let (server, address, pin, _rx) = serve();
+
+        let response = exchange_raw(address, &pin, &frame(&query("a.example", 0).encode())).unwrap();
+
+        assert_eq!(u16::from_be_bytes([response[0], response[1]]) as usize, response.len()-2);
+        assert_eq!(MessageBase::from_bytes(&response[2..], 0).unwrap().get_id(), 0);
+        server.close();
+    }
+
+    #[test]
+    fn declined_query_resets_with_request_cancelled() {
+        let (server, address, pin, _rx) = serve();
+
+        let result = exchange_raw(address, &pin, &frame(&query("declined.example", 0).encode()));
+
+        assert_eq!(result, Err(QuicErrorCodes::RequestCancelled.get_code() as u64));
+        server.close();
+    }
+
+    #[test]
+    fn nonzero_id_closes_with_protocol_error() {
+        let (server, address, pin, rx) = serve();
+
+        let result = exchange_raw(address, &pin, &frame(&query("a.example", 0x1234).encode()));
+
+        assert_eq!(result, Err(QuicErrorCodes::ProtocolError.get_code() as u64));
+        assert!(rx.try_recv().is_err());
+        server.close();
+    }
+
+    #[test]
+    fn wrong_length_prefix_closes_with_protocol_error() {
+        let (server, address, pin, rx) = serve();
+
+        let mut stream = frame(&query("a.example", 0).encode());
+        stream[1] += 1;
+
+        assert_eq!(exchange_raw(address, &pin, &stream), Err(QuicErrorCodes::ProtocolError.get_code() as u64));
+        assert_eq!(exchange_raw(address, &pin, &stream[2..]), Err(QuicErrorCodes::ProtocolError.get_code() as u64));
+        assert!(rx.try_recv().is_err());
+        server.close();
+    }
+}
@@ -0,0 +1,114 @@
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use crate::server::inter::handler_base::{dispatch, HandlerBase};
+
+//RFC 7766 6.2.3 - SECONDS, NOT MINUTES, A CLIENT WITH MORE TO ASK WILL KEEP THE CONNECTION BUSY
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct TcpServer {
+    listener: TcpListener,
+    idle_timeout: Duration
+}
+
+impl TcpServer {
+
+    pub async fn bind(address: SocketAddr) -> Result<Self, String> {
+        Ok(Self {
+            listener: TcpListener::bind(address).await.map_err(|e| e.to_string())?,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT
+        })
+    }
+
+    //WHAT WAS ACTUALLY BOUND, FOR WHEN address ASKED FOR PORT 0
+    pub fn get_local_addr(&self) -> Result<SocketAddr, String> {
+        self.listener.local_addr().map_err(|e| e.to_string())
+    }
+
+    //HOW LONG A CONNECTION CAN GO WITHOUT A NEW QUERY BEFORE IT'S CLOSED, ANSWERS STILL BEING WORKED ON ARE SENT FIRST
+    pub fn set_idle_timeout(&mut self, idle_timeout: Duration) {
+        self.idle_timeout = idle_timeout;
+    }
+
+    pub fn get_idle_timeout(&self) -> Duration {
+        self.idle_timeout
+    }
+
+    //ONLY RETURNS ON A LISTENER ERROR, DROP THE FUTURE OR ABORT ITS TASK TO STOP SERVING
+    //EVERY CONNECTION AND EVERY QUERY ON IT IS ITS OWN TASK, handler RUNS ON THE BLOCKING POOL SO A SLOW ONE STALLS NOTHING ELSE
+    pub async fn run<H: HandlerBase + 'static>(&self, handler: H) -> Result<(), String> {
+        let handler: Arc<dyn HandlerBase> = Arc::new(handler);
+
+        loop {
+            let (stream, _) = match self.listener.accept().await {
+                Ok(accepted) => accepted,
+                //THE CLIENT GAVE UP BEFORE WE GOT TO IT
+                Err(e) if matches!(e.kind(), ErrorKind::Interrupted | ErrorKind::ConnectionAborted | ErrorKind::ConnectionReset) => continue,
+                Err(e) => return Err(e.to_string())
+            };
+
+            tokio::spawn(serve(stream, handler.clone(), self.idle_timeout));
+        }
+    }
+}
+
+//RFC 7766 6.2.1.1 - QUERIES ON ONE CONNECTION ARE ANSWERED AS THEY'RE READY, NOT IN THE ORDER THEY CAME
+async fn serve(stream: TcpStream, handler: Arc<dyn HandlerBase>, idle_timeout: Duration) {
+    let (Ok(origin), Ok(destination)) = (stream.peer_addr(), stream.local_addr()) else {
+        return;
+    };
+
+    let _ = stream.set_nodelay(true);
+    let (mut reader, writer) = stream.into_split();
+    let writer = Arc::new(Mutex::new(writer));
+
+    loop {
+        let length = match tokio::time::timeout(idle_timeout, reader.read_u16()).await {
+            Ok(Ok(length)) => length,
+            _ => break
+        };
+
+        let mut query = vec![0u8; length as usize];
+        match tokio::time::timeout(idle_timeout, reader.read_exact(&mut query)).await {
+            Ok(Ok(_)) => {}
+            _ => break
+        }
+
+        let handler = handler.clone();
+        let writer = writer.clone();
+
+        tokio::spawn(async move {
+            let response = tokio::task::spawn_blocking(move || answer(handler.as_ref(), &query, origin, destination)).await;
+
+            if let Ok(Some(response)) = response {
+                write(&writer, &response).await;
+            }
+        });
+    }
+}
+
+//THE LENGTH PREFIX CAN ONLY DESCRIBE 65535 BYTES, A BIGGER ANSWER GOES BACK TRUNCATED
+fn answer(handler: &dyn HandlerBase, query: &[u8], origin: SocketAddr, destination: SocketAddr) -> Option<Vec<u8>> {
+    let (_, response) = dispatch(handler, query, origin, destination)?;
+    let encoded = response.encode();
+
+    match encoded.len() > u16::MAX as usize {
+        true => Some(response.to_truncated().encode()),
+        false => Some(encoded)
+    }
+}
+
+//ONE WRITE PER MESSAGE UNDER THE LOCK, SO ANSWERS FINISHING AT THE SAME TIME NEVER INTERLEAVE ON THE STREAM
+async fn write(writer: &Mutex<OwnedWriteHalf>, response: &[u8]) {
+    let mut buf = Vec::with_capacity(2+response.len());
+    buf.extend_from_slice(&(response.len() as u16).to_be_bytes());
+    buf.extend_from_slice(response);
+
+    let mut writer = writer.lock().await;
+    let _ = writer.write_all(&buf).await;
+}
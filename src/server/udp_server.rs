@@ -0,0 +1,73 @@
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::UdpSocket;
+use crate::client::udp_transport::MAX_DATAGRAM_SIZE;
+use crate::server::inter::handler_base::{dispatch, HandlerBase};
+
+//RFC 1035 4.2.1 - WITHOUT EDNS THE ANSWER HAS TO FIT IN 512 BYTES, RFC 6891 6.2.5 - LESS IS TREATED AS 512 TOO
+const MIN_PAYLOAD_SIZE: usize = 512;
+
+pub struct UdpServer {
+    socket: Arc<UdpSocket>
+}
+
+impl UdpServer {
+
+    pub async fn bind(address: SocketAddr) -> Result<Self, String> {
+        Ok(Self {
+            socket: Arc::new(UdpSocket::bind(address).await.map_err(|e| e.to_string())?)
+        })
+    }
+
+    //WHAT WAS ACTUALLY BOUND, FOR WHEN address ASKED FOR PORT 0
+    pub fn get_local_addr(&self) -> Result<SocketAddr, String> {
+        self.socket.local_addr().map_err(|e| e.to_string())
+    }
+
+    //ONLY RETURNS ON A SOCKET ERROR, DROP THE FUTURE OR ABORT ITS TASK TO STOP SERVING
+    //EVERY QUERY IS ITS OWN TASK AND handler RUNS ON THE BLOCKING POOL, SO A SLOW ONE STALLS NOTHING ELSE
+    pub async fn run<H: HandlerBase + 'static>(&self, handler: H) -> Result<(), String> {
+        let handler: Arc<dyn HandlerBase> = Arc::new(handler);
+        let local = self.get_local_addr()?;
+        let mut buf = vec![0u8; MAX_DATAGRAM_SIZE];
+
+        loop {
+            let (size, source) = match self.socket.recv_from(&mut buf).await {
+                Ok(received) => received,
+                //AN ICMP ERROR FROM AN EARLIER ANSWER, NOTHING WRONG WITH THE SOCKET
+                Err(e) if matches!(e.kind(), ErrorKind::Interrupted | ErrorKind::ConnectionRefused | ErrorKind::ConnectionReset) => continue,
+                Err(e) => return Err(e.to_string())
+            };
+
+            let query = buf[..size].to_vec();
+            let socket = self.socket.clone();
+            let handler = handler.clone();
+
+            tokio::spawn(async move {
+                let response = tokio::task::spawn_blocking(move || answer(handler.as_ref(), &query, source, local)).await;
+
+                if let Ok(Some(response)) = response {
+                    let _ = socket.send_to(&response, source).await;
+                }
+            });
+        }
+    }
+}
+
+//ANYTHING BIGGER THAN THE CLIENT SAID IT CAN TAKE GOES BACK TRUNCATED, SO IT ASKS AGAIN OVER TCP
+fn answer(handler: &dyn HandlerBase, query: &[u8], origin: SocketAddr, destination: SocketAddr) -> Option<Vec<u8>> {
+    let (request, response) = dispatch(handler, query, origin, destination)?;
+
+    let payload_size = request.get_opt_record()
+        .map(|opt| opt.get_payload_size() as usize)
+        .unwrap_or(MIN_PAYLOAD_SIZE)
+        .max(MIN_PAYLOAD_SIZE);
+
+    let encoded = response.encode();
+
+    match encoded.len() > payload_size {
+        true => Some(response.to_truncated().encode()),
+        false => Some(encoded)
+    }
+}
@@ -0,0 +1,71 @@
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn encode(buf: &[u8]) -> String {
+    let mut encoded = String::with_capacity(buf.len().div_ceil(3) * 4);
+
+    for chunk in buf.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((n >> (18 - i * 6)) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+//RFC 4648 5 - URL AND FILENAME SAFE ALPHABET, NO PADDING
+pub fn encode_url(buf: &[u8]) -> String {
+    encode(buf).trim_end_matches('=').replace('+', "-").replace('/', "_")
+}
+
+pub fn decode(encoded: &str) -> Result<Vec<u8>, String> {
+    let mut buf = Vec::with_capacity(encoded.len() / 4 * 3);
+    let mut n = 0u32;
+    let mut bits = 0;
+    let mut padding = 0;
+
+    for c in encoded.chars() {
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+
+        if c == '=' {
+            padding += 1;
+            continue;
+        }
+
+        if padding > 0 {
+            return Err("Invalid base64: data after padding".to_string());
+        }
+
+        let value = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            _ => return Err(format!("Invalid base64 character: '{}'", c))
+        };
+
+        n = (n << 6) | value;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            buf.push((n >> bits) as u8);
+            n &= (1 << bits) - 1;
+        }
+    }
+
+    if bits >= 6 || padding > 2 {
+        return Err("Invalid base64 length".to_string());
+    }
+
+    Ok(buf)
+}
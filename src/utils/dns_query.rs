@@ -8,6 +8,7 @@ pub struct DnsQuery {
     query: Option<String>,
     _type: Types,
     dns_class: DnsClasses,
+    unicast_response: bool,
     length: usize
 }
 
@@ -18,6 +19,7 @@ impl Default for DnsQuery {
             query: None,
             _type: Types::A,
             dns_class: DnsClasses::In,
+            unicast_response: false,
             length: 4
         }
     }
@@ -30,37 +32,50 @@ impl DnsQuery {
             query: Some(query.to_string()),
             _type,
             dns_class,
+            unicast_response: false,
             length: query.len()+6
         }
     }
 
-    pub fn from_bytes(buf: &[u8], off: usize) -> Self {
-        let (query, length) = unpack_domain(buf, off);
+    pub fn from_bytes(buf: &[u8], off: usize) -> Result<Self, String> {
+        let (query, length) = unpack_domain(buf, off)?;
         let off = off+length;
 
-        let _type = Types::from_code(u16::from_be_bytes([buf[off], buf[off+1]])).unwrap();
-        let dns_class = DnsClasses::from_code(u16::from_be_bytes([buf[off+2], buf[off+3]])).unwrap();
+        if off+4 > buf.len() {
+            return Err("Question runs past the end of the message".to_string());
+        }
 
-        Self {
+        let code = u16::from_be_bytes([buf[off], buf[off+1]]);
+        let _type = Types::from_code(code).unwrap_or(Types::Unknown(code));
+
+        //MDNS (RFC 6762 5.4) USES THE TOP BIT OF THE CLASS TO ASK FOR A UNICAST RESPONSE
+        let dns_class = u16::from_be_bytes([buf[off+2], buf[off+3]]);
+        let unicast_response = (dns_class & 0x8000) != 0;
+        let dns_class = DnsClasses::from_code(dns_class & 0x7FFF)?;
+
+        Ok(Self {
             query: Some(query),
             _type,
             dns_class,
+            unicast_response,
             length: length+4
-        }
+        })
     }
 
-    pub fn to_bytes(&self, label_map: &mut HashMap<String, usize>, off: usize) -> Vec<u8> {
-        let mut buf = vec![0u8; self.length];
+    pub fn to_bytes(&self, label_map: &mut HashMap<String, usize>, off: usize) -> Result<Vec<u8>, String> {
+        //THE PACKED NAME MAY BE SHORTER THAN THE QUERY ONCE COMPRESSED, SO BUILD TO FIT
+        let mut buf = pack_domain(self.query.as_ref().unwrap().as_str(), label_map, off)?;
 
-        let address = pack_domain(self.query.as_ref().unwrap().as_str(), label_map, off);
-        buf[0..address.len()].copy_from_slice(&address);
+        buf.extend_from_slice(&self._type.get_code().to_be_bytes());
 
-        let length = address.len();
+        let mut dns_class = self.dns_class.get_code();
+        if self.unicast_response {
+            dns_class |= 0x8000;
+        }
 
-        buf.splice(length..length+2, self.dns_class.get_code().to_be_bytes());
-        buf.splice(length+2..length+4, self.dns_class.get_code().to_be_bytes());
+        buf.extend_from_slice(&dns_class.to_be_bytes());
 
-        buf
+        Ok(buf)
     }
 
     pub fn set_query(&mut self, query: String) {
@@ -90,6 +105,14 @@ impl DnsQuery {
         self.dns_class
     }
 
+    pub fn set_unicast_response(&mut self, unicast_response: bool) {
+        self.unicast_response = unicast_response;
+    }
+
+    pub fn is_unicast_response(&self) -> bool {
+        self.unicast_response
+    }
+
     pub fn get_length(&self) -> usize {
         self.length
     }
@@ -98,3 +121,26 @@ impl DnsQuery {
         format!("[QUERY] {}: type {:?}, class {:?}", self.query.as_ref().unwrap(), self._type, self.dns_class)
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashMap;
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::types::Types;
+    use crate::utils::dns_query::DnsQuery;
+
+    #[test]
+    fn round_trip_keeps_type_and_class() {
+        let query = DnsQuery::new("example.com", Types::Mx, DnsClasses::Ch);
+        let buf = query.to_bytes(&mut HashMap::new(), 12).unwrap();
+
+        assert_eq!(&buf[13..], &[0, 15, 0, 3]);
+
+        let decoded = DnsQuery::from_bytes(&buf, 0).unwrap();
+        assert_eq!(decoded.get_query().unwrap(), "example.com");
+        assert_eq!(decoded.get_type(), Types::Mx);
+        assert_eq!(decoded.get_dns_class(), DnsClasses::Ch);
+        assert_eq!(decoded.get_length(), buf.len());
+    }
+}
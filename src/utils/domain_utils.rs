@@ -1,93 +1,307 @@
 use std::collections::HashMap;
 
-pub fn pack_domain_uncompressed(domain: &str) -> Vec<u8> {
+pub fn pack_domain_uncompressed(domain: &str) -> Result<Vec<u8>, String> {
     let mut buf = Vec::new();
 
-    let parts: Vec<&str> = domain.split('.').collect();
-
-    for part in parts {
-        buf.push(part.len() as u8);
-        buf.extend(part.as_bytes());
+    for label in to_labels(domain)? {
+        buf.push(label.len() as u8);
+        buf.extend(label);
     }
 
     buf.push(0x00);
 
-    buf
+    Ok(buf)
 }
 
-pub fn pack_domain(domain: &str, labels_map: &mut HashMap<String, usize>, off: usize) -> Vec<u8> {
+pub fn pack_domain(domain: &str, labels_map: &mut HashMap<String, usize>, off: usize) -> Result<Vec<u8>, String> {
     let mut buf = Vec::new();
     let mut off = off;
 
-    let parts: Vec<&str> = domain.split('.').collect();
-
-    if parts.len() < 2 {
-        buf.push(0x00);
-        return buf;
-    }
+    let labels = to_labels(domain)?;
 
-    for i in 0..parts.len() {
-        let label = parts[i..].join(".");
+    for i in 0..labels.len() {
+        let key = labels[i..].iter().map(|label| escape_label(label)).collect::<Vec<_>>().join(".");
 
-        if let Some(&ptr_offset) = labels_map.get(&label) {
+        if let Some(&ptr_offset) = labels_map.get(&key) {
             buf.extend_from_slice(&[(0xC0 | (ptr_offset >> 8)) as u8, (ptr_offset & 0xFF) as u8]);
-            return buf;
+            return Ok(buf);
         }
 
-        let addr = parts.get(i).unwrap().as_bytes();
-        buf.push(addr.len() as u8);
-        buf.extend_from_slice(addr);
-        labels_map.insert(label.clone(), off);
-        off += addr.len()+1;
+        buf.push(labels[i].len() as u8);
+        buf.extend_from_slice(&labels[i]);
+        labels_map.insert(key, off);
+        off += labels[i].len()+1;
     }
 
     buf.push(0x00);
 
-    buf
+    Ok(buf)
 }
 
-pub fn unpack_domain(buf: &[u8], off: usize) -> (String, usize) {
+//THE WIRE LABELS OF A STORED NAME - AN ESCAPED DOT IS PART OF ITS LABEL, ANY OTHER DOT ENDS ONE
+//EMPTY LABELS ONLY COME FROM THE ROOT OR A TRAILING DOT, THE TERMINATOR COVERS BOTH
+//RFC 1035 2.3.4 - 63 BYTES A LABEL, 255 FOR THE WHOLE NAME ON THE WIRE, LENGTH BYTES AND TERMINATOR INCLUDED
+fn to_labels(domain: &str) -> Result<Vec<Vec<u8>>, String> {
+    let mut labels = Vec::new();
+    let mut label = Vec::new();
+    let mut bytes = domain.bytes().peekable();
+
+    while let Some(b) = bytes.next() {
+        match b {
+            b'\\' if bytes.peek() == Some(&b'.') => {
+                bytes.next();
+                label.push(b'.');
+            }
+            b'.' => labels.push(std::mem::take(&mut label)),
+            _ => label.push(b)
+        }
+    }
+
+    labels.push(label);
+    labels.retain(|label| !label.is_empty());
+
+    if let Some(label) = labels.iter().find(|label| label.len() > 63) {
+        return Err(format!("Label '{}' is longer than 63 bytes", escape_label(label)));
+    }
+
+    if labels.iter().map(|label| label.len()+1).sum::<usize>()+1 > 255 {
+        return Err(format!("Domain '{}' is longer than 255 bytes", domain));
+    }
+
+    Ok(labels)
+}
+
+//A DOT INSIDE A LABEL IS KEPT ESCAPED, SO IT CAN'T BE MISTAKEN FOR THE END OF ONE
+fn escape_label(label: &[u8]) -> String {
+    String::from_utf8_lossy(label).replace('.', "\\.")
+}
+
+//POINTERS MUST POINT FURTHER BACK THAN THE LAST ONE WE FOLLOWED, SO A LOOP ENDS IN AN ERROR INSTEAD OF SPINNING
+pub fn unpack_domain(buf: &[u8], off: usize) -> Result<(String, usize), String> {
     let mut builder = String::new();
     let mut pos = off;
-    let mut jumped = false;
-    let mut original_pos = pos;
+    let mut limit = off;
+    let mut end = None;
+    let mut wire_length = 0;
 
-    while pos < buf.len() {
-        let length = buf[pos] as usize;
+    loop {
+        let length = *buf.get(pos).ok_or("Domain runs past the end of the message")? as usize;
         pos += 1;
 
-        if length == 0 {
-            break;
-        }
+        match length & 0xC0 {
+            0x00 if length == 0 => break,
+            0x00 => {
+                let label = buf.get(pos..pos+length).ok_or("Label runs past the end of the message")?;
+
+                //THE TERMINATOR TAKES THE LAST OF THE 255
+                wire_length += length+1;
+                if wire_length+1 > 255 {
+                    return Err("Domain is longer than 255 bytes".to_string());
+                }
 
-        if (length & 0xC0) == 0xC0 {
-            if pos >= buf.len() {
-                break;
+                if !builder.is_empty() {
+                    builder.push('.');
+                }
+
+                builder.push_str(&escape_label(label));
+                pos += length;
             }
-            let pointer_offset = ((length & 0x3F) << 8) | buf[pos] as usize;
-            pos += 1;
+            0xC0 => {
+                let pointer_offset = ((length & 0x3F) << 8) | *buf.get(pos).ok_or("Compression pointer runs past the end of the message")? as usize;
+                pos += 1;
+
+                if pointer_offset >= limit {
+                    return Err(format!("Compression pointer to {} doesn't point backwards", pointer_offset));
+                }
+
+                if end.is_none() {
+                    end = Some(pos);
+                }
 
-            if !jumped {
-                original_pos = pos;
+                limit = pointer_offset;
+                pos = pointer_offset;
             }
-            pos = pointer_offset;
-            jumped = true;
+            _ => return Err(format!("Unsupported label type: 0x{:02x}", length & 0xC0))
+        }
+    }
+
+    Ok((builder, end.unwrap_or(pos)-off))
+}
+
+//PRESENTATION FORM WITH THE TRAILING DOT, ESCAPED DOTS ARE ALREADY ESCAPED IN THE STORED NAME
+pub fn to_fqdn(domain: &str) -> String {
+    if domain.is_empty() {
+        return ".".to_string();
+    }
 
-        } else {
-            if !builder.is_empty() {
-                builder.push('.');
+    let mut fqdn = String::new();
+    let mut chars = domain.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'.') => {
+                chars.next();
+                fqdn.push_str("\\.");
+            }
+            '\\' | '"' | '(' | ')' | ';' | '@' | '$' => {
+                fqdn.push('\\');
+                fqdn.push(c);
+            }
+            '!'..='~' => fqdn.push(c),
+            _ => {
+                for byte in c.to_string().bytes() {
+                    fqdn.push_str(&format!("\\{:03}", byte));
+                }
             }
+        }
+    }
+
+    fqdn.push('.');
+    fqdn
+}
+
+//THE STORED FORM OF A PRESENTATION NAME, \X AND \DDD BECOME THE BYTES THEY STAND FOR - THE INVERSE OF to_fqdn
+pub fn from_fqdn(domain: &str) -> Result<String, String> {
+    if domain == "." {
+        return Ok(String::new());
+    }
 
-            if pos + length > buf.len() {
-                break;
+    let absolute = domain.ends_with('.') && !domain.ends_with("\\.");
+    let bytes = if absolute { &domain.as_bytes()[..domain.len()-1] } else { domain.as_bytes() };
+
+    let mut labels = Vec::new();
+    let mut label = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if i+3 < bytes.len() && bytes[i+1..i+4].iter().all(|b| b.is_ascii_digit()) => {
+                let value = (bytes[i+1] - b'0') as u16 * 100 + (bytes[i+2] - b'0') as u16 * 10 + (bytes[i+3] - b'0') as u16;
+
+                if value > 255 {
+                    return Err(format!("Invalid escape '\\{}' in '{}'", String::from_utf8_lossy(&bytes[i+1..i+4]), domain));
+                }
+
+                label.push(value as u8);
+                i += 4;
+            }
+            b'\\' => {
+                match bytes.get(i+1) {
+                    Some(&b) => label.push(b),
+                    None => return Err(format!("Dangling escape at the end of '{}'", domain))
+                }
+                i += 2;
             }
+            b'.' => {
+                if label.is_empty() {
+                    return Err(format!("Empty label in name '{}'", domain));
+                }
 
-            let label = &buf[pos..pos + length];
-            builder.push_str(&String::from_utf8_lossy(label));
-            pos += length;
+                labels.push(std::mem::take(&mut label));
+                i += 1;
+            }
+            b => {
+                label.push(b);
+                i += 1;
+            }
         }
     }
 
-    let final_pos = if jumped { original_pos } else { pos };
-    (builder, final_pos - off)
+    if label.is_empty() {
+        return Err(format!("Empty label in name '{}'", domain));
+    }
+    labels.push(label);
+
+    let name = labels.iter().map(|label| escape_label(label)).collect::<Vec<_>>().join(".");
+    to_labels(&name)?;
+
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashMap;
+    use crate::utils::domain_utils::{from_fqdn, pack_domain, pack_domain_uncompressed, to_fqdn, unpack_domain};
+
+    #[test]
+    fn unpack_follows_backward_pointers() {
+        let buf = [3, b'c', b'o', b'm', 0, 3, b'w', b'w', b'w', 0xC0, 0x00];
+        assert_eq!(unpack_domain(&buf, 5).unwrap(), ("www.com".to_string(), 6));
+    }
+
+    #[test]
+    fn unpack_rejects_forward_pointers() {
+        let buf = [0xC0, 0x02, 3, b'c', b'o', b'm', 0];
+        assert!(unpack_domain(&buf, 0).is_err());
+    }
+
+    #[test]
+    fn unpack_rejects_self_pointers() {
+        assert!(unpack_domain(&[0xC0, 0x00], 0).is_err());
+        assert!(unpack_domain(&[0, 0xC0, 0x01], 1).is_err());
+    }
+
+    #[test]
+    fn unpack_rejects_pointer_loops() {
+        //www THEN A POINTER BACK TO ITSELF, EACH PASS LOOKS BACKWARDS FROM WHERE IT STARTED
+        let buf = [3, b'w', b'w', b'w', 0xC0, 0x00];
+        assert!(unpack_domain(&buf, 4).is_err());
+    }
+
+    #[test]
+    fn unpack_rejects_truncated_names() {
+        assert!(unpack_domain(&[3, b'c', b'o'], 0).is_err());
+        assert!(unpack_domain(&[3, b'c', b'o', b'm'], 0).is_err());
+        assert!(unpack_domain(&[0xC0], 0).is_err());
+    }
+
+    #[test]
+    fn escaped_dot_stays_inside_its_label() {
+        let buf = pack_domain_uncompressed("a\\.b.example").unwrap();
+        assert_eq!(buf, [3, b'a', b'.', b'b', 7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0]);
+        assert_eq!(unpack_domain(&buf, 0).unwrap(), ("a\\.b.example".to_string(), buf.len()));
+
+        //a.b.example IS A DIFFERENT NAME, IT MUSTN'T BE COMPRESSED AGAINST a\.b.example
+        let mut labels_map = HashMap::new();
+        pack_domain("a\\.b.example", &mut labels_map, 0).unwrap();
+        assert_eq!(pack_domain("a.b.example", &mut labels_map, 13).unwrap(), [1, b'a', 1, b'b', 0xC0, 4]);
+    }
+
+    #[test]
+    fn pack_rejects_long_labels() {
+        let label = "a".repeat(63);
+        assert!(pack_domain_uncompressed(&format!("{}.example", label)).is_ok());
+        assert!(pack_domain_uncompressed(&format!("{}a.example", label)).is_err());
+        assert!(pack_domain(&format!("{}a.example", label), &mut HashMap::new(), 0).is_err());
+    }
+
+    #[test]
+    fn pack_rejects_long_names() {
+        //FOUR 62 BYTE LABELS ARE 4*63+1 = 253 ON THE WIRE, A FIFTH LABEL OF ONE BYTE MAKES 255, OF TWO 256
+        let name = vec!["a".repeat(62); 4].join(".");
+        assert_eq!(pack_domain_uncompressed(&format!("b.{}", name)).unwrap().len(), 255);
+        assert!(pack_domain_uncompressed(&format!("bb.{}", name)).is_err());
+        assert!(pack_domain(&format!("bb.{}", name), &mut HashMap::new(), 0).is_err());
+
+        let buf = pack_domain_uncompressed(&format!("b.{}", name)).unwrap();
+        assert!(unpack_domain(&buf, 0).is_ok());
+
+        let mut buf = buf;
+        buf.insert(1, b'b');
+        buf[0] = 2;
+        assert!(unpack_domain(&buf, 0).is_err());
+    }
+
+    #[test]
+    fn from_fqdn_undoes_to_fqdn() {
+        for name in ["", "www.example.com", "a\\.b.example", "caf\u{e9}.example", "semi;colon.example"] {
+            assert_eq!(from_fqdn(&to_fqdn(name)).unwrap(), name);
+        }
+
+        assert_eq!(from_fqdn("\\065b.example").unwrap(), "Ab.example");
+        assert!(from_fqdn("a..example.").is_err());
+        assert!(from_fqdn("a\\256.example.").is_err());
+        assert!(from_fqdn(&format!("{}.example.", "a".repeat(64))).is_err());
+    }
 }
@@ -0,0 +1,36 @@
+pub fn encode(buf: &[u8]) -> String {
+    let mut encoded = String::with_capacity(buf.len() * 2);
+
+    for byte in buf {
+        encoded.push_str(&format!("{:02x}", byte));
+    }
+
+    encoded
+}
+
+pub fn decode(encoded: &str) -> Result<Vec<u8>, String> {
+    let mut buf = Vec::with_capacity(encoded.len() / 2);
+    let mut high: Option<u8> = None;
+
+    for c in encoded.chars() {
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+
+        let nibble = match c.to_digit(16) {
+            Some(nibble) => nibble as u8,
+            None => return Err(format!("Invalid hex character '{}'", c))
+        };
+
+        match high.take() {
+            Some(high) => buf.push((high << 4) | nibble),
+            None => high = Some(nibble)
+        }
+    }
+
+    if high.is_some() {
+        return Err("Odd number of hex digits".to_string());
+    }
+
+    Ok(buf)
+}
@@ -3,3 +3,8 @@ pub mod domain_utils;
 pub mod linked_hashmap;
 pub mod ordered_map;
 pub mod random;
+pub mod base64;
+pub mod hex;
+pub mod time_utils;
+pub mod string_utils;
+pub mod name;
@@ -0,0 +1,268 @@
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+const IPV4_REVERSE: &str = "in-addr.arpa";
+const IPV6_REVERSE: &str = "ip6.arpa";
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Name {
+    name: String
+}
+
+impl Name {
+
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.trim_end_matches('.').to_string()
+        }
+    }
+
+    //RFC 1035 3.5 AND RFC 3596 2.5 - THE OCTETS OR NIBBLES LEAST SIGNIFICANT FIRST, UNDER in-addr.arpa OR ip6.arpa
+    //AN IPV4 MAPPED IPV6 ADDRESS STAYS IPV6, THE ip6.arpa TREE IS WHERE ITS PTR WOULD BE
+    pub fn from_ip(address: IpAddr) -> Self {
+        let name = match address {
+            IpAddr::V4(v4) => {
+                let [a, b, c, d] = v4.octets();
+                format!("{}.{}.{}.{}.{}", d, c, b, a, IPV4_REVERSE)
+            }
+            IpAddr::V6(v6) => {
+                let mut name = String::with_capacity(72);
+
+                for octet in v6.octets().iter().rev() {
+                    name.push_str(&format!("{:x}.{:x}.", octet & 0x0f, octet >> 4));
+                }
+
+                name.push_str(IPV6_REVERSE);
+                name
+            }
+        };
+
+        Self {
+            name
+        }
+    }
+
+    //THE ADDRESS AND HOW MANY LEADING BITS OF IT THE NAME PINS DOWN, A FULL NAME IS 32 OR 128, A ZONE LIKE 2.0.192.in-addr.arpa IS 24
+    //RFC 2317 - A CLASSLESS LABEL, 0/25 OR 0-127, STANDS IN FOR THE LAST OCTET OF ITS BLOCK, AND A HOST UNDER IT STILL GIVES THE FULL ADDRESS
+    pub fn to_ip(&self) -> Result<(IpAddr, u8), String> {
+        let lower = self.name.to_ascii_lowercase();
+
+        if let Some(labels) = strip_zone(&lower, IPV4_REVERSE) {
+            return parse_ipv4(labels).map_err(|e| format!("{}: {}", e, self.name));
+        }
+
+        if let Some(labels) = strip_zone(&lower, IPV6_REVERSE) {
+            return parse_ipv6(labels).map_err(|e| format!("{}: {}", e, self.name));
+        }
+
+        Err(format!("Not a reverse name: {}", self.name))
+    }
+
+    //ONLY WHEN THE NAME IS A WHOLE ADDRESS, NOT A ZONE
+    pub fn to_full_ip(&self) -> Option<IpAddr> {
+        match self.to_ip().ok()? {
+            (address @ IpAddr::V4(_), 32) | (address @ IpAddr::V6(_), 128) => Some(address),
+            _ => None
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for Name {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+//THE LABELS UNDER zone, MOST SIGNIFICANT FIRST - THE ZONE APEX ITSELF HAS NONE
+fn strip_zone<'a>(name: &'a str, zone: &str) -> Option<Vec<&'a str>> {
+    if name == zone {
+        return Some(Vec::new());
+    }
+
+    let labels = name.strip_suffix(zone)?.strip_suffix('.')?;
+    Some(labels.split('.').rev().collect())
+}
+
+fn parse_ipv4(labels: Vec<&str>) -> Result<(IpAddr, u8), String> {
+    let mut octets = [0u8; 4];
+    let mut prefix = 0u8;
+    let mut labels = labels.into_iter();
+
+    while let Some(label) = labels.next() {
+        let position = (prefix/8) as usize;
+
+        if !prefix.is_multiple_of(8) || position >= 4 {
+            return Err(format!("Unexpected label '{}' in reverse name", label));
+        }
+
+        match parse_classless(label, position)? {
+            Some((base, length)) => {
+                octets[position] = base;
+                prefix = length;
+
+                //THE HOST UNDER THE BLOCK, IT HAS TO FALL INSIDE IT
+                if let Some(host) = labels.next() {
+                    let host = parse_octet(host)?;
+                    let mask = (0xffu16 << (position*8+8-length as usize)) as u8;
+
+                    if host & mask != base {
+                        return Err(format!("{} isn't inside the block '{}'", host, label));
+                    }
+
+                    octets[position] = host;
+                    prefix = (position*8+8) as u8;
+                }
+            }
+            None => {
+                octets[position] = parse_octet(label)?;
+                prefix += 8;
+            }
+        }
+    }
+
+    Ok((IpAddr::V4(Ipv4Addr::from(octets)), prefix))
+}
+
+fn parse_ipv6(labels: Vec<&str>) -> Result<(IpAddr, u8), String> {
+    if labels.len() > 32 {
+        return Err("More than 32 nibbles in reverse name".to_string());
+    }
+
+    let mut bits = 0u128;
+
+    for (i, label) in labels.iter().enumerate() {
+        let nibble = match label.len() {
+            1 => u8::from_str_radix(label, 16).map_err(|_| format!("'{}' isn't a hex nibble", label))?,
+            _ => return Err(format!("'{}' isn't a hex nibble", label))
+        };
+
+        bits |= (nibble as u128) << (124-i*4);
+    }
+
+    Ok((IpAddr::V6(Ipv6Addr::from(bits)), (labels.len()*4) as u8))
+}
+
+//NO LEADING ZEROS, 01 ISN'T HOW ANY REVERSE ZONE SPELLS 1
+fn parse_octet(label: &str) -> Result<u8, String> {
+    if label.is_empty() || (label.len() > 1 && label.starts_with('0')) || !label.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("'{}' isn't an octet", label));
+    }
+
+    label.parse::<u8>().map_err(|_| format!("'{}' isn't an octet", label))
+}
+
+//0/25 IS THE BASE AND PREFIX LENGTH, 0-127 IS THE FIRST AND LAST ADDRESS OF THE BLOCK, None FOR A PLAIN OCTET
+//EITHER WAY THE BLOCK HAS TO BE ALIGNED AND INSIDE THE OCTET AT position
+fn parse_classless(label: &str, position: usize) -> Result<Option<(u8, u8)>, String> {
+    let (base, size) = if let Some((base, length)) = label.split_once('/') {
+        let length = length.parse::<usize>().map_err(|_| format!("'{}' isn't a prefix length", length))?;
+        let host_bits = (position*8+8).checked_sub(length).filter(|bits| *bits < 8)
+            .ok_or(format!("Classless label '{}' doesn't fit octet {}", label, position+1))?;
+        (parse_octet(base)?, 1u16 << host_bits)
+    } else if let Some((first, last)) = label.split_once('-') {
+        let (first, last) = (parse_octet(first)?, parse_octet(last)?);
+        let size = (last as u16+1).checked_sub(first as u16).filter(|size| size.is_power_of_two() && *size < 256)
+            .ok_or(format!("'{}' isn't a block of addresses", label))?;
+        (first, size)
+    } else {
+        return Ok(None);
+    };
+
+    if !(base as u16).is_multiple_of(size) {
+        return Err(format!("'{}' isn't aligned to its size", label));
+    }
+
+    Ok(Some((base, (position*8+8) as u8-size.trailing_zeros() as u8)))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::net::IpAddr;
+    use crate::utils::name::Name;
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    fn to_ip(name: &str) -> Result<(IpAddr, u8), String> {
+        Name::new(name).to_ip()
+    }
+
+    #[test]
+    fn ipv4_round_trip() {
+        let name = Name::from_ip(ip("192.0.2.1"));
+        assert_eq!(name.get_name(), "1.2.0.192.in-addr.arpa");
+        assert_eq!(name.to_ip(), Ok((ip("192.0.2.1"), 32)));
+        assert_eq!(name.to_full_ip(), Some(ip("192.0.2.1")));
+    }
+
+    #[test]
+    fn ipv6_round_trip() {
+        let name = Name::from_ip(ip("2001:db8::567:89ab"));
+        assert_eq!(name.get_name(), "b.a.9.8.7.6.5.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa");
+        assert_eq!(name.to_ip(), Ok((ip("2001:db8::567:89ab"), 128)));
+
+        //A MAPPED ADDRESS STAYS IN THE ip6.arpa TREE
+        let mapped = Name::from_ip(ip("::ffff:192.0.2.1"));
+        assert!(mapped.get_name().ends_with(".ip6.arpa"));
+        assert_eq!(mapped.to_full_ip(), Some(ip("::ffff:192.0.2.1")));
+    }
+
+    #[test]
+    fn partial_names_are_prefixes() {
+        assert_eq!(to_ip("2.0.192.in-addr.arpa."), Ok((ip("192.0.2.0"), 24)));
+        assert_eq!(to_ip("192.IN-ADDR.ARPA"), Ok((ip("192.0.0.0"), 8)));
+        assert_eq!(to_ip("in-addr.arpa"), Ok((ip("0.0.0.0"), 0)));
+        assert_eq!(to_ip("8.b.d.0.1.0.0.2.ip6.arpa"), Ok((ip("2001:db8::"), 32)));
+        assert_eq!(to_ip("0.8.b.d.0.1.0.0.2.ip6.arpa"), Ok((ip("2001:db8::"), 36)));
+        assert_eq!(Name::new("2.0.192.in-addr.arpa").to_full_ip(), None);
+    }
+
+    #[test]
+    fn classless_blocks() {
+        assert_eq!(to_ip("0/25.2.0.192.in-addr.arpa"), Ok((ip("192.0.2.0"), 25)));
+        assert_eq!(to_ip("128/26.2.0.192.in-addr.arpa"), Ok((ip("192.0.2.128"), 26)));
+        assert_eq!(to_ip("0-127.2.0.192.in-addr.arpa"), Ok((ip("192.0.2.0"), 25)));
+        assert_eq!(to_ip("64-79.2.0.192.in-addr.arpa"), Ok((ip("192.0.2.64"), 28)));
+    }
+
+    #[test]
+    fn hosts_under_classless_blocks() {
+        assert_eq!(to_ip("5.0/25.2.0.192.in-addr.arpa"), Ok((ip("192.0.2.5"), 32)));
+        assert_eq!(to_ip("130.128-191.2.0.192.in-addr.arpa"), Ok((ip("192.0.2.130"), 32)));
+        assert_eq!(Name::new("5.0-127.2.0.192.in-addr.arpa").to_full_ip(), Some(ip("192.0.2.5")));
+
+        //THE HOST HAS TO BE INSIDE THE BLOCK, AND NOTHING GOES UNDER A HOST
+        assert!(to_ip("200.0/25.2.0.192.in-addr.arpa").is_err());
+        assert!(to_ip("1.5.0/25.2.0.192.in-addr.arpa").is_err());
+    }
+
+    #[test]
+    fn bad_names_are_rejected() {
+        //MISALIGNED OR ODD SIZED BLOCKS
+        assert!(to_ip("64/25.2.0.192.in-addr.arpa").is_err());
+        assert!(to_ip("0-100.2.0.192.in-addr.arpa").is_err());
+        assert!(to_ip("0/33.2.0.192.in-addr.arpa").is_err());
+        assert!(to_ip("0/24.2.0.192.in-addr.arpa").is_err());
+
+        //LEADING ZEROS, OUT OF RANGE AND TOO MANY OCTETS
+        assert!(to_ip("01.2.0.192.in-addr.arpa").is_err());
+        assert!(to_ip("256.2.0.192.in-addr.arpa").is_err());
+        assert!(to_ip("1.1.2.0.192.in-addr.arpa").is_err());
+
+        //MORE THAN 32 NIBBLES, OR A LABEL THAT ISN'T ONE
+        let nibbles = vec!["0"; 33].join(".");
+        assert!(to_ip(&format!("{}.ip6.arpa", nibbles)).is_err());
+        assert!(to_ip("10.8.b.d.0.1.0.0.2.ip6.arpa").is_err());
+        assert!(to_ip("g.ip6.arpa").is_err());
+
+        assert!(to_ip("www.example.com").is_err());
+        assert!(to_ip("xin-addr.arpa").is_err());
+    }
+}
@@ -0,0 +1,40 @@
+//RFC 1035 5.1 - <character-string> IN QUOTES, NON PRINTABLE BYTES AS \DDD
+pub fn quote_string(buf: &[u8]) -> String {
+    let mut quoted = String::from("\"");
+
+    for &byte in buf {
+        match byte {
+            b'"' | b'\\' => {
+                quoted.push('\\');
+                quoted.push(byte as char);
+            }
+            b' '..=b'~' => quoted.push(byte as char),
+            _ => quoted.push_str(&format!("\\{:03}", byte))
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+//UNQUOTED FORM, ANYTHING THE LEXER WOULD SPLIT ON OR TREAT AS SPECIAL GETS ESCAPED
+pub fn escape_string(buf: &[u8], special: &[u8]) -> String {
+    let mut escaped = String::new();
+
+    for &byte in buf {
+        match byte {
+            b'"' | b'\\' | b'(' | b')' | b';' => {
+                escaped.push('\\');
+                escaped.push(byte as char);
+            }
+            _ if special.contains(&byte) => {
+                escaped.push('\\');
+                escaped.push(byte as char);
+            }
+            b'!'..=b'~' => escaped.push(byte as char),
+            _ => escaped.push_str(&format!("\\{:03}", byte))
+        }
+    }
+
+    escaped
+}
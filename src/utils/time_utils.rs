@@ -0,0 +1,36 @@
+//CIVIL DATE <-> DAYS SINCE 1970-01-01, PROLEPTIC GREGORIAN CALENDAR
+
+pub fn to_epoch(year: i64, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+
+    days * 86400 + hour as i64 * 3600 + minute as i64 * 60 + second as i64
+}
+
+pub fn from_epoch(epoch: i64) -> (i64, u32, u32, u32, u32, u32) {
+    let days = epoch.div_euclid(86400);
+    let secs = epoch.rem_euclid(86400);
+
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day, (secs / 3600) as u32, (secs % 3600 / 60) as u32, (secs % 60) as u32)
+}
+
+//RFC 4034 3.2 - YYYYMMDDHHmmSS IN UTC
+pub fn to_timestamp(epoch: u32) -> String {
+    let (year, month, day, hour, minute, second) = from_epoch(epoch as i64);
+    format!("{:04}{:02}{:02}{:02}{:02}{:02}", year, month, day, hour, minute, second)
+}
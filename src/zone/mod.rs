@@ -0,0 +1,6 @@
+pub mod zone_error;
+pub mod zone_lexer;
+pub mod zone_parser;
+pub mod rdata_parser;
+pub mod zone_file;
+pub mod zone_writer;
@@ -0,0 +1,580 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use crate::messages::inter::dns_classes::DnsClasses;
+use crate::messages::inter::types::Types;
+use crate::messages::message_base::MessageBase;
+use crate::records::a_record::ARecord;
+use crate::records::aaaa_record::AAAARecord;
+use crate::records::cname_record::CNameRecord;
+use crate::records::dname_record::DNameRecord;
+use crate::records::dnskey_record::DNSKeyRecord;
+use crate::records::https_record::HttpsRecord;
+use crate::records::inter::dnssec_algorithms::DnsSecAlgorithms;
+use crate::records::inter::record_base::RecordBase;
+use crate::records::inter::svc_param_keys::SvcParamKeys;
+use crate::records::mx_record::MxRecord;
+use crate::records::ns_record::NsRecord;
+use crate::records::nsec_record::NsecRecord;
+use crate::records::ptr_record::PtrRecord;
+use crate::records::rrsig_record::RRSigRecord;
+use crate::records::soa_record::SoaRecord;
+use crate::records::srv_record::SrvRecord;
+use crate::records::txt_record::TxtRecord;
+use crate::utils::base64;
+use crate::utils::domain_utils::{from_fqdn, pack_domain_uncompressed};
+use crate::utils::hex;
+use crate::utils::ordered_map::OrderedMap;
+use crate::utils::time_utils::to_epoch;
+use crate::zone::zone_error::ZoneError;
+use crate::zone::zone_lexer::{tokenize, unescape, Token};
+
+pub struct RdataReader<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    end: (usize, usize)
+}
+
+impl<'a> RdataReader<'a> {
+
+    //END IS WHERE A MISSING FIELD GETS REPORTED, JUST PAST THE LAST THING ON THE LINE
+    pub fn new(tokens: &'a [Token], end: (usize, usize)) -> Self {
+        Self {
+            tokens,
+            pos: 0,
+            end
+        }
+    }
+
+    pub fn next(&mut self, field: &str) -> Result<&'a Token, ZoneError> {
+        match self.tokens.get(self.pos) {
+            Some(token) => {
+                self.pos += 1;
+                Ok(token)
+            }
+            None => Err(ZoneError::new(self.end.0, self.end.1, &format!("Missing {}", field)))
+        }
+    }
+
+    pub fn next_u8(&mut self, field: &str) -> Result<u8, ZoneError> {
+        let token = self.next(field)?;
+        token.get_value().parse::<u8>().map_err(|_| token.error(&format!("Invalid {} '{}', expected 0-255", field, token.get_value())))
+    }
+
+    pub fn next_u16(&mut self, field: &str) -> Result<u16, ZoneError> {
+        let token = self.next(field)?;
+        token.get_value().parse::<u16>().map_err(|_| token.error(&format!("Invalid {} '{}', expected 0-65535", field, token.get_value())))
+    }
+
+    pub fn next_u32(&mut self, field: &str) -> Result<u32, ZoneError> {
+        let token = self.next(field)?;
+        token.get_value().parse::<u32>().map_err(|_| token.error(&format!("Invalid {} '{}', expected 0-4294967295", field, token.get_value())))
+    }
+
+    pub fn next_ttl(&mut self, field: &str) -> Result<u32, ZoneError> {
+        let token = self.next(field)?;
+        parse_ttl(token).map_err(|_| token.error(&format!("Invalid {} '{}', expected seconds or a duration like 1h30m", field, token.get_value())))
+    }
+
+    pub fn next_name(&mut self, field: &str, origin: &str) -> Result<String, ZoneError> {
+        let token = self.next(field)?;
+        parse_name(token, origin)
+    }
+
+    pub fn next_ipv4(&mut self, field: &str) -> Result<Ipv4Addr, ZoneError> {
+        let token = self.next(field)?;
+        token.get_value().parse::<Ipv4Addr>().map_err(|_| token.error(&format!("Invalid {} '{}', expected an IPv4 address", field, token.get_value())))
+    }
+
+    pub fn next_ipv6(&mut self, field: &str) -> Result<Ipv6Addr, ZoneError> {
+        let token = self.next(field)?;
+        token.get_value().parse::<Ipv6Addr>().map_err(|_| token.error(&format!("Invalid {} '{}', expected an IPv6 address", field, token.get_value())))
+    }
+
+    pub fn next_type_code(&mut self, field: &str) -> Result<u16, ZoneError> {
+        let token = self.next(field)?;
+        parse_type_code(token)
+    }
+
+    pub fn next_algorithm(&mut self, field: &str) -> Result<u8, ZoneError> {
+        let token = self.next(field)?;
+
+        if let Ok(algorithm) = token.get_value().parse::<u8>() {
+            return Ok(algorithm);
+        }
+
+        for code in 0..=255 {
+            if let Ok(algorithm) = DnsSecAlgorithms::from_code(code) {
+                if algorithm.get_mnemonic().eq_ignore_ascii_case(token.get_value()) {
+                    return Ok(code);
+                }
+            }
+        }
+
+        Err(token.error(&format!("Invalid {} '{}', expected a number or algorithm mnemonic", field, token.get_value())))
+    }
+
+    //RFC 4034 3.2 - YYYYMMDDHHmmSS IN UTC, OR A PLAIN NUMBER OF SECONDS
+    pub fn next_time(&mut self, field: &str) -> Result<u32, ZoneError> {
+        let token = self.next(field)?;
+        let value = token.get_value();
+        let error = || token.error(&format!("Invalid {} '{}', expected YYYYMMDDHHmmSS", field, value));
+
+        if value.len() == 14 && value.bytes().all(|b| b.is_ascii_digit()) {
+            let year = value[0..4].parse::<i64>().map_err(|_| error())?;
+            let month = value[4..6].parse::<u32>().map_err(|_| error())?;
+            let day = value[6..8].parse::<u32>().map_err(|_| error())?;
+            let hour = value[8..10].parse::<u32>().map_err(|_| error())?;
+            let minute = value[10..12].parse::<u32>().map_err(|_| error())?;
+            let second = value[12..14].parse::<u32>().map_err(|_| error())?;
+
+            if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 59 {
+                return Err(error());
+            }
+
+            //SERIAL NUMBER ARITHMETIC, TIMES PAST 2106 WRAP
+            return Ok(to_epoch(year, month, day, hour, minute, second).rem_euclid(1 << 32) as u32);
+        }
+
+        value.parse::<u32>().map_err(|_| error())
+    }
+
+    pub fn remaining_base64(&mut self, field: &str) -> Result<Vec<u8>, ZoneError> {
+        let first = self.next(field)?;
+        let mut encoded = first.get_value().to_string();
+
+        while let Some(token) = self.tokens.get(self.pos) {
+            encoded.push_str(token.get_value());
+            self.pos += 1;
+        }
+
+        base64::decode(&encoded).map_err(|e| first.error(&format!("Invalid {}: {}", field, e)))
+    }
+
+    pub fn remaining(&mut self) -> &'a [Token] {
+        let remaining = &self.tokens[self.pos..];
+        self.pos = self.tokens.len();
+        remaining
+    }
+
+    pub fn finish(&self, _type: &str) -> Result<(), ZoneError> {
+        match self.tokens.get(self.pos) {
+            Some(token) => Err(token.error(&format!("Unexpected '{}' after {} RDATA", token.get_value(), _type))),
+            None => Ok(())
+        }
+    }
+}
+
+pub fn parse_rdata(_type: Types, dns_class: DnsClasses, ttl: u32, tokens: &[Token], end: (usize, usize), origin: &str) -> Result<Box<dyn RecordBase>, ZoneError> {
+    //RFC 3597 5 - THE GENERIC FORM WORKS FOR ANY TYPE
+    if let Some(first) = tokens.first() {
+        if first.get_value() == "\\#" && !first.is_quoted() {
+            return parse_generic(_type, dns_class, ttl, &tokens[1..], end);
+        }
+    }
+
+    let mut reader = RdataReader::new(tokens, end);
+
+    let record: Box<dyn RecordBase> = match _type {
+        Types::A => {
+            let address = reader.next_ipv4("IPv4 address")?;
+            Box::new(ARecord::new(dns_class, false, ttl, IpAddr::V4(address)))
+        }
+        Types::Aaaa => {
+            let address = reader.next_ipv6("IPv6 address")?;
+            Box::new(AAAARecord::new(dns_class, false, ttl, IpAddr::V6(address)))
+        }
+        Types::Ns => {
+            Box::new(NsRecord::new(dns_class, ttl, &reader.next_name("name server", origin)?))
+        }
+        Types::Cname => {
+            Box::new(CNameRecord::new(dns_class, ttl, &reader.next_name("canonical name", origin)?))
+        }
+        Types::Dname => {
+            Box::new(DNameRecord::new(dns_class, ttl, &reader.next_name("target name", origin)?))
+        }
+        Types::Ptr => {
+            Box::new(PtrRecord::new(dns_class, false, ttl, &reader.next_name("pointer name", origin)?))
+        }
+        Types::Mx => {
+            let priority = reader.next_u16("MX preference")?;
+            let domain = reader.next_name("mail exchange", origin)?;
+            Box::new(MxRecord::new(dns_class, ttl, priority, &domain))
+        }
+        Types::Soa => {
+            let domain = reader.next_name("SOA primary name server", origin)?;
+            let mailbox = reader.next_name("SOA mailbox", origin)?;
+            let serial_number = reader.next_u32("SOA serial")?;
+            let refresh_interval = reader.next_ttl("SOA refresh")?;
+            let retry_interval = reader.next_ttl("SOA retry")?;
+            let expire_limit = reader.next_ttl("SOA expire")?;
+            let minimum_ttl = reader.next_ttl("SOA minimum")?;
+            Box::new(SoaRecord::new(dns_class, ttl, &domain, &mailbox, serial_number, refresh_interval, retry_interval, expire_limit, minimum_ttl))
+        }
+        Types::Txt => {
+            let strings = reader.remaining();
+
+            if strings.is_empty() {
+                return Err(ZoneError::new(end.0, end.1, "Missing TXT string"));
+            }
+
+            let mut records = Vec::new();
+
+            for token in strings {
+                let record = unescape(token)?;
+
+                if record.len() > 255 {
+                    return Err(token.error(&format!("TXT string is {} bytes, the limit is 255", record.len())));
+                }
+
+                records.push(String::from_utf8_lossy(&record).to_string());
+            }
+
+            Box::new(TxtRecord::new(dns_class, false, ttl, records))
+        }
+        Types::Srv => {
+            let priority = reader.next_u16("SRV priority")?;
+            let weight = reader.next_u16("SRV weight")?;
+            let port = reader.next_u16("SRV port")?;
+            let target = reader.next_name("SRV target", origin)?;
+            Box::new(SrvRecord::new(dns_class, false, ttl, priority, weight, port, &target))
+        }
+        Types::Rrsig => {
+            let type_covered = reader.next_type_code("RRSIG type covered")?;
+            let algorithm = reader.next_algorithm("RRSIG algorithm")?;
+            let labels = reader.next_u8("RRSIG labels")?;
+            let original_ttl = reader.next_ttl("RRSIG original TTL")?;
+            let signature_expiration = reader.next_time("RRSIG expiration")?;
+            let signature_inception = reader.next_time("RRSIG inception")?;
+            let key_tag = reader.next_u16("RRSIG key tag")?;
+            let signer_name = reader.next_name("RRSIG signer name", origin)?;
+            let signature = reader.remaining_base64("RRSIG signature")?;
+            Box::new(RRSigRecord::new(dns_class, ttl, type_covered, algorithm, labels, original_ttl, signature_expiration, signature_inception, key_tag, &signer_name, &signature))
+        }
+        Types::Nsec => {
+            let domain = reader.next_name("NSEC next domain", origin)?;
+            let mut rr_types = Vec::new();
+
+            for token in reader.remaining() {
+                rr_types.push(parse_type_code(token)?);
+            }
+
+            Box::new(NsecRecord::new(dns_class, false, ttl, &domain, rr_types))
+        }
+        Types::DnsKey => {
+            let flags = reader.next_u16("DNSKEY flags")?;
+            let protocol = reader.next_u8("DNSKEY protocol")?;
+            let algorithm = reader.next_algorithm("DNSKEY algorithm")?;
+            let public_key = reader.remaining_base64("DNSKEY public key")?;
+            Box::new(DNSKeyRecord::new(dns_class, ttl, flags, protocol, algorithm, public_key))
+        }
+        Types::Https => {
+            let svc_priority = reader.next_u16("HTTPS priority")?;
+            let target = reader.next_name("HTTPS target", origin)?;
+            let params = parse_svc_params(reader.remaining())?;
+            Box::new(HttpsRecord::new(dns_class, ttl, svc_priority, &target, params))
+        }
+        _ => {
+            return Err(ZoneError::new(end.0, end.1, &format!("Record type {} is not supported, use the RFC 3597 form: \\# <length> <hex>", _type)));
+        }
+    };
+
+    reader.finish(&_type.to_string())?;
+
+    Ok(record)
+}
+
+//\# <length> <hex>, THE RDATA IS KEPT OPAQUE EVEN FOR TYPES WE KNOW, IT'S THE SAME ON THE WIRE
+//RDATA ON ITS OWN, OUTSIDE ANY ZONE FILE - WITHOUT AN ORIGIN EVERY NAME IN IT IS ABSOLUTE
+pub fn parse_rdata_str(_type: Types, dns_class: DnsClasses, ttl: u32, rdata: &str) -> Result<Box<dyn RecordBase>, String> {
+    let lines = tokenize(rdata).map_err(|e| e.get_message().to_string())?;
+
+    let tokens = match lines.as_slice() {
+        [] => &[][..],
+        [line] => &line.get_tokens()[..],
+        _ => return Err(format!("{} RDATA must be a single line", _type))
+    };
+
+    parse_rdata(_type, dns_class, ttl, tokens, (1, rdata.chars().count()+1), "").map_err(|e| e.get_message().to_string())
+}
+
+fn parse_generic(_type: Types, dns_class: DnsClasses, ttl: u32, tokens: &[Token], end: (usize, usize)) -> Result<Box<dyn RecordBase>, ZoneError> {
+    let mut reader = RdataReader::new(tokens, end);
+    let length = reader.next_u16("RDATA length")?;
+    let hex_tokens = reader.remaining();
+
+    let mut encoded = String::new();
+    for token in hex_tokens {
+        encoded.push_str(token.get_value());
+    }
+
+    let data = hex::decode(&encoded).map_err(|e| match hex_tokens.first() {
+        Some(token) => token.error(&format!("Invalid RDATA hex: {}", e)),
+        None => ZoneError::new(end.0, end.1, &format!("Invalid RDATA hex: {}", e))
+    })?;
+
+    if data.len() != length as usize {
+        return Err(tokens[0].error(&format!("RDATA length is {} but {} bytes of hex follow", length, data.len())));
+    }
+
+    //RFC 3597 5 - A TYPE WE KNOW IS DECODED FROM THE WIRE FORM, SO IT COMES OUT THE SAME AS IF IT WAS WRITTEN OUT NORMALLY
+    let mut wire = Vec::with_capacity(10+data.len());
+    wire.extend_from_slice(&_type.get_code().to_be_bytes());
+    wire.extend_from_slice(&dns_class.get_code().to_be_bytes());
+    wire.extend_from_slice(&ttl.to_be_bytes());
+    wire.extend_from_slice(&length.to_be_bytes());
+    wire.extend_from_slice(&data);
+
+    MessageBase::record_from_bytes(&wire, 0).map_err(|e| tokens[0].error(&format!("Invalid RDATA for {}: {}", _type, e)))
+}
+
+//RFC 9460 2.1 - key=value PAIRS, SORTED BY KEY ON THE WIRE
+fn parse_svc_params(tokens: &[Token]) -> Result<OrderedMap<u16, Vec<u8>>, ZoneError> {
+    let mut params: Vec<(u16, Vec<u8>)> = Vec::new();
+
+    for token in tokens {
+        let (name, value) = match token.get_value().split_once('=') {
+            Some((name, value)) => (name, Some(Token::new(value, token.is_quoted(), token.get_line(), token.get_column()+name.len()+1))),
+            None => (token.get_value(), None)
+        };
+
+        let key = match SvcParamKeys::from_name(name) {
+            Ok(key) => key.get_code(),
+            Err(_) => match name.strip_prefix("key").and_then(|code| code.parse::<u16>().ok()) {
+                Some(code) => code,
+                None => return Err(token.error(&format!("Unknown SvcParam key '{}'", name)))
+            }
+        };
+
+        if params.iter().any(|(k, _)| *k == key) {
+            return Err(token.error(&format!("Duplicate SvcParam key '{}'", name)));
+        }
+
+        let value = match (SvcParamKeys::from_code(key), value) {
+            (Ok(SvcParamKeys::NoDefaultAlpn), None) => Vec::new(),
+            (Ok(SvcParamKeys::NoDefaultAlpn), Some(value)) => {
+                return Err(value.error("no-default-alpn takes no value"));
+            }
+            (Ok(_), None) => {
+                return Err(token.error(&format!("SvcParam '{}' needs a value", name)));
+            }
+            (Ok(SvcParamKeys::Mandatory), Some(value)) => {
+                let mut keys = Vec::new();
+
+                for key in value.get_value().split(',') {
+                    let code = match SvcParamKeys::from_name(key) {
+                        Ok(key) => key.get_code(),
+                        Err(_) => key.strip_prefix("key").and_then(|code| code.parse::<u16>().ok())
+                            .ok_or_else(|| value.error(&format!("Unknown mandatory key '{}'", key)))?
+                    };
+                    keys.push(code);
+                }
+
+                keys.sort();
+                keys.iter().flat_map(|key| key.to_be_bytes()).collect()
+            }
+            (Ok(SvcParamKeys::Alpn), Some(value)) => {
+                let mut buf = Vec::new();
+
+                for id in split_list(value.get_value()) {
+                    let id = unescape(&Token::new(&id, value.is_quoted(), value.get_line(), value.get_column()))?;
+
+                    if id.is_empty() || id.len() > 255 {
+                        return Err(value.error("Invalid alpn id length"));
+                    }
+                    buf.push(id.len() as u8);
+                    buf.extend_from_slice(&id);
+                }
+
+                buf
+            }
+            (Ok(SvcParamKeys::Port), Some(value)) => {
+                value.get_value().parse::<u16>()
+                    .map_err(|_| value.error(&format!("Invalid port '{}'", value.get_value())))?
+                    .to_be_bytes().to_vec()
+            }
+            (Ok(SvcParamKeys::Ipv4Hint), Some(value)) => {
+                let mut buf = Vec::new();
+
+                for address in value.get_value().split(',') {
+                    let address = address.parse::<Ipv4Addr>().map_err(|_| value.error(&format!("Invalid ipv4hint '{}'", address)))?;
+                    buf.extend_from_slice(&address.octets());
+                }
+
+                buf
+            }
+            (Ok(SvcParamKeys::Ipv6Hint), Some(value)) => {
+                let mut buf = Vec::new();
+
+                for address in value.get_value().split(',') {
+                    let address = address.parse::<Ipv6Addr>().map_err(|_| value.error(&format!("Invalid ipv6hint '{}'", address)))?;
+                    buf.extend_from_slice(&address.octets());
+                }
+
+                buf
+            }
+            (Ok(SvcParamKeys::Ech), Some(value)) => {
+                base64::decode(value.get_value()).map_err(|e| value.error(&format!("Invalid ech: {}", e)))?
+            }
+            (Err(_), Some(value)) => unescape(&value)?,
+            (Err(_), None) => Vec::new()
+        };
+
+        params.push((key, value));
+    }
+
+    params.sort_by_key(|(key, _)| *key);
+
+    let mut map = OrderedMap::new();
+    for (key, value) in params {
+        map.insert(key, value);
+    }
+
+    Ok(map)
+}
+
+//ALPN IDS MAY CONTAIN AN ESCAPED COMMA, ONLY SPLIT ON THE BARE ONES
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                item.push(c);
+                if let Some(next) = chars.next() {
+                    item.push(next);
+                }
+            }
+            ',' => items.push(std::mem::take(&mut item)),
+            _ => item.push(c)
+        }
+    }
+
+    items.push(item);
+    items
+}
+
+pub fn parse_name(token: &Token, origin: &str) -> Result<String, ZoneError> {
+    let value = token.get_value();
+
+    if value == "@" && !token.is_quoted() {
+        return Ok(origin.to_string());
+    }
+
+    let absolute = value.ends_with('.') && !value.ends_with("\\.");
+    let mut name = from_fqdn(value).map_err(|e| token.error(&e))?;
+
+    if !absolute && !origin.is_empty() {
+        name = format!("{}.{}", name, origin);
+    }
+
+    //RFC 1035 2.3.4 - THE LIMIT IS 255 BYTES ON THE WIRE, LENGTH BYTES AND TERMINATOR INCLUDED, NOT 255 CHARACTERS OF TEXT
+    pack_domain_uncompressed(&name).map_err(|e| token.error(&e))?;
+
+    Ok(name)
+}
+
+pub fn parse_ttl(token: &Token) -> Result<u32, ZoneError> {
+    let value = token.get_value();
+    let error = || token.error(&format!("Invalid TTL '{}'", value));
+
+    if value.is_empty() || token.is_quoted() {
+        return Err(error());
+    }
+
+    if let Ok(ttl) = value.parse::<u32>() {
+        return Ok(ttl);
+    }
+
+    let mut total: u64 = 0;
+    let mut number: Option<u64> = None;
+
+    for c in value.chars() {
+        match c {
+            '0'..='9' => {
+                number = Some(number.unwrap_or(0) * 10 + c as u64 - '0' as u64);
+                if number.unwrap() > u32::MAX as u64 {
+                    return Err(error());
+                }
+            }
+            _ => {
+                let multiplier = match c.to_ascii_lowercase() {
+                    's' => 1,
+                    'm' => 60,
+                    'h' => 3600,
+                    'd' => 86400,
+                    'w' => 604800,
+                    _ => return Err(error())
+                };
+
+                total += number.take().ok_or_else(error)? * multiplier;
+            }
+        }
+    }
+
+    if number.is_some() {
+        return Err(error());
+    }
+
+    u32::try_from(total).map_err(|_| error())
+}
+
+pub fn parse_type_code(token: &Token) -> Result<u16, ZoneError> {
+    let value = token.get_value();
+
+    Types::from_name(value)
+        .map(|_type| _type.get_code())
+        .map_err(|_| token.error(&format!("Unknown record type '{}'", value)))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::types::Types;
+    use crate::zone::rdata_parser::{parse_name, parse_rdata_str};
+    use crate::zone::zone_lexer::Token;
+
+    fn name(value: &str, origin: &str) -> Result<String, String> {
+        parse_name(&Token::new(value, false, 1, 1), origin).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn name_limit_is_255_bytes_on_the_wire() {
+        //b. PLUS FOUR 62 BYTE LABELS IS 253 CHARACTERS BUT 255 BYTES ON THE WIRE
+        let labels = vec!["a".repeat(62); 4].join(".");
+        assert_eq!(name(&format!("b.{}.", labels), "").unwrap().len(), 253);
+        assert!(name(&format!("bb.{}.", labels), "").is_err());
+
+        //THE ORIGIN COUNTS TOWARDS IT TOO
+        assert!(name("b", &labels).is_ok());
+        assert!(name("bb", &labels).is_err());
+    }
+
+    #[test]
+    fn label_limit_is_63_bytes() {
+        assert!(name(&format!("{}.example.", "a".repeat(63)), "").is_ok());
+        assert!(name(&format!("{}.example.", "a".repeat(64)), "").is_err());
+
+        //\065 IS ONE BYTE, NOT FOUR
+        assert!(name(&format!("{}.example.", "\\065".repeat(63)), "").is_ok());
+    }
+
+    #[test]
+    fn escaped_dot_is_kept_escaped() {
+        assert_eq!(name("a\\.b", "example").unwrap(), "a\\.b.example");
+        assert_eq!(name("a\\046b.", "").unwrap(), "a\\.b");
+    }
+
+    #[test]
+    fn rdata_str_has_no_origin() {
+        let record = parse_rdata_str(Types::Mx, DnsClasses::In, 300, "10 mail.example.com.").unwrap();
+        assert_eq!(record.to_string(), "300 IN MX 10 mail.example.com.");
+
+        let record = parse_rdata_str(Types::Cname, DnsClasses::In, 300, "host").unwrap();
+        assert_eq!(record.rdata_to_string(), "host.");
+
+        assert!(parse_rdata_str(Types::A, DnsClasses::In, 300, "").is_err());
+        assert!(parse_rdata_str(Types::A, DnsClasses::In, 300, "192.0.2.1 192.0.2.2").is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZoneError {
+    file: Option<String>,
+    line: usize,
+    column: usize,
+    message: String
+}
+
+impl ZoneError {
+
+    pub fn new(line: usize, column: usize, message: &str) -> Self {
+        Self {
+            file: None,
+            line,
+            column,
+            message: message.to_string()
+        }
+    }
+
+    pub fn set_file(&mut self, file: &str) {
+        //ERRORS FROM AN $INCLUDE ALREADY NAME THE FILE THEY CAME FROM
+        if self.file.is_none() {
+            self.file = Some(file.to_string());
+        }
+    }
+
+    pub fn get_file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    pub fn get_line(&self) -> usize {
+        self.line
+    }
+
+    pub fn get_column(&self) -> usize {
+        self.column
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ZoneError {
+
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.file {
+            Some(ref file) => write!(f, "{}:{}:{}: {}", file, self.line, self.column, self.message),
+            None => write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+        }
+    }
+}
+
+impl Error for ZoneError {}
@@ -0,0 +1,145 @@
+use std::io;
+use std::path::Path;
+use crate::records::inter::record_base::RecordBase;
+use crate::utils::ordered_map::OrderedMap;
+use crate::zone::zone_error::ZoneError;
+use crate::zone::zone_parser::ZoneParser;
+use crate::zone::zone_writer::ZoneWriter;
+
+pub struct ZoneFile {
+    origin: String,
+    default_ttl: Option<u32>,
+    records: OrderedMap<String, Vec<Box<dyn RecordBase>>>
+}
+
+impl ZoneFile {
+
+    pub fn new(origin: &str) -> Self {
+        Self {
+            origin: origin.trim_end_matches('.').to_string(),
+            default_ttl: None,
+            records: OrderedMap::new()
+        }
+    }
+
+    pub fn parse(text: &str, origin: &str) -> Result<Self, ZoneError> {
+        let mut zone = Self::new(origin);
+        ZoneParser::new(origin).parse(text, &mut zone)?;
+        Ok(zone)
+    }
+
+    pub fn from_file(path: &Path, origin: &str) -> Result<Self, ZoneError> {
+        let mut zone = Self::new(origin);
+        ZoneParser::new(origin).parse_file(path, &mut zone)?;
+        Ok(zone)
+    }
+
+    pub fn write(&self) -> String {
+        self.writer().write(&self.records)
+    }
+
+    pub fn to_file(&self, path: &Path) -> io::Result<()> {
+        self.writer().write_file(path, &self.records)
+    }
+
+    fn writer(&self) -> ZoneWriter {
+        let mut writer = ZoneWriter::new(&self.origin);
+        if let Some(default_ttl) = self.default_ttl {
+            writer.set_default_ttl(default_ttl);
+        }
+        writer
+    }
+
+    pub fn get_origin(&self) -> &str {
+        &self.origin
+    }
+
+    pub fn set_default_ttl(&mut self, default_ttl: u32) {
+        self.default_ttl = Some(default_ttl);
+    }
+
+    pub fn get_default_ttl(&self) -> Option<u32> {
+        self.default_ttl
+    }
+
+    pub fn add_record(&mut self, name: &str, record: Box<dyn RecordBase>) {
+        self.records.entry(name.to_string()).or_default().push(record);
+    }
+
+    pub fn get_records(&self) -> &OrderedMap<String, Vec<Box<dyn RecordBase>>> {
+        &self.records
+    }
+
+    pub fn total_records(&self) -> usize {
+        self.records.iter().map(|(_, records)| records.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::zone::zone_file::ZoneFile;
+
+    //EVERY TYPE THE PARSER BUILDS A RECORD FOR, PLUS ONE IT ONLY KNOWS BY NUMBER
+    const ZONE: &str = r#"
+$TTL 3600
+@ IN SOA ns1 hostmaster 2024010101 7200 3600 1209600 300
+@ IN NS ns1
+@ IN MX 10 mail
+@ IN TXT "v=spf1 -all" "with \"quotes\" and spaces"
+@ IN DNSKEY 257 3 13 mdsswUyr3DPW132mOi8V9xESWE8jTo0dxCjjnopKl+GqJxpVXckHAeF+KkxLbxILfDLUT0rAK9iUzy1L53eKGQ==
+@ IN RRSIG SOA 13 2 3600 20240201000000 20240101000000 12345 example.com. oJB1W6WNGv+ldvQ3WDG0MQkg5IEhjRip8WTrPYGv07h108dUKGMeDPKijVCHX3DDKdfb+v6oB9wfuh3DTJXUAfI/M0zmO/zz8bW0Rznl8O3tGNazPwQKkRN20XPXV6nwwfoXmJQbsLNrLfkGJ5D6fwFm8nN+6pBzeDQfsS3Ap3o=
+ns1 IN A 192.0.2.1
+ns1 IN AAAA 2001:db8::1
+mail IN A 192.0.2.2
+www IN CNAME @
+_sip._tcp IN SRV 10 60 5060 sip
+legacy IN DNAME example.net.
+1.2.0.192.in-addr.arpa. IN PTR ns1
+a\.b IN A 192.0.2.3
+svc IN HTTPS 1 . alpn=h2,h3 port=8443 ipv4hint=192.0.2.4
+@ IN NSEC ns1 A NS SOA MX TXT RRSIG NSEC DNSKEY
+private IN TYPE65280 \# 4 0a0b0c0d
+"#;
+
+    //OWNER AND PRESENTATION FORM OF EVERY RECORD, IN ORDER
+    fn records(zone: &ZoneFile) -> Vec<String> {
+        zone.get_records().iter()
+            .flat_map(|(owner, records)| records.iter().map(move |record| format!("{} {}", owner, record.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn written_zone_parses_back_the_same() {
+        let zone = ZoneFile::parse(ZONE, "example.com").unwrap();
+        let written = zone.write();
+
+        let reparsed = ZoneFile::parse(&written, "example.com").unwrap();
+
+        let mut before = records(&zone);
+        let mut after = records(&reparsed);
+        before.sort();
+        after.sort();
+
+        assert_eq!(before.len(), 17);
+        assert_eq!(before, after);
+
+        //AND WRITING IT AGAIN CHANGES NOTHING
+        assert_eq!(reparsed.write(), written);
+    }
+
+    #[test]
+    fn long_keys_and_signatures_are_wrapped() {
+        let written = ZoneFile::parse(ZONE, "example.com").unwrap().write();
+
+        assert!(written.lines().any(|line| line.contains("\tDNSKEY\t") && line.ends_with(" (")));
+        assert!(written.lines().any(|line| line.contains("\tRRSIG\t") && line.ends_with(" (")));
+        assert!(written.lines().filter(|line| line.starts_with("\t\t\t\t")).all(|line| line.len() <= 4+56+2));
+    }
+
+    #[test]
+    fn unknown_type_is_written_in_the_generic_form() {
+        let written = ZoneFile::parse(ZONE, "example.com").unwrap().write();
+        assert!(written.contains("private\t3600\tIN\tTYPE65280\t\\# 4 0a0b0c0d\n"));
+    }
+}
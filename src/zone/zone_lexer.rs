@@ -0,0 +1,254 @@
+use crate::zone::zone_error::ZoneError;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    value: String,
+    quoted: bool,
+    line: usize,
+    column: usize
+}
+
+impl Token {
+
+    pub fn new(value: &str, quoted: bool, line: usize, column: usize) -> Self {
+        Self {
+            value: value.to_string(),
+            quoted,
+            line,
+            column
+        }
+    }
+
+    pub fn get_value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn is_quoted(&self) -> bool {
+        self.quoted
+    }
+
+    pub fn get_line(&self) -> usize {
+        self.line
+    }
+
+    pub fn get_column(&self) -> usize {
+        self.column
+    }
+
+    pub fn error(&self, message: &str) -> ZoneError {
+        ZoneError::new(self.line, self.column, message)
+    }
+}
+
+//ONE LOGICAL ENTRY, PARENTHESES MAY HAVE JOINED SEVERAL PHYSICAL LINES
+#[derive(Clone, Debug)]
+pub struct ZoneLine {
+    tokens: Vec<Token>,
+    indented: bool,
+    line: usize
+}
+
+impl ZoneLine {
+
+    pub fn get_tokens(&self) -> &Vec<Token> {
+        &self.tokens
+    }
+
+    pub fn is_indented(&self) -> bool {
+        self.indented
+    }
+
+    pub fn get_line(&self) -> usize {
+        self.line
+    }
+}
+
+pub fn tokenize(text: &str) -> Result<Vec<ZoneLine>, ZoneError> {
+    let mut lines = Vec::new();
+    let mut tokens = Vec::new();
+    let mut indented = false;
+    let mut line_start = true;
+    let mut depth = 0;
+    let mut paren_pos = (0, 0);
+
+    let mut word = String::new();
+    let mut word_pos: Option<(usize, usize)> = None;
+    let mut quoted = false;
+    let mut in_quote = false;
+
+    let mut line = 1;
+    let mut column = 0;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        column += 1;
+
+        if in_quote {
+            match c {
+                '"' => in_quote = false,
+                '\\' => {
+                    word.push(c);
+                    if let Some(next) = chars.next() {
+                        column += 1;
+                        if next == '\n' {
+                            line += 1;
+                            column = 0;
+                        }
+                        word.push(next);
+                    }
+                }
+                '\n' => {
+                    return Err(ZoneError::new(word_pos.unwrap().0, word_pos.unwrap().1, "Unterminated quoted string"));
+                }
+                _ => word.push(c)
+            }
+            continue;
+        }
+
+        match c {
+            ' ' | '\t' | '\r' => {
+                finish_word(&mut tokens, &mut word, &mut word_pos, &mut quoted);
+
+                if line_start && depth == 0 && tokens.is_empty() {
+                    indented = true;
+                }
+            }
+            '\n' => {
+                finish_word(&mut tokens, &mut word, &mut word_pos, &mut quoted);
+
+                if depth == 0 {
+                    finish_line(&mut lines, &mut tokens, &mut indented);
+                    line_start = true;
+                }
+
+                line += 1;
+                column = 0;
+            }
+            ';' => {
+                finish_word(&mut tokens, &mut word, &mut word_pos, &mut quoted);
+
+                while let Some(&next) = chars.peek() {
+                    if next == '\n' {
+                        break;
+                    }
+                    chars.next();
+                }
+            }
+            '(' => {
+                finish_word(&mut tokens, &mut word, &mut word_pos, &mut quoted);
+                line_start = false;
+
+                if depth == 0 {
+                    paren_pos = (line, column);
+                }
+                depth += 1;
+            }
+            ')' => {
+                finish_word(&mut tokens, &mut word, &mut word_pos, &mut quoted);
+
+                if depth == 0 {
+                    return Err(ZoneError::new(line, column, "Unbalanced ')'"));
+                }
+                depth -= 1;
+            }
+            '"' => {
+                line_start = false;
+                word_pos.get_or_insert((line, column));
+                quoted = true;
+                in_quote = true;
+            }
+            '\\' => {
+                line_start = false;
+                word_pos.get_or_insert((line, column));
+                word.push(c);
+
+                if let Some(next) = chars.next() {
+                    column += 1;
+                    if next == '\n' {
+                        line += 1;
+                        column = 0;
+                    }
+                    word.push(next);
+                }
+            }
+            _ => {
+                line_start = false;
+                word_pos.get_or_insert((line, column));
+                word.push(c);
+            }
+        }
+    }
+
+    if in_quote {
+        return Err(ZoneError::new(word_pos.unwrap().0, word_pos.unwrap().1, "Unterminated quoted string"));
+    }
+
+    if depth > 0 {
+        return Err(ZoneError::new(paren_pos.0, paren_pos.1, "Unbalanced '('"));
+    }
+
+    finish_word(&mut tokens, &mut word, &mut word_pos, &mut quoted);
+    finish_line(&mut lines, &mut tokens, &mut indented);
+
+    Ok(lines)
+}
+
+fn finish_word(tokens: &mut Vec<Token>, word: &mut String, word_pos: &mut Option<(usize, usize)>, quoted: &mut bool) {
+    if let Some((line, column)) = word_pos.take() {
+        tokens.push(Token::new(word, *quoted, line, column));
+    }
+
+    word.clear();
+    *quoted = false;
+}
+
+fn finish_line(lines: &mut Vec<ZoneLine>, tokens: &mut Vec<Token>, indented: &mut bool) {
+    if !tokens.is_empty() {
+        let line = tokens[0].line;
+
+        lines.push(ZoneLine {
+            tokens: std::mem::take(tokens),
+            indented: *indented,
+            line
+        });
+    }
+
+    *indented = false;
+}
+
+//TURNS \X AND \DDD PRESENTATION ESCAPES INTO THE BYTES THEY STAND FOR
+pub fn unescape(token: &Token) -> Result<Vec<u8>, ZoneError> {
+    let bytes = token.value.as_bytes();
+    let mut buf = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'\\' {
+            buf.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+
+        if i+3 < bytes.len() && bytes[i+1..i+4].iter().all(|b| b.is_ascii_digit()) {
+            let value = (bytes[i+1] - b'0') as u16 * 100 + (bytes[i+2] - b'0') as u16 * 10 + (bytes[i+3] - b'0') as u16;
+
+            if value > 255 {
+                return Err(token.error(&format!("Invalid escape '\\{}' in '{}'", &token.value[i+1..i+4], token.value)));
+            }
+
+            buf.push(value as u8);
+            i += 4;
+            continue;
+        }
+
+        match bytes.get(i+1) {
+            Some(&b) => {
+                buf.push(b);
+                i += 2;
+            }
+            None => return Err(token.error(&format!("Dangling escape at the end of '{}'", token.value)))
+        }
+    }
+
+    Ok(buf)
+}
@@ -0,0 +1,620 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::messages::inter::dns_classes::DnsClasses;
+use crate::messages::inter::types::Types;
+use crate::records::inter::record_base::RecordBase;
+use crate::zone::rdata_parser::{parse_name, parse_rdata, parse_ttl, RdataReader};
+use crate::zone::zone_error::ZoneError;
+use crate::zone::zone_file::ZoneFile;
+use crate::zone::zone_lexer::{tokenize, unescape, Token, ZoneLine};
+
+const MAX_INCLUDE_DEPTH: usize = 16;
+const MAX_GENERATE_RECORDS: i64 = 65536;
+
+pub struct ZoneParser {
+    origin: String,
+    default_ttl: Option<u32>,
+    last_owner: Option<String>,
+    last_ttl: Option<u32>,
+    last_class: Option<DnsClasses>,
+    base_dir: Option<PathBuf>,
+    depth: usize
+}
+
+impl ZoneParser {
+
+    pub fn new(origin: &str) -> Self {
+        Self {
+            origin: origin.trim_end_matches('.').to_string(),
+            default_ttl: None,
+            last_owner: None,
+            last_ttl: None,
+            last_class: None,
+            base_dir: None,
+            depth: 0
+        }
+    }
+
+    pub fn set_default_ttl(&mut self, default_ttl: Option<u32>) {
+        self.default_ttl = default_ttl;
+    }
+
+    pub fn get_origin(&self) -> &str {
+        &self.origin
+    }
+
+    //RELATIVE $INCLUDE PATHS ARE RESOLVED AGAINST THIS, OTHERWISE THE WORKING DIRECTORY
+    pub fn set_base_dir(&mut self, base_dir: &Path) {
+        self.base_dir = Some(base_dir.to_path_buf());
+    }
+
+    pub fn parse(&mut self, text: &str, zone: &mut ZoneFile) -> Result<(), ZoneError> {
+        for line in tokenize(text)? {
+            self.parse_line(&line, zone)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn parse_file(&mut self, path: &Path, zone: &mut ZoneFile) -> Result<(), ZoneError> {
+        let text = fs::read_to_string(path).map_err(|e| {
+            let mut error = ZoneError::new(0, 0, &format!("Couldn't read zone file: {}", e));
+            error.set_file(&path.display().to_string());
+            error
+        })?;
+
+        if let Some(parent) = path.parent() {
+            self.base_dir = Some(parent.to_path_buf());
+        }
+
+        self.parse(&text, zone).map_err(|mut e| {
+            e.set_file(&path.display().to_string());
+            e
+        })
+    }
+
+    fn parse_line(&mut self, line: &ZoneLine, zone: &mut ZoneFile) -> Result<(), ZoneError> {
+        let tokens = line.get_tokens();
+        let first = &tokens[0];
+
+        if !line.is_indented() && !first.is_quoted() && first.get_value().starts_with('$') {
+            return match first.get_value().to_ascii_uppercase().as_str() {
+                "$ORIGIN" => self.parse_origin(tokens),
+                "$TTL" => self.parse_default_ttl(tokens, zone),
+                "$INCLUDE" => self.parse_include(tokens, zone),
+                "$GENERATE" => self.parse_generate(tokens, zone),
+                _ => Err(first.error(&format!("Unknown directive '{}'", first.get_value())))
+            };
+        }
+
+        let (owner, record) = self.parse_record(line)?;
+        zone.add_record(&owner, record);
+
+        Ok(())
+    }
+
+    //A SINGLE RECORD ON ITS OWN, "www 300 IN MX 10 mail.example.com."
+    pub fn parse_str(&mut self, text: &str) -> Result<(String, Box<dyn RecordBase>), ZoneError> {
+        let lines = tokenize(text.trim_start())?;
+
+        let line = match lines.first() {
+            Some(line) => line,
+            None => return Err(ZoneError::new(1, 1, "Missing record"))
+        };
+
+        if let Some(extra) = lines.get(1) {
+            return Err(extra.get_tokens()[0].error("Expected a single record"));
+        }
+
+        let first = &line.get_tokens()[0];
+        if !first.is_quoted() && first.get_value().starts_with('$') {
+            return Err(first.error(&format!("Directive '{}' isn't allowed in a single record", first.get_value())));
+        }
+
+        self.parse_record(line)
+    }
+
+    pub fn parse_record(&mut self, line: &ZoneLine) -> Result<(String, Box<dyn RecordBase>), ZoneError> {
+        let tokens = line.get_tokens();
+
+        let (owner, fields) = if line.is_indented() {
+            match self.last_owner {
+                Some(ref owner) => (owner.clone(), &tokens[..]),
+                None => return Err(tokens[0].error("Record has no owner name and there is no previous owner to inherit"))
+            }
+        } else {
+            (parse_name(&tokens[0], &self.origin)?, &tokens[1..])
+        };
+
+        let record = self.parse_fields(fields, end_of(tokens))?;
+        self.last_owner = Some(owner.clone());
+
+        Ok((owner, record))
+    }
+
+    //[TTL] [CLASS] TYPE RDATA, TTL AND CLASS IN EITHER ORDER
+    fn parse_fields(&mut self, fields: &[Token], end: (usize, usize)) -> Result<Box<dyn RecordBase>, ZoneError> {
+        let mut ttl = None;
+        let mut dns_class = None;
+        let mut i = 0;
+
+        while i < fields.len() && i < 2 {
+            let token = &fields[i];
+
+            if ttl.is_none() && token.get_value().starts_with(|c: char| c.is_ascii_digit()) {
+                ttl = Some(parse_ttl(token)?);
+                i += 1;
+                continue;
+            }
+
+            if dns_class.is_none() {
+                if let Ok(c) = DnsClasses::from_name(token.get_value()) {
+                    dns_class = Some(c);
+                    i += 1;
+                    continue;
+                }
+            }
+
+            break;
+        }
+
+        let type_token = match fields.get(i) {
+            Some(token) => token,
+            None => return Err(ZoneError::new(end.0, end.1, "Missing record type"))
+        };
+
+        let _type = Types::from_name(type_token.get_value())
+            .map_err(|_| type_token.error(&format!("Unknown record type '{}'", type_token.get_value())))?;
+        let rdata = &fields[i+1..];
+
+        let dns_class = dns_class.or(self.last_class).unwrap_or(DnsClasses::In);
+
+        let ttl = match ttl.or(self.default_ttl).or(self.last_ttl) {
+            Some(ttl) => ttl,
+            None if _type == Types::Soa => {
+                //RFC 2308 - WITH NOTHING ELSE TO GO ON THE SOA MINIMUM IS THE TTL
+                let mut reader = RdataReader::new(rdata, end);
+                for field in ["SOA primary name server", "SOA mailbox", "SOA serial", "SOA refresh", "SOA retry", "SOA expire"] {
+                    reader.next(field)?;
+                }
+                reader.next_ttl("SOA minimum")?
+            }
+            None => return Err(type_token.error("No TTL specified and no $TTL in effect"))
+        };
+
+        let record = parse_rdata(_type, dns_class, ttl, rdata, end, &self.origin)?;
+
+        self.last_ttl = Some(ttl);
+        self.last_class = Some(dns_class);
+
+        Ok(record)
+    }
+
+    fn parse_origin(&mut self, tokens: &[Token]) -> Result<(), ZoneError> {
+        let origin = match tokens.get(1) {
+            Some(token) => parse_name(token, &self.origin)?,
+            None => return Err(error_after(tokens, "$ORIGIN needs a domain name"))
+        };
+
+        expect_end(tokens, 2)?;
+        self.origin = origin;
+
+        Ok(())
+    }
+
+    fn parse_default_ttl(&mut self, tokens: &[Token], zone: &mut ZoneFile) -> Result<(), ZoneError> {
+        let ttl = match tokens.get(1) {
+            Some(token) => parse_ttl(token)?,
+            None => return Err(error_after(tokens, "$TTL needs a TTL value"))
+        };
+
+        expect_end(tokens, 2)?;
+        self.default_ttl = Some(ttl);
+
+        if self.depth == 0 && zone.get_default_ttl().is_none() {
+            zone.set_default_ttl(ttl);
+        }
+
+        Ok(())
+    }
+
+    fn parse_include(&mut self, tokens: &[Token], zone: &mut ZoneFile) -> Result<(), ZoneError> {
+        let file = match tokens.get(1) {
+            Some(token) => String::from_utf8_lossy(&unescape(token)?).to_string(),
+            None => return Err(error_after(tokens, "$INCLUDE needs a file name"))
+        };
+
+        if self.depth >= MAX_INCLUDE_DEPTH {
+            return Err(tokens[0].error(&format!("$INCLUDE nested deeper than {} files", MAX_INCLUDE_DEPTH)));
+        }
+
+        let origin = match tokens.get(2) {
+            Some(token) => parse_name(token, &self.origin)?,
+            None => self.origin.clone()
+        };
+
+        expect_end(tokens, 3)?;
+
+        let path = match self.base_dir {
+            Some(ref base_dir) if Path::new(&file).is_relative() => base_dir.join(&file),
+            _ => PathBuf::from(&file)
+        };
+
+        //THE INCLUDED FILE GETS ITS OWN ORIGIN, OURS IS UNTOUCHED WHEN IT RETURNS
+        let mut parser = Self::new(&origin);
+        parser.default_ttl = self.default_ttl;
+        parser.last_ttl = self.last_ttl;
+        parser.last_class = self.last_class;
+        parser.depth = self.depth+1;
+
+        parser.parse_file(&path, zone)
+    }
+
+    //$GENERATE start-stop[/step] lhs [ttl] [class] type rhs
+    fn parse_generate(&mut self, tokens: &[Token], zone: &mut ZoneFile) -> Result<(), ZoneError> {
+        if tokens.len() < 4 {
+            return Err(error_after(tokens, "$GENERATE needs a range, owner, type and RDATA"));
+        }
+
+        let range = &tokens[1];
+        let error = || range.error(&format!("Invalid $GENERATE range '{}', expected start-stop[/step]", range.get_value()));
+
+        let (bounds, step) = match range.get_value().split_once('/') {
+            Some((bounds, step)) => (bounds, step.parse::<i64>().map_err(|_| error())?),
+            None => (range.get_value(), 1)
+        };
+
+        let (start, stop) = bounds.split_once('-').ok_or_else(error)?;
+        let start = start.parse::<i64>().map_err(|_| error())?;
+        let stop = stop.parse::<i64>().map_err(|_| error())?;
+
+        if start < 0 || stop < start || step < 1 {
+            return Err(error());
+        }
+
+        if (stop - start) / step >= MAX_GENERATE_RECORDS {
+            return Err(range.error(&format!("$GENERATE range produces more than {} records", MAX_GENERATE_RECORDS)));
+        }
+
+        let mut i = start;
+        while i <= stop {
+            let mut generated = Vec::with_capacity(tokens.len()-2);
+
+            for token in &tokens[2..] {
+                let value = substitute(token, i)?;
+                generated.push(Token::new(&value, token.is_quoted(), token.get_line(), token.get_column()));
+            }
+
+            let owner = parse_name(&generated[0], &self.origin)?;
+            let record = self.parse_fields(&generated[1..], end_of(tokens))?;
+            zone.add_record(&owner, record);
+            self.last_owner = Some(owner);
+
+            i += step;
+        }
+
+        Ok(())
+    }
+}
+
+//BIND $GENERATE TEMPLATES: $, ${offset[,width[,base]]}, $$ AND \$ FOR A LITERAL $
+fn substitute(token: &Token, value: i64) -> Result<String, ZoneError> {
+    let template = token.get_value();
+    let mut buf = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'$') => {
+                chars.next();
+                buf.push('$');
+            }
+            '$' if chars.peek() == Some(&'$') => {
+                chars.next();
+                buf.push('$');
+            }
+            '$' if chars.peek() == Some(&'{') => {
+                chars.next();
+
+                let mut spec = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => spec.push(c),
+                        None => return Err(token.error(&format!("Unterminated '${{' in '{}'", template)))
+                    }
+                }
+
+                buf.push_str(&format_modifier(token, &spec, value)?);
+            }
+            '$' => buf.push_str(&value.to_string()),
+            _ => buf.push(c)
+        }
+    }
+
+    Ok(buf)
+}
+
+fn format_modifier(token: &Token, spec: &str, value: i64) -> Result<String, ZoneError> {
+    let error = || token.error(&format!("Invalid $GENERATE modifier '${{{}}}'", spec));
+    let parts: Vec<&str> = spec.split(',').collect();
+
+    if parts.len() > 3 {
+        return Err(error());
+    }
+
+    let offset = match parts[0] {
+        "" => 0,
+        offset => offset.parse::<i64>().map_err(|_| error())?
+    };
+
+    let width = match parts.get(1) {
+        Some(width) => width.parse::<usize>().map_err(|_| error())?,
+        None => 0
+    };
+
+    let value = value+offset;
+    if value < 0 {
+        return Err(token.error(&format!("$GENERATE value {} is negative after applying '${{{}}}'", value, spec)));
+    }
+
+    Ok(match parts.get(2).copied().unwrap_or("d") {
+        "d" => format!("{:0width$}", value, width = width),
+        "o" => format!("{:0width$o}", value, width = width),
+        "x" => format!("{:0width$x}", value, width = width),
+        "X" => format!("{:0width$X}", value, width = width),
+        base @ ("n" | "N") => {
+            //NIBBLE MODE, REVERSED HEX DIGITS FOR ip6.arpa
+            let hex = match base {
+                "n" => format!("{:0width$x}", value, width = width.max(1)),
+                _ => format!("{:0width$X}", value, width = width.max(1))
+            };
+            hex.chars().rev().map(|c| c.to_string()).collect::<Vec<_>>().join(".")
+        }
+        _ => return Err(error())
+    })
+}
+
+fn end_of(tokens: &[Token]) -> (usize, usize) {
+    match tokens.last() {
+        Some(token) => {
+            let length = token.get_value().chars().count() + if token.is_quoted() { 2 } else { 0 };
+            (token.get_line(), token.get_column()+length)
+        }
+        None => (0, 0)
+    }
+}
+
+fn error_after(tokens: &[Token], message: &str) -> ZoneError {
+    let (line, column) = end_of(tokens);
+    ZoneError::new(line, column, message)
+}
+
+fn expect_end(tokens: &[Token], count: usize) -> Result<(), ZoneError> {
+    match tokens.get(count) {
+        Some(token) => Err(token.error(&format!("Unexpected '{}' after {}", token.get_value(), tokens[0].get_value()))),
+        None => Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::fs;
+    use std::path::PathBuf;
+    use crate::messages::inter::dns_classes::DnsClasses;
+    use crate::messages::inter::types::Types;
+    use crate::records::a_record::ARecord;
+    use crate::zone::zone_file::ZoneFile;
+    use crate::zone::zone_parser::ZoneParser;
+
+    fn record(origin: &str, text: &str) -> Result<(String, String), String> {
+        ZoneParser::new(origin).parse_str(text)
+            .map(|(owner, record)| (owner, record.to_string()))
+            .map_err(|e| e.to_string())
+    }
+
+    fn record_rdata(text: &str) -> Result<String, String> {
+        ZoneParser::new("").parse_str(text).map(|(_, record)| record.rdata_to_string()).map_err(|e| e.to_string())
+    }
+
+    //EVERY RECORD AS OWNER AND PRESENTATION FORM, GROUPED BY OWNER THE WAY THE ZONE KEEPS THEM
+    fn zone(origin: &str, text: &str) -> Result<Vec<(String, String)>, String> {
+        let mut zone = ZoneFile::new(origin);
+        ZoneParser::new(origin).parse(text, &mut zone).map_err(|e| e.to_string())?;
+
+        Ok(zone.get_records().iter()
+            .flat_map(|(owner, records)| records.iter().map(move |record| (owner.clone(), record.to_string())))
+            .collect())
+    }
+
+    fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+        expected.iter().map(|(owner, record)| (owner.to_string(), record.to_string())).collect()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("find9-{}-{}", std::process::id(), name));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn parse_str_single_record() {
+        assert_eq!(record("example.com.", "www 300 IN MX 10 mail").unwrap(), ("www.example.com".to_string(), "300 IN MX 10 mail.example.com.".to_string()));
+        assert_eq!(record("", "host.example. CH 60 TXT \"a b\"").unwrap(), ("host.example".to_string(), "60 CH TXT \"a b\"".to_string()));
+        assert_eq!(record("example.com", "@ 1h A 192.0.2.1").unwrap(), ("example.com".to_string(), "3600 IN A 192.0.2.1".to_string()));
+    }
+
+    #[test]
+    fn parse_str_generic_form() {
+        let (_, record) = ZoneParser::new("").parse_str("x. 300 TYPE65280 \\# 3 abcdef").unwrap();
+        assert_eq!(record.get_type(), Types::Unknown(65280));
+        assert_eq!(record.get_dns_class().unwrap(), DnsClasses::In);
+        assert_eq!(record.rdata_to_string(), "\\# 3 abcdef");
+
+        assert_eq!(record_rdata("x. 300 TYPE65280 \\# 0"), Ok("\\# 0".to_string()));
+
+        //THE LENGTH HAS TO MATCH THE HEX THAT FOLLOWS IT
+        assert!(record_rdata("x. 300 TYPE65280 \\# 4 abcdef").is_err());
+        assert!(record_rdata("x. 300 TYPE65280 \\# 3 abcdeg").is_err());
+        assert!(record_rdata("x. 300 TYPE65280 \\# 3").is_err());
+        assert!(record_rdata("x. 300 TYPE65280 0a").is_err());
+    }
+
+    #[test]
+    fn parse_str_takes_exactly_one_record() {
+        assert!(record("", "").is_err());
+        assert!(record("", "a. 300 A 192.0.2.1\nb. 300 A 192.0.2.2").is_err());
+        assert!(record("", "$TTL 300").is_err());
+        assert!(record("", "a. A 192.0.2.1").unwrap_err().contains("No TTL"));
+        assert_eq!(record("", "a. 300 BOGUS x").unwrap_err(), "line 1, column 8: Unknown record type 'BOGUS'");
+    }
+
+    #[test]
+    fn origin_and_ttl_directives() {
+        let text = "\
+$ORIGIN example.com.
+$TTL 1h
+@ NS ns1
+www 300 A 192.0.2.1
+$ORIGIN sub.example.com.
+mail MX 10 www.example.com.
+host.other. A 192.0.2.9
+";
+
+        assert_eq!(zone("", text).unwrap(), pairs(&[
+            ("example.com", "3600 IN NS ns1.example.com."),
+            ("www.example.com", "300 IN A 192.0.2.1"),
+            ("mail.sub.example.com", "3600 IN MX 10 www.example.com."),
+            ("host.other", "3600 IN A 192.0.2.9")
+        ]));
+    }
+
+    #[test]
+    fn owner_ttl_and_class_are_inherited() {
+        //WITHOUT A $TTL THE LAST TTL CARRIES ON, AN INDENTED LINE KEEPS THE LAST OWNER
+        let text = "\
+a 300 CH TXT \"one\"
+  TXT \"two\"
+b 600 TXT \"three\"
+  IN 60 TXT \"four\"
+";
+
+        assert_eq!(zone("example.", text).unwrap(), pairs(&[
+            ("a.example", "300 CH TXT \"one\""),
+            ("a.example", "300 CH TXT \"two\""),
+            ("b.example", "600 CH TXT \"three\""),
+            ("b.example", "60 IN TXT \"four\"")
+        ]));
+
+        //$TTL BEATS THE LAST TTL, AN EXPLICIT ONE BEATS BOTH
+        assert_eq!(zone("example.", "$TTL 100\na 300 A 192.0.2.1\nb A 192.0.2.2").unwrap()[1].1, "100 IN A 192.0.2.2");
+
+        assert!(zone("example.", "  A 192.0.2.1").unwrap_err().contains("no previous owner"));
+    }
+
+    #[test]
+    fn parentheses_span_lines() {
+        let text = "\
+@ 3600 IN SOA ns1 hostmaster (
+        2024010101 ; SERIAL
+        7200       ; REFRESH
+        3600 1209600
+        300 )
+";
+
+        assert_eq!(zone("example.com.", text).unwrap(), pairs(&[
+            ("example.com", "3600 IN SOA ns1.example.com. hostmaster.example.com. 2024010101 7200 3600 1209600 300")
+        ]));
+    }
+
+    #[test]
+    fn quoted_semicolon_is_not_a_comment() {
+        assert_eq!(zone("example.", "a 300 TXT \"x;y\" ; A COMMENT").unwrap(), pairs(&[("a.example", "300 IN TXT \"x;y\"")]));
+    }
+
+    #[test]
+    fn generate_with_modifiers() {
+        let text = "\
+$TTL 300
+$GENERATE 1-3 host$ A 192.0.2.$
+$GENERATE 8-10/2 ${0,3,d}-${16,2,x}-${0,0,X}-$$ CNAME ${0,1,n}.rev
+";
+
+        assert_eq!(zone("example.", text).unwrap(), pairs(&[
+            ("host1.example", "300 IN A 192.0.2.1"),
+            ("host2.example", "300 IN A 192.0.2.2"),
+            ("host3.example", "300 IN A 192.0.2.3"),
+            ("008-18-8-$.example", "300 IN CNAME 8.rev.example."),
+            ("010-1a-A-$.example", "300 IN CNAME a.rev.example.")
+        ]));
+    }
+
+    #[test]
+    fn generate_is_capped() {
+        let error = zone("example.", "$GENERATE 0-65536 h$ 300 A 192.0.2.1").unwrap_err();
+        assert!(error.contains("more than 65536 records"), "{}", error);
+
+        //THE SAME SPAN WITH A STEP IS FINE
+        assert_eq!(zone("example.", "$GENERATE 0-65536/65536 h$ 300 A 192.0.2.1").unwrap().len(), 2);
+
+        assert!(zone("example.", "$GENERATE 3-1 h$ 300 A 192.0.2.1").is_err());
+        assert!(zone("example.", "$GENERATE 1-3 h${-2} 300 A 192.0.2.1").is_err());
+        assert!(zone("example.", "$GENERATE 1-3 h${0,1,z} 300 A 192.0.2.1").is_err());
+    }
+
+    #[test]
+    fn include_with_its_own_origin() {
+        let dir = temp_dir("include");
+        fs::write(dir.join("sub.zone"), "www 300 A 192.0.2.2\n$ORIGIN elsewhere.\nx 300 A 192.0.2.3\n").unwrap();
+        fs::write(dir.join("main.zone"), "$INCLUDE sub.zone sub.example.com.\nwww 300 A 192.0.2.1\n").unwrap();
+
+        let zone = ZoneFile::from_file(&dir.join("main.zone"), "example.com.").unwrap();
+        let records: Vec<(String, String)> = zone.get_records().iter()
+            .flat_map(|(owner, records)| records.iter().map(move |record| (owner.clone(), record.to_string())))
+            .collect();
+
+        //THE ORIGIN THE INCLUDED FILE SET DOESN'T LEAK BACK OUT
+        assert_eq!(records, pairs(&[
+            ("www.sub.example.com", "300 IN A 192.0.2.2"),
+            ("x.elsewhere", "300 IN A 192.0.2.3"),
+            ("www.example.com", "300 IN A 192.0.2.1")
+        ]));
+
+        let address = zone.get_records().get(&"www.example.com".to_string()).unwrap()[0].as_any().downcast_ref::<ARecord>().unwrap().get_address();
+        assert_eq!(address, Some([192, 0, 2, 1].into()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn include_depth_is_limited() {
+        let dir = temp_dir("include-loop");
+        fs::write(dir.join("loop.zone"), "$INCLUDE loop.zone\n").unwrap();
+
+        let error = ZoneFile::from_file(&dir.join("loop.zone"), "example.").err().unwrap();
+        assert_eq!(error.get_message(), "$INCLUDE nested deeper than 16 files");
+        assert!(error.get_file().unwrap().ends_with("loop.zone"));
+        assert_eq!((error.get_line(), error.get_column()), (1, 1));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn errors_carry_line_and_column() {
+        assert_eq!(zone("example.", "a 300 A 192.0.2.1\n\nb 300 A 192.0.2.300").unwrap_err(), "line 3, column 9: Invalid IPv4 address '192.0.2.300', expected an IPv4 address");
+        assert_eq!(zone("example.", "$TTL\n").unwrap_err(), "line 1, column 5: $TTL needs a TTL value");
+        assert_eq!(zone("example.", "$BOGUS 1").unwrap_err(), "line 1, column 1: Unknown directive '$BOGUS'");
+        assert_eq!(zone("example.", "a A 192.0.2.1").unwrap_err(), "line 1, column 3: No TTL specified and no $TTL in effect");
+    }
+
+    #[test]
+    fn generic_form_of_a_known_type_is_typed() {
+        let (_, record) = ZoneParser::new("").parse_str("a. 300 A \\# 4 0a000001").unwrap();
+        assert_eq!(record.as_any().downcast_ref::<ARecord>().unwrap().get_address(), Some([10, 0, 0, 1].into()));
+        assert_eq!(record.to_string(), "300 IN A 10.0.0.1");
+
+        //THE RDATA STILL HAS TO BE VALID FOR THE TYPE
+        assert!(record_rdata("a. 300 A \\# 3 0a0000").is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::messages::inter::types::Types;
+use crate::records::inter::record_base::RecordBase;
+use crate::utils::domain_utils::to_fqdn;
+use crate::utils::ordered_map::OrderedMap;
+
+//BASE64 CHARACTERS PER LINE ONCE A KEY OR SIGNATURE IS WRAPPED
+const WRAP_CHUNK: usize = 56;
+
+pub struct ZoneWriter {
+    origin: String,
+    default_ttl: Option<u32>,
+    line_width: usize
+}
+
+impl ZoneWriter {
+
+    pub fn new(origin: &str) -> Self {
+        Self {
+            origin: origin.trim_end_matches('.').to_string(),
+            default_ttl: None,
+            line_width: 80
+        }
+    }
+
+    pub fn set_default_ttl(&mut self, default_ttl: u32) {
+        self.default_ttl = Some(default_ttl);
+    }
+
+    pub fn get_default_ttl(&self) -> Option<u32> {
+        self.default_ttl
+    }
+
+    pub fn set_line_width(&mut self, line_width: usize) {
+        self.line_width = line_width;
+    }
+
+    pub fn get_line_width(&self) -> usize {
+        self.line_width
+    }
+
+    pub fn write(&self, records: &OrderedMap<String, Vec<Box<dyn RecordBase>>>) -> String {
+        let mut buf = format!("$ORIGIN {}\n", to_fqdn(&self.origin));
+
+        if let Some(default_ttl) = self.default_ttl {
+            buf.push_str(&format!("$TTL {}\n", default_ttl));
+        }
+
+        //THE APEX GOES FIRST SO THE SOA OPENS THE ZONE
+        let mut owners: Vec<&String> = records.keys().iter().filter(|owner| owner.eq_ignore_ascii_case(&self.origin)).collect();
+        owners.extend(records.keys().iter().filter(|owner| !owner.eq_ignore_ascii_case(&self.origin)));
+
+        for owner in owners {
+            let mut name = Some(self.relativize(owner));
+
+            for rrset in Self::group_rrsets(records.get(owner).unwrap()) {
+                for record in rrset {
+                    //ONLY THE FIRST LINE CARRIES THE OWNER, THE REST INHERIT IT
+                    let owner = name.take().unwrap_or_default();
+                    buf.push_str(&self.write_record(&owner, record));
+                }
+            }
+        }
+
+        buf
+    }
+
+    pub fn write_file(&self, path: &Path, records: &OrderedMap<String, Vec<Box<dyn RecordBase>>>) -> io::Result<()> {
+        fs::write(path, self.write(records))
+    }
+
+    fn write_record(&self, owner: &str, record: &dyn RecordBase) -> String {
+        let ttl = record.get_ttl();
+        let dns_class = record.get_dns_class().map(|dns_class| dns_class.get_name().to_string()).unwrap_or_default();
+        let _type = record.get_type();
+        let rdata = record.rdata_to_string();
+
+        let line = format!("{}\t{}\t{}\t{}\t{}", owner, ttl, dns_class, _type, rdata);
+
+        if line.len() <= self.line_width || (_type != Types::Rrsig && _type != Types::DnsKey) {
+            return format!("{}\n", line);
+        }
+
+        //THE KEY OR SIGNATURE IS ALWAYS THE LAST FIELD
+        let (fields, data) = match rdata.rsplit_once(' ') {
+            Some(split) => split,
+            None => return format!("{}\n", line)
+        };
+
+        let mut buf = format!("{}\t{}\t{}\t{}\t{} (\n", owner, ttl, dns_class, _type, fields);
+        let chunks: Vec<&[u8]> = data.as_bytes().chunks(WRAP_CHUNK).collect();
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            buf.push_str("\t\t\t\t");
+            buf.push_str(&String::from_utf8_lossy(chunk));
+            if i == chunks.len()-1 {
+                buf.push_str(" )");
+            }
+            buf.push('\n');
+        }
+
+        buf
+    }
+
+    fn relativize(&self, name: &str) -> String {
+        if name.eq_ignore_ascii_case(&self.origin) {
+            return "@".to_string();
+        }
+
+        let fqdn = to_fqdn(name);
+
+        if self.origin.is_empty() {
+            return fqdn;
+        }
+
+        let suffix = format!(".{}", to_fqdn(&self.origin));
+
+        if fqdn.len() > suffix.len() && fqdn.to_ascii_lowercase().ends_with(&suffix.to_ascii_lowercase()) {
+            let relative = &fqdn[..fqdn.len()-suffix.len()];
+
+            //AN ODD RUN OF BACKSLASHES MEANS THE DOT WE SPLIT ON WAS ESCAPED
+            let escapes = relative.bytes().rev().take_while(|&b| b == b'\\').count();
+            if escapes.is_multiple_of(2) {
+                return relative.to_string();
+            }
+        }
+
+        fqdn
+    }
+
+    //RECORDS OF THE SAME TYPE PRINT TOGETHER, SOA FIRST, OTHERWISE IN THE ORDER THEY WERE FIRST SEEN
+    fn group_rrsets(records: &[Box<dyn RecordBase>]) -> Vec<Vec<&dyn RecordBase>> {
+        let mut rrsets: Vec<Vec<&dyn RecordBase>> = Vec::new();
+
+        for record in records {
+            if record.get_type() == Types::Opt {
+                continue;
+            }
+
+            match rrsets.iter_mut().find(|rrset| rrset[0].get_type() == record.get_type()) {
+                Some(rrset) => rrset.push(record.as_ref()),
+                None => rrsets.push(vec![record.as_ref()])
+            }
+        }
+
+        rrsets.sort_by_key(|rrset| rrset[0].get_type() != Types::Soa);
+        rrsets
+    }
+}